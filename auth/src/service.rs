@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use amqprs::{channel::BasicPublishArguments, BasicProperties, DELIVERY_MODE_PERSISTENT};
+use amqprs::channel::BasicPublishArguments;
 use chrono::Utc;
 use serde_json::json;
 use shared::{
@@ -17,7 +17,7 @@ use shared::{
     },
     utils::{
         jwt::{AccessToken, AccessTokenPayload, RefreshToken, RefreshTokenPayload, JWT},
-        message::VerificationMessage,
+        message::{Envelope, Payload, VerificationMessage},
     },
 };
 use tonic::{Request, Response, Status};
@@ -58,6 +58,7 @@ impl AuthService for AuthServiceImpl {
                 username: input.usermame.to_owned(),
                 email: input.email.to_owned(),
                 image: None,
+                locale: input.locale.to_owned(),
             },
         )
         .await
@@ -161,24 +162,18 @@ impl AuthService for AuthServiceImpl {
             ),
         };
 
-        let payload = json!(VerificationMessage {
+        let envelope = Envelope::new(Payload::Verification(VerificationMessage {
             email: user.email.to_owned(),
             verification_link,
-        })
-        .to_string()
-        .into_bytes();
+            locale: user.locale.to_owned(),
+        }));
+
+        let payload = json!(envelope).to_string().into_bytes();
 
         let publish_args = BasicPublishArguments::new("", "notification");
 
         match channel
-            .basic_publish(
-                BasicProperties::default()
-                    .with_message_type("orta.notification.verification")
-                    .with_delivery_mode(DELIVERY_MODE_PERSISTENT)
-                    .finish(),
-                payload,
-                publish_args,
-            )
+            .basic_publish(envelope.properties(), payload, publish_args)
             .await
         {
             Ok(()) => Ok(Response::new(SignupResponse {
@@ -259,24 +254,18 @@ impl AuthService for AuthServiceImpl {
 
             println!("{}", verification_link);
 
-            let payload = json!(VerificationMessage {
+            let envelope = Envelope::new(Payload::Verification(VerificationMessage {
                 email: user.email.to_owned(),
                 verification_link,
-            })
-            .to_string()
-            .into_bytes();
+                locale: user.locale.to_owned(),
+            }));
+
+            let payload = json!(envelope).to_string().into_bytes();
 
             let publish_args = BasicPublishArguments::new("", "notification");
 
             match channel
-                .basic_publish(
-                    BasicProperties::default()
-                        .with_message_type("orta.notification.verification")
-                        .with_delivery_mode(DELIVERY_MODE_PERSISTENT)
-                        .finish(),
-                    payload,
-                    publish_args,
-                )
+                .basic_publish(envelope.properties(), payload, publish_args)
                 .await
             {
                 Ok(()) => {
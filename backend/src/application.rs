@@ -23,11 +23,15 @@ use tower_http::{
 };
 use tracing::info;
 
-use crate::routes;
+use crate::{
+    events::{self, EventHub},
+    routes,
+};
 
 #[derive(Clone)]
 pub struct State {
     pub key: Key,
+    pub events: Arc<EventHub>,
 }
 
 impl FromRef<State> for Key {
@@ -52,8 +56,16 @@ impl Application {
 
         let address = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
 
+        let events = Arc::new(EventHub::new(configuration.stream.history));
+
+        tokio::spawn(events::listen(
+            configuration.message_broker.clone(),
+            events.clone(),
+        ));
+
         let state = Arc::new(State {
             key: Key::generate(),
+            events,
         });
 
         let listener = TcpListener::bind(&address).await?;
@@ -0,0 +1,392 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use amqprs::{
+    channel::{
+        BasicConsumeArguments, ExchangeDeclareArguments, QueueBindArguments, QueueDeclareArguments,
+    },
+    connection::{Connection, OpenConnectionArguments},
+};
+use async_trait::async_trait;
+use secrecy::ExposeSecret;
+use serde_json::json;
+use shared::{
+    article::{self, article_service_client::ArticleServiceClient},
+    common::Visibility,
+    configuration::MessageBrokerSettings,
+    list::{self, list_service_client::ListServiceClient},
+    models::enums::CommentableType,
+    series::{self, series_service_client::SeriesServiceClient},
+    utils::message::{Envelope, Payload, EVENTS_EXCHANGE},
+};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tonic::{transport::Channel, Code};
+use tracing::{error, info, warn};
+
+/// Domain event as delivered to browsers, `topics` decide who receives it
+#[derive(Debug, Clone)]
+pub struct StreamEvent {
+    pub id: String,
+    pub event: &'static str,
+    pub topics: Vec<String>,
+    pub data: serde_json::Value,
+}
+
+impl StreamEvent {
+    pub fn from_envelope(envelope: &Envelope) -> Option<Self> {
+        let id = envelope.id.to_owned();
+        match &envelope.payload {
+            Payload::CommentCreated(event) => {
+                let target = match event.comment.r#type {
+                    CommentableType::Article => "article",
+                    CommentableType::List => "list",
+                    CommentableType::Series => "series",
+                };
+                let mut topics = vec![format!("{}:{}", target, event.comment.target_id)];
+                topics.extend(event.recipients.iter().map(|user_id| user_topic(user_id)));
+                Some(Self {
+                    id,
+                    event: "comment.created",
+                    topics,
+                    data: json!(event.comment),
+                })
+            }
+            Payload::ArticleLiked(event) => {
+                let mut topics = vec![format!("article:{}", event.article_id)];
+                // Authors are only notified of new likes, counts go to the article
+                if event.liked {
+                    topics.extend(event.recipients.iter().map(|user_id| user_topic(user_id)));
+                }
+                Some(Self {
+                    id,
+                    event: "article.liked",
+                    topics,
+                    data: json!({
+                        "article_id": event.article_id,
+                        "user_id": event.user_id,
+                        "liked": event.liked,
+                        "like_count": event.like_count,
+                    }),
+                })
+            }
+            Payload::UserFollowed(event) => Some(Self {
+                id,
+                event: "user.followed",
+                topics: vec![user_topic(&event.following_id)],
+                data: json!({
+                    "follower_id": event.follower_id,
+                    "following_id": event.following_id,
+                }),
+            }),
+            _ => None,
+        }
+    }
+}
+
+pub fn user_topic(user_id: &str) -> String {
+    format!("user:{}", user_id)
+}
+
+/// Decides whether a user may follow the events of an article, list or
+/// series
+#[async_trait]
+pub trait TopicAccess: Sync {
+    async fn can_read(&self, user_id: &str, kind: &str, id: &str) -> bool;
+}
+
+/// Checks access through the resource service. Drafts are only followed by
+/// their authors and lists that aren't public by their owner.
+pub struct ResourceAccess(pub Channel);
+
+#[async_trait]
+impl TopicAccess for ResourceAccess {
+    async fn can_read(&self, user_id: &str, kind: &str, id: &str) -> bool {
+        let by_user = Some(user_id.to_owned());
+        let allowed = match kind {
+            "article" => ArticleServiceClient::new(self.0.clone())
+                .get(article::GetRequest {
+                    article_id: id.to_owned(),
+                    by_user,
+                })
+                .await
+                .map(|res| {
+                    let article = res.get_ref();
+                    article.published_at.is_some()
+                        || article.users.iter().any(|author| author.id == user_id)
+                }),
+            "list" => ListServiceClient::new(self.0.clone())
+                .get(list::GetRequest {
+                    list_id: id.to_owned(),
+                    by_user,
+                })
+                .await
+                .map(|res| {
+                    let list = res.get_ref();
+                    list.visibility() == Visibility::Public || list.user_id == user_id
+                }),
+            "series" => SeriesServiceClient::new(self.0.clone())
+                .get(series::GetRequest {
+                    series_id: id.to_owned(),
+                })
+                .await
+                .map(|_| true),
+            _ => return false,
+        };
+
+        match allowed {
+            Ok(allowed) => allowed,
+            Err(err) => {
+                if err.code() != Code::NotFound {
+                    error!("{:?}", err);
+                }
+                false
+            }
+        }
+    }
+}
+
+/// Topics a stream listens to. The user's own notifications are always
+/// included, notifications of other users can not be subscribed to, nor
+/// content the user can not read.
+#[derive(Debug, Clone)]
+pub struct Subscription {
+    user_id: String,
+    topics: HashSet<String>,
+}
+
+impl Subscription {
+    /// Subscribes to the allowed `topics`, the others are dropped
+    pub async fn new(
+        user_id: &str,
+        topics: impl IntoIterator<Item = String>,
+        access: &impl TopicAccess,
+    ) -> Self {
+        let mut subscription = Self {
+            user_id: user_id.to_owned(),
+            topics: HashSet::from([user_topic(user_id)]),
+        };
+        for topic in topics {
+            if !subscription.subscribe(topic.to_owned(), access).await {
+                warn!("Topic not allowed {:?}", topic);
+            }
+        }
+        subscription
+    }
+
+    /// Returns false if the topic is not allowed
+    pub async fn subscribe(&mut self, topic: String, access: &impl TopicAccess) -> bool {
+        if self.topics.contains(&topic) {
+            return true;
+        }
+        let allowed = match topic.split_once(':') {
+            Some(("user", user_id)) => user_id == self.user_id,
+            Some((kind @ ("article" | "list" | "series"), id)) => {
+                !id.is_empty() && access.can_read(&self.user_id, kind, id).await
+            }
+            _ => false,
+        };
+        if allowed {
+            self.topics.insert(topic);
+        }
+        allowed
+    }
+
+    pub fn unsubscribe(&mut self, topic: &str) {
+        if topic != user_topic(&self.user_id) {
+            self.topics.remove(topic);
+        }
+    }
+
+    pub fn matches(&self, event: &StreamEvent) -> bool {
+        event.topics.iter().any(|topic| self.topics.contains(topic))
+    }
+}
+
+/// Fans out broker events to every open stream and keeps the most recent
+/// ones so a reconnecting client can resume from its last event id
+pub struct EventHub {
+    sender: broadcast::Sender<Arc<StreamEvent>>,
+    history: Mutex<VecDeque<Arc<StreamEvent>>>,
+    capacity: usize,
+}
+
+impl EventHub {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self {
+            sender,
+            history: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+        }
+    }
+
+    pub fn publish(&self, event: StreamEvent) {
+        let event = Arc::new(event);
+        let mut history = self.history.lock().unwrap();
+        if history.len() >= self.capacity {
+            history.pop_front();
+        }
+        history.push_back(event.clone());
+        // No open streams is not an error
+        let _ = self.sender.send(event);
+    }
+
+    /// Opens a stream, replaying events published after `last_event_id`.
+    /// The history lock is held while subscribing so no event is missed or
+    /// sent twice.
+    pub fn subscribe(
+        &self,
+        subscription: Subscription,
+        last_event_id: Option<&str>,
+    ) -> EventStream {
+        let history = self.history.lock().unwrap();
+        let receiver = self.sender.subscribe();
+
+        let (replay, reset) = match last_event_id {
+            None => (VecDeque::new(), false),
+            Some(last_event_id) => {
+                match history.iter().position(|event| event.id == last_event_id) {
+                    Some(position) => (history.iter().skip(position + 1).cloned().collect(), false),
+                    // Too old to resume, the client has to refetch
+                    None => (VecDeque::new(), true),
+                }
+            }
+        };
+
+        EventStream {
+            subscription,
+            replay,
+            receiver,
+            reset,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Outgoing {
+    Event(Arc<StreamEvent>),
+    /// Events were missed, clients should refetch what they display
+    Reset,
+}
+
+pub struct EventStream {
+    pub subscription: Subscription,
+    replay: VecDeque<Arc<StreamEvent>>,
+    receiver: broadcast::Receiver<Arc<StreamEvent>>,
+    reset: bool,
+}
+
+impl EventStream {
+    /// Next event matching the subscription, cancel safe
+    pub async fn next(&mut self) -> Option<Outgoing> {
+        if self.reset {
+            self.reset = false;
+            return Some(Outgoing::Reset);
+        }
+
+        while let Some(event) = self.replay.pop_front() {
+            if self.subscription.matches(&event) {
+                return Some(Outgoing::Event(event));
+            }
+        }
+
+        loop {
+            match self.receiver.recv().await {
+                Ok(event) => {
+                    if self.subscription.matches(&event) {
+                        return Some(Outgoing::Event(event));
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("Stream lagged behind by {} events", skipped);
+                    return Some(Outgoing::Reset);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+/// Consumes the events exchange into the hub, reconnecting on failure
+pub async fn listen(configuration: MessageBrokerSettings, hub: Arc<EventHub>) {
+    loop {
+        if let Err(err) = consume(&configuration, &hub).await {
+            error!("Event listener stopped: {:?}", err);
+        }
+        tokio::time::sleep(Duration::from_secs(5)).await;
+    }
+}
+
+async fn consume(
+    configuration: &MessageBrokerSettings,
+    hub: &EventHub,
+) -> Result<(), anyhow::Error> {
+    let args = OpenConnectionArguments::new(
+        &configuration.hostname,
+        configuration.port,
+        &configuration.username,
+        configuration.password.expose_secret(),
+    )
+    .finish();
+
+    let connection = Connection::open(&args).await?;
+    let channel = connection.open_channel(None).await?;
+
+    channel
+        .exchange_declare(
+            ExchangeDeclareArguments::new(EVENTS_EXCHANGE, "topic")
+                .durable(true)
+                .finish(),
+        )
+        .await?;
+
+    // Every gateway instance gets its own queue, events are live only
+    let (queue, _, _) = channel
+        .queue_declare(QueueDeclareArguments::exclusive_server_named())
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("No queue declared"))?;
+
+    channel
+        .queue_bind(QueueBindArguments::new(
+            &queue,
+            EVENTS_EXCHANGE,
+            "orta.event.#",
+        ))
+        .await?;
+
+    let (_, mut message_rx) = channel
+        .basic_consume_rx(
+            BasicConsumeArguments::new(&queue, "")
+                .manual_ack(false)
+                .finish(),
+        )
+        .await?;
+
+    info!("Listening to events on {}", queue);
+
+    while let Some(message) = message_rx.recv().await {
+        let content = match message.content {
+            Some(content) => content,
+            None => continue,
+        };
+
+        let properties = message.basic_properties.as_ref();
+        match Envelope::parse(
+            &content,
+            properties.and_then(|properties| properties.message_type().map(|v| v.as_str())),
+            properties.and_then(|properties| properties.message_id().map(|v| v.as_str())),
+        ) {
+            Ok(envelope) => {
+                if let Some(event) = StreamEvent::from_envelope(&envelope) {
+                    hub.publish(event);
+                }
+            }
+            Err(err) => warn!("Skipping event: {:?}", err),
+        }
+    }
+
+    Err(anyhow::anyhow!("Consumer closed"))
+}
@@ -1,4 +1,5 @@
 pub mod application;
+pub mod events;
 pub mod middlewares;
 pub mod routes;
 pub mod utils;
@@ -9,15 +9,20 @@ use axum_extra::{
     extract::Query,
     headers::{authorization::Bearer, Authorization, HeaderMapExt},
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 use shared::{
     article::{
         article_service_client::ArticleServiceClient, AddAuthorRequest, CreateRequest,
-        DeleteRequest, EditRequest, GetRequest, HistoryRequest, LikeRequest, PublishRequest,
-        RemoveAuthorRequest, SearchRequest, SetTagsRequest, UnlikeRequest, UpdateRequest,
+        CreateSnapshotRequest, DeleteRequest, DiffRequest, EditRequest, GetRequest, HistoryRequest,
+        LikeRequest, ListSnapshotsRequest, PublishRequest, RemoveAuthorRequest, RestoreRequest,
+        SearchRequest, SearchSort, SetTagsRequest, UnlikeRequest, UpdateRequest,
+    },
+    common,
+    models::{
+        article_model::{Article, ArticleDiff, ArticleVersion, FullArticle, Snapshot},
+        enums,
     },
-    models::article_model::{Article, ArticleVersion, FullArticle},
     utils::jwt::{AccessToken, AccessTokenPayload, JWT},
 };
 use tonic::{codec::CompressionEncoding, transport::Channel};
@@ -34,6 +39,25 @@ use crate::{
 #[derive(Debug, Deserialize)]
 pub struct ArticlesQueryParams {
     query: Option<String>,
+    sort: Option<enums::SearchSort>,
+}
+
+/// An article found by a query, with the fragments that matched it
+#[derive(Debug, Serialize)]
+pub struct SearchedArticle {
+    #[serde(flatten)]
+    article: FullArticle,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    headline: Option<String>,
+}
+
+impl From<&common::FullArticle> for SearchedArticle {
+    fn from(value: &common::FullArticle) -> Self {
+        SearchedArticle {
+            article: FullArticle::from(value),
+            headline: value.headline.to_owned(),
+        }
+    }
 }
 
 pub async fn search_articles(
@@ -61,6 +85,7 @@ pub async fn search_articles(
             cursor: cursor.cursor,
             limit: cursor.limit,
             by_user,
+            sort: SearchSort::from(query.sort.unwrap_or_default()).into(),
         })
         .await
     {
@@ -68,12 +93,12 @@ pub async fn search_articles(
             let res = res.get_ref();
             (
                 StatusCode::OK,
-                Json(json!(ResultPaging::<FullArticle> {
+                Json(json!(ResultPaging::<SearchedArticle> {
                     next_cursor: res.next_cursor.to_owned(),
                     items: res
                         .articles
                         .iter()
-                        .map(|article| FullArticle::from(article))
+                        .map(|article| SearchedArticle::from(article))
                         .collect()
                 })),
             )
@@ -401,6 +426,183 @@ pub async fn get_history(
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct DiffQueryParams {
+    from: String,
+    /// The current content when missing
+    to: Option<String>,
+}
+
+pub async fn get_diff(
+    Extension(channel): Extension<Channel>,
+    Extension(user): Extension<AccessTokenPayload>,
+    Path(params): Path<PathParams>,
+    Query(query): Query<DiffQueryParams>,
+) -> Response {
+    let article_id = match params.article_id {
+        Some(v) => v,
+        None => return (StatusCode::BAD_REQUEST, "Wrong parameters").into_response(),
+    };
+    info!(
+        "Get Article Diff Request {:?} {:?} {:?}",
+        user, article_id, query
+    );
+    match ArticleServiceClient::new(channel)
+        .diff(DiffRequest {
+            user_id: user.user_id,
+            article_id,
+            from: query.from,
+            to: query.to,
+        })
+        .await
+    {
+        Ok(res) => (
+            StatusCode::OK,
+            Json(json!(ArticleDiff::from(res.get_ref()))),
+        )
+            .into_response(),
+        Err(err) => {
+            error!("{:?}", err);
+            let message = err.message().to_string();
+            let status_code = code_to_statudecode(err.code());
+            (status_code, message).into_response()
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RestoreArticleRequestBody {
+    pub version_id: String,
+}
+
+pub async fn restore_article(
+    Extension(channel): Extension<Channel>,
+    Extension(user): Extension<AccessTokenPayload>,
+    Path(params): Path<PathParams>,
+    Json(payload): Json<RestoreArticleRequestBody>,
+) -> Response {
+    let article_id = match params.article_id {
+        Some(v) => v,
+        None => return (StatusCode::BAD_REQUEST, "Wrong parameters").into_response(),
+    };
+    info!(
+        "Restore Article Version Request {:?} {:?} {:?}",
+        user, article_id, payload
+    );
+    match ArticleServiceClient::new(channel)
+        .restore(RestoreRequest {
+            user_id: user.user_id,
+            article_id,
+            version_id: payload.version_id,
+        })
+        .await
+    {
+        Ok(res) => (
+            StatusCode::OK,
+            Json(json!(ArticleVersion::from(res.get_ref()))),
+        )
+            .into_response(),
+        Err(err) => {
+            error!("{:?}", err);
+            let message = err.message().to_string();
+            let status_code = code_to_statudecode(err.code());
+            (status_code, message).into_response()
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SnapshotRequestBody {
+    pub version_id: String,
+    pub name: String,
+    pub note: Option<String>,
+}
+
+pub async fn post_snapshot(
+    Extension(channel): Extension<Channel>,
+    Extension(user): Extension<AccessTokenPayload>,
+    Path(params): Path<PathParams>,
+    Json(payload): Json<SnapshotRequestBody>,
+) -> Response {
+    let article_id = match params.article_id {
+        Some(v) => v,
+        None => return (StatusCode::BAD_REQUEST, "Wrong parameters").into_response(),
+    };
+    info!(
+        "Create Article Snapshot Request {:?} {:?} {:?}",
+        user, article_id, payload
+    );
+    match ArticleServiceClient::new(channel)
+        .create_snapshot(CreateSnapshotRequest {
+            user_id: user.user_id,
+            article_id,
+            version_id: payload.version_id,
+            name: payload.name,
+            note: payload.note,
+        })
+        .await
+    {
+        Ok(res) => (
+            StatusCode::CREATED,
+            Json(json!(Snapshot::from(res.get_ref()))),
+        )
+            .into_response(),
+        Err(err) => {
+            error!("{:?}", err);
+            let message = err.message().to_string();
+            let status_code = code_to_statudecode(err.code());
+            (status_code, message).into_response()
+        }
+    }
+}
+
+pub async fn get_snapshots(
+    Extension(channel): Extension<Channel>,
+    Extension(user): Extension<AccessTokenPayload>,
+    Path(params): Path<PathParams>,
+    Query(cursor): Query<CursorPagination>,
+) -> Response {
+    let article_id = match params.article_id {
+        Some(v) => v,
+        None => return (StatusCode::BAD_REQUEST, "Wrong parameters").into_response(),
+    };
+    info!(
+        "Get Article Snapshots Request {:?} {:?} {:?}",
+        user, article_id, cursor
+    );
+    match ArticleServiceClient::new(channel)
+        .list_snapshots(ListSnapshotsRequest {
+            user_id: user.user_id,
+            article_id,
+            cursor: cursor.cursor,
+            limit: cursor.limit,
+        })
+        .await
+    {
+        Ok(res) => {
+            let res = res.get_ref();
+            (
+                StatusCode::OK,
+                Json(json!(ResultPaging::<Snapshot> {
+                    next_cursor: res.next_cursor.to_owned(),
+                    items: res
+                        .snapshots
+                        .iter()
+                        .map(|snapshot| Snapshot::from(snapshot))
+                        .collect()
+                })),
+            )
+                .into_response()
+        }
+        Err(err) => {
+            error!("{:?}", err);
+            let message = err.message().to_string();
+            let status_code = code_to_statudecode(err.code());
+            (status_code, message).into_response()
+        }
+    }
+}
+
 pub async fn like_article(
     Extension(channel): Extension<Channel>,
     Extension(user): Extension<AccessTokenPayload>,
@@ -1,75 +1,498 @@
 use axum::{
-    body::Bytes,
-    extract::Path,
-    http::StatusCode,
+    body::Body,
+    extract::{Multipart, Path, Query},
+    http::{
+        header::{
+            ACCEPT_RANGES, CACHE_CONTROL, CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE, ETAG,
+            IF_NONE_MATCH, IF_RANGE, RANGE,
+        },
+        HeaderMap, HeaderValue, StatusCode,
+    },
     response::{IntoResponse, Response},
-    Extension,
+    Extension, Json,
 };
-use axum_typed_multipart::{FieldData, TryFromMultipart, TypedMultipart};
-use shared::storage_proto::{
-    storage_service_client::StorageServiceClient, RetriveRequest, StoreRequest,
+use futures::{channel::mpsc, future, SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use shared::{
+    models::asset_model::{Asset, Orphan},
+    storage_proto::{
+        storage_service_client::StorageServiceClient, CompleteUploadRequest, DeleteRequest,
+        GetAllRequest, OrphansRequest, PresignDownloadRequest, PresignUploadRequest,
+        RenditionFormat, RetriveRenditionRequest, RetriveRequest, StatRequest, StoreRequest,
+    },
+    utils::jwt::AccessTokenPayload,
 };
 use tonic::{codec::CompressionEncoding, transport::Channel};
 use tracing::{debug, error, info};
 
-use crate::utils::{mapper::code_to_statudecode, params::PathParams};
+use crate::utils::{
+    caching::{etag_matches, parse_range, RangeRequest},
+    mapper::code_to_statudecode,
+    params::{CursorPagination, PathParams, ResultPaging},
+};
+
+/// Content-addressed objects never change
+const IMMUTABLE: &str = "public, max-age=31536000, immutable";
+/// Anything else is revalidated against its ETag
+const REVALIDATE: &str = "public, no-cache";
+
+/// Uploads are forwarded to storage in chunks of this size
+const CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Deserialize)]
+pub struct AssetQueryParams {
+    /// Name of a configured rendition, the original when missing
+    pub rendition: Option<String>,
+    /// `webp` (default) or `original`
+    pub format: Option<String>,
+}
+
+impl AssetQueryParams {
+    fn format(&self) -> Option<RenditionFormat> {
+        match self.format.as_deref() {
+            None | Some("webp") => Some(RenditionFormat::Webp),
+            Some("original") => Some(RenditionFormat::Original),
+            Some(_) => None,
+        }
+    }
+}
 
 pub async fn get_asset(
     Extension(channel): Extension<Channel>,
     Path(params): Path<PathParams>,
+    Query(query): Query<AssetQueryParams>,
+    headers: HeaderMap,
 ) -> Response {
     let asset_name = match params.asset_name {
         Some(v) => v,
         None => return (StatusCode::BAD_REQUEST, "Wrong parameters").into_response(),
     };
 
-    info!("Get Asset Request {}", asset_name);
+    info!("Get Asset Request {} {:?}", asset_name, query);
 
-    match StorageServiceClient::new(channel)
-        .accept_compressed(CompressionEncoding::Gzip)
-        .max_decoding_message_size(50 * 1024 * 1024)
-        .retrive(RetriveRequest { asset_name })
+    let format = match query.format() {
+        Some(format) => format,
+        None => return (StatusCode::BAD_REQUEST, "Unknown format").into_response(),
+    };
+
+    let mut client =
+        StorageServiceClient::new(channel).accept_compressed(CompressionEncoding::Gzip);
+
+    let stat = match client
+        .stat(StatRequest {
+            asset_name: asset_name.to_owned(),
+            rendition: query.rendition.to_owned(),
+            format: format as i32,
+        })
         .await
     {
-        Ok(res) => (StatusCode::OK, res.get_ref().data.to_owned()).into_response(),
+        Ok(res) => res.into_inner(),
         Err(err) => {
             error!("{:?}", err);
             let message = err.message().to_string();
             let status_code = code_to_statudecode(err.code());
-            (status_code, message).into_response()
+            return (status_code, message).into_response();
+        }
+    };
+
+    let mut response_headers = HeaderMap::new();
+    response_headers.insert(
+        CACHE_CONTROL,
+        HeaderValue::from_static(match stat.immutable {
+            true => IMMUTABLE,
+            false => REVALIDATE,
+        }),
+    );
+    response_headers.insert(ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    if let Ok(etag) = HeaderValue::from_str(&stat.etag) {
+        response_headers.insert(ETAG, etag);
+    }
+
+    if let Some(if_none_match) = headers.get(IF_NONE_MATCH).and_then(|v| v.to_str().ok()) {
+        if etag_matches(if_none_match, &stat.etag) {
+            return (StatusCode::NOT_MODIFIED, response_headers).into_response();
         }
     }
-}
 
-#[derive(Debug, TryFromMultipart)]
-pub struct UploadAssetRequest {
-    #[form_data(limit = "unlimited")]
-    asset: FieldData<Bytes>,
+    // A range is only honoured while the client's copy is still current
+    let range = match headers.get(RANGE).and_then(|v| v.to_str().ok()) {
+        Some(range)
+            if headers
+                .get(IF_RANGE)
+                .map_or(true, |if_range| if_range.as_bytes() == stat.etag.as_bytes()) =>
+        {
+            parse_range(range, stat.size)
+        }
+        _ => RangeRequest::Full,
+    };
+
+    let (status_code, offset, length) = match range {
+        RangeRequest::Full => (StatusCode::OK, None, None),
+        RangeRequest::Partial(range) => {
+            if let Ok(content_range) = HeaderValue::from_str(&range.content_range(stat.size)) {
+                response_headers.insert(CONTENT_RANGE, content_range);
+            }
+            (
+                StatusCode::PARTIAL_CONTENT,
+                Some(range.start),
+                Some(range.length()),
+            )
+        }
+        RangeRequest::Unsatisfiable => {
+            if let Ok(content_range) = HeaderValue::from_str(&format!("bytes */{}", stat.size)) {
+                response_headers.insert(CONTENT_RANGE, content_range);
+            }
+            return (StatusCode::RANGE_NOT_SATISFIABLE, response_headers).into_response();
+        }
+    };
+
+    response_headers.insert(
+        CONTENT_TYPE,
+        HeaderValue::from_str(&stat.content_type)
+            .unwrap_or_else(|_| HeaderValue::from_static("application/octet-stream")),
+    );
+    response_headers.insert(
+        CONTENT_LENGTH,
+        HeaderValue::from(length.unwrap_or(stat.size)),
+    );
+
+    let response = match query.rendition {
+        Some(rendition) => {
+            client
+                .retrive_rendition(RetriveRenditionRequest {
+                    asset_name,
+                    rendition,
+                    format: format as i32,
+                    offset,
+                    length,
+                })
+                .await
+        }
+        None => {
+            client
+                .retrive(RetriveRequest {
+                    asset_name,
+                    offset,
+                    length,
+                })
+                .await
+        }
+    };
+
+    let chunks = match response {
+        Ok(res) => res.into_inner(),
+        Err(err) => {
+            error!("{:?}", err);
+            let message = err.message().to_string();
+            let status_code = code_to_statudecode(err.code());
+            return (status_code, message).into_response();
+        }
+    };
+
+    let body = Body::from_stream(chunks.map(|chunk| chunk.map(|chunk| chunk.data)));
+
+    (status_code, response_headers, body).into_response()
 }
 
 pub async fn post_asset(
     Extension(channel): Extension<Channel>,
-    TypedMultipart(UploadAssetRequest { asset }): TypedMultipart<UploadAssetRequest>,
+    Extension(user): Extension<AccessTokenPayload>,
+    mut multipart: Multipart,
 ) -> Response {
-    info!("Post Asset Request {:?}", asset.metadata);
+    let mut field = loop {
+        match multipart.next_field().await {
+            Ok(Some(field)) if field.name() == Some("asset") => break field,
+            Ok(Some(_)) => continue,
+            Ok(None) => return (StatusCode::BAD_REQUEST, "No asset").into_response(),
+            Err(err) => return err.into_response(),
+        }
+    };
 
-    let file_name = match asset.metadata.file_name {
-        Some(file_name) => file_name,
+    let file_name = match field.file_name() {
+        Some(file_name) => file_name.to_string(),
         None => return (StatusCode::NOT_ACCEPTABLE, "No Filename").into_response(),
     };
+    let content_type = field.content_type().map(str::to_string);
+
+    info!("Post Asset Request {} {:?}", file_name, content_type);
+
+    let (mut sender, receiver) = mpsc::channel::<StoreRequest>(4);
+
+    let forward = async move {
+        let mut metadata = Some((file_name, content_type, user.user_id));
+        let mut buffer = Vec::with_capacity(CHUNK_SIZE);
+
+        loop {
+            let chunk = field.chunk().await.map_err(IntoResponse::into_response)?;
+
+            if let Some(chunk) = &chunk {
+                buffer.extend_from_slice(chunk);
+            }
+
+            if buffer.len() >= CHUNK_SIZE || (chunk.is_none() && !buffer.is_empty()) {
+                let (asset_name, content_type, user_id) = metadata.take().unwrap_or_default();
+                let message = StoreRequest {
+                    asset_data: std::mem::take(&mut buffer),
+                    asset_name,
+                    content_type,
+                    user_id,
+                };
+                // Storage stopped reading, its status is returned instead
+                if sender.send(message).await.is_err() {
+                    break;
+                }
+            }
+
+            if chunk.is_none() {
+                break;
+            }
+        }
+
+        Ok(())
+    };
+
+    let store = async {
+        StorageServiceClient::new(channel)
+            .accept_compressed(CompressionEncoding::Gzip)
+            .store(receiver)
+            .await
+            .map_err(|err| {
+                error!("{:?}", err);
+                let message = err.message().to_string();
+                let status_code = code_to_statudecode(err.code());
+                (status_code, message).into_response()
+            })
+    };
+
+    // A failed body drops the store call, which cancels the upload instead
+    // of storing a truncated asset
+    match future::try_join(forward, store).await {
+        Ok(((), res)) => (StatusCode::CREATED, res.get_ref().asset_name.to_owned()).into_response(),
+        Err(response) => response,
+    }
+}
+
+/// Lets clients fetch an asset from the bucket directly
+pub async fn get_asset_url(
+    Extension(channel): Extension<Channel>,
+    Path(params): Path<PathParams>,
+    Query(query): Query<AssetQueryParams>,
+) -> Response {
+    let asset_name = match params.asset_name {
+        Some(v) => v,
+        None => return (StatusCode::BAD_REQUEST, "Wrong parameters").into_response(),
+    };
+
+    info!("Get Asset Url Request {} {:?}", asset_name, query);
+
+    let format = match query.format() {
+        Some(format) => format,
+        None => return (StatusCode::BAD_REQUEST, "Unknown format").into_response(),
+    };
 
     match StorageServiceClient::new(channel)
-        .accept_compressed(CompressionEncoding::Gzip)
-        .max_encoding_message_size(50 * 1024 * 1024)
-        .max_decoding_message_size(50 * 1024 * 1024)
-        .store(StoreRequest {
-            asset_data: asset.contents.to_vec(),
-            asset_name: file_name,
-            content_type: asset.metadata.content_type,
+        .presign_download(PresignDownloadRequest {
+            asset_name,
+            rendition: query.rendition,
+            format: format as i32,
+        })
+        .await
+    {
+        Ok(res) => {
+            let presigned = res.into_inner();
+            (
+                StatusCode::OK,
+                Json(json!({
+                    "url": presigned.url,
+                    "expires_in": presigned.expires_in,
+                })),
+            )
+                .into_response()
+        }
+        Err(err) => {
+            error!("{:?}", err);
+            let message = err.message().to_string();
+            let status_code = code_to_statudecode(err.code());
+            return (status_code, message).into_response();
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PostUploadRequestBody {
+    pub file_name: String,
+    /// What the file is going to be, checked again once it is uploaded
+    pub content_type: String,
+    pub size: u64,
+}
+
+/// Starts a direct upload for files too large to go through the gateway.
+/// The client puts the file to the returned URL, then completes the upload.
+pub async fn post_upload(
+    Extension(channel): Extension<Channel>,
+    Extension(user): Extension<AccessTokenPayload>,
+    Json(payload): Json<PostUploadRequestBody>,
+) -> Response {
+    info!("Post Upload Request {:?}", payload);
+
+    match StorageServiceClient::new(channel)
+        .presign_upload(PresignUploadRequest {
+            user_id: user.user_id,
+            file_name: payload.file_name,
+            content_type: payload.content_type,
+            size: payload.size,
+        })
+        .await
+    {
+        Ok(res) => {
+            let presigned = res.into_inner();
+            (
+                StatusCode::CREATED,
+                Json(json!({
+                    "upload_id": presigned.upload_id,
+                    "url": presigned.url,
+                    "expires_in": presigned.expires_in,
+                })),
+            )
+                .into_response()
+        }
+        Err(err) => {
+            error!("{:?}", err);
+            let message = err.message().to_string();
+            let status_code = code_to_statudecode(err.code());
+            return (status_code, message).into_response();
+        }
+    }
+}
+
+pub async fn post_complete_upload(
+    Extension(channel): Extension<Channel>,
+    Extension(user): Extension<AccessTokenPayload>,
+    Path(params): Path<PathParams>,
+) -> Response {
+    let upload_id = match params.upload_id {
+        Some(v) => v,
+        None => return (StatusCode::BAD_REQUEST, "Wrong parameters").into_response(),
+    };
+
+    info!("Complete Upload Request {}", upload_id);
+
+    match StorageServiceClient::new(channel)
+        .complete_upload(CompleteUploadRequest {
+            user_id: user.user_id,
+            upload_id,
         })
         .await
     {
         Ok(res) => (StatusCode::CREATED, res.get_ref().asset_name.to_owned()).into_response(),
+        Err(err) => {
+            error!("{:?}", err);
+            let message = err.message().to_string();
+            let status_code = code_to_statudecode(err.code());
+            return (status_code, message).into_response();
+        }
+    }
+}
+
+pub async fn delete_asset(
+    Extension(channel): Extension<Channel>,
+    Extension(user): Extension<AccessTokenPayload>,
+    Path(params): Path<PathParams>,
+) -> Response {
+    let asset_id = match params.asset_id {
+        Some(v) => v,
+        None => return (StatusCode::BAD_REQUEST, "Wrong parameters").into_response(),
+    };
+
+    info!("Delete Asset Request {}", asset_id);
+
+    match StorageServiceClient::new(channel)
+        .delete(DeleteRequest {
+            user_id: user.user_id,
+            asset_id,
+        })
+        .await
+    {
+        Ok(res) => (StatusCode::OK, res.get_ref().message.to_owned()).into_response(),
+        Err(err) => {
+            error!("{:?}", err);
+            let message = err.message().to_string();
+            let status_code = code_to_statudecode(err.code());
+            (status_code, message).into_response()
+        }
+    }
+}
+
+pub async fn get_assets(
+    Extension(channel): Extension<Channel>,
+    Extension(user): Extension<AccessTokenPayload>,
+    Query(cursor): Query<CursorPagination>,
+) -> Response {
+    info!("Get Assets Request {:?}", cursor);
+
+    match StorageServiceClient::new(channel)
+        .get_all(GetAllRequest {
+            user_id: user.user_id,
+            limit: cursor.limit,
+            cursor: cursor.cursor,
+        })
+        .await
+    {
+        Ok(res) => {
+            let res = res.get_ref();
+            (
+                StatusCode::OK,
+                Json(json!(ResultPaging::<Asset> {
+                    next_cursor: res.next_cursor.to_owned(),
+                    items: res.assets.iter().map(|asset| Asset::from(asset)).collect(),
+                })),
+            )
+                .into_response()
+        }
+        Err(err) => {
+            error!("{:?}", err);
+            let message = err.message().to_string();
+            let status_code = code_to_statudecode(err.code());
+            (status_code, message).into_response()
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrphanReport {
+    #[serde(flatten)]
+    pub orphan: Orphan,
+    /// Removed on the next collector run
+    pub expired: bool,
+}
+
+pub async fn get_orphans(Extension(channel): Extension<Channel>) -> Response {
+    info!("Get Orphaned Assets Request");
+
+    match StorageServiceClient::new(channel)
+        .orphans(OrphansRequest {})
+        .await
+    {
+        Ok(res) => {
+            let res = res.get_ref();
+            (
+                StatusCode::OK,
+                Json(json!({
+                    "grace_period": res.grace_period,
+                    "size": res.size,
+                    "items": res
+                        .orphans
+                        .iter()
+                        .map(|orphan| OrphanReport {
+                            orphan: Orphan::from(orphan),
+                            expired: orphan.expired,
+                        })
+                        .collect::<Vec<OrphanReport>>(),
+                })),
+            )
+                .into_response()
+        }
         Err(err) => {
             error!("{:?}", err);
             let message = err.message().to_string();
@@ -1,6 +1,6 @@
 use axum::{
     extract::{Query, State},
-    http::StatusCode,
+    http::{header::ACCEPT_LANGUAGE, HeaderMap, StatusCode},
     response::{IntoResponse, Response},
     routing::{get, post},
     Extension, Json, Router,
@@ -35,19 +35,31 @@ pub struct SignUpRequest {
     pub email: String,
     #[validate(length(min = 8))]
     pub password: String,
+    pub locale: Option<String>,
 }
 
 pub async fn signup(
     Extension(channel): Extension<Channel>,
     State(_state): State<AppState>,
+    headers: HeaderMap,
     Json(payload): Json<SignUpRequest>,
 ) -> Response {
     info!("Signup request");
+    // Fall back to the first language the client advertises
+    let locale = payload.locale.or_else(|| {
+        headers
+            .get(ACCEPT_LANGUAGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(',').next())
+            .map(|tag| tag.split(';').next().unwrap_or_default().trim().to_string())
+            .filter(|tag| !tag.is_empty() && tag != "*")
+    });
     match AuthServiceClient::new(channel)
         .signup(SignupRequest {
             email: payload.email,
             password: payload.password,
             usermame: payload.username,
+            locale,
         })
         .await
     {
@@ -108,6 +108,8 @@ pub async fn get_comments(
 #[derive(Debug, Deserialize)]
 pub struct PostCommentRequestBody {
     content: String,
+    /// Comment being replied to
+    parent_id: Option<String>,
 }
 
 pub async fn post_comment(
@@ -142,6 +144,7 @@ pub async fn post_comment(
             target_id,
             r#type: common::CommentableType::from(r#type) as i32,
             content: payload.content,
+            parent_id: payload.parent_id,
         })
         .await
     {
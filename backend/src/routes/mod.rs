@@ -2,6 +2,7 @@ use crate::{
     application::AppState,
     middlewares::{
         auth::auth_middleware,
+        role::role_middleware,
         service_client::{
             auth_service_middleware, resource_service_middleware, storage_service_middleware,
         },
@@ -13,34 +14,47 @@ use axum::{
     extract::DefaultBodyLimit,
     handler::Handler,
     middleware,
-    routing::{get, patch, post, put},
+    routing::{delete, get, patch, post, put},
     Router,
 };
 use axum_prometheus::PrometheusMetricLayer;
 use list::get_list_articles;
 use series::{get_series_articles, patch_series_article};
+use shared::models::enums::Role;
 use tags::get_tags;
 use user::{get_user_articles, get_user_drafts};
 
 use self::{
     admin::health_checker,
     article::{
-        delete_article, delete_author, edit_article, get_article, get_history, like_article,
-        patch_article, post_article, publish, put_author, search_articles, unlike_article,
+        delete_article, delete_author, edit_article, get_article, get_diff, get_history,
+        get_snapshots, like_article, patch_article, post_article, post_snapshot, publish,
+        put_author, restore_article, search_articles, unlike_article,
+    },
+    assets::{
+        delete_asset, get_asset, get_asset_url, get_assets, get_orphans, post_asset,
+        post_complete_upload, post_upload,
     },
-    assets::{get_asset, post_asset},
     comment::{delete_comment, get_comments, patch_comment, post_comment},
     list::{
         delete_list, delete_list_article, get_list, get_lists, patch_list, post_list,
         put_list_article,
     },
+    newsletter::{get_issues, get_open, get_subscribers, post_confirm, post_subscribe},
+    search::{search, suggest},
     series::{
         delete_series, delete_series_article, get_series, get_serieses, patch_series, post_series,
         put_series_article,
     },
+    stream::stream,
     user::{
-        follow, get_feed, get_followers, get_following, get_user, get_user_lists,
-        get_user_serieses, get_users, patch_user, unfollow,
+        follow, get_feed, get_followers, get_following, get_notification_settings,
+        get_unsubscribe, get_user, get_user_lists, get_user_serieses, get_users,
+        patch_notification_settings, patch_user, post_unsubscribe, unfollow,
+    },
+    webhook::{
+        delete_webhook, get_deliveries, get_webhook, get_webhooks, patch_webhook, post_webhook,
+        redeliver,
     },
 };
 
@@ -50,9 +64,13 @@ pub mod assets;
 pub mod auth;
 pub mod comment;
 pub mod list;
+pub mod newsletter;
+pub mod search;
 pub mod series;
+pub mod stream;
 pub mod tags;
 pub mod user;
+pub mod webhook;
 
 pub fn router(state: AppState) -> Router<AppState> {
     let (prometheus_layer, metric_handle) = PrometheusMetricLayer::pair();
@@ -63,8 +81,23 @@ pub fn router(state: AppState) -> Router<AppState> {
                 .nest(
                     "/assets",
                     Router::new()
-                        .route("/", post(post_asset))
+                        .route(
+                            "/",
+                            post(post_asset)
+                                .layer(middleware::from_fn(auth_middleware))
+                                // Streamed through, storage enforces the upload limits
+                                .layer(DefaultBodyLimit::disable()),
+                        )
+                        .route(
+                            "/uploads",
+                            post(post_upload).layer(middleware::from_fn(auth_middleware)),
+                        )
+                        .route(
+                            "/uploads/:upload_id",
+                            post(post_complete_upload).layer(middleware::from_fn(auth_middleware)),
+                        )
                         .route("/:asset_name", get(get_asset))
+                        .route("/:asset_name/url", get(get_asset_url))
                         .layer(middleware::from_fn(storage_service_middleware)),
                 )
                 .merge(
@@ -80,18 +113,80 @@ pub fn router(state: AppState) -> Router<AppState> {
                         .route("/metrics", get(|| async move { metric_handle.render() }))
                         .layer(middleware::from_fn(auth_middleware)),
                 )
+                .nest(
+                    "/admin/assets",
+                    Router::new()
+                        .route("/orphans", get(get_orphans))
+                        .layer(middleware::from_fn_with_state(Role::Admin, role_middleware))
+                        .layer(middleware::from_fn(auth_middleware))
+                        .layer(middleware::from_fn(storage_service_middleware)),
+                )
                 .nest(
                     "/me",
                     Router::new()
                         .route("/", get(get_session))
                         .route("/feed", get(get_feed))
                         .route("/drafts", get(get_user_drafts))
+                        .route(
+                            "/notification-settings",
+                            get(get_notification_settings).patch(patch_notification_settings),
+                        )
+                        .nest(
+                            "/newsletter",
+                            Router::new()
+                                .route("/subscribers", get(get_subscribers))
+                                .route("/issues", get(get_issues)),
+                        )
+                        .nest(
+                            "/webhooks",
+                            Router::new()
+                                .route("/", get(get_webhooks).post(post_webhook))
+                                .route(
+                                    "/:webhook_id",
+                                    get(get_webhook).patch(patch_webhook).delete(delete_webhook),
+                                )
+                                .route("/:webhook_id/deliveries", get(get_deliveries))
+                                .route(
+                                    "/:webhook_id/deliveries/:delivery_id/redeliver",
+                                    post(redeliver),
+                                ),
+                        )
                         .layer(middleware::from_fn_with_state(
                             state.clone(),
                             auth_middleware,
                         ))
                         .layer(middleware::from_fn(resource_service_middleware)),
                 )
+                // Authenticates on its own, browsers pass the token as a query param
+                .route(
+                    "/me/stream",
+                    get(stream.layer(middleware::from_fn(resource_service_middleware))),
+                )
+                .nest(
+                    "/me/assets",
+                    Router::new()
+                        .route("/", get(get_assets))
+                        .route("/:asset_id", delete(delete_asset))
+                        .layer(middleware::from_fn(auth_middleware))
+                        .layer(middleware::from_fn(storage_service_middleware)),
+                )
+                .route(
+                    "/unsubscribe",
+                    get(get_unsubscribe).post(post_unsubscribe.layer(middleware::from_fn(
+                        resource_service_middleware,
+                    ))),
+                )
+                .nest(
+                    "/newsletter",
+                    Router::new()
+                        .route("/confirm", post(post_confirm))
+                        .route(
+                            "/unsubscribe",
+                            get(newsletter::get_unsubscribe).post(newsletter::post_unsubscribe),
+                        )
+                        .route("/opens/:send_id", get(get_open))
+                        .layer(middleware::from_fn(resource_service_middleware)),
+                )
                 .nest(
                     "/users",
                     Router::new()
@@ -111,6 +206,7 @@ pub fn router(state: AppState) -> Router<AppState> {
                                 .route("/articles", get(get_user_articles))
                                 .route("/series", get(get_user_serieses))
                                 .route("/lists", get(get_user_lists))
+                                .route("/newsletter", post(post_subscribe))
                                 // .route("/interests", get(get_user_articles))
                                 .route(
                                     "/follow",
@@ -190,6 +286,29 @@ pub fn router(state: AppState) -> Router<AppState> {
                                         auth_middleware,
                                     )),
                                 )
+                                .route(
+                                    "/diff",
+                                    get(get_diff).layer(middleware::from_fn_with_state(
+                                        state.clone(),
+                                        auth_middleware,
+                                    )),
+                                )
+                                .route(
+                                    "/restore",
+                                    post(restore_article).layer(middleware::from_fn_with_state(
+                                        state.clone(),
+                                        auth_middleware,
+                                    )),
+                                )
+                                .route(
+                                    "/snapshots",
+                                    get(get_snapshots).post(post_snapshot).layer(
+                                        middleware::from_fn_with_state(
+                                            state.clone(),
+                                            auth_middleware,
+                                        ),
+                                    ),
+                                )
                                 .route(
                                     "/publish",
                                     patch(publish).layer(middleware::from_fn_with_state(
@@ -225,6 +344,18 @@ pub fn router(state: AppState) -> Router<AppState> {
                         .route("/", get(get_tags))
                         .layer(middleware::from_fn(resource_service_middleware)),
                 )
+                .nest(
+                    "/search",
+                    Router::new()
+                        .route("/", get(search))
+                        .layer(middleware::from_fn(resource_service_middleware)),
+                )
+                .nest(
+                    "/suggest",
+                    Router::new()
+                        .route("/", get(suggest))
+                        .layer(middleware::from_fn(resource_service_middleware)),
+                )
                 .nest(
                     "/lists",
                     Router::new()
@@ -0,0 +1,236 @@
+use axum::{
+    extract::{Path, Query},
+    http::{
+        header::{CACHE_CONTROL, CONTENT_TYPE},
+        StatusCode,
+    },
+    response::{IntoResponse, Redirect, Response},
+    Extension, Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+use shared::{
+    configuration::CONFIG,
+    models::newsletter_model::{NewsletterIssue, NewsletterSubscriber},
+    newsletter::{
+        newsletter_service_client::NewsletterServiceClient, IssuesRequest, OpenRequest,
+        SubscribeRequest, SubscribersRequest, TokenRequest,
+    },
+    utils::jwt::AccessTokenPayload,
+};
+use tonic::transport::Channel;
+use tracing::{error, info};
+use validator::Validate;
+
+use crate::utils::{
+    mapper::code_to_statudecode,
+    params::{CursorPagination, PathParams, ResultPaging},
+};
+
+/// Transparent 1x1 GIF returned by the open tracking pixel
+const PIXEL: &[u8] = &[
+    0x47, 0x49, 0x46, 0x38, 0x39, 0x61, 0x01, 0x00, 0x01, 0x00, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0xff, 0xff, 0xff, 0x21, 0xf9, 0x04, 0x01, 0x00, 0x00, 0x00, 0x00, 0x2c, 0x00, 0x00, 0x00, 0x00,
+    0x01, 0x00, 0x01, 0x00, 0x00, 0x02, 0x02, 0x44, 0x01, 0x00, 0x3b,
+];
+
+#[derive(Debug, Validate, Deserialize)]
+pub struct SubscribeRequestBody {
+    #[validate(email)]
+    pub email: String,
+    pub locale: Option<String>,
+}
+
+pub async fn post_subscribe(
+    Extension(channel): Extension<Channel>,
+    Path(params): Path<PathParams>,
+    Json(payload): Json<SubscribeRequestBody>,
+) -> Response {
+    let username = match params.username {
+        Some(username) => username,
+        None => return (StatusCode::BAD_REQUEST).into_response(),
+    };
+
+    info!("Subscribe Newsletter Request {:?}", username);
+
+    if let Err(err) = payload.validate() {
+        return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
+    }
+
+    match NewsletterServiceClient::new(channel)
+        .subscribe(SubscribeRequest {
+            username,
+            email: payload.email,
+            locale: payload.locale,
+        })
+        .await
+    {
+        Ok(res) => (StatusCode::ACCEPTED, res.get_ref().message.to_owned()).into_response(),
+        Err(err) => {
+            error!("{:#?}", err);
+            let message = err.message().to_string();
+            let status_code = code_to_statudecode(err.code());
+            return (status_code, message).into_response();
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewsletterTokenQueryParams {
+    pub token: String,
+}
+
+pub async fn post_confirm(
+    Extension(channel): Extension<Channel>,
+    Query(query): Query<NewsletterTokenQueryParams>,
+) -> Response {
+    info!("Confirm Newsletter Request");
+
+    match NewsletterServiceClient::new(channel)
+        .confirm(TokenRequest { token: query.token })
+        .await
+    {
+        Ok(_) => (StatusCode::OK, "Subscribed").into_response(),
+        Err(err) => {
+            error!("{:#?}", err);
+            let message = err.message().to_string();
+            let status_code = code_to_statudecode(err.code());
+            return (status_code, message).into_response();
+        }
+    }
+}
+
+/// Same as the notification unsubscribe, GET only sends the reader to the
+/// client confirmation page and mail clients POST the one-click request.
+pub async fn get_unsubscribe(Query(query): Query<NewsletterTokenQueryParams>) -> Response {
+    let scheme = match CONFIG.client.ssl {
+        true => "https",
+        false => "http",
+    };
+    Redirect::to(&format!(
+        "{}://{}:{}/newsletter/unsubscribe?token={}",
+        scheme, CONFIG.client.host, CONFIG.client.port, query.token
+    ))
+    .into_response()
+}
+
+pub async fn post_unsubscribe(
+    Extension(channel): Extension<Channel>,
+    Query(query): Query<NewsletterTokenQueryParams>,
+) -> Response {
+    info!("Unsubscribe Newsletter Request");
+
+    match NewsletterServiceClient::new(channel)
+        .unsubscribe(TokenRequest { token: query.token })
+        .await
+    {
+        Ok(_) => (StatusCode::OK, "Unsubscribed").into_response(),
+        Err(err) => {
+            error!("{:#?}", err);
+            let message = err.message().to_string();
+            let status_code = code_to_statudecode(err.code());
+            return (status_code, message).into_response();
+        }
+    }
+}
+
+/// Tracking pixel, always answers with the image so mail clients never show
+/// a broken one
+pub async fn get_open(
+    Extension(channel): Extension<Channel>,
+    Path(params): Path<PathParams>,
+) -> Response {
+    if let Some(send_id) = params.send_id {
+        if let Err(err) = NewsletterServiceClient::new(channel)
+            .open(OpenRequest { send_id })
+            .await
+        {
+            error!("{:#?}", err);
+        }
+    }
+
+    (
+        StatusCode::OK,
+        [(CONTENT_TYPE, "image/gif"), (CACHE_CONTROL, "no-store")],
+        PIXEL,
+    )
+        .into_response()
+}
+
+pub async fn get_subscribers(
+    Extension(channel): Extension<Channel>,
+    Extension(user): Extension<AccessTokenPayload>,
+    Query(cursor): Query<CursorPagination>,
+) -> Response {
+    info!("Get Newsletter Subscribers Request {:?}", cursor);
+
+    match NewsletterServiceClient::new(channel)
+        .subscribers(SubscribersRequest {
+            user_id: user.user_id,
+            limit: cursor.limit,
+            cursor: cursor.cursor,
+        })
+        .await
+    {
+        Ok(res) => {
+            let res = res.get_ref();
+            (
+                StatusCode::OK,
+                Json(json!(ResultPaging::<NewsletterSubscriber> {
+                    next_cursor: res.next_cursor.to_owned(),
+                    items: res
+                        .subscribers
+                        .iter()
+                        .map(|subscriber| NewsletterSubscriber::from(subscriber))
+                        .collect()
+                })),
+            )
+                .into_response()
+        }
+        Err(err) => {
+            error!("{:#?}", err);
+            let message = err.message().to_string();
+            let status_code = code_to_statudecode(err.code());
+            return (status_code, message).into_response();
+        }
+    }
+}
+
+pub async fn get_issues(
+    Extension(channel): Extension<Channel>,
+    Extension(user): Extension<AccessTokenPayload>,
+    Query(cursor): Query<CursorPagination>,
+) -> Response {
+    info!("Get Newsletter Issues Request {:?}", cursor);
+
+    match NewsletterServiceClient::new(channel)
+        .issues(IssuesRequest {
+            user_id: user.user_id,
+            limit: cursor.limit,
+            cursor: cursor.cursor,
+        })
+        .await
+    {
+        Ok(res) => {
+            let res = res.get_ref();
+            (
+                StatusCode::OK,
+                Json(json!(ResultPaging::<NewsletterIssue> {
+                    next_cursor: res.next_cursor.to_owned(),
+                    items: res
+                        .issues
+                        .iter()
+                        .map(|issue| NewsletterIssue::from(issue))
+                        .collect()
+                })),
+            )
+                .into_response()
+        }
+        Err(err) => {
+            error!("{:#?}", err);
+            let message = err.message().to_string();
+            let status_code = code_to_statudecode(err.code());
+            return (status_code, message).into_response();
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use axum_extra::{
+    extract::Query,
+    headers::{authorization::Bearer, Authorization, HeaderMapExt},
+};
+use serde::{Deserialize, Serialize};
+use serde_aux::prelude::default_i64;
+use serde_json::json;
+use shared::{
+    models::{
+        enums::{SearchType, SuggestType},
+        list_model::List,
+        search_model::{SearchCounts, SearchFilter, Suggestion, TagFacet},
+        series_model::Series,
+        tag_model::Tag,
+        user_model::FullUser,
+    },
+    search::{self, search_service_client::SearchServiceClient, SearchRequest, SuggestRequest},
+    utils::jwt::{AccessToken, JWT},
+};
+use tonic::{codec::CompressionEncoding, transport::Channel};
+use tracing::{error, info};
+
+use crate::{
+    application::AppState,
+    routes::article::SearchedArticle,
+    utils::{mapper::code_to_statudecode, params::ResultPaging},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct SearchQueryParams {
+    /// Every group when empty
+    #[serde(default)]
+    types: Vec<SearchType>,
+    articles_cursor: Option<String>,
+    users_cursor: Option<String>,
+    tags_cursor: Option<String>,
+    lists_cursor: Option<String>,
+    series_cursor: Option<String>,
+    #[serde(default = "default_i64::<10>")]
+    limit: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SuggestQueryParams {
+    #[serde(rename = "type")]
+    suggest_type: SuggestType,
+    q: String,
+    #[serde(default = "default_i64::<5>")]
+    limit: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Facets {
+    #[serde(flatten)]
+    counts: SearchCounts,
+    article_tags: Vec<TagFacet>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchResults {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    articles: Option<ResultPaging<SearchedArticle>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    users: Option<ResultPaging<FullUser>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tags: Option<ResultPaging<Tag>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lists: Option<ResultPaging<List>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    series: Option<ResultPaging<Series>>,
+    facets: Option<Facets>,
+}
+
+impl From<&search::SearchResponse> for SearchResults {
+    fn from(value: &search::SearchResponse) -> Self {
+        SearchResults {
+            articles: value.articles.as_ref().map(|articles| ResultPaging {
+                items: articles
+                    .articles
+                    .iter()
+                    .map(|article| SearchedArticle::from(article))
+                    .collect(),
+                next_cursor: articles.next_cursor.to_owned(),
+            }),
+            users: value.users.as_ref().map(|users| ResultPaging {
+                items: users
+                    .users
+                    .iter()
+                    .map(|user| FullUser::from(user))
+                    .collect(),
+                next_cursor: users.next_cursor.to_owned(),
+            }),
+            tags: value.tags.as_ref().map(|tags| ResultPaging {
+                items: tags.tags.iter().map(|tag| Tag::from(tag)).collect(),
+                next_cursor: tags.next_cursor.to_owned(),
+            }),
+            lists: value.lists.as_ref().map(|lists| ResultPaging {
+                items: lists.lists.iter().map(|list| List::from(list)).collect(),
+                next_cursor: lists.next_cursor.to_owned(),
+            }),
+            series: value.series.as_ref().map(|series| ResultPaging {
+                items: series
+                    .series
+                    .iter()
+                    .map(|series| Series::from(series))
+                    .collect(),
+                next_cursor: series.next_cursor.to_owned(),
+            }),
+            facets: value.facets.as_ref().map(|facets| Facets {
+                counts: SearchCounts {
+                    articles: facets.articles,
+                    users: facets.users,
+                    tags: facets.tags,
+                    lists: facets.lists,
+                    series: facets.series,
+                },
+                article_tags: facets
+                    .article_tags
+                    .iter()
+                    .map(|tag| TagFacet::from(tag))
+                    .collect(),
+            }),
+        }
+    }
+}
+
+pub async fn search(
+    headers: HeaderMap,
+    Extension(channel): Extension<Channel>,
+    Query(filter): Query<SearchFilter>,
+    Query(params): Query<SearchQueryParams>,
+    State(_state): State<AppState>,
+) -> Response {
+    info!("Search Request {:?} {:?}", filter, params);
+    let by_user = headers
+        .typed_get::<Authorization<Bearer>>()
+        .map(|token| {
+            AccessToken::validate(token.token())
+                .ok()
+                .map(|token_payload| token_payload.payload.user_id.to_owned())
+        })
+        .flatten();
+
+    match SearchServiceClient::new(channel)
+        .accept_compressed(CompressionEncoding::Gzip)
+        .max_decoding_message_size(50 * 1024 * 1024)
+        .search(SearchRequest {
+            filter: Some(search::SearchFilter::from(&filter)),
+            limit: params.limit,
+            types: params
+                .types
+                .into_iter()
+                .map(|search_type| search::SearchType::from(search_type) as i32)
+                .collect(),
+            by_user,
+            articles_cursor: params.articles_cursor,
+            users_cursor: params.users_cursor,
+            tags_cursor: params.tags_cursor,
+            lists_cursor: params.lists_cursor,
+            series_cursor: params.series_cursor,
+        })
+        .await
+    {
+        Ok(res) => (
+            StatusCode::OK,
+            Json(json!(SearchResults::from(res.get_ref()))),
+        )
+            .into_response(),
+        Err(err) => {
+            error!("{:?}", err);
+            let message = err.message().to_string();
+            let status_code = code_to_statudecode(err.code());
+            (status_code, message).into_response()
+        }
+    }
+}
+
+pub async fn suggest(
+    Extension(channel): Extension<Channel>,
+    Query(params): Query<SuggestQueryParams>,
+    State(_state): State<AppState>,
+) -> Response {
+    info!("Suggest Request {:?}", params);
+
+    match SearchServiceClient::new(channel)
+        .suggest(SuggestRequest {
+            suggest_type: search::SuggestType::from(params.suggest_type) as i32,
+            query: params.q,
+            limit: params.limit,
+        })
+        .await
+    {
+        Ok(res) => (
+            StatusCode::OK,
+            Json(json!(res
+                .get_ref()
+                .suggestions
+                .iter()
+                .map(|suggestion| Suggestion::from(suggestion))
+                .collect::<Vec<Suggestion>>())),
+        )
+            .into_response(),
+        Err(err) => {
+            error!("{:?}", err);
+            let message = err.message().to_string();
+            let status_code = code_to_statudecode(err.code());
+            (status_code, message).into_response()
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use std::{convert::Infallible, time::Duration};
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
+    http::{HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    Extension,
+};
+use axum_extra::headers::{authorization::Bearer, Authorization, HeaderMapExt};
+use serde::Deserialize;
+use serde_json::json;
+use shared::{
+    configuration::CONFIG,
+    utils::jwt::{AccessToken, JWT},
+};
+use tonic::transport::Channel;
+use tracing::{error, info, warn};
+
+use crate::{
+    application::AppState,
+    events::{EventStream, Outgoing, ResourceAccess, Subscription},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct StreamQueryParams {
+    /// Comma separated, e.g. `article:ID,series:ID`
+    pub topics: Option<String>,
+    pub last_event_id: Option<String>,
+    /// Browsers can not set headers on EventSource and WebSocket requests
+    pub access_token: Option<String>,
+}
+
+/// Commands accepted from WebSocket clients
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum StreamCommand {
+    Subscribe(Vec<String>),
+    Unsubscribe(Vec<String>),
+}
+
+pub async fn stream(
+    Extension(channel): Extension<Channel>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<StreamQueryParams>,
+    ws: Option<WebSocketUpgrade>,
+) -> Response {
+    let token = match headers.typed_get::<Authorization<Bearer>>() {
+        Some(token) => token.token().to_owned(),
+        None => match query.access_token {
+            Some(token) => token,
+            None => return (StatusCode::BAD_REQUEST, "No token").into_response(),
+        },
+    };
+
+    let user = match AccessToken::validate(&token) {
+        Ok(token_payload) => token_payload.payload,
+        Err(error) => {
+            error!("Unable to validate token: {:#?}", error);
+            return (StatusCode::UNAUTHORIZED, "Verification failed").into_response();
+        }
+    };
+
+    let last_event_id = headers
+        .get("last-event-id")
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_owned())
+        .or(query.last_event_id);
+
+    let access = ResourceAccess(channel);
+
+    let subscription = Subscription::new(
+        &user.user_id,
+        query
+            .topics
+            .unwrap_or_default()
+            .split(',')
+            .map(|topic| topic.trim().to_owned())
+            .filter(|topic| !topic.is_empty()),
+        &access,
+    )
+    .await;
+
+    info!("Stream Request {:?} {:?}", user.user_id, subscription);
+
+    let events = state
+        .events
+        .subscribe(subscription, last_event_id.as_deref());
+
+    match ws {
+        Some(ws) => ws.on_upgrade(move |socket| websocket(socket, events, access)),
+        None => {
+            let stream = futures::stream::unfold(events, |mut events| async move {
+                let event = match events.next().await? {
+                    Outgoing::Event(event) => Event::default()
+                        .id(&event.id)
+                        .event(event.event)
+                        .data(event.data.to_string()),
+                    Outgoing::Reset => Event::default().event("reset").data(""),
+                };
+                Some((Ok::<_, Infallible>(event), events))
+            });
+
+            Sse::new(stream)
+                .keep_alive(
+                    KeepAlive::new().interval(Duration::from_secs(CONFIG.stream.keep_alive)),
+                )
+                .into_response()
+        }
+    }
+}
+
+async fn websocket(mut socket: WebSocket, mut events: EventStream, access: ResourceAccess) {
+    loop {
+        tokio::select! {
+            outgoing = events.next() => {
+                let message = match outgoing {
+                    Some(Outgoing::Event(event)) => json!({
+                        "id": event.id,
+                        "event": event.event,
+                        "data": event.data,
+                    }),
+                    Some(Outgoing::Reset) => json!({ "event": "reset" }),
+                    None => break,
+                };
+                if socket.send(Message::Text(message.to_string())).await.is_err() {
+                    break;
+                }
+            }
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Text(text))) => {
+                    match serde_json::from_str::<StreamCommand>(&text) {
+                        Ok(StreamCommand::Subscribe(topics)) => {
+                            for topic in topics {
+                                if !events.subscription.subscribe(topic.to_owned(), &access).await {
+                                    warn!("Topic not allowed {:?}", topic);
+                                }
+                            }
+                        }
+                        Ok(StreamCommand::Unsubscribe(topics)) => {
+                            for topic in topics {
+                                events.subscription.unsubscribe(&topic);
+                            }
+                        }
+                        Err(err) => warn!("Invalid stream command: {}", err),
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            }
+        }
+    }
+}
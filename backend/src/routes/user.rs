@@ -1,28 +1,34 @@
 use axum::{
     extract::{Path, Query, Request, State},
     http::{HeaderMap, StatusCode},
-    response::{IntoResponse, Response},
+    response::{IntoResponse, Redirect, Response},
     Extension, Json,
 };
 use axum_extra::headers::{authorization::Bearer, Authorization, HeaderMapExt};
 use serde::Deserialize;
 use serde_json::json;
 use shared::{
+    common,
+    configuration::CONFIG,
     models::{
         article_model::FullArticle,
+        enums::{DigestFrequency, NotificationEvent},
         list_model::List,
+        notification_model::NotificationSettings,
         series_model::Series,
         user_model::{FullUser, User},
     },
     user::{
         user_service_client::UserServiceClient, ArticlesRequest, FeedRequest, FollowRequest,
-        FollowersRequest, FollowingRequest, GetRequest, ListsRequest, SearchRequest,
-        SeriesesRequest, UnfollowRequest, UpdateRequest,
+        FollowersRequest, FollowingRequest, GetRequest, ListsRequest, NotificationSettingsRequest,
+        SearchRequest, SeriesesRequest, UnfollowRequest, UpdateNotificationSetting,
+        UpdateNotificationSettingsRequest, UpdateRequest,
     },
-    utils::jwt::{AccessToken, AccessTokenPayload, JWT},
+    utils::jwt::{AccessToken, AccessTokenPayload, UnsubscribeToken, JWT},
 };
 use tonic::transport::Channel;
 use tracing::{error, info};
+use url::Url;
 
 use crate::{
     application::AppState,
@@ -347,6 +353,7 @@ pub struct PatchUserRequestBody {
     pub image: Option<String>,
     pub bio: Option<String>,
     pub urls: Option<Vec<String>>,
+    pub locale: Option<String>,
 }
 
 pub async fn patch_user(
@@ -363,6 +370,7 @@ pub async fn patch_user(
             image: payload.image,
             bio: payload.bio,
             urls: payload.urls.unwrap_or_default(),
+            locale: payload.locale,
         })
         .await
     {
@@ -588,3 +596,151 @@ pub async fn get_feed(
 //     };
 //     unimplemented!()
 // }
+
+pub async fn get_notification_settings(
+    Extension(channel): Extension<Channel>,
+    Extension(user): Extension<AccessTokenPayload>,
+) -> Response {
+    info!("Get Notification Settings Request {:?}", user);
+
+    match UserServiceClient::new(channel)
+        .notification_settings(NotificationSettingsRequest {
+            user_id: user.user_id,
+        })
+        .await
+    {
+        Ok(res) => (
+            StatusCode::OK,
+            Json(json!(NotificationSettings::from(res.get_ref()))),
+        )
+            .into_response(),
+        Err(err) => {
+            error!("{:#?}", err);
+            let message = err.message().to_string();
+            let status_code = code_to_statudecode(err.code());
+            return (status_code, message).into_response();
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PatchNotificationSettingBody {
+    pub event: NotificationEvent,
+    pub in_app: Option<bool>,
+    pub email: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PatchNotificationSettingsBody {
+    #[serde(default)]
+    pub settings: Vec<PatchNotificationSettingBody>,
+    pub digest_frequency: Option<DigestFrequency>,
+}
+
+pub async fn patch_notification_settings(
+    Extension(channel): Extension<Channel>,
+    Extension(user): Extension<AccessTokenPayload>,
+    Json(payload): Json<PatchNotificationSettingsBody>,
+) -> Response {
+    info!(
+        "Patch Notification Settings Request {:?} {:?}",
+        user, payload
+    );
+
+    match UserServiceClient::new(channel)
+        .update_notification_settings(UpdateNotificationSettingsRequest {
+            user_id: user.user_id,
+            settings: payload
+                .settings
+                .iter()
+                .map(|setting| UpdateNotificationSetting {
+                    event: common::NotificationEvent::from(setting.event) as i32,
+                    in_app: setting.in_app,
+                    email: setting.email,
+                })
+                .collect(),
+            digest_frequency: payload
+                .digest_frequency
+                .map(|frequency| common::DigestFrequency::from(frequency) as i32),
+        })
+        .await
+    {
+        Ok(res) => (
+            StatusCode::OK,
+            Json(json!(NotificationSettings::from(res.get_ref()))),
+        )
+            .into_response(),
+        Err(err) => {
+            error!("{:#?}", err);
+            let message = err.message().to_string();
+            let status_code = code_to_statudecode(err.code());
+            return (status_code, message).into_response();
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UnsubscribeQueryParams {
+    pub token: String,
+}
+
+/// RFC 8058 one-click unsubscribe, authenticated by the signed token only.
+/// Mail clients POST `List-Unsubscribe=One-Click` here, so GET must not
+/// change anything and only sends the user to the client confirmation page.
+pub async fn get_unsubscribe(Query(query): Query<UnsubscribeQueryParams>) -> Response {
+    let scheme = match CONFIG.client.ssl {
+        true => "https",
+        false => "http",
+    };
+    let mut url = match Url::parse(&format!(
+        "{}://{}:{}/unsubscribe",
+        scheme, CONFIG.client.host, CONFIG.client.port
+    )) {
+        Ok(url) => url,
+        Err(err) => {
+            error!("{:?}", err);
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Something went wrong").into_response();
+        }
+    };
+
+    // The token comes from the request, it may not add parameters of its own
+    url.query_pairs_mut().append_pair("token", &query.token);
+
+    Redirect::to(url.as_str()).into_response()
+}
+
+pub async fn post_unsubscribe(
+    Extension(channel): Extension<Channel>,
+    Query(query): Query<UnsubscribeQueryParams>,
+) -> Response {
+    let claims = match UnsubscribeToken::validate(&query.token) {
+        Ok(claims) => claims,
+        Err(err) => {
+            error!("{:#?}", err);
+            return (StatusCode::BAD_REQUEST, "Invalid unsubscribe link").into_response();
+        }
+    };
+
+    info!("Unsubscribe Request {:?}", claims.payload);
+
+    match UserServiceClient::new(channel)
+        .update_notification_settings(UpdateNotificationSettingsRequest {
+            user_id: claims.payload.user_id,
+            settings: vec![UpdateNotificationSetting {
+                event: common::NotificationEvent::from(claims.payload.event) as i32,
+                in_app: None,
+                email: Some(false),
+            }],
+            digest_frequency: None,
+        })
+        .await
+    {
+        Ok(_) => (StatusCode::OK, "Unsubscribed").into_response(),
+        Err(err) => {
+            error!("{:#?}", err);
+            let message = err.message().to_string();
+            let status_code = code_to_statudecode(err.code());
+            return (status_code, message).into_response();
+        }
+    }
+}
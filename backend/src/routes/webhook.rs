@@ -0,0 +1,288 @@
+use axum::{
+    extract::{Path, Query},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+use shared::{
+    models::{
+        enums::WebhookEvent,
+        webhook_model::{Webhook, WebhookDelivery},
+    },
+    utils::jwt::AccessTokenPayload,
+    webhook::{
+        self, webhook_service_client::WebhookServiceClient, CreateRequest, DeleteRequest,
+        DeliveriesRequest, GetAllRequest, GetRequest, RedeliverRequest, UpdateRequest,
+    },
+};
+use tonic::transport::Channel;
+use tracing::{error, info};
+
+use crate::utils::{
+    mapper::code_to_statudecode,
+    params::{CursorPagination, PathParams, ResultPaging},
+};
+
+pub async fn get_webhooks(
+    Extension(channel): Extension<Channel>,
+    Extension(user): Extension<AccessTokenPayload>,
+) -> Response {
+    info!("Get Webhooks Request {:?}", user);
+
+    match WebhookServiceClient::new(channel)
+        .get_all(GetAllRequest {
+            user_id: user.user_id,
+        })
+        .await
+    {
+        Ok(res) => (
+            StatusCode::OK,
+            Json(json!(res
+                .get_ref()
+                .webhooks
+                .iter()
+                .map(|webhook| Webhook::from(webhook))
+                .collect::<Vec<Webhook>>())),
+        )
+            .into_response(),
+        Err(err) => {
+            error!("{:#?}", err);
+            let message = err.message().to_string();
+            let status_code = code_to_statudecode(err.code());
+            return (status_code, message).into_response();
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PostWebhookRequestBody {
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    #[serde(default)]
+    pub global: bool,
+}
+
+pub async fn post_webhook(
+    Extension(channel): Extension<Channel>,
+    Extension(user): Extension<AccessTokenPayload>,
+    Json(payload): Json<PostWebhookRequestBody>,
+) -> Response {
+    info!("Post Webhook Request {:?}", payload);
+
+    match WebhookServiceClient::new(channel)
+        .create(CreateRequest {
+            user_id: user.user_id,
+            url: payload.url,
+            events: payload
+                .events
+                .into_iter()
+                .map(|event| webhook::WebhookEvent::from(event) as i32)
+                .collect(),
+            global: payload.global,
+        })
+        .await
+    {
+        Ok(res) => (
+            StatusCode::CREATED,
+            Json(json!(Webhook::from(res.get_ref()))),
+        )
+            .into_response(),
+        Err(err) => {
+            error!("{:#?}", err);
+            let message = err.message().to_string();
+            let status_code = code_to_statudecode(err.code());
+            return (status_code, message).into_response();
+        }
+    }
+}
+
+pub async fn get_webhook(
+    Extension(channel): Extension<Channel>,
+    Extension(user): Extension<AccessTokenPayload>,
+    Path(params): Path<PathParams>,
+) -> Response {
+    let webhook_id = match params.webhook_id {
+        Some(webhook_id) => webhook_id,
+        None => return (StatusCode::BAD_REQUEST).into_response(),
+    };
+
+    info!("Get Webhook Request {:?}", webhook_id);
+
+    match WebhookServiceClient::new(channel)
+        .get(GetRequest {
+            user_id: user.user_id,
+            webhook_id,
+        })
+        .await
+    {
+        Ok(res) => (StatusCode::OK, Json(json!(Webhook::from(res.get_ref())))).into_response(),
+        Err(err) => {
+            error!("{:#?}", err);
+            let message = err.message().to_string();
+            let status_code = code_to_statudecode(err.code());
+            return (status_code, message).into_response();
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PatchWebhookRequestBody {
+    pub url: Option<String>,
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+    pub active: Option<bool>,
+}
+
+pub async fn patch_webhook(
+    Extension(channel): Extension<Channel>,
+    Extension(user): Extension<AccessTokenPayload>,
+    Path(params): Path<PathParams>,
+    Json(payload): Json<PatchWebhookRequestBody>,
+) -> Response {
+    let webhook_id = match params.webhook_id {
+        Some(webhook_id) => webhook_id,
+        None => return (StatusCode::BAD_REQUEST).into_response(),
+    };
+
+    info!("Patch Webhook Request {:?} {:?}", webhook_id, payload);
+
+    match WebhookServiceClient::new(channel)
+        .update(UpdateRequest {
+            user_id: user.user_id,
+            webhook_id,
+            url: payload.url,
+            events: payload
+                .events
+                .into_iter()
+                .map(|event| webhook::WebhookEvent::from(event) as i32)
+                .collect(),
+            active: payload.active,
+        })
+        .await
+    {
+        Ok(res) => (StatusCode::OK, Json(json!(Webhook::from(res.get_ref())))).into_response(),
+        Err(err) => {
+            error!("{:#?}", err);
+            let message = err.message().to_string();
+            let status_code = code_to_statudecode(err.code());
+            return (status_code, message).into_response();
+        }
+    }
+}
+
+pub async fn delete_webhook(
+    Extension(channel): Extension<Channel>,
+    Extension(user): Extension<AccessTokenPayload>,
+    Path(params): Path<PathParams>,
+) -> Response {
+    let webhook_id = match params.webhook_id {
+        Some(webhook_id) => webhook_id,
+        None => return (StatusCode::BAD_REQUEST).into_response(),
+    };
+
+    info!("Delete Webhook Request {:?}", webhook_id);
+
+    match WebhookServiceClient::new(channel)
+        .delete(DeleteRequest {
+            user_id: user.user_id,
+            webhook_id,
+        })
+        .await
+    {
+        Ok(res) => (StatusCode::OK, res.get_ref().message.to_owned()).into_response(),
+        Err(err) => {
+            error!("{:#?}", err);
+            let message = err.message().to_string();
+            let status_code = code_to_statudecode(err.code());
+            return (status_code, message).into_response();
+        }
+    }
+}
+
+pub async fn get_deliveries(
+    Extension(channel): Extension<Channel>,
+    Extension(user): Extension<AccessTokenPayload>,
+    Path(params): Path<PathParams>,
+    Query(cursor): Query<CursorPagination>,
+) -> Response {
+    let webhook_id = match params.webhook_id {
+        Some(webhook_id) => webhook_id,
+        None => return (StatusCode::BAD_REQUEST).into_response(),
+    };
+
+    info!(
+        "Get Webhook Deliveries Request {:?} {:?}",
+        webhook_id, cursor
+    );
+
+    match WebhookServiceClient::new(channel)
+        .deliveries(DeliveriesRequest {
+            user_id: user.user_id,
+            webhook_id,
+            limit: cursor.limit,
+            cursor: cursor.cursor,
+        })
+        .await
+    {
+        Ok(res) => {
+            let res = res.get_ref();
+            (
+                StatusCode::OK,
+                Json(json!(ResultPaging::<WebhookDelivery> {
+                    next_cursor: res.next_cursor.to_owned(),
+                    items: res
+                        .deliveries
+                        .iter()
+                        .map(|delivery| WebhookDelivery::from(delivery))
+                        .collect()
+                })),
+            )
+                .into_response()
+        }
+        Err(err) => {
+            error!("{:#?}", err);
+            let message = err.message().to_string();
+            let status_code = code_to_statudecode(err.code());
+            return (status_code, message).into_response();
+        }
+    }
+}
+
+pub async fn redeliver(
+    Extension(channel): Extension<Channel>,
+    Extension(user): Extension<AccessTokenPayload>,
+    Path(params): Path<PathParams>,
+) -> Response {
+    let (webhook_id, delivery_id) = match (params.webhook_id, params.delivery_id) {
+        (Some(webhook_id), Some(delivery_id)) => (webhook_id, delivery_id),
+        _ => return (StatusCode::BAD_REQUEST).into_response(),
+    };
+
+    info!(
+        "Redeliver Webhook Request {:?} {:?}",
+        webhook_id, delivery_id
+    );
+
+    match WebhookServiceClient::new(channel)
+        .redeliver(RedeliverRequest {
+            user_id: user.user_id,
+            webhook_id,
+            delivery_id,
+        })
+        .await
+    {
+        Ok(res) => (
+            StatusCode::ACCEPTED,
+            Json(json!(WebhookDelivery::from(res.get_ref()))),
+        )
+            .into_response(),
+        Err(err) => {
+            error!("{:#?}", err);
+            let message = err.message().to_string();
+            let status_code = code_to_statudecode(err.code());
+            return (status_code, message).into_response();
+        }
+    }
+}
@@ -0,0 +1,84 @@
+/// A satisfiable byte range, both ends inclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl ByteRange {
+    pub fn length(&self) -> u64 {
+        self.end - self.start + 1
+    }
+
+    pub fn content_range(&self, size: u64) -> String {
+        format!("bytes {}-{}/{}", self.start, self.end, size)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeRequest {
+    /// No usable range, the whole object is served
+    Full,
+    Partial(ByteRange),
+    Unsatisfiable,
+}
+
+/// Parses a `Range` header for an object of `size` bytes. Only single
+/// `bytes` ranges are served partially, anything else the RFC allows us to
+/// ignore gets the whole object.
+pub fn parse_range(header: &str, size: u64) -> RangeRequest {
+    let spec = match header.trim().strip_prefix("bytes=") {
+        Some(spec) if !spec.contains(',') => spec.trim(),
+        _ => return RangeRequest::Full,
+    };
+
+    let (start, end) = match spec.split_once('-') {
+        Some((start, end)) => (start.trim(), end.trim()),
+        None => return RangeRequest::Full,
+    };
+
+    // `bytes=-500` asks for the last 500 bytes
+    if start.is_empty() {
+        return match end.parse::<u64>() {
+            Ok(0) => RangeRequest::Unsatisfiable,
+            Ok(_) if size == 0 => RangeRequest::Unsatisfiable,
+            Ok(suffix) => RangeRequest::Partial(ByteRange {
+                start: size.saturating_sub(suffix),
+                end: size - 1,
+            }),
+            Err(_) => RangeRequest::Full,
+        };
+    }
+
+    let start = match start.parse::<u64>() {
+        Ok(start) => start,
+        Err(_) => return RangeRequest::Full,
+    };
+
+    let end = match end.is_empty() {
+        true => u64::MAX,
+        false => match end.parse::<u64>() {
+            Ok(end) if end >= start => end,
+            _ => return RangeRequest::Full,
+        },
+    };
+
+    if start >= size {
+        return RangeRequest::Unsatisfiable;
+    }
+
+    RangeRequest::Partial(ByteRange {
+        start,
+        end: end.min(size - 1),
+    })
+}
+
+/// Whether an `If-None-Match` header matches `etag`. The comparison is weak,
+/// as the RFC asks for this header.
+pub fn etag_matches(header: &str, etag: &str) -> bool {
+    let etag = etag.trim_start_matches("W/");
+    header
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
@@ -1,3 +1,4 @@
+pub mod caching;
 pub mod cookie;
 pub mod errors;
 pub mod mapper;
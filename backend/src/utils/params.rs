@@ -38,4 +38,9 @@ pub struct PathParams {
     pub list_id: Option<String>,
     pub username: Option<String>,
     pub asset_name: Option<String>,
+    pub asset_id: Option<String>,
+    pub upload_id: Option<String>,
+    pub webhook_id: Option<String>,
+    pub delivery_id: Option<String>,
+    pub send_id: Option<String>,
 }
@@ -0,0 +1,70 @@
+use backend::utils::caching::{etag_matches, parse_range, ByteRange, RangeRequest};
+
+#[test]
+fn parses_single_ranges() {
+    assert_eq!(
+        parse_range("bytes=0-99", 1000),
+        RangeRequest::Partial(ByteRange { start: 0, end: 99 })
+    );
+    assert_eq!(
+        parse_range("bytes=900-", 1000),
+        RangeRequest::Partial(ByteRange {
+            start: 900,
+            end: 999
+        })
+    );
+    assert_eq!(
+        parse_range("bytes=-100", 1000),
+        RangeRequest::Partial(ByteRange {
+            start: 900,
+            end: 999
+        })
+    );
+    assert_eq!(
+        parse_range("bytes=500-5000", 1000),
+        RangeRequest::Partial(ByteRange {
+            start: 500,
+            end: 999
+        })
+    );
+}
+
+#[test]
+fn suffix_longer_than_the_object_covers_all_of_it() {
+    assert_eq!(
+        parse_range("bytes=-5000", 1000),
+        RangeRequest::Partial(ByteRange { start: 0, end: 999 })
+    );
+}
+
+#[test]
+fn rejects_ranges_past_the_end() {
+    assert_eq!(parse_range("bytes=1000-", 1000), RangeRequest::Unsatisfiable);
+    assert_eq!(parse_range("bytes=-0", 1000), RangeRequest::Unsatisfiable);
+    assert_eq!(parse_range("bytes=-10", 0), RangeRequest::Unsatisfiable);
+}
+
+#[test]
+fn ignores_what_it_does_not_serve() {
+    assert_eq!(parse_range("bytes=0-1,5-6", 1000), RangeRequest::Full);
+    assert_eq!(parse_range("items=0-1", 1000), RangeRequest::Full);
+    assert_eq!(parse_range("bytes=10-5", 1000), RangeRequest::Full);
+    assert_eq!(parse_range("bytes=a-b", 1000), RangeRequest::Full);
+}
+
+#[test]
+fn content_range_includes_the_size() {
+    let range = ByteRange { start: 0, end: 99 };
+
+    assert_eq!(range.length(), 100);
+    assert_eq!(range.content_range(1000), "bytes 0-99/1000");
+}
+
+#[test]
+fn matches_etags_weakly() {
+    assert!(etag_matches("\"abc\"", "\"abc\""));
+    assert!(etag_matches("W/\"abc\"", "\"abc\""));
+    assert!(etag_matches("\"x\", \"abc\"", "\"abc\""));
+    assert!(etag_matches("*", "\"abc\""));
+    assert!(!etag_matches("\"abcd\"", "\"abc\""));
+}
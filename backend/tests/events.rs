@@ -0,0 +1,84 @@
+use async_trait::async_trait;
+use backend::events::{EventHub, Outgoing, StreamEvent, Subscription, TopicAccess};
+use serde_json::json;
+
+/// Grants access to the listed topics only
+struct Readable(&'static [&'static str]);
+
+#[async_trait]
+impl TopicAccess for Readable {
+    async fn can_read(&self, _user_id: &str, kind: &str, id: &str) -> bool {
+        self.0.contains(&format!("{}:{}", kind, id).as_str())
+    }
+}
+
+fn event(id: &str, topic: &str) -> StreamEvent {
+    StreamEvent {
+        id: id.to_owned(),
+        event: "comment.created",
+        topics: vec![topic.to_owned()],
+        data: json!({}),
+    }
+}
+
+#[tokio::test]
+async fn subscription_rejects_other_users() {
+    let access = Readable(&["article:1"]);
+    let mut subscription = Subscription::new("a", vec!["user:b".to_owned()], &access).await;
+
+    assert!(!subscription.subscribe("user:b".to_owned(), &access).await);
+    assert!(
+        subscription
+            .subscribe("article:1".to_owned(), &access)
+            .await
+    );
+    assert!(subscription.matches(&event("1", "user:a")));
+    assert!(!subscription.matches(&event("2", "user:b")));
+}
+
+#[tokio::test]
+async fn subscription_drops_unreadable_topics() {
+    let access = Readable(&["article:1", "list:1"]);
+    let mut subscription = Subscription::new(
+        "a",
+        vec!["article:1".to_owned(), "article:2".to_owned()],
+        &access,
+    )
+    .await;
+
+    assert!(subscription.matches(&event("1", "article:1")));
+    assert!(!subscription.matches(&event("2", "article:2")));
+    assert!(subscription.subscribe("list:1".to_owned(), &access).await);
+    assert!(!subscription.subscribe("list:2".to_owned(), &access).await);
+    assert!(!subscription.subscribe("series:".to_owned(), &access).await);
+}
+
+#[tokio::test]
+async fn stream_resumes_after_last_event_id() {
+    let hub = EventHub::new(10);
+    hub.publish(event("1", "article:1"));
+    hub.publish(event("2", "article:2"));
+    hub.publish(event("3", "article:1"));
+
+    let subscription =
+        Subscription::new("a", vec!["article:1".to_owned()], &Readable(&["article:1"])).await;
+    let mut stream = hub.subscribe(subscription, Some("1"));
+
+    match stream.next().await {
+        Some(Outgoing::Event(event)) => assert_eq!(event.id, "3"),
+        other => panic!("Unexpected {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn stream_resets_on_unknown_event_id() {
+    let hub = EventHub::new(1);
+    hub.publish(event("1", "article:1"));
+    hub.publish(event("2", "article:1"));
+
+    let subscription =
+        Subscription::new("a", vec!["article:1".to_owned()], &Readable(&["article:1"])).await;
+    let mut stream = hub.subscribe(subscription, Some("1"));
+
+    assert!(matches!(stream.next().await, Some(Outgoing::Reset)));
+}
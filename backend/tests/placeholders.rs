@@ -0,0 +1,126 @@
+use std::sync::Arc;
+
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+    routing::get,
+    Extension, Router,
+};
+use axum_extra::extract::cookie::Key;
+use backend::{application::State, events::EventHub, routes::user::get_user};
+use shared::{
+    common::{
+        FullArticles, FullUser, FullUsers, ImagePlaceholder, Lists, MessageResponse,
+        NotificationSettings, Serieses, User,
+    },
+    user::{
+        user_service_server::{UserService, UserServiceServer},
+        ArticlesRequest, DeleteRequest, FeedRequest, FollowRequest, FollowersRequest,
+        FollowingRequest, GetRequest, ListsRequest, NotificationSettingsRequest, SearchRequest,
+        SeriesesRequest, UnfollowRequest, UpdateNotificationSettingsRequest, UpdateRequest,
+    },
+};
+use tokio::net::TcpListener;
+use tonic::{
+    transport::{Endpoint, Server},
+    Response, Status,
+};
+use tower::ServiceExt;
+
+/// Answers `Get` the way the resource service does once placeholders are
+/// filled in
+struct Users;
+
+macro_rules! unimplemented_rpcs {
+    ($($name:ident($request:ty) -> $response:ty;)*) => {
+        $(async fn $name(
+            &self,
+            _request: tonic::Request<$request>,
+        ) -> Result<Response<$response>, Status> {
+            Err(Status::unimplemented(stringify!($name)))
+        })*
+    };
+}
+
+#[tonic::async_trait]
+impl UserService for Users {
+    async fn get(&self, request: tonic::Request<GetRequest>) -> Result<Response<FullUser>, Status> {
+        Ok(Response::new(FullUser {
+            id: "u1".to_owned(),
+            username: request.into_inner().username,
+            image: Some("/api/assets/abc".to_owned()),
+            image_placeholder: Some(ImagePlaceholder {
+                blurhash: "LEHV6nWB2yk8pyo0adR*.7kCMdnj".to_owned(),
+                dominant_color: "#a0b0c0".to_owned(),
+            }),
+            created_at: Some(prost_types::Timestamp::default()),
+            ..Default::default()
+        }))
+    }
+
+    unimplemented_rpcs! {
+        search(SearchRequest) -> FullUsers;
+        feed(FeedRequest) -> FullArticles;
+        drafts(ArticlesRequest) -> FullArticles;
+        articles(ArticlesRequest) -> FullArticles;
+        lists(ListsRequest) -> Lists;
+        serieses(SeriesesRequest) -> Serieses;
+        update(UpdateRequest) -> User;
+        delete(DeleteRequest) -> MessageResponse;
+        followers(FollowersRequest) -> FullUsers;
+        following(FollowingRequest) -> FullUsers;
+        follow(FollowRequest) -> MessageResponse;
+        unfollow(UnfollowRequest) -> MessageResponse;
+        notification_settings(NotificationSettingsRequest) -> NotificationSettings;
+        update_notification_settings(UpdateNotificationSettingsRequest) -> NotificationSettings;
+    }
+}
+
+#[tokio::test]
+async fn user_responses_carry_image_placeholders() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let incoming = futures::stream::unfold(listener, |listener| async move {
+        let stream = listener.accept().await.map(|(stream, _)| stream);
+        Some((stream, listener))
+    });
+    tokio::spawn(
+        Server::builder()
+            .add_service(UserServiceServer::new(Users))
+            .serve_with_incoming(incoming),
+    );
+
+    let channel = Endpoint::from_shared(format!("http://{}", address))
+        .unwrap()
+        .connect_lazy();
+    let state = Arc::new(State {
+        key: Key::generate(),
+        events: Arc::new(EventHub::new(1)),
+    });
+    let app = Router::new()
+        .route("/users/:username", get(get_user))
+        .layer(Extension(channel))
+        .with_state(state);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/users/rustacean")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let user: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(user["username"], "rustacean");
+    assert_eq!(
+        user["image_placeholder"],
+        serde_json::json!({
+            "blurhash": "LEHV6nWB2yk8pyo0adR*.7kCMdnj",
+            "dominant_color": "#a0b0c0",
+        })
+    );
+}
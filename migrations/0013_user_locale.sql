@@ -0,0 +1,2 @@
+ALTER TABLE Users
+ADD COLUMN locale TEXT NOT NULL DEFAULT 'en';
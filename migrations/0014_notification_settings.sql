@@ -0,0 +1,15 @@
+CREATE TYPE NotificationEvent AS ENUM ('FOLLOW', 'COMMENT', 'LIKE', 'COAUTHOR_INVITE', 'DIGEST');
+
+-- Rows only exist for events a user has changed, missing ones are enabled
+CREATE TABLE NotificationSettings (
+  user_id TEXT NOT NULL REFERENCES Users (id) ON UPDATE CASCADE ON DELETE CASCADE,
+  event NotificationEvent NOT NULL,
+  in_app BOOLEAN NOT NULL DEFAULT TRUE,
+  email BOOLEAN NOT NULL DEFAULT TRUE,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now (),
+  updated_at TIMESTAMPTZ,
+  PRIMARY KEY (user_id, event)
+);
+
+SELECT
+  trigger_updated_at ('NotificationSettings');
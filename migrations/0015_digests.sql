@@ -0,0 +1,24 @@
+CREATE TYPE DigestFrequency AS ENUM ('NEVER', 'DAILY', 'WEEKLY');
+
+-- Users without a row get the weekly digest
+CREATE TABLE DigestSubscriptions (
+  user_id TEXT PRIMARY KEY REFERENCES Users (id) ON UPDATE CASCADE ON DELETE CASCADE,
+  frequency DigestFrequency NOT NULL DEFAULT 'WEEKLY',
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now (),
+  updated_at TIMESTAMPTZ
+);
+
+SELECT
+  trigger_updated_at ('DigestSubscriptions');
+
+-- One row per user and period, claimed before sending so a restart never
+-- sends the same digest twice. `sent_at` stays NULL for empty or interrupted
+-- digests.
+CREATE TABLE DigestDeliveries (
+  user_id TEXT NOT NULL REFERENCES Users (id) ON UPDATE CASCADE ON DELETE CASCADE,
+  frequency DigestFrequency NOT NULL,
+  period_start TIMESTAMPTZ NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now (),
+  sent_at TIMESTAMPTZ,
+  PRIMARY KEY (user_id, frequency, period_start)
+);
@@ -0,0 +1,43 @@
+CREATE TYPE WebhookEvent AS ENUM ('ARTICLE_PUBLISHED', 'COMMENT_CREATED', 'USER_FOLLOWED');
+
+CREATE TYPE WebhookDeliveryStatus AS ENUM ('PENDING', 'SUCCEEDED', 'FAILED');
+
+-- Global webhooks (admins only) receive every event on the platform, the rest
+-- only events about the owner's content.
+CREATE TABLE Webhooks (
+  id TEXT DEFAULT nanoid () PRIMARY KEY,
+  user_id TEXT NOT NULL REFERENCES Users (id) ON UPDATE CASCADE ON DELETE CASCADE,
+  url TEXT NOT NULL,
+  secret TEXT NOT NULL DEFAULT nanoid (32),
+  events WebhookEvent[] NOT NULL DEFAULT ARRAY[]::WebhookEvent[],
+  global BOOLEAN NOT NULL DEFAULT FALSE,
+  active BOOLEAN NOT NULL DEFAULT TRUE,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now (),
+  updated_at TIMESTAMPTZ
+);
+
+SELECT
+  trigger_updated_at ('Webhooks');
+
+CREATE INDEX ON Webhooks (user_id);
+
+CREATE TABLE WebhookDeliveries (
+  id TEXT DEFAULT nanoid () PRIMARY KEY,
+  webhook_id TEXT NOT NULL REFERENCES Webhooks (id) ON UPDATE CASCADE ON DELETE CASCADE,
+  event WebhookEvent NOT NULL,
+  payload JSONB NOT NULL,
+  status WebhookDeliveryStatus NOT NULL DEFAULT 'PENDING',
+  attempts INTEGER NOT NULL DEFAULT 0,
+  next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT now (),
+  response_status INTEGER,
+  response_body TEXT,
+  error TEXT,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now (),
+  delivered_at TIMESTAMPTZ
+);
+
+CREATE INDEX ON WebhookDeliveries (webhook_id, created_at);
+
+CREATE INDEX ON WebhookDeliveries (next_attempt_at)
+WHERE
+  status = 'PENDING';
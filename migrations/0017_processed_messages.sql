@@ -0,0 +1,9 @@
+-- Ids of broker messages already handled, consumers skip redeliveries
+CREATE TABLE ProcessedMessages (
+  id TEXT PRIMARY KEY,
+  consumer TEXT NOT NULL,
+  message_type TEXT NOT NULL,
+  processed_at TIMESTAMPTZ NOT NULL DEFAULT now ()
+);
+
+CREATE INDEX ON ProcessedMessages (processed_at);
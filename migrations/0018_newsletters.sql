@@ -0,0 +1,46 @@
+CREATE TYPE SubscriberStatus AS ENUM ('PENDING', 'CONFIRMED', 'UNSUBSCRIBED', 'BOUNCED');
+
+CREATE TYPE NewsletterSendStatus AS ENUM ('PENDING', 'SENT', 'BOUNCED', 'FAILED');
+
+-- Readers subscribe to an author by email, an account is not required.
+-- `token` authenticates the confirmation and unsubscribe links.
+CREATE TABLE NewsletterSubscribers (
+  id TEXT DEFAULT nanoid () PRIMARY KEY,
+  author_id TEXT NOT NULL REFERENCES Users (id) ON UPDATE CASCADE ON DELETE CASCADE,
+  email TEXT NOT NULL,
+  locale TEXT NOT NULL DEFAULT 'en',
+  status SubscriberStatus NOT NULL DEFAULT 'PENDING',
+  token TEXT NOT NULL UNIQUE DEFAULT nanoid (32),
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now (),
+  updated_at TIMESTAMPTZ,
+  confirmed_at TIMESTAMPTZ,
+  UNIQUE (author_id, email)
+);
+
+SELECT
+  trigger_updated_at ('NewsletterSubscribers');
+
+-- One issue per article, sent the first time it is published
+CREATE TABLE Newsletters (
+  id TEXT DEFAULT nanoid () PRIMARY KEY,
+  article_id TEXT NOT NULL UNIQUE REFERENCES Articles (id) ON UPDATE CASCADE ON DELETE CASCADE,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now ()
+);
+
+CREATE TABLE NewsletterSends (
+  id TEXT DEFAULT nanoid () PRIMARY KEY,
+  newsletter_id TEXT NOT NULL REFERENCES Newsletters (id) ON UPDATE CASCADE ON DELETE CASCADE,
+  subscriber_id TEXT NOT NULL REFERENCES NewsletterSubscribers (id) ON UPDATE CASCADE ON DELETE CASCADE,
+  status NewsletterSendStatus NOT NULL DEFAULT 'PENDING',
+  attempts INTEGER NOT NULL DEFAULT 0,
+  next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT now (),
+  error TEXT,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now (),
+  sent_at TIMESTAMPTZ,
+  opened_at TIMESTAMPTZ,
+  UNIQUE (newsletter_id, subscriber_id)
+);
+
+CREATE INDEX ON NewsletterSends (next_attempt_at)
+WHERE
+  status = 'PENDING';
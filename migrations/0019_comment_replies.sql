@@ -0,0 +1,2 @@
+-- `parent` holds the path of ancestor comment ids, root first
+CREATE INDEX ON Comments USING gist (parent);
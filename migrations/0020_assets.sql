@@ -0,0 +1,15 @@
+-- Objects in the storage bucket and who uploaded them
+CREATE TABLE Assets (
+  id TEXT DEFAULT nanoid () PRIMARY KEY,
+  user_id TEXT NOT NULL REFERENCES Users (id) ON UPDATE CASCADE ON DELETE CASCADE,
+  asset_name TEXT UNIQUE NOT NULL,
+  file_name TEXT NOT NULL,
+  content_type TEXT NOT NULL,
+  size BIGINT NOT NULL,
+  checksum TEXT NOT NULL,
+  width INTEGER,
+  height INTEGER,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now ()
+);
+
+CREATE INDEX ON Assets (user_id, created_at);
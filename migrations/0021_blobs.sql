@@ -0,0 +1,47 @@
+-- Objects in the bucket, shared by every asset with the same content. New
+-- objects are named by the SHA-256 of the uploaded bytes, the bytes are
+-- removed once `ref_count` drops to zero.
+CREATE TABLE Blobs (
+  name TEXT PRIMARY KEY,
+  checksum TEXT NOT NULL,
+  content_type TEXT NOT NULL,
+  size BIGINT NOT NULL,
+  width INTEGER,
+  height INTEGER,
+  ref_count INTEGER NOT NULL DEFAULT 0,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now ()
+);
+
+INSERT INTO
+  Blobs (
+    name,
+    checksum,
+    content_type,
+    size,
+    width,
+    height,
+    ref_count,
+    created_at
+  )
+SELECT
+  asset_name,
+  checksum,
+  content_type,
+  size,
+  width,
+  height,
+  1,
+  created_at
+FROM
+  Assets;
+
+ALTER TABLE Assets
+DROP CONSTRAINT assets_asset_name_key,
+DROP COLUMN content_type,
+DROP COLUMN size,
+DROP COLUMN checksum,
+DROP COLUMN width,
+DROP COLUMN height,
+ADD FOREIGN KEY (asset_name) REFERENCES Blobs (name) ON UPDATE CASCADE;
+
+CREATE INDEX ON Assets (asset_name);
@@ -0,0 +1,19 @@
+-- Set by the collector once nothing links to the asset, cleared again when
+-- something does before the grace period is over
+ALTER TABLE Assets
+ADD COLUMN orphaned_at TIMESTAMPTZ;
+
+CREATE INDEX ON Assets (orphaned_at)
+WHERE
+  orphaned_at IS NOT NULL;
+
+-- Whether any article, article version, avatar, list or series cover links
+-- to the object
+CREATE OR REPLACE FUNCTION asset_is_referenced (asset_name TEXT) RETURNS BOOLEAN AS $$
+  SELECT
+    EXISTS (SELECT 1 FROM Articles WHERE strpos(content, asset_name) > 0)
+    OR EXISTS (SELECT 1 FROM ArticleVersions WHERE strpos(content, asset_name) > 0)
+    OR EXISTS (SELECT 1 FROM Users WHERE strpos(image, asset_name) > 0)
+    OR EXISTS (SELECT 1 FROM Lists WHERE strpos(image, asset_name) > 0)
+    OR EXISTS (SELECT 1 FROM Series WHERE strpos(image, asset_name) > 0)
+$$ LANGUAGE sql STABLE;
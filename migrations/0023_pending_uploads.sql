@@ -0,0 +1,13 @@
+-- Direct uploads handed a presigned URL, the client puts the file to
+-- `uploads/{id}` and completes the upload to register it
+CREATE TABLE PendingUploads (
+  id TEXT DEFAULT nanoid () PRIMARY KEY,
+  user_id TEXT NOT NULL REFERENCES Users (id) ON UPDATE CASCADE ON DELETE CASCADE,
+  file_name TEXT NOT NULL,
+  content_type TEXT NOT NULL,
+  size BIGINT NOT NULL,
+  expires_at TIMESTAMPTZ NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now ()
+);
+
+CREATE INDEX ON PendingUploads (expires_at);
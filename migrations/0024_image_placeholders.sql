@@ -0,0 +1,4 @@
+-- Shown by clients while an image loads, computed when it is stored
+ALTER TABLE Blobs
+ADD COLUMN blurhash TEXT,
+ADD COLUMN dominant_color TEXT;
@@ -0,0 +1,41 @@
+-- Kept outside of Articles so queries selecting `a.*` keep their shape
+CREATE TABLE ArticleSearch (
+  article_id TEXT PRIMARY KEY REFERENCES Articles (id) ON DELETE CASCADE ON UPDATE CASCADE,
+  document TSVECTOR NOT NULL
+);
+
+CREATE INDEX ON ArticleSearch USING GIN (document);
+
+-- Matches in the title rank above the description, which ranks above the
+-- content
+CREATE OR REPLACE FUNCTION article_document (title TEXT, description TEXT, content TEXT) RETURNS TSVECTOR AS $$
+  SELECT
+    setweight(to_tsvector(COALESCE(title, '')), 'A')
+    || setweight(to_tsvector(COALESCE(description, '')), 'B')
+    || setweight(to_tsvector(COALESCE(content, '')), 'C')
+$$ LANGUAGE sql STABLE;
+
+CREATE OR REPLACE FUNCTION index_article () RETURNS TRIGGER AS $$
+BEGIN
+  INSERT INTO ArticleSearch (article_id, document)
+  VALUES (NEW.id, article_document(NEW.title, NEW.description, NEW.content))
+  ON CONFLICT (article_id) DO UPDATE SET document = EXCLUDED.document;
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER index_article
+AFTER INSERT
+OR
+UPDATE OF title,
+description,
+content ON Articles FOR EACH ROW
+EXECUTE FUNCTION index_article ();
+
+INSERT INTO
+  ArticleSearch (article_id, document)
+SELECT
+  id,
+  article_document (title, description, content)
+FROM
+  Articles;
@@ -0,0 +1,29 @@
+-- Articles matching a search and its filters, with their relevance. Drafts
+-- are only found by their authors
+CREATE OR REPLACE FUNCTION matching_articles (
+  query TEXT,
+  author TEXT,
+  tag TEXT,
+  since TIMESTAMPTZ,
+  until TIMESTAMPTZ,
+  published_only BOOLEAN,
+  by_user TEXT
+) RETURNS TABLE (article_id TEXT, rank REAL) AS $$
+  SELECT
+    a.id,
+    ts_rank_cd(d.document, websearch_to_tsquery(query))
+  FROM Articles a
+  JOIN ArticleSearch d ON a.id = d.article_id
+  WHERE d.document @@ websearch_to_tsquery(query)
+    AND (
+      a.published_at IS NOT NULL
+      OR (
+        NOT published_only
+        AND EXISTS (SELECT 1 FROM Authors au WHERE au.article_id = a.id AND au.author_id = by_user)
+      )
+    )
+    AND (author IS NULL OR EXISTS (SELECT 1 FROM Authors au WHERE au.article_id = a.id AND au.author_id = author))
+    AND (tag IS NULL OR EXISTS (SELECT 1 FROM ArticleTags at WHERE at.article_id = a.id AND at.tag_slug = tag))
+    AND (since IS NULL OR COALESCE(a.published_at, a.created_at) >= since)
+    AND (until IS NULL OR COALESCE(a.published_at, a.created_at) < until)
+$$ LANGUAGE sql STABLE;
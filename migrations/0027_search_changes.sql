@@ -0,0 +1,47 @@
+-- Articles, users and tags changed since the search index last caught up,
+-- drained by the search service
+CREATE TABLE SearchChanges (
+  id BIGSERIAL PRIMARY KEY,
+  kind TEXT NOT NULL,
+  target_id TEXT NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now ()
+);
+
+-- Called with the kind of the row and the name of its key column
+CREATE OR REPLACE FUNCTION record_search_change () RETURNS TRIGGER AS $$
+BEGIN
+  INSERT INTO SearchChanges (kind, target_id)
+  VALUES (
+    TG_ARGV[0],
+    to_jsonb(CASE WHEN TG_OP = 'DELETE' THEN OLD ELSE NEW END) ->> TG_ARGV[1]
+  );
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER record_search_change
+AFTER INSERT
+OR DELETE
+OR
+UPDATE OF title,
+description,
+content,
+published_at ON Articles FOR EACH ROW
+EXECUTE FUNCTION record_search_change ('article', 'id');
+
+CREATE TRIGGER record_search_change
+AFTER INSERT
+OR DELETE
+OR
+UPDATE OF username,
+bio,
+deleted_at ON Users FOR EACH ROW
+EXECUTE FUNCTION record_search_change ('user', 'id');
+
+CREATE TRIGGER record_search_change
+AFTER INSERT
+OR DELETE
+OR
+UPDATE OF label,
+tag_status ON Tags FOR EACH ROW
+EXECUTE FUNCTION record_search_change ('tag', 'slug');
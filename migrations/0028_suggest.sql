@@ -0,0 +1,6 @@
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- Serve prefix (ILIKE) and fuzzy (%) matches for suggestions
+CREATE INDEX ON Users USING GIN (username gin_trgm_ops);
+
+CREATE INDEX ON Tags USING GIN (label gin_trgm_ops);
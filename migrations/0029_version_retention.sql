@@ -0,0 +1,2 @@
+-- Versions are read per article newest first, and thinned by age
+CREATE INDEX ON ArticleVersions (article_id, created_at);
@@ -0,0 +1,14 @@
+-- Named milestones in an article's history, their versions are never
+-- compacted away
+CREATE TABLE ArticleSnapshots (
+  id TEXT DEFAULT nanoid () PRIMARY KEY,
+  version_id TEXT NOT NULL REFERENCES ArticleVersions (id) ON DELETE CASCADE ON UPDATE CASCADE,
+  article_id TEXT NOT NULL REFERENCES Articles (id) ON DELETE CASCADE ON UPDATE CASCADE,
+  user_id TEXT REFERENCES Users (id) ON DELETE SET NULL ON UPDATE CASCADE,
+  name TEXT NOT NULL,
+  note TEXT,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now (),
+  UNIQUE (version_id, name)
+);
+
+CREATE INDEX ON ArticleSnapshots (article_id, created_at);
@@ -0,0 +1,3 @@
+-- Only the status of webhook responses is kept
+ALTER TABLE WebhookDeliveries
+DROP COLUMN response_body;
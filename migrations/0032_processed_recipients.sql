@@ -0,0 +1,9 @@
+-- Recipients a message was already sent to, a retried message skips them
+CREATE TABLE ProcessedRecipients (
+  message_id TEXT NOT NULL,
+  recipient_id TEXT NOT NULL,
+  processed_at TIMESTAMPTZ NOT NULL DEFAULT now (),
+  PRIMARY KEY (message_id, recipient_id)
+);
+
+CREATE INDEX ON ProcessedRecipients (processed_at);
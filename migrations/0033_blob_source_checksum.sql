@@ -0,0 +1,13 @@
+-- Images are re-encoded before they are stored, so the object is named by
+-- the SHA-256 of the stored bytes and the upload it came from is kept here
+-- to find it again. Images stored until now were named by their upload.
+ALTER TABLE Blobs
+ADD COLUMN source_checksum TEXT;
+
+UPDATE Blobs
+SET
+  source_checksum = checksum
+WHERE
+  width IS NOT NULL;
+
+CREATE INDEX ON Blobs (source_checksum);
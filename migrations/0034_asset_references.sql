@@ -0,0 +1,121 @@
+-- Objects each article, article version, avatar, list or series cover links
+-- to, kept up to date as they are written so the collector can look an
+-- object up instead of searching every row for its name
+CREATE TABLE AssetReferences (
+  asset_name TEXT NOT NULL,
+  source TEXT NOT NULL,
+  source_id TEXT NOT NULL,
+  PRIMARY KEY (source, source_id, asset_name)
+);
+
+CREATE INDEX ON AssetReferences (asset_name);
+
+-- Stored objects named in a text, which links to them by URL. Objects are
+-- uploaded before anything links to them, so only stored names are kept.
+CREATE OR REPLACE FUNCTION referenced_assets (body TEXT) RETURNS SETOF TEXT AS $$
+  SELECT DISTINCT b.name
+  FROM regexp_split_to_table(coalesce(body, ''), '[\s"''<>()/?#@]+') token
+  JOIN Blobs b ON b.name = token
+$$ LANGUAGE sql STABLE;
+
+-- Called with the name of the column holding the links
+CREATE OR REPLACE FUNCTION record_asset_references () RETURNS TRIGGER AS $$
+BEGIN
+  IF TG_OP <> 'INSERT' THEN
+    DELETE FROM AssetReferences
+    WHERE source = TG_TABLE_NAME AND source_id = OLD.id;
+  END IF;
+  IF TG_OP <> 'DELETE' THEN
+    INSERT INTO AssetReferences (asset_name, source, source_id)
+    SELECT name, TG_TABLE_NAME, NEW.id
+    FROM referenced_assets(to_jsonb(NEW) ->> TG_ARGV[0]) name;
+  END IF;
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER record_asset_references
+AFTER INSERT
+OR DELETE
+OR
+UPDATE OF id,
+content ON Articles FOR EACH ROW
+EXECUTE FUNCTION record_asset_references ('content');
+
+CREATE TRIGGER record_asset_references
+AFTER INSERT
+OR DELETE
+OR
+UPDATE OF id,
+content ON ArticleVersions FOR EACH ROW
+EXECUTE FUNCTION record_asset_references ('content');
+
+CREATE TRIGGER record_asset_references
+AFTER INSERT
+OR DELETE
+OR
+UPDATE OF id,
+image ON Users FOR EACH ROW
+EXECUTE FUNCTION record_asset_references ('image');
+
+CREATE TRIGGER record_asset_references
+AFTER INSERT
+OR DELETE
+OR
+UPDATE OF id,
+image ON Lists FOR EACH ROW
+EXECUTE FUNCTION record_asset_references ('image');
+
+CREATE TRIGGER record_asset_references
+AFTER INSERT
+OR DELETE
+OR
+UPDATE OF id,
+image ON Series FOR EACH ROW
+EXECUTE FUNCTION record_asset_references ('image');
+
+INSERT INTO
+  AssetReferences (asset_name, source, source_id)
+SELECT
+  name,
+  'articles',
+  id
+FROM
+  Articles,
+  referenced_assets (content) name
+UNION
+SELECT
+  name,
+  'articleversions',
+  id
+FROM
+  ArticleVersions,
+  referenced_assets (content) name
+UNION
+SELECT
+  name,
+  'users',
+  id
+FROM
+  Users,
+  referenced_assets (image) name
+UNION
+SELECT
+  name,
+  'lists',
+  id
+FROM
+  Lists,
+  referenced_assets (image) name
+UNION
+SELECT
+  name,
+  'series',
+  id
+FROM
+  Series,
+  referenced_assets (image) name;
+
+CREATE OR REPLACE FUNCTION asset_is_referenced (asset_name TEXT) RETURNS BOOLEAN AS $$
+  SELECT EXISTS (SELECT 1 FROM AssetReferences r WHERE r.asset_name = $1)
+$$ LANGUAGE sql STABLE;
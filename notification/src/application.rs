@@ -1,31 +1,46 @@
-use std::str;
+use std::sync::Arc;
 
 use amqprs::{
     callbacks::{DefaultChannelCallback, DefaultConnectionCallback},
     channel::{
-        BasicAckArguments, BasicConsumeArguments, BasicQosArguments, Channel,
-        ExchangeDeclareArguments, QueueBindArguments, QueueDeclareArguments,
+        BasicQosArguments, Channel, ExchangeDeclareArguments, QueueBindArguments,
+        QueueDeclareArguments,
     },
     connection::{Connection, OpenConnectionArguments},
 };
-use lettre::AsyncTransport;
 use secrecy::ExposeSecret;
 use shared::{
-    configuration::{MessageBrokerSettings, Settings},
-    utils::message::VerificationMessage,
+    configuration::{
+        DatabaseSettings, DigestSettings, MessageBrokerSettings, NewsletterSettings, ReplySettings,
+        Settings, WebhookSettings,
+    },
+    utils::message::{COMMENT_CREATED, EVENTS_EXCHANGE, NEWSLETTER_CONFIRMATION, VERIFICATION},
 };
+use sqlx::{postgres::PgPoolOptions, PgPool};
 use tokio::{
     sync::Notify,
     time::{sleep, Duration},
 };
 use tracing::{debug, error, info, warn};
 
-use crate::services::mail::{verification_message, MailService};
+use crate::{
+    consumer::Consumer,
+    handlers::{CommentHandler, NewsletterConfirmationHandler, VerificationHandler},
+    services::{
+        digest::DigestService, mail::MailService, newsletter::NewsletterService,
+        reply::ReplyService, webhook::WebhookService,
+    },
+};
 
 pub struct Application {
     connection: Connection,
     channel: Channel,
-    mail_service: MailService,
+    mail_service: Arc<MailService>,
+    db: PgPool,
+    digest: DigestSettings,
+    webhook: WebhookSettings,
+    newsletter: NewsletterSettings,
+    reply: ReplySettings,
 }
 
 impl Application {
@@ -46,7 +61,27 @@ impl Application {
         )
         .await;
 
-        let mail_service = MailService::build(&configuration.mail).expect("Mail Service Error");
+        // Comment emails are sent from the domain event, not a queued email
+        channel
+            .exchange_declare(
+                ExchangeDeclareArguments::new(EVENTS_EXCHANGE, "topic")
+                    .durable(true)
+                    .finish(),
+            )
+            .await?;
+        channel
+            .queue_bind(QueueBindArguments::new(
+                "notification",
+                EVENTS_EXCHANGE,
+                COMMENT_CREATED,
+            ))
+            .await?;
+
+        let db = get_connection_pool(&configuration.database).await;
+
+        let mail_service = Arc::new(
+            MailService::build(&configuration.mail, db.clone()).expect("Mail Service Error"),
+        );
 
         info!("Finished notification service build");
 
@@ -54,62 +89,81 @@ impl Application {
             mail_service,
             connection,
             channel,
+            db,
+            digest: configuration.digest,
+            webhook: configuration.webhook,
+            newsletter: configuration.newsletter,
+            reply: configuration.reply,
         })
     }
 
     pub async fn run(self) {
-        let consumer_args = BasicConsumeArguments::new("notification", "");
-        info!("Consuming messages");
-        tokio::spawn(async move {
-            match self.channel.basic_consume_rx(consumer_args.clone()).await {
-                Ok((_, mut message_rx)) => {
-                    while let Some(message) = message_rx.recv().await {
-                        match message.basic_properties {
-                            Some(basic_properties) => match basic_properties.message_type() {
-                                Some(message_type) => match message.content {
-                                    Some(payload) => match str::from_utf8(&payload) {
-                                        Ok(paylaod_str) => {
-                                            if message_type == "orta.notification.verification" {
-                                                match serde_json::from_str::<VerificationMessage>(paylaod_str) {
-                                                    Ok(verification_message_paylaod) => match verification_message(
-                                                        &verification_message_paylaod.email,
-                                                        &verification_message_paylaod.verification_link
-                                                    ) {
-                                                        Ok(verification_message) => match self.mail_service.mailer.send(verification_message).await {
-                                                            Ok(_) => {
-                                                                self.channel.basic_ack(BasicAckArguments::new(message.deliver.unwrap().delivery_tag(),false,)).await.unwrap()
-                                                            },
-                                                            Err(err) => error!("Error sending message: {:?}", err),
-                                                        },
-                                                        Err(err) => error!("Unable to create verification message: {:?}", err)
-                                                    },
-                                                    Err(err) => error!("Unable to parse payload: {:?}", err)
-                                                }
-                                            } else {
-                                                warn!("Unknown message type")
-                                            }
-                                        }
-                                        Err(err) => {
-                                            error!("Unable to parse payload string {:?}", err)
-                                        }
-                                    },
-                                    None => error!("No message content"),
-                                },
-                                None => error!("No message type"),
-                            },
-                            None => error!("No basic properties"),
-                        }
-                    }
-                }
-                Err(err) => error!("Error consuming message from rabbit: {}", err),
+        tokio::spawn(
+            DigestService {
+                db: self.db.clone(),
+                mail_service: self.mail_service.clone(),
+                settings: self.digest.clone(),
+            }
+            .run(),
+        );
+
+        tokio::spawn(
+            NewsletterService {
+                db: self.db.clone(),
+                mail_service: self.mail_service.clone(),
+                settings: self.newsletter.clone(),
             }
-        });
+            .run(),
+        );
+
+        match ReplyService::build(self.db.clone(), self.reply.clone()) {
+            Ok(reply_service) => {
+                tokio::spawn(reply_service.run());
+            }
+            Err(err) => error!("Unable to start reply listener: {:?}", err),
+        }
+
+        match WebhookService::build(self.db.clone(), self.webhook.clone()) {
+            Ok(webhook_service) => {
+                tokio::spawn(webhook_service.run());
+            }
+            Err(err) => error!("Unable to start webhook dispatcher: {:?}", err),
+        }
+
+        tokio::spawn(
+            Consumer::new("notification", self.channel, self.db.clone())
+                .register(
+                    VERIFICATION,
+                    VerificationHandler {
+                        mail_service: self.mail_service.clone(),
+                    },
+                )
+                .register(
+                    COMMENT_CREATED,
+                    CommentHandler {
+                        mail_service: self.mail_service.clone(),
+                        db: self.db.clone(),
+                        settings: self.reply.clone(),
+                    },
+                )
+                .register(
+                    NEWSLETTER_CONFIRMATION,
+                    NewsletterConfirmationHandler {
+                        mail_service: self.mail_service.clone(),
+                    },
+                )
+                .run(),
+        );
 
         let guard = Notify::new();
         guard.notified().await;
     }
 }
 
+pub async fn get_connection_pool(configuration: &DatabaseSettings) -> PgPool {
+    PgPoolOptions::new().connect_lazy_with(configuration.with_db())
+}
+
 pub async fn connect_rabbitmq(configuration: &MessageBrokerSettings) -> Connection {
     info!("Connecting to rabbitmq");
 
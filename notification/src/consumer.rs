@@ -0,0 +1,227 @@
+use std::{collections::HashMap, sync::Arc};
+
+use amqprs::{
+    channel::{BasicAckArguments, BasicConsumeArguments, BasicNackArguments, Channel},
+    consumer::ConsumerMessage,
+};
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use shared::{
+    repositories::processed_message_repository::{
+        ProcessedMessageRepository, ProcessedMessageRepositoryImpl,
+    },
+    utils::message::Envelope,
+};
+use sqlx::PgPool;
+use tracing::{debug, error, info, warn};
+
+/// Processed message ids are kept this long, well past any redelivery
+const DEDUP_RETENTION_DAYS: i64 = 7;
+
+#[derive(Debug)]
+pub enum HandlerError {
+    /// Transient failure, the message is requeued once
+    Retry(anyhow::Error),
+    /// The message can never be handled, it is dropped
+    Reject(anyhow::Error),
+}
+
+impl std::fmt::Display for HandlerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            HandlerError::Retry(err) => write!(f, "Retry: {}", err),
+            HandlerError::Reject(err) => write!(f, "Reject: {}", err),
+        }
+    }
+}
+
+#[async_trait]
+pub trait Handler: Send + Sync {
+    async fn handle(&self, envelope: &Envelope) -> Result<(), HandlerError>;
+}
+
+/// Acknowledgement decided for a delivery
+#[derive(Debug)]
+enum Outcome {
+    Ack,
+    Requeue,
+    Drop,
+}
+
+/// Consumes a queue and dispatches envelopes to the handler registered for
+/// their message type. A message is acked once its handler succeeds and its
+/// id is recorded, so redeliveries of handled messages are skipped.
+pub struct Consumer {
+    name: String,
+    channel: Channel,
+    db: PgPool,
+    handlers: HashMap<&'static str, Arc<dyn Handler>>,
+}
+
+impl Consumer {
+    pub fn new(name: &str, channel: Channel, db: PgPool) -> Self {
+        Self {
+            name: name.to_owned(),
+            channel,
+            db,
+            handlers: HashMap::new(),
+        }
+    }
+
+    pub fn register<H: Handler + 'static>(
+        mut self,
+        message_type: &'static str,
+        handler: H,
+    ) -> Self {
+        self.handlers.insert(message_type, Arc::new(handler));
+        self
+    }
+
+    pub async fn run(self) {
+        let consumer_args = BasicConsumeArguments::new(&self.name, "");
+        let mut message_rx = match self.channel.basic_consume_rx(consumer_args).await {
+            Ok((_, message_rx)) => message_rx,
+            Err(err) => {
+                error!("Error consuming message from rabbit: {}", err);
+                return;
+            }
+        };
+
+        info!("Consuming messages from {}", self.name);
+
+        let mut prune_interval = tokio::time::interval(std::time::Duration::from_secs(60 * 60));
+
+        loop {
+            tokio::select! {
+                message = message_rx.recv() => match message {
+                    Some(message) => self.process(message).await,
+                    None => {
+                        warn!("Consumer {} stopped", self.name);
+                        return;
+                    }
+                },
+                _ = prune_interval.tick() => {
+                    if let Err(err) = self.prune().await {
+                        error!("Unable to prune processed messages: {:?}", err);
+                    }
+                }
+            }
+        }
+    }
+
+    async fn process(&self, message: ConsumerMessage) {
+        let deliver = match message.deliver {
+            Some(deliver) => deliver,
+            None => {
+                error!("No delivery info");
+                return;
+            }
+        };
+
+        let outcome = match self
+            .dispatch(
+                message.basic_properties.as_ref(),
+                message.content.as_deref(),
+            )
+            .await
+        {
+            Ok(outcome) => outcome,
+            Err(HandlerError::Retry(err)) if !deliver.redelivered() => {
+                warn!("Requeueing message: {:?}", err);
+                Outcome::Requeue
+            }
+            Err(err) => {
+                error!("Dropping message: {}", err);
+                Outcome::Drop
+            }
+        };
+
+        let delivery_tag = deliver.delivery_tag();
+        let result = match outcome {
+            Outcome::Ack => {
+                self.channel
+                    .basic_ack(BasicAckArguments::new(delivery_tag, false))
+                    .await
+            }
+            Outcome::Requeue => {
+                self.channel
+                    .basic_nack(BasicNackArguments::new(delivery_tag, false, true))
+                    .await
+            }
+            Outcome::Drop => {
+                self.channel
+                    .basic_nack(BasicNackArguments::new(delivery_tag, false, false))
+                    .await
+            }
+        };
+
+        if let Err(err) = result {
+            error!("Unable to acknowledge message {}: {}", delivery_tag, err);
+        }
+    }
+
+    async fn dispatch(
+        &self,
+        properties: Option<&amqprs::BasicProperties>,
+        content: Option<&[u8]>,
+    ) -> Result<Outcome, HandlerError> {
+        let content =
+            content.ok_or_else(|| HandlerError::Reject(anyhow::anyhow!("No message content")))?;
+
+        let envelope = Envelope::parse(
+            content,
+            properties.and_then(|properties| properties.message_type().map(|v| v.as_str())),
+            properties.and_then(|properties| properties.message_id().map(|v| v.as_str())),
+        )
+        .map_err(|err| HandlerError::Reject(err.into()))?;
+
+        let message_type = envelope.payload.message_type();
+
+        let handler = self.handlers.get(message_type).ok_or_else(|| {
+            HandlerError::Reject(anyhow::anyhow!("No handler for {}", message_type))
+        })?;
+
+        // The claim is held in an open transaction while the handler runs, a
+        // concurrent duplicate waits on it and a failed attempt leaves no trace
+        let mut transaction = self
+            .db
+            .begin()
+            .await
+            .map_err(|err| HandlerError::Retry(err.into()))?;
+
+        let claimed = ProcessedMessageRepositoryImpl::claim(
+            &mut transaction,
+            &envelope.id,
+            &self.name,
+            message_type,
+        )
+        .await
+        .map_err(|err| HandlerError::Retry(err.into()))?;
+
+        if !claimed {
+            debug!("Skipping duplicate message {}", envelope.id);
+            return Ok(Outcome::Ack);
+        }
+
+        handler.handle(&envelope).await?;
+
+        transaction
+            .commit()
+            .await
+            .map_err(|err| HandlerError::Retry(err.into()))?;
+
+        Ok(Outcome::Ack)
+    }
+
+    async fn prune(&self) -> Result<(), anyhow::Error> {
+        let mut transaction = self.db.begin().await?;
+        let pruned = ProcessedMessageRepositoryImpl::prune(
+            &mut transaction,
+            Utc::now() - Duration::days(DEDUP_RETENTION_DAYS),
+        )
+        .await?;
+        transaction.commit().await?;
+        debug!("Pruned {} processed messages", pruned);
+        Ok(())
+    }
+}
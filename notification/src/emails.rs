@@ -0,0 +1,93 @@
+use serde::{Serialize, Serializer};
+use shared::models::{
+    digest_model::{DigestActivity, DigestArticle},
+    enums::{DigestFrequency, NotificationEvent},
+};
+
+use crate::services::template::EmailTemplate;
+
+#[derive(Serialize, Debug)]
+pub struct VerificationEmail {
+    pub verification_link: String,
+}
+
+impl EmailTemplate for VerificationEmail {
+    const NAME: &'static str = "verification";
+}
+
+#[derive(Serialize, Debug)]
+pub struct DigestEmail {
+    pub username: String,
+    pub frequency: DigestFrequency,
+    pub client_url: String,
+    pub followed: Vec<DigestArticle>,
+    pub interests: Vec<DigestArticle>,
+    pub activity: Vec<DigestActivity>,
+}
+
+impl EmailTemplate for DigestEmail {
+    const NAME: &'static str = "digest";
+    const EVENT: Option<NotificationEvent> = Some(NotificationEvent::Digest);
+}
+
+#[derive(Serialize, Debug)]
+pub struct NewsletterConfirmationEmail {
+    pub author: String,
+    pub confirmation_link: String,
+}
+
+impl EmailTemplate for NewsletterConfirmationEmail {
+    const NAME: &'static str = "newsletter_confirmation";
+}
+
+/// A published article sent to the author's newsletter subscribers
+#[derive(Serialize, Debug)]
+pub struct NewsletterEmail {
+    pub author: String,
+    pub title: String,
+    pub description: Option<String>,
+    /// Article HTML as stored by the editor, sanitized when rendered
+    #[serde(serialize_with = "sanitize")]
+    pub content: String,
+    pub article_link: String,
+    /// Tracking pixel recording the first open
+    pub open_link: String,
+    #[serde(skip)]
+    pub unsubscribe_link: String,
+}
+
+impl EmailTemplate for NewsletterEmail {
+    const NAME: &'static str = "newsletter";
+
+    fn unsubscribe_link(&self) -> Option<String> {
+        Some(self.unsubscribe_link.to_owned())
+    }
+}
+
+/// Keeps the formatting of article HTML, scripts, styles, event handlers and
+/// anything else the allow-list does not know are dropped
+fn sanitize<S: Serializer>(html: &str, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&ammonia::clean(html))
+}
+
+/// A comment on the recipient's content, replying to the email posts a
+/// threaded answer
+#[derive(Serialize, Debug)]
+pub struct CommentEmail {
+    pub commenter: String,
+    pub title: String,
+    pub content: String,
+    pub link: String,
+    /// Signed `reply+` address for the recipient
+    #[serde(skip)]
+    pub reply_to: String,
+}
+
+impl EmailTemplate for CommentEmail {
+    const NAME: &'static str = "comment";
+    const EVENT: Option<NotificationEvent> = Some(NotificationEvent::Comment);
+
+    fn reply_to(&self) -> Option<String> {
+        Some(self.reply_to.to_owned())
+    }
+}
@@ -0,0 +1,260 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use secrecy::ExposeSecret;
+use shared::{
+    configuration::{ReplySettings, CONFIG},
+    models::enums::CommentableType,
+    repositories::{
+        article_repository::{ArticleRepository, ArticleRepositoryImpl},
+        list_repository::{ListRepository, ListRepositoryImpl},
+        processed_message_repository::{
+            ProcessedMessageRepository, ProcessedMessageRepositoryImpl,
+        },
+        series_repository::{SeriesRepository, SeriesRepositoryImpl},
+        user_repository::{UserRepository, UserRepositoryImpl},
+    },
+    utils::{
+        message::{Envelope, Payload},
+        reply,
+    },
+};
+use sqlx::PgPool;
+use tracing::warn;
+
+use crate::{
+    consumer::{Handler, HandlerError},
+    emails::{CommentEmail, NewsletterConfirmationEmail, VerificationEmail},
+    services::mail::{MailService, Recipient},
+};
+
+pub struct VerificationHandler {
+    pub mail_service: Arc<MailService>,
+}
+
+#[async_trait]
+impl Handler for VerificationHandler {
+    async fn handle(&self, envelope: &Envelope) -> Result<(), HandlerError> {
+        let message = match &envelope.payload {
+            Payload::Verification(message) => message,
+            _ => {
+                return Err(HandlerError::Reject(anyhow::anyhow!(
+                    "Unexpected payload {}",
+                    envelope.payload.message_type()
+                )))
+            }
+        };
+
+        self.mail_service
+            .send(
+                &Recipient {
+                    user_id: None,
+                    email: message.email.to_owned(),
+                    locale: message.locale.to_owned(),
+                },
+                &VerificationEmail {
+                    verification_link: message.verification_link.to_owned(),
+                },
+            )
+            .await
+            .map_err(HandlerError::Retry)
+    }
+}
+
+pub struct NewsletterConfirmationHandler {
+    pub mail_service: Arc<MailService>,
+}
+
+#[async_trait]
+impl Handler for NewsletterConfirmationHandler {
+    async fn handle(&self, envelope: &Envelope) -> Result<(), HandlerError> {
+        let message = match &envelope.payload {
+            Payload::NewsletterConfirmation(message) => message,
+            _ => {
+                return Err(HandlerError::Reject(anyhow::anyhow!(
+                    "Unexpected payload {}",
+                    envelope.payload.message_type()
+                )))
+            }
+        };
+
+        self.mail_service
+            .send(
+                &Recipient {
+                    user_id: None,
+                    email: message.email.to_owned(),
+                    locale: message.locale.to_owned(),
+                },
+                &NewsletterConfirmationEmail {
+                    author: message.author.to_owned(),
+                    confirmation_link: message.confirmation_link.to_owned(),
+                },
+            )
+            .await
+            .map_err(HandlerError::Retry)
+    }
+}
+
+/// Emails the owners of the commented content, with a reply-to address
+/// that posts the answer back as a reply to the comment
+pub struct CommentHandler {
+    pub mail_service: Arc<MailService>,
+    pub db: PgPool,
+    pub settings: ReplySettings,
+}
+
+#[async_trait]
+impl Handler for CommentHandler {
+    async fn handle(&self, envelope: &Envelope) -> Result<(), HandlerError> {
+        let event = match &envelope.payload {
+            Payload::CommentCreated(event) => event,
+            _ => {
+                return Err(HandlerError::Reject(anyhow::anyhow!(
+                    "Unexpected payload {}",
+                    envelope.payload.message_type()
+                )))
+            }
+        };
+
+        let comment = &event.comment;
+
+        let mut transaction = self
+            .db
+            .begin()
+            .await
+            .map_err(|err| HandlerError::Retry(err.into()))?;
+
+        let commenter =
+            match UserRepositoryImpl::find(&mut transaction, &comment.commenter_id).await {
+                Ok(commenter) => commenter,
+                Err(sqlx::Error::RowNotFound) => {
+                    return Err(HandlerError::Reject(anyhow::anyhow!(
+                        "Commenter {} not found",
+                        comment.commenter_id
+                    )))
+                }
+                Err(err) => return Err(HandlerError::Retry(err.into())),
+            };
+
+        let (title, path) = match comment.r#type {
+            CommentableType::Article => (
+                ArticleRepositoryImpl::find(&mut transaction, &comment.target_id, None)
+                    .await
+                    .map(|article| article.title),
+                "article",
+            ),
+            CommentableType::List => (
+                ListRepositoryImpl::find(&mut transaction, &comment.target_id, None)
+                    .await
+                    .map(|list| list.label),
+                "list",
+            ),
+            CommentableType::Series => (
+                SeriesRepositoryImpl::find(&mut transaction, &comment.target_id)
+                    .await
+                    .map(|series| series.label),
+                "series",
+            ),
+        };
+
+        let title = match title {
+            Ok(title) => title,
+            Err(sqlx::Error::RowNotFound) => {
+                return Err(HandlerError::Reject(anyhow::anyhow!(
+                    "Comment target {} not found",
+                    comment.target_id
+                )))
+            }
+            Err(err) => return Err(HandlerError::Retry(err.into())),
+        };
+
+        let scheme = match CONFIG.client.ssl {
+            true => "https",
+            false => "http",
+        };
+        let link = format!(
+            "{}://{}:{}/{}/{}#comment-{}",
+            scheme, CONFIG.client.host, CONFIG.client.port, path, comment.target_id, comment.id
+        );
+
+        let mut recipients = Vec::new();
+
+        for recipient_id in event.recipients.iter() {
+            let recipient = match UserRepositoryImpl::find(&mut transaction, recipient_id).await {
+                Ok(recipient) => recipient,
+                Err(sqlx::Error::RowNotFound) => continue,
+                Err(err) => return Err(HandlerError::Retry(err.into())),
+            };
+
+            let reply_to = reply::address(
+                CONFIG.auth.hmac_secret.expose_secret(),
+                &self.settings.domain,
+                &comment.id,
+                &recipient.id,
+            )
+            .map_err(|err| {
+                HandlerError::Reject(anyhow::anyhow!("Invalid reply secret: {}", err))
+            })?;
+
+            recipients.push((recipient, reply_to));
+        }
+
+        transaction
+            .commit()
+            .await
+            .map_err(|err| HandlerError::Retry(err.into()))?;
+
+        // Every recipient is claimed while their email is sent, so when the
+        // message is retried only those whose email failed get it again
+        let mut failed = None;
+
+        for (recipient, reply_to) in recipients {
+            let sent = async {
+                let mut transaction = self.db.begin().await?;
+
+                let claimed = ProcessedMessageRepositoryImpl::claim_recipient(
+                    &mut transaction,
+                    &envelope.id,
+                    &recipient.id,
+                )
+                .await?;
+
+                if !claimed {
+                    return Ok(());
+                }
+
+                self.mail_service
+                    .send(
+                        &Recipient {
+                            user_id: Some(recipient.id.to_owned()),
+                            email: recipient.email.to_owned(),
+                            locale: recipient.locale.to_owned(),
+                        },
+                        &CommentEmail {
+                            commenter: commenter.username.to_owned(),
+                            title: title.to_owned(),
+                            content: comment.content.to_owned(),
+                            link: link.to_owned(),
+                            reply_to,
+                        },
+                    )
+                    .await?;
+
+                transaction.commit().await?;
+
+                Ok::<_, anyhow::Error>(())
+            }
+            .await;
+
+            if let Err(err) = sent {
+                warn!("Unable to email comment to {}: {:?}", recipient.id, err);
+                failed = Some(err);
+            }
+        }
+
+        match failed {
+            Some(err) => Err(HandlerError::Retry(err)),
+            None => Ok(()),
+        }
+    }
+}
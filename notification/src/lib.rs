@@ -1,2 +1,5 @@
 pub mod application;
+pub mod consumer;
+pub mod emails;
+pub mod handlers;
 pub mod services;
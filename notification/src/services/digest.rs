@@ -0,0 +1,183 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Datelike, Duration, Utc};
+use shared::{
+    configuration::{DigestSettings, CONFIG},
+    models::{digest_model::DigestRecipient, enums::DigestFrequency},
+    repositories::digest_repository::{DigestRepository, DigestRepositoryImpl},
+};
+use sqlx::PgPool;
+use tracing::{error, info};
+
+use crate::emails::DigestEmail;
+
+use super::mail::{MailService, Recipient};
+
+/// The last complete period for `frequency` at `now`, in UTC. Daily digests
+/// cover the previous day, weekly ones the previous Monday-to-Monday week.
+pub fn period(
+    frequency: DigestFrequency,
+    now: DateTime<Utc>,
+) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let today = now.date_naive().and_hms_opt(0, 0, 0)?.and_utc();
+    match frequency {
+        DigestFrequency::Never => None,
+        DigestFrequency::Daily => Some((today - Duration::days(1), today)),
+        DigestFrequency::Weekly => {
+            let until = today - Duration::days(now.weekday().num_days_from_monday() as i64);
+            Some((until - Duration::weeks(1), until))
+        }
+    }
+}
+
+pub struct DigestService {
+    pub db: PgPool,
+    pub mail_service: Arc<MailService>,
+    pub settings: DigestSettings,
+}
+
+impl DigestService {
+    pub async fn run(self) {
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(self.settings.interval * 60));
+        loop {
+            interval.tick().await;
+            let now = Utc::now();
+            for frequency in [DigestFrequency::Daily, DigestFrequency::Weekly] {
+                if let Err(err) = self.send_due(frequency, now).await {
+                    error!("Unable to send {} digests: {:?}", frequency, err);
+                }
+            }
+        }
+    }
+
+    pub async fn send_due(
+        &self,
+        frequency: DigestFrequency,
+        now: DateTime<Utc>,
+    ) -> Result<(), anyhow::Error> {
+        let (since, until) = match period(frequency, now) {
+            Some(period) => period,
+            None => return Ok(()),
+        };
+
+        loop {
+            let mut transaction = self.db.begin().await?;
+            let recipients = DigestRepositoryImpl::find_due(
+                &mut transaction,
+                frequency,
+                since,
+                self.settings.batch_size,
+            )
+            .await?;
+            transaction.commit().await?;
+
+            if recipients.is_empty() {
+                return Ok(());
+            }
+
+            info!("Sending {} {} digests", recipients.len(), frequency);
+
+            let mut processed = 0;
+            for recipient in recipients.iter() {
+                match self.deliver(frequency, since, until, recipient).await {
+                    Ok(_) => processed += 1,
+                    Err(err) => error!("Unable to send digest to {}: {:?}", recipient.id, err),
+                }
+            }
+
+            // Failed deliveries are released and show up again, stop instead
+            // of retrying them in a loop until the next tick
+            if processed == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    async fn deliver(
+        &self,
+        frequency: DigestFrequency,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        recipient: &DigestRecipient,
+    ) -> Result<(), anyhow::Error> {
+        let mut transaction = self.db.begin().await?;
+
+        // Another instance got to this user first
+        if !DigestRepositoryImpl::claim(&mut transaction, &recipient.id, frequency, since).await? {
+            return Ok(());
+        }
+
+        let limit = self.settings.limit;
+        let followed = DigestRepositoryImpl::followed_articles(
+            &mut transaction,
+            &recipient.id,
+            since,
+            until,
+            limit,
+        )
+        .await?;
+        let interests = DigestRepositoryImpl::interest_articles(
+            &mut transaction,
+            &recipient.id,
+            since,
+            until,
+            limit,
+        )
+        .await?;
+        let activity =
+            DigestRepositoryImpl::activity(&mut transaction, &recipient.id, since, until, limit)
+                .await?;
+
+        // The claim is committed before sending, an interrupted send is lost
+        // rather than delivered twice
+        transaction.commit().await?;
+
+        if followed.is_empty() && interests.is_empty() && activity.is_empty() {
+            return Ok(());
+        }
+
+        let scheme = match CONFIG.client.ssl {
+            true => "https",
+            false => "http",
+        };
+
+        let sent = self
+            .mail_service
+            .send(
+                &Recipient {
+                    user_id: Some(recipient.id.to_owned()),
+                    email: recipient.email.to_owned(),
+                    locale: recipient.locale.to_owned(),
+                },
+                &DigestEmail {
+                    username: recipient.username.to_owned(),
+                    frequency,
+                    client_url: format!(
+                        "{}://{}:{}",
+                        scheme, CONFIG.client.host, CONFIG.client.port
+                    ),
+                    followed,
+                    interests,
+                    activity,
+                },
+            )
+            .await;
+
+        let mut transaction = self.db.begin().await?;
+        match sent {
+            Ok(_) => {
+                DigestRepositoryImpl::mark_sent(&mut transaction, &recipient.id, frequency, since)
+                    .await?;
+                transaction.commit().await?;
+                Ok(())
+            }
+            Err(err) => {
+                DigestRepositoryImpl::release(&mut transaction, &recipient.id, frequency, since)
+                    .await?;
+                transaction.commit().await?;
+                Err(err)
+            }
+        }
+    }
+}
@@ -1,17 +1,38 @@
 use lettre::{
-    message::header::ContentType,
-    transport::{smtp::authentication::Credentials, smtp::Error},
-    AsyncSmtpTransport, Message, Tokio1Executor,
+    message::{
+        header::{Header, HeaderName, HeaderValue},
+        MultiPart,
+    },
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
 };
 use secrecy::ExposeSecret;
-use shared::configuration::{MailSettings, CONFIG};
+use shared::{
+    configuration::MailSettings,
+    repositories::notification_settings_repository::{
+        NotificationSettingsRepository, NotificationSettingsRepositoryImpl,
+    },
+    utils::jwt::{UnsubscribeToken, UnsubscribeTokenPayload},
+};
+use sqlx::PgPool;
+
+use super::template::{EmailTemplate, TemplateService};
+
+pub struct Recipient {
+    pub user_id: Option<String>,
+    pub email: String,
+    pub locale: String,
+}
 
 pub struct MailService {
     pub mailer: AsyncSmtpTransport<Tokio1Executor>,
+    pub templates: TemplateService,
+    db: PgPool,
+    sender: String,
 }
 
 impl MailService {
-    pub fn build(configuration: &MailSettings) -> Result<Self, Error> {
+    pub fn build(configuration: &MailSettings, db: PgPool) -> Result<Self, anyhow::Error> {
         let credentials = Credentials::new(
             configuration.smtp_user.to_owned(),
             configuration.smtp_password.expose_secret().to_owned(),
@@ -20,282 +41,116 @@ impl MailService {
             mailer: AsyncSmtpTransport::<Tokio1Executor>::relay("smtp.gmail.com")?
                 .credentials(credentials)
                 .build(),
+            templates: TemplateService::build()?,
+            db,
+            sender: format!("Orta <{}>", configuration.smtp_user),
         })
     }
+
+    /// Builds a multipart/alternative message with text and HTML bodies.
+    pub fn compose<T: EmailTemplate>(
+        &self,
+        to: &Recipient,
+        email: &T,
+    ) -> Result<Message, anyhow::Error> {
+        let unsubscribe_link = match (email.unsubscribe_link(), T::EVENT, &to.user_id) {
+            (Some(link), _, _) => Some(link),
+            (None, Some(event), Some(user_id)) => {
+                Some(UnsubscribeToken::link(UnsubscribeTokenPayload {
+                    user_id: user_id.to_owned(),
+                    event,
+                })?)
+            }
+            _ => None,
+        };
+
+        let rendered = self
+            .templates
+            .render(&to.locale, email, unsubscribe_link.as_deref())?;
+
+        let reply_to = match email.reply_to() {
+            Some(reply_to) => format!("Orta <{}>", reply_to),
+            None => self.sender.to_owned(),
+        };
+
+        let mut builder = Message::builder()
+            .from(self.sender.parse()?)
+            .reply_to(reply_to.parse()?)
+            .to(to.email.parse()?)
+            .subject(rendered.subject);
+
+        if let Some(unsubscribe_link) = unsubscribe_link {
+            builder = builder
+                .header(ListUnsubscribe(unsubscribe_link))
+                .header(ListUnsubscribePost);
+        }
+
+        Ok(builder.multipart(MultiPart::alternative_plain_html(
+            rendered.text,
+            rendered.html,
+        ))?)
+    }
+
+    /// Sends the email, unless it has an event the user turned emails off for
+    pub async fn send<T: EmailTemplate>(
+        &self,
+        to: &Recipient,
+        email: &T,
+    ) -> Result<(), anyhow::Error> {
+        if let (Some(event), Some(user_id)) = (T::EVENT, &to.user_id) {
+            let mut transaction = self.db.begin().await?;
+            let setting =
+                NotificationSettingsRepositoryImpl::find(&mut transaction, user_id, event).await?;
+            transaction.commit().await?;
+
+            if !setting.email {
+                return Ok(());
+            }
+        }
+
+        let message = self.compose(to, email)?;
+        self.mailer.send(message).await?;
+        Ok(())
+    }
 }
 
-pub fn verification_message(
-    to: &str,
-    verification_link: &str,
-) -> Result<Message, lettre::error::Error> {
-    let plain_text = format!(
-        r##"
-            <!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.0 Transitional//EN" "http://www.w3.org/TR/xhtml1/DTD/xhtml1-transitional.dtd">
-            <html xmlns="http://www.w3.org/1999/xhtml">
-              <head>
-                <meta name="viewport" content="width=device-width, initial-scale=1.0" />
-                <meta http-equiv="Content-Type" content="text/html; charset=UTF-8" />
-                <title>Verify your email address</title>
-                <style type="text/css" rel="stylesheet" media="all">
-                  *:not(br):not(tr):not(html) {{
-                    font-family: Arial, "Helvetica Neue", Helvetica, sans-serif;
-                    -webkit-box-sizing: border-box;
-                    box-sizing: border-box;
-                  }}
-                  body {{
-                    width: 100% !important;
-                    height: 100%;
-                    margin: 0;
-                    line-height: 1.4;
-                    background-color: #f5f7f9;
-                    color: #839197;
-                    -webkit-text-size-adjust: none;
-                  }}
-                  a {{
-                    color: #414ef9;
-                  }}
-                  .email-wrapper {{
-                    width: 100%;
-                    margin: 0;
-                    padding: 0;
-                    background-color: #f5f7f9;
-                  }}
-                  .email-content {{
-                    width: 100%;
-                    margin: 0;
-                    padding: 0;
-                  }}
-                  .email-masthead {{
-                    padding: 25px 0;
-                    text-align: center;
-                  }}
-                  .email-masthead_logo {{
-                    max-width: 400px;
-                    border: 0;
-                  }}
-                  .email-masthead_name {{
-                    font-size: 16px;
-                    font-weight: bold;
-                    color: #839197;
-                    text-decoration: none;
-                    text-shadow: 0 1px 0 white;
-                  }}
-                  .email-body {{
-                    width: 100%;
-                    margin: 0;
-                    padding: 0;
-                    border-top: 1px solid #e7eaec;
-                    border-bottom: 1px solid #e7eaec;
-                    background-color: #ffffff;
-                  }}
-                  .email-body_inner {{
-                    width: 570px;
-                    margin: 0 auto;
-                    padding: 0;
-                  }}
-                  .email-footer {{
-                    width: 570px;
-                    margin: 0 auto;
-                    padding: 0;
-                    text-align: center;
-                  }}
-                  .email-footer p {{
-                    color: #839197;
-                  }}
-                  .body-action {{
-                    width: 100%;
-                    margin: 30px auto;
-                    padding: 0;
-                    text-align: center;
-                  }}
-                  .body-sub {{
-                    margin-top: 25px;
-                    padding-top: 25px;
-                    border-top: 1px solid #e7eaec;
-                  }}
-                  .content-cell {{
-                    padding: 35px;
-                  }}
-                  .align-right {{
-                    text-align: right;
-                  }}
-                  h1 {{
-                    margin-top: 0;
-                    color: #292e31;
-                    font-size: 19px;
-                    font-weight: bold;
-                    text-align: left;
-                  }}
-                  h2 {{
-                    margin-top: 0;
-                    color: #292e31;
-                    font-size: 16px;
-                    font-weight: bold;
-                    text-align: left;
-                  }}
-                  h3 {{
-                    margin-top: 0;
-                    color: #292e31;
-                    font-size: 14px;
-                    font-weight: bold;
-                    text-align: left;
-                  }}
-                  p {{
-                    margin-top: 0;
-                    color: #839197;
-                    font-size: 16px;
-                    line-height: 1.5em;
-                    text-align: left;
-                  }}
-                  p.sub {{
-                    font-size: 12px;
-                  }}
-                  p.center {{
-                    text-align: center;
-                  }}
-                  .button {{
-                    display: inline-block;
-                    width: 200px;
-                    background-color: #414ef9;
-                    border-radius: 3px;
-                    color: #ffffff;
-                    font-size: 15px;
-                    line-height: 45px;
-                    text-align: center;
-                    text-decoration: none; -webkit-text-size-adjust: none;
-                    mso-hide: all;
-                  }}
-                  .button--green {{
-                    background-color: #28db67;
-                  }}
-                  .button--red {{
-                    background-color: #ff3665;
-                  }}
-                  .button--blue {{
-                    background-color: #414ef9;
-                  }}
+/// RFC 2369 `List-Unsubscribe`
+#[derive(Debug, Clone)]
+pub struct ListUnsubscribe(pub String);
 
-                  /*Media Queries ------------------------------ */
-                  @media only screen and (max-width: 600px) {{
-                    .email-body_inner,
-                    .email-footer {{
-                      width: 100% !important;
-                    }}
-                  }}
-                  @media only screen and (max-width: 500px) {{
-                    .button {{
-                      width: 100% !important;
-                    }}
-                  }}
-                </style>
-              </head>
-              <body>
-                <table class="email-wrapper" width="100%" cellpadding="0" cellspacing="0">
-                  <tr>
-                    <td align="center">
-                      <table
-                        class="email-content"
-                        width="100%"
-                        cellpadding="0"
-                        cellspacing="0"
-                      >
-                        <!-- Logo -->
-                        <tr>
-                          <td class="email-masthead">
-                            <a class="email-masthead_name">Orta</a>
-                          </td>
-                        </tr>
-                        <!-- Email Body -->
-                        <tr>
-                          <td class="email-body" width="100%">
-                            <table
-                              class="email-body_inner"
-                              align="center"
-                              width="570"
-                              cellpadding="0"
-                              cellspacing="0"
-                            >
-                              <!-- Body content -->
-                              <tr>
-                                <td class="content-cell">
-                                  <h1>Verify your email address</h1>
-                                  <p>
-                                    Thanks for signing up for Orta! We're excited to have
-                                    you as an early user.
-                                  </p>
-                                  <!-- Action -->
-                                  <table
-                                    class="body-action"
-                                    align="center"
-                                    width="100%"
-                                    cellpadding="0"
-                                    cellspacing="0"
-                                  >
-                                    <tr>
-                                      <td align="center">
-                                        <div>
-                                            <a href="{verification_link}" class="button button--blue">Verify Email</a>
-                                        </div>
-                                      </td>
-                                    </tr>
-                                  </table>
-                                  <p>Thanks,<br />The Orta Team</p>
-                                  <!-- Sub copy -->
-                                  <table class="body-sub">
-                                    <tr>
-                                      <td>
-                                        <p class="sub">
-                                          If you’re having trouble clicking the button, copy
-                                          and paste the URL below into your web browser.
-                                        </p>
-                                        <p class="sub">
-                                          <a href="{verification_link}">{verification_link}</a>
-                                        </p>
-                                      </td>
-                                    </tr>
-                                  </table>
-                                </td>
-                              </tr>
-                            </table>
-                          </td>
-                        </tr>
-                        <tr>
-                          <td>
-                            <table
-                              class="email-footer"
-                              align="center"
-                              width="570"
-                              cellpadding="0"
-                              cellspacing="0"
-                            >
-                              <tr>
-                                <td class="content-cell">
-                                  <p class="sub center">
-                                    Orta, Inc.
-                                    <!-- <br />325 9th St, San Francisco, CA 94103 -->
-                                  </p>
-                                </td>
-                              </tr>
-                            </table>
-                          </td>
-                        </tr>
-                      </table>
-                    </td>
-                  </tr>
-                </table>
-              </body>
-            </html>
-            "##
-    );
+impl Header for ListUnsubscribe {
+    fn name() -> HeaderName {
+        HeaderName::new_from_ascii_str("List-Unsubscribe")
+    }
 
-    let from = format!("Orta <{}>", CONFIG.mail.smtp_user);
-    let reply_to = format!("Orta <{}>", CONFIG.mail.smtp_user);
+    fn parse(s: &str) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self(
+            s.trim()
+                .trim_start_matches('<')
+                .trim_end_matches('>')
+                .to_string(),
+        ))
+    }
+
+    fn display(&self) -> HeaderValue {
+        HeaderValue::new(Self::name(), format!("<{}>", self.0))
+    }
+}
 
-    Message::builder()
-        .from(from.parse().unwrap())
-        .reply_to(reply_to.parse().unwrap())
-        .to(to.parse().unwrap())
-        .subject("[Orta] Please verify your email")
-        .header(ContentType::TEXT_HTML)
-        .body(plain_text)
+/// RFC 8058 `List-Unsubscribe-Post`, marks the unsubscribe link as one-click
+#[derive(Debug, Clone)]
+pub struct ListUnsubscribePost;
+
+impl Header for ListUnsubscribePost {
+    fn name() -> HeaderName {
+        HeaderName::new_from_ascii_str("List-Unsubscribe-Post")
+    }
+
+    fn parse(_: &str) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self)
+    }
+
+    fn display(&self) -> HeaderValue {
+        HeaderValue::new(Self::name(), String::from("List-Unsubscribe=One-Click"))
+    }
 }
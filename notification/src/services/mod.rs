@@ -1 +1,6 @@
+pub mod digest;
 pub mod mail;
+pub mod newsletter;
+pub mod reply;
+pub mod template;
+pub mod webhook;
@@ -0,0 +1,172 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use lettre::AsyncTransport;
+use shared::{
+    configuration::{NewsletterSettings, CONFIG},
+    models::{enums::NewsletterSendStatus, newsletter_model::PendingNewsletterSend},
+    repositories::newsletter_repository::{NewsletterRepository, NewsletterRepositoryImpl},
+};
+use sqlx::PgPool;
+use tokio::time::{interval, Duration, MissedTickBehavior};
+use tracing::{error, info, warn};
+
+use crate::emails::NewsletterEmail;
+
+use super::{
+    mail::{MailService, Recipient},
+    webhook::backoff,
+};
+
+pub struct NewsletterService {
+    pub db: PgPool,
+    pub mail_service: Arc<MailService>,
+    pub settings: NewsletterSettings,
+}
+
+impl NewsletterService {
+    pub async fn run(self) {
+        let mut interval = interval(Duration::from_secs(self.settings.interval));
+        loop {
+            interval.tick().await;
+            if let Err(err) = self.send_due().await {
+                error!("Unable to send newsletters: {:?}", err);
+            }
+        }
+    }
+
+    pub async fn send_due(&self) -> Result<(), anyhow::Error> {
+        let rate = self.settings.rate.max(1);
+        let mut throttle = interval(Duration::from_secs(1) / rate);
+        throttle.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        // Long enough for the whole batch at the configured rate
+        let lease = self.settings.batch_size / rate as i64 * 2 + 60;
+
+        loop {
+            let mut transaction = self.db.begin().await?;
+            let sends = NewsletterRepositoryImpl::claim_due(
+                &mut transaction,
+                self.settings.batch_size,
+                lease,
+            )
+            .await?;
+            transaction.commit().await?;
+
+            if sends.is_empty() {
+                return Ok(());
+            }
+
+            info!("Sending {} newsletter emails", sends.len());
+
+            for send in sends.iter() {
+                throttle.tick().await;
+                if let Err(err) = self.deliver(send).await {
+                    error!("Unable to record newsletter send {}: {:?}", send.id, err);
+                }
+            }
+        }
+    }
+
+    async fn deliver(&self, send: &PendingNewsletterSend) -> Result<(), anyhow::Error> {
+        let api_scheme = match CONFIG.api_server.ssl {
+            true => "https",
+            false => "http",
+        };
+        let api_url = format!(
+            "{}://{}:{}/api/newsletter",
+            api_scheme, CONFIG.api_server.host, CONFIG.api_server.port
+        );
+        let client_scheme = match CONFIG.client.ssl {
+            true => "https",
+            false => "http",
+        };
+
+        let email = NewsletterEmail {
+            author: send.author.to_owned(),
+            title: send.title.to_owned(),
+            description: send.description.to_owned(),
+            content: send.content.to_owned(),
+            article_link: format!(
+                "{}://{}:{}/article/{}",
+                client_scheme, CONFIG.client.host, CONFIG.client.port, send.article_id
+            ),
+            open_link: format!("{}/opens/{}", api_url, send.id),
+            unsubscribe_link: format!("{}/unsubscribe?token={}", api_url, send.token),
+        };
+
+        let recipient = Recipient {
+            user_id: None,
+            email: send.email.to_owned(),
+            locale: send.locale.to_owned(),
+        };
+
+        let sent = match self.mail_service.compose(&recipient, &email) {
+            Ok(message) => self
+                .mail_service
+                .mailer
+                .send(message)
+                .await
+                .map_err(Failure::Smtp),
+            Err(err) => Err(Failure::Compose(err)),
+        };
+
+        let mut transaction = self.db.begin().await?;
+
+        match sent {
+            Ok(_) => {
+                NewsletterRepositoryImpl::complete(
+                    &mut transaction,
+                    &send.id,
+                    NewsletterSendStatus::Sent,
+                    None,
+                    None,
+                )
+                .await?;
+            }
+            // Bad address or template, retrying will not help
+            Err(Failure::Compose(err)) => {
+                warn!("Unable to compose newsletter {}: {:?}", send.id, err);
+                NewsletterRepositoryImpl::complete(
+                    &mut transaction,
+                    &send.id,
+                    NewsletterSendStatus::Failed,
+                    None,
+                    Some(&err.to_string()),
+                )
+                .await?;
+            }
+            Err(Failure::Smtp(err)) if err.is_permanent() => {
+                warn!("Newsletter {} bounced: {}", send.id, err);
+                NewsletterRepositoryImpl::bounce(&mut transaction, &send.id, &err.to_string())
+                    .await?;
+            }
+            Err(Failure::Smtp(err)) => {
+                warn!("Newsletter {} failed: {}", send.id, err);
+                let (status, next_attempt_at) = match send.attempts >= self.settings.max_attempts {
+                    true => (NewsletterSendStatus::Failed, None),
+                    false => (
+                        NewsletterSendStatus::Pending,
+                        Some(Utc::now() + backoff(send.attempts, self.settings.backoff)),
+                    ),
+                };
+                NewsletterRepositoryImpl::complete(
+                    &mut transaction,
+                    &send.id,
+                    status,
+                    next_attempt_at,
+                    Some(&err.to_string()),
+                )
+                .await?;
+            }
+        }
+
+        transaction.commit().await?;
+        Ok(())
+    }
+}
+
+enum Failure {
+    Compose(anyhow::Error),
+    Smtp(lettre::transport::smtp::Error),
+}
@@ -0,0 +1,459 @@
+use std::sync::Arc;
+
+use mail_parser::{Message, MessageParser};
+use secrecy::ExposeSecret;
+use shared::{
+    comment::{comment_service_client::CommentServiceClient, CreateRequest},
+    common,
+    configuration::{ReplySettings, CONFIG},
+    repositories::{
+        comment_repository::{CommentRepository, CommentRepositoryImpl},
+        processed_message_repository::{
+            ProcessedMessageRepository, ProcessedMessageRepositoryImpl,
+        },
+        user_repository::{UserRepository, UserRepositoryImpl},
+    },
+    utils::reply,
+};
+use sqlx::PgPool;
+use tokio::{
+    io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+};
+use tonic::{transport::Channel, Code};
+use tracing::{error, info, warn};
+
+/// Longest command or data line, RFC 5321 allows 1000 with the line break
+const MAX_LINE: u64 = 1000;
+
+/// Outcome of delivering a message to one recipient
+#[derive(Debug)]
+enum Delivery {
+    Accepted,
+    /// Permanent failure, the MTA bounces the message to the sender
+    Rejected(String),
+    /// Temporary failure, the MTA retries later
+    Deferred(String),
+}
+
+impl Delivery {
+    fn reply(&self) -> String {
+        match self {
+            Delivery::Accepted => String::from("250 2.0.0 Ok"),
+            Delivery::Rejected(message) => format!("550 5.7.1 {}", message),
+            Delivery::Deferred(message) => format!("451 4.3.0 {}", message),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Session {
+    lmtp: bool,
+    sender: Option<String>,
+    recipients: Vec<String>,
+}
+
+impl Session {
+    fn reset(&mut self) {
+        self.sender = None;
+        self.recipients.clear();
+    }
+}
+
+/// Receives replies to comment notifications over LMTP, or SMTP for local
+/// testing, and posts them as threaded comments. The reply address is
+/// signed for the notified user, so a reply is only accepted from that
+/// user's email.
+pub struct ReplyService {
+    db: PgPool,
+    settings: ReplySettings,
+    channel: Channel,
+}
+
+impl ReplyService {
+    pub fn build(db: PgPool, settings: ReplySettings) -> Result<Self, anyhow::Error> {
+        // Reply addresses signed with an empty secret can be forged by anyone
+        if CONFIG.auth.hmac_secret.expose_secret().is_empty() {
+            return Err(anyhow::anyhow!(
+                "No auth.hmac_secret to verify replies with"
+            ));
+        }
+
+        let channel = Channel::from_shared(format!(
+            "http://{}:{}",
+            CONFIG.resource_server.host, CONFIG.resource_server.port
+        ))?
+        .connect_lazy();
+
+        Ok(Self {
+            db,
+            settings,
+            channel,
+        })
+    }
+
+    pub async fn run(self) {
+        let listener =
+            match TcpListener::bind((self.settings.host.as_str(), self.settings.port)).await {
+                Ok(listener) => listener,
+                Err(err) => {
+                    error!("Unable to start reply listener: {:?}", err);
+                    return;
+                }
+            };
+
+        info!(
+            "Listening for replies on {}:{}",
+            self.settings.host, self.settings.port
+        );
+
+        let service = Arc::new(self);
+
+        loop {
+            match listener.accept().await {
+                Ok((stream, address)) => {
+                    let service = service.clone();
+                    tokio::spawn(async move {
+                        if let Err(err) = service.session(stream).await {
+                            warn!("Reply session with {} failed: {:?}", address, err);
+                        }
+                    });
+                }
+                Err(err) => error!("Unable to accept reply connection: {:?}", err),
+            }
+        }
+    }
+
+    async fn session(&self, stream: TcpStream) -> Result<(), anyhow::Error> {
+        let (reader, mut writer) = stream.into_split();
+        let mut reader = BufReader::new(reader);
+        let mut session = Session::default();
+        let mut line = Vec::new();
+
+        writer
+            .write_all(format!("220 {} LMTP ready\r\n", self.settings.domain).as_bytes())
+            .await?;
+
+        loop {
+            if read_line(&mut reader, &mut line).await? == 0 {
+                return Ok(());
+            }
+
+            let command = String::from_utf8_lossy(&line).trim_end().to_string();
+            let (verb, argument) = command.split_once(' ').unwrap_or((&command, ""));
+
+            let reply = match verb.to_uppercase().as_str() {
+                verb @ ("LHLO" | "EHLO") => {
+                    session.reset();
+                    session.lmtp = verb == "LHLO";
+                    format!(
+                        "250-{}\r\n250-PIPELINING\r\n250-8BITMIME\r\n250-ENHANCEDSTATUSCODES\r\n250 SIZE {}",
+                        self.settings.domain, self.settings.max_size
+                    )
+                }
+                "HELO" => {
+                    session.reset();
+                    format!("250 {}", self.settings.domain)
+                }
+                "MAIL" => match path(argument, "FROM:") {
+                    Some(sender) => {
+                        session.reset();
+                        session.sender = Some(sender.to_owned());
+                        String::from("250 2.1.0 Ok")
+                    }
+                    None => String::from("501 5.5.4 Syntax: MAIL FROM:<address>"),
+                },
+                "RCPT" => match (&session.sender, path(argument, "TO:")) {
+                    (None, _) => String::from("503 5.5.1 Need MAIL first"),
+                    (_, None) => String::from("501 5.5.4 Syntax: RCPT TO:<address>"),
+                    (Some(_), Some(recipient)) => {
+                        match reply::parse(recipient, &self.settings.domain) {
+                            Some(_) => {
+                                session.recipients.push(recipient.to_owned());
+                                String::from("250 2.1.5 Ok")
+                            }
+                            None => String::from("550 5.1.1 No such recipient"),
+                        }
+                    }
+                },
+                "DATA" if session.recipients.is_empty() => {
+                    String::from("503 5.5.1 No valid recipients")
+                }
+                "DATA" => {
+                    writer
+                        .write_all(b"354 End data with <CR><LF>.<CR><LF>\r\n")
+                        .await?;
+
+                    let (data, oversized) =
+                        read_data(&mut reader, &mut line, self.settings.max_size).await?;
+
+                    let mut deliveries = Vec::new();
+                    for recipient in session.recipients.iter() {
+                        deliveries.push(match oversized {
+                            true => Delivery::Rejected(String::from("Message too large")),
+                            false => self.deliver(recipient, &data).await,
+                        });
+                    }
+
+                    session.reset();
+
+                    // LMTP answers for every recipient, SMTP once for the message
+                    match session.lmtp {
+                        true => deliveries
+                            .iter()
+                            .map(|delivery| delivery.reply())
+                            .collect::<Vec<_>>()
+                            .join("\r\n"),
+                        false => deliveries
+                            .iter()
+                            .find(|delivery| !matches!(delivery, Delivery::Accepted))
+                            .unwrap_or(&Delivery::Accepted)
+                            .reply(),
+                    }
+                }
+                "RSET" => {
+                    session.reset();
+                    String::from("250 2.0.0 Ok")
+                }
+                "NOOP" => String::from("250 2.0.0 Ok"),
+                "VRFY" => String::from("252 2.5.0 Cannot verify"),
+                "QUIT" => {
+                    writer.write_all(b"221 2.0.0 Bye\r\n").await?;
+                    return Ok(());
+                }
+                _ => String::from("500 5.5.2 Command not recognized"),
+            };
+
+            writer
+                .write_all(format!("{}\r\n", reply).as_bytes())
+                .await?;
+        }
+    }
+
+    async fn deliver(&self, recipient: &str, data: &[u8]) -> Delivery {
+        match self.post_reply(recipient, data).await {
+            Ok(delivery) => delivery,
+            Err(err) => {
+                error!("Unable to post reply to {}: {:?}", recipient, err);
+                Delivery::Deferred(String::from("Try again later"))
+            }
+        }
+    }
+
+    async fn post_reply(&self, recipient: &str, data: &[u8]) -> Result<Delivery, anyhow::Error> {
+        let (comment_id, signature) = match reply::parse(recipient, &self.settings.domain) {
+            Some(parsed) => parsed,
+            None => return Ok(Delivery::Rejected(String::from("No such recipient"))),
+        };
+
+        let message = match MessageParser::default().parse(data) {
+            Some(message) => message,
+            None => return Ok(Delivery::Rejected(String::from("Malformed message"))),
+        };
+
+        // Vacation responders would otherwise post themselves as comments
+        if is_auto_reply(&message) {
+            info!("Dropping automatic reply to {}", recipient);
+            return Ok(Delivery::Accepted);
+        }
+
+        if let Some(results) = message.header_raw("Authentication-Results") {
+            if results.to_lowercase().contains("dmarc=fail") {
+                return Ok(Delivery::Rejected(String::from("Sender failed DMARC")));
+            }
+        }
+
+        let sender = match message
+            .from()
+            .and_then(|from| from.first())
+            .and_then(|from| from.address())
+        {
+            Some(sender) => sender.to_lowercase(),
+            None => return Ok(Delivery::Rejected(String::from("Missing sender"))),
+        };
+
+        let mut transaction = self.db.begin().await?;
+
+        let user = match UserRepositoryImpl::find_by_email(&mut transaction, &sender).await {
+            Ok(user) => user,
+            Err(sqlx::Error::RowNotFound) => {
+                return Ok(Delivery::Rejected(String::from("Unknown sender")))
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        if !reply::verify(
+            CONFIG.auth.hmac_secret.expose_secret(),
+            comment_id,
+            &user.id,
+            signature,
+        )
+        .map_err(|err| anyhow::anyhow!("Invalid reply secret: {}", err))?
+        {
+            warn!("Reply to {} from {} failed verification", recipient, sender);
+            return Ok(Delivery::Rejected(String::from("Invalid reply address")));
+        }
+
+        let parent = match CommentRepositoryImpl::find(&mut transaction, comment_id, None).await {
+            Ok(parent) => parent,
+            Err(sqlx::Error::RowNotFound) => {
+                return Ok(Delivery::Rejected(String::from("Comment not found")))
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        // The MTA redelivers when a reply times out after it was posted
+        if let Some(message_id) = message.message_id() {
+            if !ProcessedMessageRepositoryImpl::claim(
+                &mut transaction,
+                message_id,
+                "reply",
+                "comment",
+            )
+            .await?
+            {
+                return Ok(Delivery::Accepted);
+            }
+        }
+
+        let content = strip_reply(&message.body_text(0).unwrap_or_default());
+
+        if content.is_empty() {
+            return Ok(Delivery::Rejected(String::from("Empty reply")));
+        }
+
+        if let Err(status) = CommentServiceClient::new(self.channel.clone())
+            .create(CreateRequest {
+                user_id: user.id,
+                target_id: parent.target_id,
+                content,
+                r#type: common::CommentableType::from(parent.r#type) as i32,
+                parent_id: Some(parent.id),
+            })
+            .await
+        {
+            return match status.code() {
+                Code::InvalidArgument | Code::NotFound | Code::PermissionDenied => {
+                    Ok(Delivery::Rejected(status.message().to_owned()))
+                }
+                _ => Err(status.into()),
+            };
+        }
+
+        transaction.commit().await?;
+
+        info!("Posted reply from {} to comment {}", sender, comment_id);
+
+        Ok(Delivery::Accepted)
+    }
+}
+
+/// Keeps the text the user wrote: drops quoted lines, the quote header
+/// mail clients add above them, forwarded originals and signatures
+pub fn strip_reply(body: &str) -> String {
+    let body = body.replace("\r\n", "\n");
+    let lines: Vec<&str> = body.lines().collect();
+    let mut reply = Vec::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+
+        if is_signature(line) || is_original_message(trimmed) || is_quote_header(trimmed) {
+            break;
+        }
+
+        // Quote headers are often wrapped over two lines
+        if trimmed.starts_with("On ") || trimmed.starts_with("В ") {
+            if let Some(next) = lines.get(index + 1) {
+                if is_quote_header(next.trim()) {
+                    break;
+                }
+            }
+        }
+
+        if trimmed.starts_with('>') {
+            continue;
+        }
+
+        reply.push(*line);
+    }
+
+    reply.join("\n").trim().to_string()
+}
+
+fn is_signature(line: &str) -> bool {
+    let trimmed = line.trim();
+    line == "-- "
+        || trimmed == "--"
+        || trimmed.starts_with("Sent from my ")
+        || trimmed.starts_with("Отправлено с ")
+}
+
+fn is_original_message(line: &str) -> bool {
+    (line.starts_with("-----") && line.ends_with("-----"))
+        || (line.len() >= 10 && line.chars().all(|char| char == '_'))
+}
+
+fn is_quote_header(line: &str) -> bool {
+    line.ends_with("wrote:") || line.ends_with("написал:") || line.ends_with("написал(а):")
+}
+
+fn is_auto_reply(message: &Message) -> bool {
+    message
+        .header_raw("Auto-Submitted")
+        .map(|value| !value.trim().eq_ignore_ascii_case("no"))
+        .unwrap_or(false)
+        || message.header_raw("X-Autoreply").is_some()
+}
+
+/// Address of a `MAIL FROM:<...>` or `RCPT TO:<...>` argument, parameters
+/// after it are ignored
+fn path<'a>(argument: &'a str, prefix: &str) -> Option<&'a str> {
+    let argument = argument.trim();
+    if argument.len() < prefix.len() || !argument[..prefix.len()].eq_ignore_ascii_case(prefix) {
+        return None;
+    }
+    let path = argument[prefix.len()..].trim_start();
+    let path = path.split_whitespace().next().unwrap_or_default();
+    Some(path.trim_start_matches('<').trim_end_matches('>'))
+}
+
+async fn read_line<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+    line: &mut Vec<u8>,
+) -> std::io::Result<usize> {
+    line.clear();
+    (&mut *reader).take(MAX_LINE).read_until(b'\n', line).await
+}
+
+/// Reads the message up to the lone dot, undoing dot stuffing. Oversized
+/// messages are still read to the end so the session can continue.
+async fn read_data<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+    line: &mut Vec<u8>,
+    max_size: usize,
+) -> Result<(Vec<u8>, bool), anyhow::Error> {
+    let mut data = Vec::new();
+    let mut oversized = false;
+
+    loop {
+        if read_line(reader, line).await? == 0 {
+            return Err(anyhow::anyhow!("Connection closed during DATA"));
+        }
+
+        if line == b".\r\n" || line == b".\n" {
+            return Ok((data, oversized));
+        }
+
+        let content = match line.first() {
+            Some(b'.') => &line[1..],
+            _ => &line[..],
+        };
+
+        if data.len() + content.len() > max_size {
+            oversized = true;
+        } else {
+            data.extend_from_slice(content);
+        }
+    }
+}
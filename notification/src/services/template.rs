@@ -0,0 +1,102 @@
+use serde::Serialize;
+use shared::models::enums::NotificationEvent;
+use tera::{Context, Tera};
+
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Typed context for a transactional email.
+///
+/// `NAME` is the template directory under `templates/{locale}/`, which must
+/// contain `subject.txt`, `body.html` and `body.txt`. Emails with an `EVENT`
+/// are only sent by `MailService::send` to users who keep emails for it on in
+/// their notification settings, and can be unsubscribed from.
+pub trait EmailTemplate: Serialize {
+    const NAME: &'static str;
+    const EVENT: Option<NotificationEvent> = None;
+
+    /// Overrides the notification settings link, for emails to non-users
+    fn unsubscribe_link(&self) -> Option<String> {
+        None
+    }
+
+    /// Address replies go to instead of the sender
+    fn reply_to(&self) -> Option<String> {
+        None
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RenderedEmail {
+    pub locale: String,
+    pub subject: String,
+    pub html: String,
+    pub text: String,
+}
+
+pub struct TemplateService {
+    tera: Tera,
+}
+
+impl TemplateService {
+    pub fn build() -> Result<Self, tera::Error> {
+        Self::from_glob(concat!(env!("CARGO_MANIFEST_DIR"), "/templates/**/*"))
+    }
+
+    pub fn from_glob(glob: &str) -> Result<Self, tera::Error> {
+        Ok(Self {
+            tera: Tera::new(glob)?,
+        })
+    }
+
+    pub fn render<T: EmailTemplate>(
+        &self,
+        locale: &str,
+        email: &T,
+        unsubscribe_link: Option<&str>,
+    ) -> Result<RenderedEmail, tera::Error> {
+        let locale = self.resolve_locale(T::NAME, locale);
+
+        let mut context = Context::from_serialize(email)?;
+        context.insert("locale", &locale);
+        if let Some(unsubscribe_link) = unsubscribe_link {
+            context.insert("unsubscribe_link", unsubscribe_link);
+        }
+
+        let path = format!("{}/{}", locale, T::NAME);
+
+        Ok(RenderedEmail {
+            subject: self
+                .tera
+                .render(&format!("{}/subject.txt", path), &context)?
+                .trim()
+                .to_string(),
+            html: self.tera.render(&format!("{}/body.html", path), &context)?,
+            text: self
+                .tera
+                .render(&format!("{}/body.txt", path), &context)?
+                .trim()
+                .to_string(),
+            locale,
+        })
+    }
+
+    /// Picks the most specific locale that has the template: `pt-br`, then
+    /// `pt`, then [`DEFAULT_LOCALE`]. Locale directories are lowercase.
+    pub fn resolve_locale(&self, name: &str, locale: &str) -> String {
+        let locale = locale.trim().replace('_', "-").to_lowercase();
+        let language = locale.split('-').next().unwrap_or_default().to_string();
+
+        [locale, language]
+            .into_iter()
+            .filter(|candidate| !candidate.is_empty())
+            .find(|candidate| self.has_template(candidate, name))
+            .unwrap_or_else(|| DEFAULT_LOCALE.to_string())
+    }
+
+    fn has_template(&self, locale: &str, name: &str) -> bool {
+        let path = format!("{}/{}/body.html", locale, name);
+        self.tera
+            .get_template_names()
+            .any(|template| template == path)
+    }
+}
@@ -0,0 +1,197 @@
+use std::{net::SocketAddr, sync::Arc};
+
+use chrono::{Duration, Utc};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use serde_json::json;
+use shared::{
+    configuration::WebhookSettings,
+    models::{enums::WebhookDeliveryStatus, webhook_model::PendingDelivery},
+    repositories::webhook_repository::{WebhookRepository, WebhookRepositoryImpl},
+    utils::webhook::{
+        is_public_ip, is_public_url, sign, DELIVERY_HEADER, EVENT_HEADER, SIGNATURE_HEADER,
+        TIMESTAMP_HEADER,
+    },
+};
+use sqlx::PgPool;
+use tokio::task::JoinSet;
+use tracing::{error, info, warn};
+
+/// Delay before retrying after `attempts` failed attempts, doubling from
+/// `base` seconds and capped at a day.
+pub fn backoff(attempts: i32, base: i64) -> Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 20) as u32;
+    Duration::seconds(base.saturating_mul(2_i64.pow(exponent)).min(24 * 60 * 60))
+}
+
+/// Resolves host names like the system does, but fails for hosts with a
+/// non-public address. Urls are checked before sending, this keeps a host
+/// from resolving to an internal address once checked.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addresses: Vec<SocketAddr> =
+                tokio::net::lookup_host((name.as_str(), 0)).await?.collect();
+            if addresses.iter().any(|address| !is_public_ip(address.ip())) {
+                return Err(format!("{} resolves to a non-public address", name.as_str()).into());
+            }
+            Ok(Box::new(addresses.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct WebhookService {
+    pub db: PgPool,
+    pub client: reqwest::Client,
+    pub settings: WebhookSettings,
+}
+
+impl WebhookService {
+    pub fn build(db: PgPool, settings: WebhookSettings) -> Result<Self, anyhow::Error> {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(settings.timeout))
+            .redirect(reqwest::redirect::Policy::none())
+            .https_only(true)
+            .dns_resolver(Arc::new(PublicResolver))
+            .user_agent("Orta-Webhooks")
+            .build()?;
+        Ok(Self {
+            db,
+            client,
+            settings,
+        })
+    }
+
+    pub async fn run(self) {
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(self.settings.interval));
+        loop {
+            interval.tick().await;
+            if let Err(err) = self.dispatch_due().await {
+                error!("Unable to dispatch webhooks: {:?}", err);
+            }
+        }
+    }
+
+    pub async fn dispatch_due(&self) -> Result<(), anyhow::Error> {
+        loop {
+            // Claimed deliveries are pushed back by the lease, if this instance
+            // dies mid-request another one picks them up once it expires
+            let mut transaction = self.db.begin().await?;
+            let deliveries = WebhookRepositoryImpl::claim_due(
+                &mut transaction,
+                self.settings.batch_size,
+                self.settings.timeout as i64 * 2,
+            )
+            .await?;
+            transaction.commit().await?;
+
+            if deliveries.is_empty() {
+                return Ok(());
+            }
+
+            info!("Dispatching {} webhook deliveries", deliveries.len());
+
+            let mut requests = JoinSet::new();
+            for delivery in deliveries {
+                let service = self.clone();
+                requests.spawn(async move {
+                    if let Err(err) = service.deliver(&delivery).await {
+                        error!("Unable to record delivery {}: {:?}", delivery.id, err);
+                    }
+                });
+            }
+            while requests.join_next().await.is_some() {}
+        }
+    }
+
+    async fn deliver(&self, delivery: &PendingDelivery) -> Result<(), anyhow::Error> {
+        if !is_public_url(&delivery.url).await {
+            warn!(
+                "Webhook delivery {} refused, {} is not a public https url",
+                delivery.id, delivery.url
+            );
+            let mut transaction = self.db.begin().await?;
+            WebhookRepositoryImpl::complete(
+                &mut transaction,
+                &delivery.id,
+                WebhookDeliveryStatus::Failed,
+                None,
+                None,
+                Some("Url is not a public https url"),
+            )
+            .await?;
+            transaction.commit().await?;
+            return Ok(());
+        }
+
+        let body = json!({
+            "id": delivery.id,
+            "event": delivery.event,
+            "created_at": delivery.created_at,
+            "data": delivery.payload,
+        })
+        .to_string();
+
+        let timestamp = Utc::now().timestamp();
+        let signature = sign(&delivery.secret, timestamp, &body)
+            .map_err(|err| anyhow::anyhow!("Invalid webhook secret: {}", err))?;
+
+        let response = self
+            .client
+            .post(&delivery.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, delivery.event.to_string())
+            .header(DELIVERY_HEADER, &delivery.id)
+            .header(TIMESTAMP_HEADER, timestamp)
+            .header(SIGNATURE_HEADER, signature)
+            .body(body)
+            .send()
+            .await;
+
+        // Only the status is kept, response bodies could hand back whatever
+        // the receiver returns to the webhook's owner
+        let (response_status, error) = match response {
+            Ok(response) => {
+                let status = response.status();
+                let error = match status.is_success() {
+                    true => None,
+                    false => Some(format!("Unexpected status {}", status)),
+                };
+                (Some(status.as_u16() as i32), error)
+            }
+            Err(err) => (None, Some(err.to_string())),
+        };
+
+        let (status, next_attempt_at) = match &error {
+            None => (WebhookDeliveryStatus::Succeeded, None),
+            Some(_) if delivery.attempts >= self.settings.max_attempts => {
+                warn!(
+                    "Webhook delivery {} failed after {} attempts",
+                    delivery.id, delivery.attempts
+                );
+                (WebhookDeliveryStatus::Failed, None)
+            }
+            Some(_) => (
+                WebhookDeliveryStatus::Pending,
+                Some(Utc::now() + backoff(delivery.attempts, self.settings.backoff)),
+            ),
+        };
+
+        let mut transaction = self.db.begin().await?;
+        WebhookRepositoryImpl::complete(
+            &mut transaction,
+            &delivery.id,
+            status,
+            next_attempt_at,
+            response_status,
+            error.as_deref(),
+        )
+        .await?;
+        transaction.commit().await?;
+
+        Ok(())
+    }
+}
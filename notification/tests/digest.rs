@@ -0,0 +1,41 @@
+use chrono::{TimeZone, Utc};
+use notification::services::digest::period;
+use shared::models::enums::DigestFrequency;
+
+#[test]
+fn daily_period_is_previous_day() {
+    let now = Utc.with_ymd_and_hms(2024, 5, 15, 9, 30, 0).unwrap();
+
+    assert_eq!(
+        period(DigestFrequency::Daily, now),
+        Some((
+            Utc.with_ymd_and_hms(2024, 5, 14, 0, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2024, 5, 15, 0, 0, 0).unwrap(),
+        ))
+    );
+}
+
+#[test]
+fn weekly_period_is_previous_week() {
+    // Wednesday, the period is the full week ending on Monday
+    let now = Utc.with_ymd_and_hms(2024, 5, 15, 9, 30, 0).unwrap();
+    let expected = Some((
+        Utc.with_ymd_and_hms(2024, 5, 6, 0, 0, 0).unwrap(),
+        Utc.with_ymd_and_hms(2024, 5, 13, 0, 0, 0).unwrap(),
+    ));
+
+    assert_eq!(period(DigestFrequency::Weekly, now), expected);
+    // Every run within the same week maps to the same period
+    assert_eq!(
+        period(
+            DigestFrequency::Weekly,
+            Utc.with_ymd_and_hms(2024, 5, 19, 23, 59, 59).unwrap()
+        ),
+        expected
+    );
+}
+
+#[test]
+fn never_has_no_period() {
+    assert_eq!(period(DigestFrequency::Never, Utc::now()), None);
+}
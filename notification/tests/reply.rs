@@ -0,0 +1,34 @@
+use notification::services::reply::strip_reply;
+
+#[test]
+fn keeps_reply_above_quote_header() {
+    let body = "Thanks, fixed the typo!\r\n\r\nOn Mon, 3 Jun 2024 at 10:00, Orta <reply+abc.0123456789abcdef@reply.orta.kz> wrote:\r\n> Great article, but there is a typo\r\n";
+
+    assert_eq!(strip_reply(body), "Thanks, fixed the typo!");
+}
+
+#[test]
+fn handles_wrapped_quote_header_and_signature() {
+    let body = "Agreed.\n\nSecond line.\n\nOn Mon, 3 Jun 2024 at 10:00, Orta\n<reply+abc.0123456789abcdef@reply.orta.kz> wrote:\n> quoted\n";
+    assert_eq!(strip_reply(body), "Agreed.\n\nSecond line.");
+
+    let body = "Agreed.\n-- \nJohn Doe\nhttps://example.com\n";
+    assert_eq!(strip_reply(body), "Agreed.");
+
+    let body = "Agreed.\n\nSent from my iPhone\n";
+    assert_eq!(strip_reply(body), "Agreed.");
+}
+
+#[test]
+fn drops_interleaved_quotes_and_outlook_originals() {
+    let body = "> first point\nYes\n> second point\nNo\n";
+    assert_eq!(strip_reply(body), "Yes\nNo");
+
+    let body = "Sure\n\n-----Original Message-----\nFrom: Orta\n";
+    assert_eq!(strip_reply(body), "Sure");
+
+    let body = "Sure\n________________________________\nFrom: Orta\n";
+    assert_eq!(strip_reply(body), "Sure");
+
+    assert_eq!(strip_reply("> only quoted\n"), "");
+}
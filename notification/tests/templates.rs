@@ -0,0 +1,96 @@
+use notification::{
+    emails::{NewsletterEmail, VerificationEmail},
+    services::template::TemplateService,
+};
+
+fn verification_email() -> VerificationEmail {
+    VerificationEmail {
+        verification_link: "http://localhost:3000/auth?token=token".to_string(),
+    }
+}
+
+#[test]
+fn renders_html_and_text_bodies() {
+    let templates = TemplateService::build().unwrap();
+
+    let email = templates.render("en", &verification_email(), None).unwrap();
+
+    assert_eq!(email.subject, "[Orta] Please verify your email");
+    assert!(email.html.contains("<h1>Verify your email address</h1>"));
+    assert!(email
+        .text
+        .contains("http://localhost:3000/auth?token=token"));
+    assert!(!email.text.contains('<'));
+}
+
+#[test]
+fn falls_back_to_language_then_default_locale() {
+    let templates = TemplateService::build().unwrap();
+
+    assert_eq!(
+        templates
+            .render("ru-RU", &verification_email(), None)
+            .unwrap()
+            .locale,
+        "ru"
+    );
+    assert_eq!(
+        templates
+            .render("ru_ru", &verification_email(), None)
+            .unwrap()
+            .locale,
+        "ru"
+    );
+    assert_eq!(
+        templates
+            .render("de", &verification_email(), None)
+            .unwrap()
+            .locale,
+        "en"
+    );
+    assert_eq!(
+        templates
+            .render("", &verification_email(), None)
+            .unwrap()
+            .locale,
+        "en"
+    );
+}
+
+#[test]
+fn renders_unsubscribe_link_in_footer() {
+    let templates = TemplateService::build().unwrap();
+    let link = "http://localhost:5000/api/unsubscribe?token=token";
+
+    let email = templates
+        .render("en", &verification_email(), Some(link))
+        .unwrap();
+
+    assert!(email.text.contains(link));
+    assert!(email.html.contains("Unsubscribe"));
+    assert!(!templates
+        .render("en", &verification_email(), None)
+        .unwrap()
+        .text
+        .contains("unsubscribe"));
+}
+
+#[test]
+fn sanitizes_newsletter_content() {
+    let templates = TemplateService::build().unwrap();
+    let newsletter = NewsletterEmail {
+        author: "rustacean".to_string(),
+        title: "Ownership".to_string(),
+        description: None,
+        content: r#"<p onclick="f()"><b>Hi</b></p><script>f()</script>"#.to_string(),
+        article_link: "http://localhost:3000/article/a1".to_string(),
+        open_link: "http://localhost:5000/opens/s1".to_string(),
+        unsubscribe_link: "http://localhost:5000/unsubscribe".to_string(),
+    };
+
+    let email = templates.render("en", &newsletter, None).unwrap();
+
+    assert!(email.html.contains("<p><b>Hi</b></p>"));
+    assert!(!email.html.contains("<script"));
+    assert!(!email.html.contains("onclick"));
+}
@@ -0,0 +1,10 @@
+use chrono::Duration;
+use notification::services::webhook::backoff;
+
+#[test]
+fn backoff_doubles_and_is_capped() {
+    assert_eq!(backoff(1, 30), Duration::seconds(30));
+    assert_eq!(backoff(2, 30), Duration::seconds(60));
+    assert_eq!(backoff(4, 30), Duration::seconds(240));
+    assert_eq!(backoff(40, 30), Duration::days(1));
+}
@@ -3,34 +3,50 @@ use std::{
     sync::Arc,
 };
 
+use amqprs::{
+    channel::ExchangeDeclareArguments,
+    connection::{Connection, OpenConnectionArguments},
+};
+use secrecy::ExposeSecret;
 use shared::{
     article::article_service_server::ArticleServiceServer,
     comment::comment_service_server::CommentServiceServer,
     configuration::{DatabaseSettings, Settings},
     list::list_service_server::ListServiceServer,
+    newsletter::newsletter_service_server::NewsletterServiceServer,
+    search::search_service_server::SearchServiceServer,
     series::series_service_server::SeriesServiceServer,
     tag::tag_service_server::TagServiceServer,
     user::user_service_server::UserServiceServer,
+    utils::message::EVENTS_EXCHANGE,
+    webhook::webhook_service_server::WebhookServiceServer,
 };
 use sqlx::{postgres::PgPoolOptions, PgPool};
 use tonic::transport::{server::Router, Server};
 use tracing::info;
 
-use crate::services::{
-    article_service::ArticleServiceImpl, comment_service::CommentServiceImpl,
-    list_service::ListServiceImpl, series_service::SeriesServiceImpl, tag_service::TagServiceImpl,
-    user_service::UserServiceImpl,
+use crate::{
+    compactor::Compactor,
+    services::{
+        article_service::ArticleServiceImpl, comment_service::CommentServiceImpl,
+        list_service::ListServiceImpl, newsletter_service::NewsletterServiceImpl,
+        search_service::SearchServiceImpl, series_service::SeriesServiceImpl,
+        tag_service::TagServiceImpl, user_service::UserServiceImpl,
+        webhook_service::WebhookServiceImpl,
+    },
 };
 
 pub struct AppState {
     pub db: PgPool,
     pub limit: i64,
+    pub connection: Connection,
 }
 
 pub struct Application {
     pub port: u16,
     pub server: Router,
     pub address: SocketAddr,
+    pub compactor: Compactor,
 }
 
 impl Application {
@@ -43,9 +59,35 @@ impl Application {
 
         let address = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
 
+        let args = OpenConnectionArguments::new(
+            &configuration.message_broker.hostname,
+            configuration.message_broker.port,
+            &configuration.message_broker.username,
+            configuration.message_broker.password.expose_secret(),
+        )
+        .finish();
+
+        let connection = Connection::open(&args).await?;
+
+        let channel = connection.open_channel(None).await?;
+        channel
+            .exchange_declare(
+                ExchangeDeclareArguments::new(EVENTS_EXCHANGE, "topic")
+                    .durable(true)
+                    .finish(),
+            )
+            .await?;
+        channel.close().await?;
+
+        let compactor = Compactor {
+            db: pool.clone(),
+            settings: configuration.history,
+        };
+
         let state = Arc::new(AppState {
             db: pool,
             limit: configuration.query.per_page,
+            connection,
         });
 
         let server = Server::builder()
@@ -66,6 +108,15 @@ impl Application {
             }))
             .add_service(TagServiceServer::new(TagServiceImpl {
                 state: state.clone(),
+            }))
+            .add_service(WebhookServiceServer::new(WebhookServiceImpl {
+                state: state.clone(),
+            }))
+            .add_service(NewsletterServiceServer::new(NewsletterServiceImpl {
+                state: state.clone(),
+            }))
+            .add_service(SearchServiceServer::new(SearchServiceImpl {
+                state: state.clone(),
             }));
 
         info!("Finished resource service build");
@@ -74,10 +125,13 @@ impl Application {
             port,
             server,
             address,
+            compactor,
         })
     }
 
     pub async fn run(self) -> Result<(), tonic::transport::Error> {
+        tokio::spawn(self.compactor.run());
+
         info!("Server is running on {}", self.port);
         self.server.serve(self.address).await
     }
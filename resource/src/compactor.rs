@@ -0,0 +1,62 @@
+use shared::{
+    configuration::HistorySettings,
+    repositories::article_repository::{ArticleRepository, ArticleRepositoryImpl},
+};
+use sqlx::PgPool;
+use tracing::{error, info};
+
+/// Thins out article histories following the retention in
+/// [`HistorySettings`], as every save of a draft adds a version
+pub struct Compactor {
+    pub db: PgPool,
+    pub settings: HistorySettings,
+}
+
+impl Compactor {
+    pub async fn run(self) {
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(self.settings.interval));
+        loop {
+            interval.tick().await;
+            if let Err(err) = self.compact().await {
+                error!("Unable to compact article versions: {:?}", err);
+            }
+        }
+    }
+
+    pub async fn compact(&self) -> Result<(), anyhow::Error> {
+        let mut after: Option<String> = None;
+        let mut deleted = 0;
+
+        loop {
+            let mut transaction = self.db.begin().await?;
+
+            let article_ids = ArticleRepositoryImpl::find_compactable(
+                &mut transaction,
+                self.settings.keep_all,
+                after.as_deref(),
+                self.settings.batch_size,
+            )
+            .await?;
+
+            deleted += ArticleRepositoryImpl::compact(
+                &mut transaction,
+                &article_ids,
+                self.settings.keep_all,
+                self.settings.keep_hourly,
+            )
+            .await?;
+
+            transaction.commit().await?;
+
+            if (article_ids.len() as i64) < self.settings.batch_size {
+                break;
+            }
+            after = article_ids.last().cloned();
+        }
+
+        info!("{} article versions compacted", deleted);
+
+        Ok(())
+    }
+}
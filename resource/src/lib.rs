@@ -1,3 +1,4 @@
 pub mod application;
+pub mod compactor;
 pub mod services;
 pub mod utils;
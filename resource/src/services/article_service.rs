@@ -1,23 +1,31 @@
 use std::sync::Arc;
 
+use serde_json::json;
 use shared::{
     article::{
-        article_service_server::ArticleService, AddAuthorRequest, CreateRequest, DeleteRequest,
-        EditRequest, GetRequest, HistoryRequest, LikeRequest, PublishRequest, RemoveAuthorRequest,
-        SearchRequest, SetTagsRequest, UnlikeRequest, UpdateRequest, VersionRequest,
+        article_service_server::ArticleService, AddAuthorRequest, ArticleDiff, CreateRequest,
+        CreateSnapshotRequest, DeleteRequest, DiffRequest, EditRequest, GetRequest, HistoryRequest,
+        LikeRequest, ListSnapshotsRequest, PublishRequest, RemoveAuthorRequest, RestoreRequest,
+        SearchRequest, SetTagsRequest, Snapshot, Snapshots, UnlikeRequest, UpdateRequest,
+        VersionRequest,
     },
     common::{
         Article, ArticleVersion, ArticleVersions, FullArticle, FullArticles, MessageResponse, Tags,
     },
     models::{
-        article_model::{AddAuthor, CreateArticle, DeleteAuthor, UpdateArticle},
-        enums::TagStatus,
+        article_model::{
+            self, AddAuthor, CreateArticle, CreateSnapshot, DeleteAuthor, UpdateArticle,
+        },
+        enums::{TagStatus, WebhookEvent},
         tag_model::CreateTag,
+        webhook_model::WebhookUser,
     },
     repositories::{
         article_repository::{ArticleRepository, ArticleRepositoryImpl},
+        newsletter_repository::{NewsletterRepository, NewsletterRepositoryImpl},
         tag_repository::{TagRepository, TagRepositoryImpl},
     },
+    utils::diff::{changed_words, diff_html},
 };
 use slug::slugify;
 use tonic::{Request, Response, Status};
@@ -26,11 +34,17 @@ use tracing::{error, info};
 use crate::{
     application::AppState,
     utils::{
+        events::publish_like,
         permissions::{is_owner, ContentType},
+        placeholders::with_placeholders,
         split_cursor::parse_cursor,
+        webhooks::emit,
     },
 };
 
+/// Name of the snapshot publishing takes of the newest version
+const PUBLISHED_SNAPSHOT: &str = "published";
+
 #[derive(Clone)]
 pub struct ArticleServiceImpl {
     pub state: Arc<AppState>,
@@ -70,6 +84,7 @@ impl ArticleService for ArticleServiceImpl {
         let articles = match ArticleRepositoryImpl::find_all(
             &mut transaction,
             input.query.as_deref(),
+            input.sort().into(),
             input.limit,
             id,
             created_at,
@@ -90,16 +105,46 @@ impl ArticleService for ArticleServiceImpl {
             .nth(input.limit as usize - 1)
             .map(|item| format!("{}_{}", item.id, item.created_at.to_rfc3339()));
 
-        let articles = articles
+        let mut articles: Vec<FullArticle> = articles
             .iter()
             .map(|article| FullArticle::from(article))
             .collect();
 
+        if let Some(query) = &input.query {
+            let article_ids: Vec<String> =
+                articles.iter().map(|article| article.id.clone()).collect();
+
+            let headlines =
+                match ArticleRepositoryImpl::headlines(&mut transaction, query, &article_ids).await
+                {
+                    Ok(headlines) => headlines,
+                    Err(err) => {
+                        error!("{:?}", err);
+                        return Err(Status::internal("Something went wrong"));
+                    }
+                };
+
+            for headline in headlines {
+                if let Some(article) = articles
+                    .iter_mut()
+                    .find(|article| article.id == headline.article_id)
+                {
+                    article.headline = Some(headline.headline);
+                }
+            }
+        }
+
         match transaction.commit().await {
-            Ok(_) => Ok(Response::new(FullArticles {
-                articles,
-                next_cursor,
-            })),
+            Ok(_) => Ok(Response::new(
+                with_placeholders(
+                    &self.state.db,
+                    FullArticles {
+                        articles,
+                        next_cursor,
+                    },
+                )
+                .await,
+            )),
             Err(err) => {
                 error!("{:?}", err);
                 return Err(Status::internal("Something went wrong"));
@@ -138,7 +183,9 @@ impl ArticleService for ArticleServiceImpl {
         };
 
         match transaction.commit().await {
-            Ok(_) => Ok(Response::new(FullArticle::from(&article))),
+            Ok(_) => Ok(Response::new(
+                with_placeholders(&self.state.db, FullArticle::from(&article)).await,
+            )),
             Err(err) => {
                 error!("{:?}", err);
                 return Err(Status::internal("Something went wrong"));
@@ -504,15 +551,16 @@ impl ArticleService for ArticleServiceImpl {
                 }
             };
 
-        match transaction.commit().await {
-            Ok(_) => Ok(Response::new(MessageResponse {
-                message: format!("{} liked article {}", like.0, like.1),
-            })),
-            Err(err) => {
-                error!("{:?}", err);
-                return Err(Status::internal("Something went wrong"));
-            }
+        if let Err(err) = transaction.commit().await {
+            error!("{:?}", err);
+            return Err(Status::internal("Something went wrong"));
         }
+
+        publish_like(self.state.clone(), &input.article_id, &input.user_id, true);
+
+        Ok(Response::new(MessageResponse {
+            message: format!("{} liked article {}", like.0, like.1),
+        }))
     }
 
     async fn unlike(
@@ -545,15 +593,16 @@ impl ArticleService for ArticleServiceImpl {
             }
         };
 
-        match transaction.commit().await {
-            Ok(_) => Ok(Response::new(MessageResponse {
-                message: format!("{} unliked article {}", like.0, like.1),
-            })),
-            Err(err) => {
-                error!("{:?}", err);
-                return Err(Status::internal("Something went wrong"));
-            }
+        if let Err(err) = transaction.commit().await {
+            error!("{:?}", err);
+            return Err(Status::internal("Something went wrong"));
         }
+
+        publish_like(self.state.clone(), &input.article_id, &input.user_id, false);
+
+        Ok(Response::new(MessageResponse {
+            message: format!("{} unliked article {}", like.0, like.1),
+        }))
     }
 
     async fn publish(
@@ -602,6 +651,76 @@ impl ArticleService for ArticleServiceImpl {
                 }
             };
 
+        let full_article =
+            match ArticleRepositoryImpl::find(&mut transaction, &article.id, None).await {
+                Ok(article) => article,
+                Err(err) => {
+                    error!("{:?}", err);
+                    return Err(Status::internal("Something went wrong"));
+                }
+            };
+
+        let authors: Vec<String> = full_article
+            .users
+            .iter()
+            .flatten()
+            .map(|author| author.id.to_owned())
+            .collect();
+
+        let mut payload = json!(full_article);
+        payload["users"] = json!(full_article
+            .users
+            .iter()
+            .flatten()
+            .map(WebhookUser::from)
+            .collect::<Vec<_>>());
+
+        if let Err(err) = emit(
+            &mut transaction,
+            WebhookEvent::ArticlePublished,
+            &authors,
+            &payload,
+        )
+        .await
+        {
+            error!("{:?}", err);
+            return Err(Status::internal("Something went wrong"));
+        }
+
+        match ArticleRepositoryImpl::version(&mut transaction, &article.id).await {
+            Ok(version) => {
+                if let Err(err) = ArticleRepositoryImpl::create_snapshot(
+                    &mut transaction,
+                    &CreateSnapshot {
+                        version_id: version.id,
+                        article_id: article.id.to_owned(),
+                        user_id: input.user_id.to_owned(),
+                        name: PUBLISHED_SNAPSHOT.to_string(),
+                        note: None,
+                    },
+                )
+                .await
+                {
+                    error!("{:?}", err);
+                    return Err(Status::internal("Something went wrong"));
+                }
+            }
+            // Nothing was written yet
+            Err(sqlx::error::Error::RowNotFound) => {}
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        }
+
+        // Queued for the notification service, only the first publish is sent
+        if let Err(err) =
+            NewsletterRepositoryImpl::create_issue(&mut transaction, &article.id).await
+        {
+            error!("{:?}", err);
+            return Err(Status::internal("Something went wrong"));
+        }
+
         match transaction.commit().await {
             Ok(_) => Ok(Response::new(MessageResponse {
                 message: format!("Published article: {}", article.id),
@@ -919,4 +1038,344 @@ impl ArticleService for ArticleServiceImpl {
             }
         }
     }
+
+    async fn diff(&self, request: Request<DiffRequest>) -> Result<Response<ArticleDiff>, Status> {
+        let mut transaction = match self.state.db.begin().await {
+            Ok(transaction) => transaction,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        let input = request.get_ref();
+
+        info!("Diff Article Versions Request {:?}", input);
+
+        match is_owner(
+            &mut transaction,
+            ContentType::Article,
+            &input.user_id,
+            &input.article_id,
+        )
+        .await
+        {
+            Ok(is_owner) => {
+                if !is_owner {
+                    error!("Forbidden");
+                    return Err(Status::permission_denied("Forbidden"));
+                }
+            }
+            Err(err) => {
+                error!("{:?}", err);
+                if let sqlx::error::Error::RowNotFound = err {
+                    return Err(Status::not_found("Article not found"));
+                }
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        let from = match ArticleRepositoryImpl::find_version(
+            &mut transaction,
+            &input.article_id,
+            &input.from,
+        )
+        .await
+        {
+            Ok(version) => version.content,
+            Err(sqlx::error::Error::RowNotFound) => {
+                return Err(Status::not_found("Version not found"))
+            }
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        let to = match &input.to {
+            Some(to) => {
+                match ArticleRepositoryImpl::find_version(&mut transaction, &input.article_id, to)
+                    .await
+                {
+                    Ok(version) => version.content,
+                    Err(sqlx::error::Error::RowNotFound) => {
+                        return Err(Status::not_found("Version not found"))
+                    }
+                    Err(err) => {
+                        error!("{:?}", err);
+                        return Err(Status::internal("Something went wrong"));
+                    }
+                }
+            }
+            None => {
+                match ArticleRepositoryImpl::find(&mut transaction, &input.article_id, None).await {
+                    Ok(article) => article.content,
+                    Err(err) => {
+                        error!("{:?}", err);
+                        return Err(Status::internal("Something went wrong"));
+                    }
+                }
+            }
+        };
+
+        let blocks = diff_html(&from, &to);
+        let (insertions, deletions) = changed_words(&blocks);
+
+        let article_diff = article_model::ArticleDiff {
+            from: input.from.to_owned(),
+            to: input.to.to_owned(),
+            blocks,
+            insertions,
+            deletions,
+        };
+
+        match transaction.commit().await {
+            Ok(_) => Ok(Response::new(ArticleDiff::from(&article_diff))),
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        }
+    }
+
+    async fn restore(
+        &self,
+        request: Request<RestoreRequest>,
+    ) -> Result<Response<ArticleVersion>, Status> {
+        let mut transaction = match self.state.db.begin().await {
+            Ok(transaction) => transaction,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        let input = request.get_ref();
+
+        info!("Restore Article Version Request {:?}", input);
+
+        match is_owner(
+            &mut transaction,
+            ContentType::Article,
+            &input.user_id,
+            &input.article_id,
+        )
+        .await
+        {
+            Ok(is_owner) => {
+                if !is_owner {
+                    error!("Forbidden");
+                    return Err(Status::permission_denied("Forbidden"));
+                }
+            }
+            Err(err) => {
+                error!("{:?}", err);
+                if let sqlx::error::Error::RowNotFound = err {
+                    return Err(Status::not_found("Article not found"));
+                }
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        let article_version = match ArticleRepositoryImpl::restore(
+            &mut transaction,
+            &input.article_id,
+            &input.version_id,
+        )
+        .await
+        {
+            Ok(article_version) => article_version,
+            Err(sqlx::error::Error::RowNotFound) => {
+                return Err(Status::not_found("Version not found"))
+            }
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        if let Err(err) = ArticleRepositoryImpl::update_content(
+            &mut transaction,
+            &input.article_id,
+            &article_version.content,
+        )
+        .await
+        {
+            error!("{:?}", err);
+            return Err(Status::internal("Something went wrong"));
+        }
+
+        match transaction.commit().await {
+            Ok(_) => Ok(Response::new(ArticleVersion::from(&article_version))),
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        }
+    }
+
+    async fn create_snapshot(
+        &self,
+        request: Request<CreateSnapshotRequest>,
+    ) -> Result<Response<Snapshot>, Status> {
+        let mut transaction = match self.state.db.begin().await {
+            Ok(transaction) => transaction,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        let input = request.get_ref();
+
+        info!("Create Article Snapshot Request {:?}", input);
+
+        let name = input.name.trim();
+        if name.is_empty() {
+            return Err(Status::invalid_argument("Invalid data"));
+        }
+
+        match is_owner(
+            &mut transaction,
+            ContentType::Article,
+            &input.user_id,
+            &input.article_id,
+        )
+        .await
+        {
+            Ok(is_owner) => {
+                if !is_owner {
+                    error!("Forbidden");
+                    return Err(Status::permission_denied("Forbidden"));
+                }
+            }
+            Err(err) => {
+                error!("{:?}", err);
+                if let sqlx::error::Error::RowNotFound = err {
+                    return Err(Status::not_found("Article not found"));
+                }
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        let snapshot = match ArticleRepositoryImpl::create_snapshot(
+            &mut transaction,
+            &CreateSnapshot {
+                version_id: input.version_id.to_owned(),
+                article_id: input.article_id.to_owned(),
+                user_id: input.user_id.to_owned(),
+                name: name.to_string(),
+                note: input.note.to_owned(),
+            },
+        )
+        .await
+        {
+            Ok(snapshot) => snapshot,
+            Err(sqlx::error::Error::RowNotFound) => {
+                return Err(Status::not_found("Version not found"))
+            }
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        match transaction.commit().await {
+            Ok(_) => Ok(Response::new(Snapshot::from(&snapshot))),
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        }
+    }
+
+    async fn list_snapshots(
+        &self,
+        request: Request<ListSnapshotsRequest>,
+    ) -> Result<Response<Snapshots>, Status> {
+        let mut transaction = match self.state.db.begin().await {
+            Ok(transaction) => transaction,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        let input = request.get_ref();
+
+        info!("Get Article Snapshots Request {:?}", input);
+
+        match is_owner(
+            &mut transaction,
+            ContentType::Article,
+            &input.user_id,
+            &input.article_id,
+        )
+        .await
+        {
+            Ok(is_owner) => {
+                if !is_owner {
+                    error!("Forbidden");
+                    return Err(Status::permission_denied("Forbidden"));
+                }
+            }
+            Err(err) => {
+                error!("{:?}", err);
+                if let sqlx::error::Error::RowNotFound = err {
+                    return Err(Status::not_found("Article not found"));
+                }
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        let mut id = None;
+        let mut created_at = None;
+
+        if let Some(cursor_str) = &input.cursor {
+            (id, created_at) = match parse_cursor(cursor_str) {
+                Ok(parsed) => parsed,
+                Err(err) => {
+                    error!("Parse error {}", err);
+                    return Err(Status::invalid_argument("Invalid data"));
+                }
+            }
+        };
+
+        let snapshots = match ArticleRepositoryImpl::snapshots(
+            &mut transaction,
+            &input.article_id,
+            input.limit,
+            id,
+            created_at,
+        )
+        .await
+        {
+            Ok(snapshots) => snapshots,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        let next_cursor = snapshots
+            .iter()
+            .nth(input.limit as usize - 1)
+            .map(|item| format!("{}_{}", item.id, item.created_at.to_rfc3339()));
+
+        let snapshots = snapshots
+            .iter()
+            .map(|snapshot| Snapshot::from(snapshot))
+            .collect();
+
+        match transaction.commit().await {
+            Ok(_) => Ok(Response::new(Snapshots {
+                snapshots,
+                next_cursor,
+            })),
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        }
+    }
 }
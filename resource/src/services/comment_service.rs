@@ -6,8 +6,12 @@ use shared::{
         GetRequest, UpdateRequest,
     },
     common::{Comment, FullComment, FullComments, MessageResponse},
-    models::comment_model::{CreateComment, UpdateComment},
+    models::{
+        comment_model::{CreateComment, UpdateComment},
+        enums::{CommentableType, WebhookEvent},
+    },
     repositories::comment_repository::{CommentRepository, CommentRepositoryImpl},
+    utils::message::{CommentCreatedEvent, Payload},
 };
 use tonic::{Request, Response, Status};
 use tracing::error;
@@ -15,8 +19,10 @@ use tracing::error;
 use crate::{
     application::AppState,
     utils::{
+        events::deliver,
         permissions::{is_owner, ContentType},
         split_cursor::parse_cursor,
+        webhooks::{comment_target_owners, emit},
     },
 };
 
@@ -140,6 +146,32 @@ impl CommentService for CommentServiceImpl {
 
         let input = request.get_ref();
 
+        // Replies stay on the thread of the comment they answer
+        let parent = match &input.parent_id {
+            Some(parent_id) => {
+                match CommentRepositoryImpl::find(&mut transaction, parent_id, None).await {
+                    Ok(parent) => {
+                        if parent.target_id != input.target_id
+                            || parent.r#type != CommentableType::from(input.r#type())
+                        {
+                            return Err(Status::invalid_argument(
+                                "Parent comment is on another target",
+                            ));
+                        }
+                        Some(parent)
+                    }
+                    Err(err) => {
+                        error!("{:?}", err);
+                        if let sqlx::error::Error::RowNotFound = err {
+                            return Err(Status::not_found("Parent comment not found"));
+                        }
+                        return Err(Status::internal("Something went wrong"));
+                    }
+                }
+            }
+            None => None,
+        };
+
         let comment = match CommentRepositoryImpl::create(
             &mut transaction,
             &CreateComment {
@@ -147,6 +179,7 @@ impl CommentService for CommentServiceImpl {
                 target_id: input.target_id.to_owned(),
                 content: input.content.to_owned(),
                 r#type: input.r#type().into(),
+                parent_id: input.parent_id.to_owned(),
             },
         )
         .await
@@ -168,13 +201,60 @@ impl CommentService for CommentServiceImpl {
             }
         };
 
-        match transaction.commit().await {
-            Ok(_) => Ok(Response::new(Comment::from(&comment))),
-            Err(err) => {
-                error!("{:?}", err);
-                return Err(Status::internal("Something went wrong"));
+        let mut owners =
+            match comment_target_owners(&mut transaction, comment.r#type, &comment.target_id).await
+            {
+                Ok(owners) => owners,
+                Err(err) => {
+                    error!("{:?}", err);
+                    if let sqlx::error::Error::RowNotFound = err {
+                        return Err(Status::not_found("Target not found"));
+                    }
+                    return Err(Status::internal("Something went wrong"));
+                }
+            };
+
+        if let Err(err) = emit(
+            &mut transaction,
+            WebhookEvent::CommentCreated,
+            &owners,
+            &comment,
+        )
+        .await
+        {
+            error!("{:?}", err);
+            return Err(Status::internal("Something went wrong"));
+        }
+
+        if let Err(err) = transaction.commit().await {
+            error!("{:?}", err);
+            return Err(Status::internal("Something went wrong"));
+        }
+
+        if let Some(parent) = parent {
+            if !owners.contains(&parent.commenter_id) {
+                owners.push(parent.commenter_id);
             }
         }
+
+        // Comment emails are sent from the event
+        if let Err(err) = deliver(
+            &self.state,
+            Payload::CommentCreated(CommentCreatedEvent {
+                comment: comment.clone(),
+                recipients: owners
+                    .into_iter()
+                    .filter(|owner| *owner != comment.commenter_id)
+                    .collect(),
+            }),
+        )
+        .await
+        {
+            error!("{:?}", err);
+            return Err(Status::internal("Something went wrong"));
+        }
+
+        Ok(Response::new(Comment::from(&comment)))
     }
 
     async fn update(&self, request: Request<UpdateRequest>) -> Result<Response<Comment>, Status> {
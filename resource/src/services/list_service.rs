@@ -22,6 +22,7 @@ use crate::{
     application::AppState,
     utils::{
         permissions::{is_owner, ContentType},
+        placeholders::with_placeholders,
         split_cursor::parse_cursor,
     },
 };
@@ -84,7 +85,9 @@ impl ListService for ListServiceImpl {
         let lists = lists.iter().map(|list| List::from(list)).collect();
 
         match transaction.commit().await {
-            Ok(_) => Ok(Response::new(Lists { lists, next_cursor })),
+            Ok(_) => Ok(Response::new(
+                with_placeholders(&self.state.db, Lists { lists, next_cursor }).await,
+            )),
             Err(err) => {
                 error!("{:?}", err);
                 return Err(Status::internal("Something went wrong"));
@@ -123,7 +126,9 @@ impl ListService for ListServiceImpl {
         };
 
         match transaction.commit().await {
-            Ok(_) => Ok(Response::new(List::from(&list))),
+            Ok(_) => Ok(Response::new(
+                with_placeholders(&self.state.db, List::from(&list)).await,
+            )),
             Err(err) => {
                 error!("{:?}", err);
                 return Err(Status::internal("Something went wrong"));
@@ -188,10 +193,16 @@ impl ListService for ListServiceImpl {
             .collect();
 
         match transaction.commit().await {
-            Ok(_) => Ok(Response::new(FullArticles {
-                articles,
-                next_cursor,
-            })),
+            Ok(_) => Ok(Response::new(
+                with_placeholders(
+                    &self.state.db,
+                    FullArticles {
+                        articles,
+                        next_cursor,
+                    },
+                )
+                .await,
+            )),
             Err(err) => {
                 error!("{:?}", err);
                 return Err(Status::internal("Something went wrong"));
@@ -241,7 +252,9 @@ impl ListService for ListServiceImpl {
         };
 
         match transaction.commit().await {
-            Ok(_) => Ok(Response::new(List::from(&list))),
+            Ok(_) => Ok(Response::new(
+                with_placeholders(&self.state.db, List::from(&list)).await,
+            )),
             Err(err) => {
                 error!("{:?}", err);
                 return Err(Status::internal("Something went wrong"));
@@ -310,7 +323,9 @@ impl ListService for ListServiceImpl {
         };
 
         match transaction.commit().await {
-            Ok(_) => Ok(Response::new(List::from(&list))),
+            Ok(_) => Ok(Response::new(
+                with_placeholders(&self.state.db, List::from(&list)).await,
+            )),
             Err(err) => {
                 error!("{:?}", err);
                 return Err(Status::internal("Something went wrong"));
@@ -1,6 +1,9 @@
 pub mod article_service;
 pub mod comment_service;
 pub mod list_service;
+pub mod newsletter_service;
+pub mod search_service;
 pub mod series_service;
 pub mod tag_service;
 pub mod user_service;
+pub mod webhook_service;
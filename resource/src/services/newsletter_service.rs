@@ -0,0 +1,360 @@
+use std::sync::Arc;
+
+use shared::{
+    common::MessageResponse,
+    configuration::CONFIG,
+    models::enums::SubscriberStatus,
+    newsletter::{
+        newsletter_service_server::NewsletterService, Issue, Issues, IssuesRequest, OpenRequest,
+        SubscribeRequest, Subscriber, Subscribers, SubscribersRequest, TokenRequest,
+    },
+    repositories::{
+        newsletter_repository::{NewsletterRepository, NewsletterRepositoryImpl},
+        user_repository::{UserRepository, UserRepositoryImpl},
+    },
+    utils::message::{NewsletterConfirmationMessage, Payload},
+};
+use tonic::{Request, Response, Status};
+use tracing::{error, info};
+
+use crate::{
+    application::AppState,
+    utils::{events::notify, split_cursor::parse_cursor},
+};
+
+#[derive(Clone)]
+pub struct NewsletterServiceImpl {
+    pub state: Arc<AppState>,
+}
+
+#[tonic::async_trait]
+impl NewsletterService for NewsletterServiceImpl {
+    async fn subscribe(
+        &self,
+        request: Request<SubscribeRequest>,
+    ) -> Result<Response<MessageResponse>, Status> {
+        let mut transaction = match self.state.db.begin().await {
+            Ok(transaction) => transaction,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        let input = request.get_ref();
+
+        info!("Subscribe Newsletter Request {:?}", input.username);
+
+        let email = input.email.trim();
+
+        if email.is_empty() || !email.contains('@') {
+            return Err(Status::invalid_argument("Invalid email"));
+        }
+
+        let author =
+            match UserRepositoryImpl::find_by_username(&mut transaction, &input.username, None)
+                .await
+            {
+                Ok(author) => author,
+                Err(err) => {
+                    error!("{:?}", err);
+                    if let sqlx::error::Error::RowNotFound = err {
+                        return Err(Status::not_found("User not found"));
+                    }
+                    return Err(Status::internal("Something went wrong"));
+                }
+            };
+
+        let subscriber = match NewsletterRepositoryImpl::subscribe(
+            &mut transaction,
+            &author.id,
+            email,
+            input.locale.as_deref().unwrap_or("en"),
+        )
+        .await
+        {
+            Ok(subscriber) => subscriber,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        if let Err(err) = transaction.commit().await {
+            error!("{:?}", err);
+            return Err(Status::internal("Something went wrong"));
+        }
+
+        // The response is the same either way, it must not reveal who is subscribed
+        if subscriber.status == SubscriberStatus::Pending {
+            let scheme = match CONFIG.client.ssl {
+                true => "https",
+                false => "http",
+            };
+
+            let message = NewsletterConfirmationMessage {
+                email: subscriber.email.to_owned(),
+                author: author.username.to_owned(),
+                confirmation_link: format!(
+                    "{}://{}:{}/newsletter/confirm?token={}",
+                    scheme, CONFIG.client.host, CONFIG.client.port, subscriber.token
+                ),
+                locale: subscriber.locale.to_owned(),
+            };
+
+            if let Err(err) = notify(&self.state, Payload::NewsletterConfirmation(message)).await {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        }
+
+        Ok(Response::new(MessageResponse {
+            message: format!("Confirmation email sent to {}", subscriber.email),
+        }))
+    }
+
+    async fn confirm(
+        &self,
+        request: Request<TokenRequest>,
+    ) -> Result<Response<MessageResponse>, Status> {
+        let mut transaction = match self.state.db.begin().await {
+            Ok(transaction) => transaction,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        let input = request.get_ref();
+
+        info!("Confirm Newsletter Request");
+
+        let subscriber =
+            match NewsletterRepositoryImpl::confirm(&mut transaction, &input.token).await {
+                Ok(subscriber) => subscriber,
+                Err(err) => {
+                    error!("{:?}", err);
+                    if let sqlx::error::Error::RowNotFound = err {
+                        return Err(Status::not_found("Subscription not found"));
+                    }
+                    return Err(Status::internal("Something went wrong"));
+                }
+            };
+
+        match transaction.commit().await {
+            Ok(_) => Ok(Response::new(MessageResponse {
+                message: format!("Confirmed subscription: {}", subscriber.id),
+            })),
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        }
+    }
+
+    async fn unsubscribe(
+        &self,
+        request: Request<TokenRequest>,
+    ) -> Result<Response<MessageResponse>, Status> {
+        let mut transaction = match self.state.db.begin().await {
+            Ok(transaction) => transaction,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        let input = request.get_ref();
+
+        info!("Unsubscribe Newsletter Request");
+
+        let subscriber =
+            match NewsletterRepositoryImpl::unsubscribe(&mut transaction, &input.token).await {
+                Ok(subscriber) => subscriber,
+                Err(err) => {
+                    error!("{:?}", err);
+                    if let sqlx::error::Error::RowNotFound = err {
+                        return Err(Status::not_found("Subscription not found"));
+                    }
+                    return Err(Status::internal("Something went wrong"));
+                }
+            };
+
+        match transaction.commit().await {
+            Ok(_) => Ok(Response::new(MessageResponse {
+                message: format!("Unsubscribed: {}", subscriber.id),
+            })),
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        }
+    }
+
+    async fn open(
+        &self,
+        request: Request<OpenRequest>,
+    ) -> Result<Response<MessageResponse>, Status> {
+        let mut transaction = match self.state.db.begin().await {
+            Ok(transaction) => transaction,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        let input = request.get_ref();
+
+        info!("Open Newsletter Request {:?}", input);
+
+        let opened = match NewsletterRepositoryImpl::open(&mut transaction, &input.send_id).await {
+            Ok(opened) => opened,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        if opened == 0 {
+            return Err(Status::not_found("Send not found"));
+        }
+
+        match transaction.commit().await {
+            Ok(_) => Ok(Response::new(MessageResponse {
+                message: format!("Opened: {}", input.send_id),
+            })),
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        }
+    }
+
+    async fn subscribers(
+        &self,
+        request: Request<SubscribersRequest>,
+    ) -> Result<Response<Subscribers>, Status> {
+        let mut transaction = match self.state.db.begin().await {
+            Ok(transaction) => transaction,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        let input = request.get_ref();
+
+        info!("Get Newsletter Subscribers Request {:?}", input);
+
+        let mut id = None;
+        let mut created_at = None;
+
+        if let Some(cursor_str) = &input.cursor {
+            (id, created_at) = match parse_cursor(cursor_str) {
+                Ok(parsed) => parsed,
+                Err(err) => {
+                    error!("Parse error {}", err);
+                    return Err(Status::invalid_argument("Invalid data"));
+                }
+            }
+        };
+
+        let subscribers = match NewsletterRepositoryImpl::find_subscribers(
+            &mut transaction,
+            &input.user_id,
+            input.limit,
+            id,
+            created_at,
+        )
+        .await
+        {
+            Ok(subscribers) => subscribers,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        let next_cursor = subscribers
+            .iter()
+            .nth(input.limit as usize - 1)
+            .map(|item| format!("{}_{}", item.id, item.created_at.to_rfc3339()));
+
+        let subscribers = subscribers
+            .iter()
+            .map(|subscriber| Subscriber::from(subscriber))
+            .collect();
+
+        match transaction.commit().await {
+            Ok(_) => Ok(Response::new(Subscribers {
+                subscribers,
+                next_cursor,
+            })),
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        }
+    }
+
+    async fn issues(&self, request: Request<IssuesRequest>) -> Result<Response<Issues>, Status> {
+        let mut transaction = match self.state.db.begin().await {
+            Ok(transaction) => transaction,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        let input = request.get_ref();
+
+        info!("Get Newsletter Issues Request {:?}", input);
+
+        let mut id = None;
+        let mut created_at = None;
+
+        if let Some(cursor_str) = &input.cursor {
+            (id, created_at) = match parse_cursor(cursor_str) {
+                Ok(parsed) => parsed,
+                Err(err) => {
+                    error!("Parse error {}", err);
+                    return Err(Status::invalid_argument("Invalid data"));
+                }
+            }
+        };
+
+        let issues = match NewsletterRepositoryImpl::find_issues(
+            &mut transaction,
+            &input.user_id,
+            input.limit,
+            id,
+            created_at,
+        )
+        .await
+        {
+            Ok(issues) => issues,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        let next_cursor = issues
+            .iter()
+            .nth(input.limit as usize - 1)
+            .map(|item| format!("{}_{}", item.id, item.created_at.to_rfc3339()));
+
+        let issues = issues.iter().map(|issue| Issue::from(issue)).collect();
+
+        match transaction.commit().await {
+            Ok(_) => Ok(Response::new(Issues {
+                issues,
+                next_cursor,
+            })),
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        }
+    }
+}
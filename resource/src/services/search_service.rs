@@ -0,0 +1,305 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use shared::{
+    common::{
+        FullArticle, FullArticles, FullUser, FullUsers, List, Lists, Series, Serieses, Tag, Tags,
+    },
+    models::{enums::TagStatus, search_model::SearchFilter},
+    repositories::{
+        article_repository::{ArticleRepository, ArticleRepositoryImpl},
+        list_repository::{ListRepository, ListRepositoryImpl},
+        search_repository::{SearchRepository, SearchRepositoryImpl},
+        series_repository::{SeriesRepository, SeriesRepositoryImpl},
+        tag_repository::{TagRepository, TagRepositoryImpl},
+        user_repository::{UserRepository, UserRepositoryImpl},
+    },
+    search::{
+        search_service_server::SearchService, Facets, SearchRequest, SearchResponse, SearchType,
+        SuggestRequest, SuggestResponse, SuggestType, Suggestion, TagFacet,
+    },
+};
+use sqlx::{Postgres, Transaction};
+use tonic::{Request, Response, Status};
+use tracing::{error, info, warn};
+
+use crate::{
+    application::AppState,
+    utils::{placeholders::with_placeholders, split_cursor::parse_cursor},
+};
+
+/// Tags returned as facets of the matching articles
+const TAG_FACETS: i64 = 20;
+
+/// Most suggestions returned at once
+const MAX_SUGGESTIONS: i64 = 10;
+
+/// Suggestions are given up on after this, a late one is of no use to
+/// someone typing
+const SUGGEST_TIMEOUT: Duration = Duration::from_millis(150);
+
+#[derive(Clone)]
+pub struct SearchServiceImpl {
+    pub state: Arc<AppState>,
+}
+
+#[tonic::async_trait]
+impl SearchService for SearchServiceImpl {
+    async fn search(
+        &self,
+        request: Request<SearchRequest>,
+    ) -> Result<Response<SearchResponse>, Status> {
+        let mut transaction = self.state.db.begin().await.map_err(internal)?;
+
+        let input = request.get_ref();
+
+        info!("Search Request {:?}", input);
+
+        let filter = match &input.filter {
+            Some(filter) if !filter.query.trim().is_empty() && input.limit > 0 => {
+                SearchFilter::from(filter)
+            }
+            _ => return Err(Status::invalid_argument("Invalid data")),
+        };
+        let by_user = input.by_user.as_deref();
+        let wanted = |search_type: SearchType| {
+            input.types.is_empty() || input.types.contains(&(search_type as i32))
+        };
+
+        let mut response = SearchResponse::default();
+
+        if wanted(SearchType::Articles) {
+            let (id, created_at) = cursor(input.articles_cursor.as_deref())?;
+            let articles = SearchRepositoryImpl::articles(
+                &mut transaction,
+                &filter,
+                input.limit,
+                id,
+                created_at,
+                by_user,
+            )
+            .await
+            .map_err(internal)?;
+
+            let next_cursor = articles
+                .iter()
+                .nth(input.limit as usize - 1)
+                .map(|item| format!("{}_{}", item.id, item.created_at.to_rfc3339()));
+
+            let mut articles: Vec<FullArticle> = articles
+                .iter()
+                .map(|article| FullArticle::from(article))
+                .collect();
+
+            let article_ids: Vec<String> =
+                articles.iter().map(|article| article.id.clone()).collect();
+
+            let headlines =
+                ArticleRepositoryImpl::headlines(&mut transaction, &filter.query, &article_ids)
+                    .await
+                    .map_err(internal)?;
+
+            for headline in headlines {
+                if let Some(article) = articles
+                    .iter_mut()
+                    .find(|article| article.id == headline.article_id)
+                {
+                    article.headline = Some(headline.headline);
+                }
+            }
+
+            response.articles = Some(FullArticles {
+                articles,
+                next_cursor,
+            });
+        }
+
+        if wanted(SearchType::Users) {
+            let (id, created_at) = cursor(input.users_cursor.as_deref())?;
+            let users = UserRepositoryImpl::find_all(
+                &mut transaction,
+                Some(&filter.query),
+                input.limit,
+                id,
+                created_at,
+                by_user,
+            )
+            .await
+            .map_err(internal)?;
+
+            let next_cursor = users
+                .iter()
+                .nth(input.limit as usize - 1)
+                .map(|item| format!("{}_{}", item.id, item.created_at.to_rfc3339()));
+
+            response.users = Some(FullUsers {
+                users: users.iter().map(|user| FullUser::from(user)).collect(),
+                next_cursor,
+            });
+        }
+
+        if wanted(SearchType::Tags) {
+            let tags = TagRepositoryImpl::find_all(
+                &mut transaction,
+                Some(&filter.query),
+                input.limit,
+                Some(TagStatus::Approved),
+                input.tags_cursor.as_deref(),
+            )
+            .await
+            .map_err(internal)?;
+
+            let next_cursor = tags
+                .iter()
+                .nth(input.limit as usize - 1)
+                .map(|tag| format!("{}", tag.slug));
+
+            response.tags = Some(Tags {
+                tags: tags.iter().map(|tag| Tag::from(tag)).collect(),
+                next_cursor,
+            });
+        }
+
+        if wanted(SearchType::Lists) {
+            let (id, created_at) = cursor(input.lists_cursor.as_deref())?;
+            let lists = ListRepositoryImpl::find_all(
+                &mut transaction,
+                Some(&filter.query),
+                input.limit,
+                id,
+                created_at,
+                by_user,
+            )
+            .await
+            .map_err(internal)?;
+
+            let next_cursor = lists
+                .iter()
+                .nth(input.limit as usize - 1)
+                .map(|item| format!("{}_{}", item.id, item.created_at.to_rfc3339()));
+
+            response.lists = Some(Lists {
+                lists: lists.iter().map(|list| List::from(list)).collect(),
+                next_cursor,
+            });
+        }
+
+        if wanted(SearchType::Series) {
+            let (id, created_at) = cursor(input.series_cursor.as_deref())?;
+            let serieses = SeriesRepositoryImpl::find_all(
+                &mut transaction,
+                Some(&filter.query),
+                input.limit,
+                id,
+                created_at,
+            )
+            .await
+            .map_err(internal)?;
+
+            let next_cursor = serieses
+                .iter()
+                .nth(input.limit as usize - 1)
+                .map(|item| format!("{}_{}", item.id, item.created_at.to_rfc3339()));
+
+            response.series = Some(Serieses {
+                series: serieses.iter().map(|series| Series::from(series)).collect(),
+                next_cursor,
+            });
+        }
+
+        response.facets = Some(facets(&mut transaction, &filter, by_user).await?);
+
+        transaction.commit().await.map_err(internal)?;
+
+        Ok(Response::new(
+            with_placeholders(&self.state.db, response).await,
+        ))
+    }
+
+    async fn suggest(
+        &self,
+        request: Request<SuggestRequest>,
+    ) -> Result<Response<SuggestResponse>, Status> {
+        let input = request.get_ref();
+
+        info!("Suggest Request {:?}", input);
+
+        let query = input.query.trim();
+        let suggest_type = SuggestType::try_from(input.suggest_type)
+            .map_err(|_| Status::invalid_argument("Invalid data"))?;
+        if query.is_empty() || input.limit <= 0 {
+            return Err(Status::invalid_argument("Invalid data"));
+        }
+        let limit = input.limit.min(MAX_SUGGESTIONS);
+
+        let suggestions = async {
+            let mut transaction = self.state.db.begin().await?;
+
+            let suggestions = match suggest_type {
+                SuggestType::User => {
+                    SearchRepositoryImpl::suggest_users(&mut transaction, query, limit).await?
+                }
+                SuggestType::Tag => {
+                    SearchRepositoryImpl::suggest_tags(&mut transaction, query, limit).await?
+                }
+            };
+
+            transaction.commit().await?;
+
+            Ok::<_, sqlx::Error>(suggestions)
+        };
+
+        let suggestions = match tokio::time::timeout(SUGGEST_TIMEOUT, suggestions).await {
+            Ok(suggestions) => suggestions.map_err(internal)?,
+            Err(_) => {
+                warn!("Suggestions for {:?} timed out", query);
+                vec![]
+            }
+        };
+
+        Ok(Response::new(SuggestResponse {
+            suggestions: suggestions
+                .iter()
+                .map(|suggestion| Suggestion::from(suggestion))
+                .collect(),
+        }))
+    }
+}
+
+async fn facets(
+    transaction: &mut Transaction<'_, Postgres>,
+    filter: &SearchFilter,
+    by_user: Option<&str>,
+) -> Result<Facets, Status> {
+    let counts = SearchRepositoryImpl::counts(transaction, filter, by_user)
+        .await
+        .map_err(internal)?;
+
+    let article_tags = SearchRepositoryImpl::tag_facets(transaction, filter, TAG_FACETS, by_user)
+        .await
+        .map_err(internal)?;
+
+    Ok(Facets {
+        articles: counts.articles,
+        users: counts.users,
+        tags: counts.tags,
+        lists: counts.lists,
+        series: counts.series,
+        article_tags: article_tags.iter().map(|tag| TagFacet::from(tag)).collect(),
+    })
+}
+
+fn cursor(cursor: Option<&str>) -> Result<(Option<&str>, Option<DateTime<Utc>>), Status> {
+    match cursor {
+        Some(cursor_str) => parse_cursor(cursor_str).map_err(|err| {
+            error!("Parse error {}", err);
+            Status::invalid_argument("Invalid data")
+        }),
+        None => Ok((None, None)),
+    }
+}
+
+fn internal(err: sqlx::Error) -> Status {
+    error!("{:?}", err);
+    Status::internal("Something went wrong")
+}
@@ -17,6 +17,7 @@ use crate::{
     application::AppState,
     utils::{
         permissions::{is_owner, ContentType},
+        placeholders::with_placeholders,
         split_cursor::parse_cursor,
     },
 };
@@ -78,10 +79,16 @@ impl SeriesService for SeriesServiceImpl {
         let serieses = serieses.iter().map(|series| Series::from(series)).collect();
 
         match transaction.commit().await {
-            Ok(_) => Ok(Response::new(Serieses {
-                series: serieses,
-                next_cursor,
-            })),
+            Ok(_) => Ok(Response::new(
+                with_placeholders(
+                    &self.state.db,
+                    Serieses {
+                        series: serieses,
+                        next_cursor,
+                    },
+                )
+                .await,
+            )),
             Err(err) => {
                 error!("{:?}", err);
                 return Err(Status::internal("Something went wrong"));
@@ -128,7 +135,9 @@ impl SeriesService for SeriesServiceImpl {
         //     .collect();
 
         match transaction.commit().await {
-            Ok(_) => Ok(Response::new(Series::from(&series))),
+            Ok(_) => Ok(Response::new(
+                with_placeholders(&self.state.db, Series::from(&series)).await,
+            )),
             Err(err) => {
                 error!("{:?}", err);
                 return Err(Status::internal("Something went wrong"));
@@ -177,7 +186,9 @@ impl SeriesService for SeriesServiceImpl {
         };
 
         match transaction.commit().await {
-            Ok(_) => Ok(Response::new(Series::from(&series))),
+            Ok(_) => Ok(Response::new(
+                with_placeholders(&self.state.db, Series::from(&series)).await,
+            )),
             Err(err) => {
                 error!("{:?}", err);
                 return Err(Status::internal("Something went wrong"));
@@ -245,7 +256,9 @@ impl SeriesService for SeriesServiceImpl {
         };
 
         match transaction.commit().await {
-            Ok(_) => Ok(Response::new(Series::from(&series))),
+            Ok(_) => Ok(Response::new(
+                with_placeholders(&self.state.db, Series::from(&series)).await,
+            )),
             Err(err) => {
                 error!("{:?}", err);
                 return Err(Status::internal("Something went wrong"));
@@ -355,10 +368,16 @@ impl SeriesService for SeriesServiceImpl {
             .collect();
 
         match transaction.commit().await {
-            Ok(_) => Ok(Response::new(FullArticles {
-                articles,
-                next_cursor,
-            })),
+            Ok(_) => Ok(Response::new(
+                with_placeholders(
+                    &self.state.db,
+                    FullArticles {
+                        articles,
+                        next_cursor,
+                    },
+                )
+                .await,
+            )),
             Err(err) => {
                 error!("{:?}", err);
                 return Err(Status::internal("Something went wrong"));
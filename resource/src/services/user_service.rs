@@ -1,23 +1,41 @@
 use std::sync::Arc;
 
 use chrono::{DateTime, Utc};
+use serde_json::json;
 use shared::{
     common::{
-        FullArticle, FullArticles, FullUser, FullUsers, List, Lists, MessageResponse, Series,
-        Serieses, User,
+        DigestFrequency, FullArticle, FullArticles, FullUser, FullUsers, List, Lists,
+        MessageResponse, NotificationSetting, NotificationSettings, Series, Serieses, User,
+    },
+    models::{
+        enums::WebhookEvent, notification_model::UpdateNotificationSetting, user_model::UpdateUser,
+        webhook_model::WebhookUser,
+    },
+    repositories::{
+        digest_repository::{DigestRepository, DigestRepositoryImpl},
+        notification_settings_repository::{
+            NotificationSettingsRepository, NotificationSettingsRepositoryImpl,
+        },
+        user_repository::{UserRepository, UserRepositoryImpl},
     },
-    models::user_model::UpdateUser,
-    repositories::user_repository::{UserRepository, UserRepositoryImpl},
     user::{
         user_service_server::UserService, ArticlesRequest, DeleteRequest, FeedRequest,
-        FollowRequest, FollowersRequest, FollowingRequest, GetRequest, ListsRequest, SearchRequest,
-        SeriesesRequest, UnfollowRequest, UpdateRequest,
+        FollowRequest, FollowersRequest, FollowingRequest, GetRequest, ListsRequest,
+        NotificationSettingsRequest, SearchRequest, SeriesesRequest, UnfollowRequest,
+        UpdateNotificationSettingsRequest, UpdateRequest,
     },
+    utils::message::{Payload, UserFollowedEvent},
 };
 use tonic::{Request, Response, Status};
 use tracing::{error, info};
 
-use crate::{application::AppState, utils::split_cursor::parse_cursor};
+use crate::{
+    application::AppState,
+    utils::{
+        events::publish, placeholders::with_placeholders, split_cursor::parse_cursor,
+        webhooks::emit,
+    },
+};
 
 #[derive(Clone)]
 pub struct UserServiceImpl {
@@ -77,7 +95,9 @@ impl UserService for UserServiceImpl {
         let users = users.iter().map(|user| FullUser::from(user)).collect();
 
         match transaction.commit().await {
-            Ok(_) => Ok(Response::new(FullUsers { users, next_cursor })),
+            Ok(_) => Ok(Response::new(
+                with_placeholders(&self.state.db, FullUsers { users, next_cursor }).await,
+            )),
             Err(err) => {
                 error!("{:?}", err);
                 return Err(Status::internal("Something went wrong"));
@@ -116,7 +136,9 @@ impl UserService for UserServiceImpl {
         };
 
         match transaction.commit().await {
-            Ok(_) => Ok(Response::new(FullUser::from(&user))),
+            Ok(_) => Ok(Response::new(
+                with_placeholders(&self.state.db, FullUser::from(&user)).await,
+            )),
             Err(err) => {
                 error!("{:?}", err);
                 return Err(Status::internal("Something went wrong"));
@@ -155,6 +177,7 @@ impl UserService for UserServiceImpl {
                 bio: input.bio.to_owned(),
                 image: input.image.to_owned(),
                 urls: input.urls.to_owned(),
+                locale: input.locale.to_owned(),
             },
         )
         .await
@@ -177,7 +200,9 @@ impl UserService for UserServiceImpl {
         };
 
         match transaction.commit().await {
-            Ok(_) => Ok(Response::new(User::from(&user))),
+            Ok(_) => Ok(Response::new(
+                with_placeholders(&self.state.db, User::from(&user)).await,
+            )),
             Err(err) => {
                 error!("{:?}", err);
                 return Err(Status::internal("Something went wrong"));
@@ -247,10 +272,15 @@ impl UserService for UserServiceImpl {
 
         info!("Follow User Request {:?}", input);
 
-        let user = match UserRepositoryImpl::follow(&mut transaction, &input.user_id, &input.target)
-            .await
+        // Following someone twice is a no-op and shouldn't be announced again
+        let followed = match UserRepositoryImpl::find_by_username(
+            &mut transaction,
+            &input.target,
+            Some(&input.user_id),
+        )
+        .await
         {
-            Ok(user) => user,
+            Ok(target) => target.followed.unwrap_or(false),
             Err(err) => {
                 error!("{:?}", err);
                 if let sqlx::error::Error::RowNotFound = err {
@@ -260,15 +290,74 @@ impl UserService for UserServiceImpl {
             }
         };
 
-        match transaction.commit().await {
-            Ok(_) => Ok(Response::new(MessageResponse {
-                message: format!("User {} followed {}", user.0, user.1),
-            })),
+        let user = match UserRepositoryImpl::follow(&mut transaction, &input.user_id, &input.target)
+            .await
+        {
+            Ok(user) => user,
             Err(err) => {
                 error!("{:?}", err);
+                if let sqlx::error::Error::RowNotFound = err {
+                    return Err(Status::not_found("User not found"));
+                }
                 return Err(Status::internal("Something went wrong"));
             }
+        };
+
+        let mut following_id = None;
+
+        if !followed {
+            let emitted = async {
+                let follower = UserRepositoryImpl::find(&mut transaction, &input.user_id).await?;
+                let follower = UserRepositoryImpl::find_by_username(
+                    &mut transaction,
+                    &follower.username,
+                    None,
+                )
+                .await?;
+                let following =
+                    UserRepositoryImpl::find_by_username(&mut transaction, &input.target, None)
+                        .await?;
+                emit(
+                    &mut transaction,
+                    WebhookEvent::UserFollowed,
+                    &[following.id.to_owned()],
+                    &json!({
+                        "follower": WebhookUser::from(&follower),
+                        "following": WebhookUser::from(&following),
+                    }),
+                )
+                .await?;
+                Ok::<_, sqlx::Error>(following.id)
+            }
+            .await;
+
+            match emitted {
+                Ok(id) => following_id = Some(id),
+                Err(err) => {
+                    error!("{:?}", err);
+                    return Err(Status::internal("Something went wrong"));
+                }
+            }
+        }
+
+        if let Err(err) = transaction.commit().await {
+            error!("{:?}", err);
+            return Err(Status::internal("Something went wrong"));
         }
+
+        if let Some(following_id) = following_id {
+            publish(
+                self.state.clone(),
+                Payload::UserFollowed(UserFollowedEvent {
+                    follower_id: input.user_id.to_owned(),
+                    following_id,
+                }),
+            );
+        }
+
+        Ok(Response::new(MessageResponse {
+            message: format!("User {} followed {}", user.0, user.1),
+        }))
     }
 
     async fn unfollow(
@@ -366,7 +455,9 @@ impl UserService for UserServiceImpl {
         let users = users.iter().map(|user| FullUser::from(user)).collect();
 
         match transaction.commit().await {
-            Ok(_) => Ok(Response::new(FullUsers { users, next_cursor })),
+            Ok(_) => Ok(Response::new(
+                with_placeholders(&self.state.db, FullUsers { users, next_cursor }).await,
+            )),
             Err(err) => {
                 error!("{:?}", err);
                 return Err(Status::internal("Something went wrong"));
@@ -428,7 +519,9 @@ impl UserService for UserServiceImpl {
         let users = users.iter().map(|user| FullUser::from(user)).collect();
 
         match transaction.commit().await {
-            Ok(_) => Ok(Response::new(FullUsers { users, next_cursor })),
+            Ok(_) => Ok(Response::new(
+                with_placeholders(&self.state.db, FullUsers { users, next_cursor }).await,
+            )),
             Err(err) => {
                 error!("{:?}", err);
                 return Err(Status::internal("Something went wrong"));
@@ -494,10 +587,16 @@ impl UserService for UserServiceImpl {
             .collect();
 
         match transaction.commit().await {
-            Ok(_) => Ok(Response::new(FullArticles {
-                articles,
-                next_cursor,
-            })),
+            Ok(_) => Ok(Response::new(
+                with_placeholders(
+                    &self.state.db,
+                    FullArticles {
+                        articles,
+                        next_cursor,
+                    },
+                )
+                .await,
+            )),
             Err(err) => {
                 error!("{:?}", err);
                 return Err(Status::internal("Something went wrong"));
@@ -563,10 +662,16 @@ impl UserService for UserServiceImpl {
             .collect();
 
         match transaction.commit().await {
-            Ok(_) => Ok(Response::new(FullArticles {
-                articles,
-                next_cursor,
-            })),
+            Ok(_) => Ok(Response::new(
+                with_placeholders(
+                    &self.state.db,
+                    FullArticles {
+                        articles,
+                        next_cursor,
+                    },
+                )
+                .await,
+            )),
             Err(err) => {
                 error!("{:?}", err);
                 return Err(Status::internal("Something went wrong"));
@@ -627,10 +732,16 @@ impl UserService for UserServiceImpl {
         let serieses = serieses.iter().map(|series| Series::from(series)).collect();
 
         match transaction.commit().await {
-            Ok(_) => Ok(Response::new(Serieses {
-                series: serieses,
-                next_cursor,
-            })),
+            Ok(_) => Ok(Response::new(
+                with_placeholders(
+                    &self.state.db,
+                    Serieses {
+                        series: serieses,
+                        next_cursor,
+                    },
+                )
+                .await,
+            )),
             Err(err) => {
                 error!("{:?}", err);
                 return Err(Status::internal("Something went wrong"));
@@ -689,7 +800,9 @@ impl UserService for UserServiceImpl {
         let lists = lists.iter().map(|list| List::from(list)).collect();
 
         match transaction.commit().await {
-            Ok(_) => Ok(Response::new(Lists { lists, next_cursor })),
+            Ok(_) => Ok(Response::new(
+                with_placeholders(&self.state.db, Lists { lists, next_cursor }).await,
+            )),
             Err(err) => {
                 error!("{:?}", err);
                 return Err(Status::internal("Something went wrong"));
@@ -750,9 +863,157 @@ impl UserService for UserServiceImpl {
             .collect();
 
         match transaction.commit().await {
-            Ok(_) => Ok(Response::new(FullArticles {
-                articles,
-                next_cursor,
+            Ok(_) => Ok(Response::new(
+                with_placeholders(
+                    &self.state.db,
+                    FullArticles {
+                        articles,
+                        next_cursor,
+                    },
+                )
+                .await,
+            )),
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        }
+    }
+
+    async fn notification_settings(
+        &self,
+        request: Request<NotificationSettingsRequest>,
+    ) -> Result<Response<NotificationSettings>, Status> {
+        let mut transaction = match self.state.db.begin().await {
+            Ok(transaction) => transaction,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        let input = request.get_ref();
+
+        info!("Get Notification Settings Request {:?}", input);
+
+        let settings =
+            match NotificationSettingsRepositoryImpl::find_all(&mut transaction, &input.user_id)
+                .await
+            {
+                Ok(settings) => settings,
+                Err(err) => {
+                    error!("{:?}", err);
+                    return Err(Status::internal("Something went wrong"));
+                }
+            };
+
+        let digest_frequency =
+            match DigestRepositoryImpl::find_frequency(&mut transaction, &input.user_id).await {
+                Ok(digest_frequency) => digest_frequency,
+                Err(err) => {
+                    error!("{:?}", err);
+                    return Err(Status::internal("Something went wrong"));
+                }
+            };
+
+        match transaction.commit().await {
+            Ok(_) => Ok(Response::new(NotificationSettings {
+                settings: settings
+                    .iter()
+                    .map(|setting| NotificationSetting::from(setting))
+                    .collect(),
+                digest_frequency: DigestFrequency::from(digest_frequency) as i32,
+            })),
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        }
+    }
+
+    async fn update_notification_settings(
+        &self,
+        request: Request<UpdateNotificationSettingsRequest>,
+    ) -> Result<Response<NotificationSettings>, Status> {
+        let mut transaction = match self.state.db.begin().await {
+            Ok(transaction) => transaction,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        let input = request.get_ref();
+
+        info!("Update Notification Settings Request {:?}", input);
+
+        for setting in input.settings.iter() {
+            if let Err(err) = NotificationSettingsRepositoryImpl::update(
+                &mut transaction,
+                &UpdateNotificationSetting {
+                    user_id: input.user_id.to_owned(),
+                    event: setting.event().into(),
+                    in_app: setting.in_app,
+                    email: setting.email,
+                },
+            )
+            .await
+            {
+                error!("{:?}", err);
+                if let sqlx::error::Error::Database(db_err) = err {
+                    if db_err.is_foreign_key_violation() {
+                        return Err(Status::not_found("User not found"));
+                    }
+                }
+                return Err(Status::internal("Something went wrong"));
+            }
+        }
+
+        if input.digest_frequency.is_some() {
+            if let Err(err) = DigestRepositoryImpl::update_frequency(
+                &mut transaction,
+                &input.user_id,
+                input.digest_frequency().into(),
+            )
+            .await
+            {
+                error!("{:?}", err);
+                if let sqlx::error::Error::Database(db_err) = err {
+                    if db_err.is_foreign_key_violation() {
+                        return Err(Status::not_found("User not found"));
+                    }
+                }
+                return Err(Status::internal("Something went wrong"));
+            }
+        }
+
+        let settings =
+            match NotificationSettingsRepositoryImpl::find_all(&mut transaction, &input.user_id)
+                .await
+            {
+                Ok(settings) => settings,
+                Err(err) => {
+                    error!("{:?}", err);
+                    return Err(Status::internal("Something went wrong"));
+                }
+            };
+
+        let digest_frequency =
+            match DigestRepositoryImpl::find_frequency(&mut transaction, &input.user_id).await {
+                Ok(digest_frequency) => digest_frequency,
+                Err(err) => {
+                    error!("{:?}", err);
+                    return Err(Status::internal("Something went wrong"));
+                }
+            };
+
+        match transaction.commit().await {
+            Ok(_) => Ok(Response::new(NotificationSettings {
+                settings: settings
+                    .iter()
+                    .map(|setting| NotificationSetting::from(setting))
+                    .collect(),
+                digest_frequency: DigestFrequency::from(digest_frequency) as i32,
             })),
             Err(err) => {
                 error!("{:?}", err);
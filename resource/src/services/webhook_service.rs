@@ -0,0 +1,399 @@
+use std::sync::Arc;
+
+use shared::{
+    common::MessageResponse,
+    models::{
+        enums::{Role, WebhookEvent},
+        webhook_model::{CreateWebhook, UpdateWebhook},
+    },
+    repositories::{
+        user_repository::{UserRepository, UserRepositoryImpl},
+        webhook_repository::{WebhookRepository, WebhookRepositoryImpl},
+    },
+    utils::webhook::is_public_url,
+    webhook::{
+        webhook_service_server::WebhookService, CreateRequest, DeleteRequest, Deliveries,
+        DeliveriesRequest, Delivery, GetAllRequest, GetRequest, RedeliverRequest, UpdateRequest,
+        Webhook, Webhooks,
+    },
+};
+use tonic::{Request, Response, Status};
+use tracing::{error, info};
+
+use crate::{application::AppState, utils::split_cursor::parse_cursor};
+
+#[derive(Clone)]
+pub struct WebhookServiceImpl {
+    pub state: Arc<AppState>,
+}
+
+#[tonic::async_trait]
+impl WebhookService for WebhookServiceImpl {
+    async fn get_all(&self, request: Request<GetAllRequest>) -> Result<Response<Webhooks>, Status> {
+        let mut transaction = match self.state.db.begin().await {
+            Ok(transaction) => transaction,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        let input = request.get_ref();
+
+        info!("Get Webhooks Request {:?}", input);
+
+        let webhooks = match WebhookRepositoryImpl::find_all(&mut transaction, &input.user_id).await
+        {
+            Ok(webhooks) => webhooks,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        match transaction.commit().await {
+            Ok(_) => Ok(Response::new(Webhooks {
+                webhooks: webhooks
+                    .iter()
+                    .map(|webhook| Webhook::from(webhook))
+                    .collect(),
+            })),
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        }
+    }
+
+    async fn get(&self, request: Request<GetRequest>) -> Result<Response<Webhook>, Status> {
+        let mut transaction = match self.state.db.begin().await {
+            Ok(transaction) => transaction,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        let input = request.get_ref();
+
+        info!("Get Webhook Request {:?}", input);
+
+        let webhook =
+            match WebhookRepositoryImpl::find(&mut transaction, &input.user_id, &input.webhook_id)
+                .await
+            {
+                Ok(webhook) => webhook,
+                Err(err) => {
+                    error!("{:?}", err);
+                    if let sqlx::error::Error::RowNotFound = err {
+                        return Err(Status::not_found("Webhook not found"));
+                    }
+                    return Err(Status::internal("Something went wrong"));
+                }
+            };
+
+        match transaction.commit().await {
+            Ok(_) => Ok(Response::new(Webhook::from(&webhook))),
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        }
+    }
+
+    async fn create(&self, request: Request<CreateRequest>) -> Result<Response<Webhook>, Status> {
+        let mut transaction = match self.state.db.begin().await {
+            Ok(transaction) => transaction,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        let input = request.get_ref();
+
+        info!("Create Webhook Request {:?}", input);
+
+        if !is_public_url(&input.url).await {
+            return Err(Status::invalid_argument("Invalid url"));
+        }
+
+        let events: Vec<WebhookEvent> = input.events().map(|event| event.into()).collect();
+
+        if events.is_empty() {
+            return Err(Status::invalid_argument("No events"));
+        }
+
+        // Global webhooks see activity of every user
+        if input.global {
+            match UserRepositoryImpl::find(&mut transaction, &input.user_id).await {
+                Ok(user) => {
+                    if user.role != Role::Admin {
+                        return Err(Status::permission_denied("Forbidden"));
+                    }
+                }
+                Err(err) => {
+                    error!("{:?}", err);
+                    if let sqlx::error::Error::RowNotFound = err {
+                        return Err(Status::not_found("User not found"));
+                    }
+                    return Err(Status::internal("Something went wrong"));
+                }
+            }
+        }
+
+        let webhook = match WebhookRepositoryImpl::create(
+            &mut transaction,
+            &CreateWebhook {
+                user_id: input.user_id.to_owned(),
+                url: input.url.to_owned(),
+                events,
+                global: input.global,
+            },
+        )
+        .await
+        {
+            Ok(webhook) => webhook,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        match transaction.commit().await {
+            Ok(_) => Ok(Response::new(Webhook::from(&webhook))),
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        }
+    }
+
+    async fn update(&self, request: Request<UpdateRequest>) -> Result<Response<Webhook>, Status> {
+        let mut transaction = match self.state.db.begin().await {
+            Ok(transaction) => transaction,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        let input = request.get_ref();
+
+        info!("Update Webhook Request {:?}", input);
+
+        if let Some(url) = input.url.as_deref() {
+            if !is_public_url(url).await {
+                return Err(Status::invalid_argument("Invalid url"));
+            }
+        }
+
+        let events: Vec<WebhookEvent> = input.events().map(|event| event.into()).collect();
+
+        let webhook = match WebhookRepositoryImpl::update(
+            &mut transaction,
+            &UpdateWebhook {
+                id: input.webhook_id.to_owned(),
+                user_id: input.user_id.to_owned(),
+                url: input.url.to_owned(),
+                events: match events.is_empty() {
+                    true => None,
+                    false => Some(events),
+                },
+                active: input.active,
+            },
+        )
+        .await
+        {
+            Ok(webhook) => webhook,
+            Err(err) => {
+                error!("{:?}", err);
+                if let sqlx::error::Error::RowNotFound = err {
+                    return Err(Status::not_found("Webhook not found"));
+                }
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        match transaction.commit().await {
+            Ok(_) => Ok(Response::new(Webhook::from(&webhook))),
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        }
+    }
+
+    async fn delete(
+        &self,
+        request: Request<DeleteRequest>,
+    ) -> Result<Response<MessageResponse>, Status> {
+        let mut transaction = match self.state.db.begin().await {
+            Ok(transaction) => transaction,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        let input = request.get_ref();
+
+        info!("Delete Webhook Request {:?}", input);
+
+        let webhook = match WebhookRepositoryImpl::delete(
+            &mut transaction,
+            &input.user_id,
+            &input.webhook_id,
+        )
+        .await
+        {
+            Ok(webhook) => webhook,
+            Err(err) => {
+                error!("{:?}", err);
+                if let sqlx::error::Error::RowNotFound = err {
+                    return Err(Status::not_found("Webhook not found"));
+                }
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        match transaction.commit().await {
+            Ok(_) => Ok(Response::new(MessageResponse {
+                message: format!("Deleted webhook: {}", webhook.id),
+            })),
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        }
+    }
+
+    async fn deliveries(
+        &self,
+        request: Request<DeliveriesRequest>,
+    ) -> Result<Response<Deliveries>, Status> {
+        let mut transaction = match self.state.db.begin().await {
+            Ok(transaction) => transaction,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        let input = request.get_ref();
+
+        info!("Get Webhook Deliveries Request {:?}", input);
+
+        let mut id = None;
+        let mut created_at = None;
+
+        if let Some(cursor_str) = &input.cursor {
+            (id, created_at) = match parse_cursor(cursor_str) {
+                Ok(parsed) => parsed,
+                Err(err) => {
+                    error!("Parse error {}", err);
+                    return Err(Status::invalid_argument("Invalid data"));
+                }
+            }
+        };
+
+        if let Err(err) =
+            WebhookRepositoryImpl::find(&mut transaction, &input.user_id, &input.webhook_id).await
+        {
+            error!("{:?}", err);
+            if let sqlx::error::Error::RowNotFound = err {
+                return Err(Status::not_found("Webhook not found"));
+            }
+            return Err(Status::internal("Something went wrong"));
+        }
+
+        let deliveries = match WebhookRepositoryImpl::find_deliveries(
+            &mut transaction,
+            &input.webhook_id,
+            input.limit,
+            id,
+            created_at,
+        )
+        .await
+        {
+            Ok(deliveries) => deliveries,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        let next_cursor = deliveries
+            .iter()
+            .nth(input.limit as usize - 1)
+            .map(|item| format!("{}_{}", item.id, item.created_at.to_rfc3339()));
+
+        let deliveries = deliveries
+            .iter()
+            .map(|delivery| Delivery::from(delivery))
+            .collect();
+
+        match transaction.commit().await {
+            Ok(_) => Ok(Response::new(Deliveries {
+                deliveries,
+                next_cursor,
+            })),
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        }
+    }
+
+    async fn redeliver(
+        &self,
+        request: Request<RedeliverRequest>,
+    ) -> Result<Response<Delivery>, Status> {
+        let mut transaction = match self.state.db.begin().await {
+            Ok(transaction) => transaction,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        let input = request.get_ref();
+
+        info!("Redeliver Webhook Request {:?}", input);
+
+        if let Err(err) =
+            WebhookRepositoryImpl::find(&mut transaction, &input.user_id, &input.webhook_id).await
+        {
+            error!("{:?}", err);
+            if let sqlx::error::Error::RowNotFound = err {
+                return Err(Status::not_found("Webhook not found"));
+            }
+            return Err(Status::internal("Something went wrong"));
+        }
+
+        let delivery = match WebhookRepositoryImpl::redeliver(
+            &mut transaction,
+            &input.webhook_id,
+            &input.delivery_id,
+        )
+        .await
+        {
+            Ok(delivery) => delivery,
+            Err(err) => {
+                error!("{:?}", err);
+                if let sqlx::error::Error::RowNotFound = err {
+                    return Err(Status::not_found("Delivery not found"));
+                }
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        match transaction.commit().await {
+            Ok(_) => Ok(Response::new(Delivery::from(&delivery))),
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use std::sync::Arc;
+
+use amqprs::channel::BasicPublishArguments;
+use serde_json::json;
+use shared::{
+    repositories::article_repository::{ArticleRepository, ArticleRepositoryImpl},
+    utils::message::{ArticleLikedEvent, Envelope, Payload, EVENTS_EXCHANGE},
+};
+use tracing::error;
+
+use crate::application::AppState;
+
+/// Publishes a domain event in the background, for events that only feed
+/// real-time updates. They are sent after commit and a failure is just
+/// logged, events other services send email from go through [`deliver`].
+pub fn publish(state: Arc<AppState>, payload: Payload) {
+    tokio::spawn(async move {
+        if let Err(err) = send(&state, payload).await {
+            error!("Unable to publish event: {:?}", err);
+        }
+    });
+}
+
+async fn send(state: &AppState, payload: Payload) -> Result<(), anyhow::Error> {
+    let message_type = payload.message_type();
+    send_to(state, EVENTS_EXCHANGE, message_type, payload).await
+}
+
+/// Publishes a domain event and waits for it, so a failure reaches the
+/// caller instead of losing the emails sent from the event
+pub async fn deliver(state: &AppState, payload: Payload) -> Result<(), anyhow::Error> {
+    send(state, payload).await
+}
+
+/// Queues an email with the notification service
+pub async fn notify(state: &AppState, payload: Payload) -> Result<(), anyhow::Error> {
+    send_to(state, "", "notification", payload).await
+}
+
+async fn send_to(
+    state: &AppState,
+    exchange: &str,
+    routing_key: &str,
+    payload: Payload,
+) -> Result<(), anyhow::Error> {
+    let envelope = Envelope::new(payload);
+    let channel = state.connection.open_channel(None).await?;
+    channel
+        .basic_publish(
+            envelope.properties(),
+            json!(envelope).to_string().into_bytes(),
+            BasicPublishArguments::new(exchange, routing_key),
+        )
+        .await?;
+    channel.close().await?;
+    Ok(())
+}
+
+/// Like counts are read after commit, a like that did not go through still
+/// reports the current count
+pub fn publish_like(state: Arc<AppState>, article_id: &str, user_id: &str, liked: bool) {
+    let article_id = article_id.to_owned();
+    let user_id = user_id.to_owned();
+    tokio::spawn(async move {
+        let event = async {
+            let mut transaction = state.db.begin().await?;
+            let article = ArticleRepositoryImpl::find(&mut transaction, &article_id, None).await?;
+            transaction.commit().await?;
+            Ok::<_, sqlx::Error>(ArticleLikedEvent {
+                recipients: article
+                    .users
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|author| author.id)
+                    .filter(|author_id| *author_id != user_id)
+                    .collect(),
+                article_id: article.id,
+                user_id: user_id.to_owned(),
+                liked,
+                like_count: article.like_count,
+            })
+        }
+        .await;
+
+        match event {
+            Ok(event) => {
+                if let Err(err) = send(&state, Payload::ArticleLiked(event)).await {
+                    error!("Unable to publish event: {:?}", err);
+                }
+            }
+            Err(err) => error!("Unable to load liked article: {:?}", err),
+        }
+    });
+}
@@ -1,2 +1,5 @@
+pub mod events;
 pub mod permissions;
-pub mod split_cursor;
+pub mod placeholders;
+pub use shared::utils::split_cursor;
+pub mod webhooks;
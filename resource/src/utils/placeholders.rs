@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use shared::{
+    common::{
+        FullArticle, FullArticles, FullList, FullUser, FullUsers, ImagePlaceholder, List, Lists,
+        Series, Serieses, User,
+    },
+    repositories::asset_repository::{AssetRepository, AssetRepositoryImpl},
+    search::SearchResponse,
+    utils::placeholder::asset_name,
+};
+use sqlx::PgPool;
+use tracing::error;
+
+type Slot<'a> = (&'a str, &'a mut Option<ImagePlaceholder>);
+
+/// Responses carrying `image` fields that can be given a placeholder
+pub trait Images {
+    fn images(&mut self) -> Vec<Slot<'_>>;
+}
+
+macro_rules! image {
+    ($($message:ty),*) => {
+        $(impl Images for $message {
+            fn images(&mut self) -> Vec<Slot<'_>> {
+                self.image
+                    .as_deref()
+                    .and_then(asset_name)
+                    .map(|name| (name, &mut self.image_placeholder))
+                    .into_iter()
+                    .collect()
+            }
+        })*
+    };
+}
+
+image!(User, FullUser, List, FullList, Series);
+
+impl<T: Images> Images for Vec<T> {
+    fn images(&mut self) -> Vec<Slot<'_>> {
+        self.iter_mut().flat_map(Images::images).collect()
+    }
+}
+
+impl<T: Images> Images for Option<T> {
+    fn images(&mut self) -> Vec<Slot<'_>> {
+        self.iter_mut().flat_map(Images::images).collect()
+    }
+}
+
+impl Images for FullArticle {
+    fn images(&mut self) -> Vec<Slot<'_>> {
+        let mut images = self.users.images();
+        images.extend(self.series.images());
+        images.extend(self.lists.images());
+        images
+    }
+}
+
+impl Images for FullUsers {
+    fn images(&mut self) -> Vec<Slot<'_>> {
+        self.users.images()
+    }
+}
+
+impl Images for FullArticles {
+    fn images(&mut self) -> Vec<Slot<'_>> {
+        self.articles.images()
+    }
+}
+
+impl Images for Lists {
+    fn images(&mut self) -> Vec<Slot<'_>> {
+        self.lists.images()
+    }
+}
+
+impl Images for Serieses {
+    fn images(&mut self) -> Vec<Slot<'_>> {
+        self.series.images()
+    }
+}
+
+impl Images for SearchResponse {
+    fn images(&mut self) -> Vec<Slot<'_>> {
+        let mut images = self.articles.images();
+        images.extend(self.users.images());
+        images.extend(self.lists.images());
+        images.extend(self.series.images());
+        images
+    }
+}
+
+/// Fills in placeholders for images that are stored assets. Failing to
+/// look them up only costs the client its placeholders, so the response is
+/// returned as is
+pub async fn with_placeholders<T: Images>(db: &PgPool, mut response: T) -> T {
+    let images = response.images();
+
+    if images.is_empty() {
+        return response;
+    }
+
+    let names: Vec<String> = images.iter().map(|(name, _)| name.to_string()).collect();
+
+    let mut transaction = match db.begin().await {
+        Ok(transaction) => transaction,
+        Err(err) => {
+            error!("{:?}", err);
+            return response;
+        }
+    };
+
+    let placeholders = match AssetRepositoryImpl::find_placeholders(&mut transaction, &names).await
+    {
+        Ok(placeholders) => placeholders,
+        Err(err) => {
+            error!("{:?}", err);
+            return response;
+        }
+    };
+
+    let placeholders: HashMap<_, _> = placeholders
+        .into_iter()
+        .map(|placeholder| {
+            (
+                placeholder.name,
+                ImagePlaceholder {
+                    blurhash: placeholder.blurhash,
+                    dominant_color: placeholder.dominant_color,
+                },
+            )
+        })
+        .collect();
+
+    for (name, slot) in images {
+        *slot = placeholders.get(name).cloned();
+    }
+
+    response
+}
@@ -0,0 +1,53 @@
+use serde::Serialize;
+use shared::{
+    models::enums::{CommentableType, WebhookEvent},
+    repositories::{
+        article_repository::{ArticleRepository, ArticleRepositoryImpl},
+        list_repository::{ListRepository, ListRepositoryImpl},
+        series_repository::{SeriesRepository, SeriesRepositoryImpl},
+        webhook_repository::{WebhookRepository, WebhookRepositoryImpl},
+    },
+};
+use sqlx::{Postgres, Transaction};
+
+/// Queues a delivery of `data` to every webhook subscribed to `event`. Runs in
+/// the transaction of the change itself, so a rolled back change sends nothing.
+/// `subjects` are the users the event is about, their own webhooks receive it
+/// along with the global ones.
+pub async fn emit<T: Serialize>(
+    transaction: &mut Transaction<'_, Postgres>,
+    event: WebhookEvent,
+    subjects: &[String],
+    data: &T,
+) -> Result<(), sqlx::Error> {
+    let payload = serde_json::to_value(data).map_err(|err| sqlx::Error::Encode(Box::new(err)))?;
+    WebhookRepositoryImpl::enqueue(transaction, event, subjects, &payload).await?;
+    Ok(())
+}
+
+/// Users owning the target of a comment
+pub async fn comment_target_owners(
+    transaction: &mut Transaction<'_, Postgres>,
+    r#type: CommentableType,
+    target_id: &str,
+) -> Result<Vec<String>, sqlx::Error> {
+    match r#type {
+        CommentableType::Article => {
+            let article = ArticleRepositoryImpl::find(transaction, target_id, None).await?;
+            Ok(article
+                .users
+                .unwrap_or_default()
+                .into_iter()
+                .map(|author| author.id)
+                .collect())
+        }
+        CommentableType::List => {
+            let list = ListRepositoryImpl::find(transaction, target_id, None).await?;
+            Ok(vec![list.user_id])
+        }
+        CommentableType::Series => {
+            let series = SeriesRepositoryImpl::find(transaction, target_id).await?;
+            Ok(vec![series.user_id])
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    path::Path,
+    sync::Arc,
+};
+
+use anyhow::anyhow;
+use shared::{
+    configuration::{DatabaseSettings, Settings},
+    search_index::search_index_service_server::SearchIndexServiceServer,
+};
+use sqlx::{postgres::PgPoolOptions, PgPool};
+use tonic::transport::{server::Router, Server};
+use tracing::info;
+
+use crate::{
+    index::{language, SearchIndex},
+    indexer::Indexer,
+    service::SearchIndexServiceImpl,
+};
+
+pub struct Application {
+    pub port: u16,
+    pub server: Router,
+    pub address: SocketAddr,
+    pub indexer: Arc<Indexer>,
+}
+
+impl Application {
+    pub async fn build(configuration: Settings) -> Result<Self, anyhow::Error> {
+        info!("Building search service");
+
+        let settings = configuration.search;
+
+        let language = language(&settings.language)
+            .ok_or_else(|| anyhow!("Unsupported search language {}", settings.language))?;
+
+        info!("Keeping the search index in {}", settings.index_path);
+
+        let index = Arc::new(SearchIndex::open(
+            Path::new(&settings.index_path),
+            language,
+            settings.distance,
+        )?);
+
+        let db = get_connection_pool(&configuration.database).await;
+
+        let port = configuration.search_server.port;
+
+        let address = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+
+        let indexer = Arc::new(Indexer::new(db, index.clone(), settings));
+
+        let search_index_service = SearchIndexServiceImpl {
+            index,
+            indexer: indexer.clone(),
+        };
+
+        let server =
+            Server::builder().add_service(SearchIndexServiceServer::new(search_index_service));
+
+        info!("Finished search service build");
+
+        Ok(Self {
+            port,
+            server,
+            address,
+            indexer,
+        })
+    }
+
+    pub async fn run(self) -> Result<(), tonic::transport::Error> {
+        tokio::spawn(self.indexer.run());
+
+        info!("Server is running on {}", self.port);
+        self.server.serve(self.address).await
+    }
+}
+
+pub async fn get_connection_pool(configuration: &DatabaseSettings) -> PgPool {
+    PgPoolOptions::new().connect_lazy_with(configuration.with_db())
+}
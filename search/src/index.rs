@@ -0,0 +1,308 @@
+use std::{path::Path, str::FromStr, sync::Mutex};
+
+use tantivy::{
+    collector::{Count, TopDocs},
+    directory::MmapDirectory,
+    doc,
+    query::{BooleanQuery, Occur, Query, QueryParser, TermSetQuery},
+    schema::{
+        Field, IndexRecordOption, Schema, TextFieldIndexing, TextOptions, Value, STORED, STRING,
+    },
+    tokenizer::{Language, LowerCaser, RemoveLongFilter, SimpleTokenizer, Stemmer, TextAnalyzer},
+    Index, IndexReader, IndexWriter, ReloadPolicy, TantivyDocument, TantivyError, Term,
+};
+
+/// Tokenizer of the text fields, stems words in the configured language
+const TOKENIZER: &str = "orta";
+
+/// Memory the writer buffers documents in before flushing a segment
+const WRITER_MEMORY: usize = 50_000_000;
+
+const TITLE_BOOST: f32 = 3.0;
+const DESCRIPTION_BOOST: f32 = 1.5;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    Article,
+    User,
+    Tag,
+}
+
+impl Kind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Kind::Article => "article",
+            Kind::User => "user",
+            Kind::Tag => "tag",
+        }
+    }
+}
+
+impl FromStr for Kind {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "article" => Ok(Kind::Article),
+            "user" => Ok(Kind::User),
+            "tag" => Ok(Kind::Tag),
+            _ => Err(format!("Can not parse {} into Kind", input)),
+        }
+    }
+}
+
+/// What gets indexed of an article, user or tag. Users put their username
+/// in the title and bio in the description, tags only have a title.
+#[derive(Debug)]
+pub struct Document {
+    pub kind: Kind,
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    pub content: String,
+}
+
+#[derive(Debug)]
+pub struct Hit {
+    pub kind: Kind,
+    pub id: String,
+    pub title: String,
+    pub score: f32,
+}
+
+struct Fields {
+    /// `kind:id`, unique per document so it can be replaced
+    key: Field,
+    kind: Field,
+    id: Field,
+    title: Field,
+    description: Field,
+    content: Field,
+}
+
+pub struct SearchIndex {
+    index: Index,
+    reader: IndexReader,
+    writer: Mutex<IndexWriter>,
+    fields: Fields,
+    /// Edits a query term may be away from an indexed one
+    distance: u8,
+}
+
+pub fn language(name: &str) -> Option<Language> {
+    match name.to_lowercase().as_str() {
+        "arabic" => Some(Language::Arabic),
+        "danish" => Some(Language::Danish),
+        "dutch" => Some(Language::Dutch),
+        "english" => Some(Language::English),
+        "finnish" => Some(Language::Finnish),
+        "french" => Some(Language::French),
+        "german" => Some(Language::German),
+        "greek" => Some(Language::Greek),
+        "hungarian" => Some(Language::Hungarian),
+        "italian" => Some(Language::Italian),
+        "norwegian" => Some(Language::Norwegian),
+        "portuguese" => Some(Language::Portuguese),
+        "romanian" => Some(Language::Romanian),
+        "russian" => Some(Language::Russian),
+        "spanish" => Some(Language::Spanish),
+        "swedish" => Some(Language::Swedish),
+        "tamil" => Some(Language::Tamil),
+        "turkish" => Some(Language::Turkish),
+        _ => None,
+    }
+}
+
+fn schema() -> (Schema, Fields) {
+    let mut builder = Schema::builder();
+    let text = TextOptions::default()
+        .set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer(TOKENIZER)
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+        )
+        .set_stored();
+    let fields = Fields {
+        key: builder.add_text_field("key", STRING),
+        kind: builder.add_text_field("kind", STRING | STORED),
+        id: builder.add_text_field("id", STRING | STORED),
+        title: builder.add_text_field("title", text.clone()),
+        description: builder.add_text_field("description", text.clone()),
+        content: builder.add_text_field("content", text),
+    };
+    (builder.build(), fields)
+}
+
+fn key(kind: Kind, id: &str) -> String {
+    format!("{}:{}", kind.as_str(), id)
+}
+
+impl SearchIndex {
+    /// Opens the index kept in `path`, creating it when missing
+    pub fn open(path: &Path, language: Language, distance: u8) -> tantivy::Result<Self> {
+        std::fs::create_dir_all(path)?;
+        let (schema, fields) = schema();
+        let index = Index::open_or_create(MmapDirectory::open(path)?, schema)?;
+        Self::build(index, fields, language, distance)
+    }
+
+    pub fn in_memory(language: Language, distance: u8) -> tantivy::Result<Self> {
+        let (schema, fields) = schema();
+        Self::build(Index::create_in_ram(schema), fields, language, distance)
+    }
+
+    fn build(
+        index: Index,
+        fields: Fields,
+        language: Language,
+        distance: u8,
+    ) -> tantivy::Result<Self> {
+        index.tokenizers().register(
+            TOKENIZER,
+            TextAnalyzer::builder(SimpleTokenizer::default())
+                .filter(RemoveLongFilter::limit(40))
+                .filter(LowerCaser)
+                .filter(Stemmer::new(language))
+                .build(),
+        );
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let writer = Mutex::new(index.writer(WRITER_MEMORY)?);
+
+        Ok(Self {
+            index,
+            reader,
+            writer,
+            fields,
+            distance,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.reader.searcher().num_docs() == 0
+    }
+
+    /// Replaces the document with the same kind and id. Nothing is visible
+    /// to searches before [`SearchIndex::commit`].
+    pub fn upsert(&self, document: &Document) -> tantivy::Result<()> {
+        let key = key(document.kind, &document.id);
+        let writer = self.writer.lock().map_err(|_| TantivyError::Poisoned)?;
+        writer.delete_term(Term::from_field_text(self.fields.key, &key));
+        writer.add_document(doc!(
+            self.fields.key => key,
+            self.fields.kind => document.kind.as_str(),
+            self.fields.id => document.id.as_str(),
+            self.fields.title => document.title.as_str(),
+            self.fields.description => document.description.as_str(),
+            self.fields.content => document.content.as_str(),
+        ))?;
+        Ok(())
+    }
+
+    pub fn delete(&self, kind: Kind, id: &str) -> tantivy::Result<()> {
+        let writer = self.writer.lock().map_err(|_| TantivyError::Poisoned)?;
+        writer.delete_term(Term::from_field_text(self.fields.key, &key(kind, id)));
+        Ok(())
+    }
+
+    pub fn clear(&self) -> tantivy::Result<()> {
+        let writer = self.writer.lock().map_err(|_| TantivyError::Poisoned)?;
+        writer.delete_all_documents()?;
+        Ok(())
+    }
+
+    pub fn commit(&self) -> tantivy::Result<()> {
+        let mut writer = self.writer.lock().map_err(|_| TantivyError::Poisoned)?;
+        writer.commit()?;
+        self.reader.reload()
+    }
+
+    /// Drops everything written since the last commit, so a later commit
+    /// does not publish half of a failed update
+    pub fn rollback(&self) -> tantivy::Result<()> {
+        let mut writer = self.writer.lock().map_err(|_| TantivyError::Poisoned)?;
+        writer.rollback()?;
+        Ok(())
+    }
+
+    fn parser(&self, fuzzy: bool) -> QueryParser {
+        let fields = [
+            self.fields.title,
+            self.fields.description,
+            self.fields.content,
+        ];
+        let mut parser = QueryParser::for_index(&self.index, fields.to_vec());
+        parser.set_conjunction_by_default();
+        parser.set_field_boost(self.fields.title, TITLE_BOOST);
+        parser.set_field_boost(self.fields.description, DESCRIPTION_BOOST);
+        if fuzzy {
+            for field in fields {
+                parser.set_field_fuzzy(field, true, self.distance, true);
+            }
+        }
+        parser
+    }
+
+    /// Exact matches are scored by relevance. Terms that are a prefix of, or
+    /// a few typos away from, an indexed one match too, with a flat score so
+    /// they rank below exact matches.
+    pub fn search(
+        &self,
+        query: &str,
+        kinds: &[Kind],
+        limit: usize,
+        offset: usize,
+    ) -> tantivy::Result<(usize, Vec<Hit>)> {
+        let (exact, _) = self.parser(false).parse_query_lenient(query);
+        let (fuzzy, _) = self.parser(true).parse_query_lenient(query);
+
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = vec![(
+            Occur::Must,
+            Box::new(BooleanQuery::new(vec![
+                (Occur::Should, exact),
+                (Occur::Should, fuzzy),
+            ])),
+        )];
+        if !kinds.is_empty() {
+            clauses.push((
+                Occur::Must,
+                Box::new(TermSetQuery::new(kinds.iter().map(|kind| {
+                    Term::from_field_text(self.fields.kind, kind.as_str())
+                }))),
+            ));
+        }
+        let query = BooleanQuery::new(clauses);
+
+        let searcher = self.reader.searcher();
+        let (top, count) = searcher.search(
+            &query,
+            &(TopDocs::with_limit(limit).and_offset(offset), Count),
+        )?;
+
+        let mut hits = Vec::with_capacity(top.len());
+        for (score, address) in top {
+            let document: TantivyDocument = searcher.doc(address)?;
+            let text = |field| {
+                document
+                    .get_first(field)
+                    .and_then(|value| value.as_str())
+                    .unwrap_or_default()
+                    .to_owned()
+            };
+            let kind = match Kind::from_str(&text(self.fields.kind)) {
+                Ok(kind) => kind,
+                Err(_) => continue,
+            };
+            hits.push(Hit {
+                kind,
+                id: text(self.fields.id),
+                title: text(self.fields.title),
+                score,
+            });
+        }
+
+        Ok((count, hits))
+    }
+}
@@ -0,0 +1,358 @@
+use std::{str::FromStr, sync::Arc, time::Duration};
+
+use shared::{
+    configuration::SearchSettings,
+    models::index_model::{IndexedArticle, IndexedTag, IndexedUser},
+    repositories::index_repository::{IndexRepository, IndexRepositoryImpl},
+};
+use sqlx::{PgPool, Postgres, Transaction};
+use tokio::sync::Mutex;
+use tracing::{error, info, warn};
+
+use crate::index::{Document, Kind, SearchIndex};
+
+/// Documents of each kind written by a rebuild
+#[derive(Debug, Default)]
+pub struct Rebuilt {
+    pub articles: usize,
+    pub users: usize,
+    pub tags: usize,
+}
+
+/// Keeps the index in line with the database by draining the changes the
+/// database records for articles, users and tags
+pub struct Indexer {
+    pub db: PgPool,
+    pub index: Arc<SearchIndex>,
+    pub settings: SearchSettings,
+    /// Syncs and rebuilds must not interleave
+    lock: Mutex<()>,
+}
+
+impl From<IndexedArticle> for Document {
+    fn from(value: IndexedArticle) -> Self {
+        Document {
+            kind: Kind::Article,
+            id: value.id,
+            title: value.title,
+            description: value.description.unwrap_or_default(),
+            content: strip_markup(&value.content),
+        }
+    }
+}
+
+/// Article bodies are HTML, only their text is indexed so tag and attribute
+/// names do not match searches
+fn strip_markup(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+impl From<IndexedUser> for Document {
+    fn from(value: IndexedUser) -> Self {
+        Document {
+            kind: Kind::User,
+            id: value.id,
+            title: value.username,
+            description: value.bio,
+            content: String::new(),
+        }
+    }
+}
+
+impl From<IndexedTag> for Document {
+    fn from(value: IndexedTag) -> Self {
+        Document {
+            kind: Kind::Tag,
+            id: value.slug,
+            title: value.label,
+            description: String::new(),
+            content: String::new(),
+        }
+    }
+}
+
+impl Indexer {
+    pub fn new(db: PgPool, index: Arc<SearchIndex>, settings: SearchSettings) -> Self {
+        Self {
+            db,
+            index,
+            settings,
+            lock: Mutex::new(()),
+        }
+    }
+
+    pub async fn run(self: Arc<Self>) {
+        if self.index.is_empty() {
+            match self.rebuild().await {
+                Ok(rebuilt) => info!("Search index built {:?}", rebuilt),
+                Err(err) => error!("Unable to build the search index: {:?}", err),
+            }
+        }
+
+        let mut interval = tokio::time::interval(Duration::from_secs(self.settings.interval));
+        loop {
+            interval.tick().await;
+            if let Err(err) = self.sync().await {
+                error!("Unable to update the search index: {:?}", err);
+            }
+        }
+    }
+
+    /// Applies recorded changes until none are left. Changed rows are read
+    /// again rather than trusted from the change, so applying one twice is
+    /// harmless and changes are only dropped once the index is committed.
+    pub async fn sync(&self) -> Result<(), anyhow::Error> {
+        let _guard = self.lock.lock().await;
+
+        loop {
+            let applied = match self.apply_changes().await {
+                Ok(applied) => applied,
+                Err(err) => {
+                    self.rollback().await;
+                    return Err(err);
+                }
+            };
+
+            if (applied as i64) < self.settings.batch_size {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Replaces the index with everything searchable in the database
+    pub async fn rebuild(&self) -> Result<Rebuilt, anyhow::Error> {
+        let _guard = self.lock.lock().await;
+
+        let rebuilt = self.replace_all().await;
+        if rebuilt.is_err() {
+            self.rollback().await;
+        }
+        rebuilt
+    }
+
+    /// Runs index work on the blocking pool, writes wait for the writer lock
+    /// and commits for the disk
+    async fn blocking<T: Send + 'static>(
+        &self,
+        work: impl FnOnce(&SearchIndex) -> tantivy::Result<T> + Send + 'static,
+    ) -> Result<T, anyhow::Error> {
+        let index = self.index.clone();
+        Ok(tokio::task::spawn_blocking(move || work(&index)).await??)
+    }
+
+    /// Drops what a failed sync or rebuild wrote to the index
+    async fn rollback(&self) {
+        if let Err(err) = self.blocking(|index| index.rollback()).await {
+            error!("Unable to roll back the search index: {:?}", err);
+        }
+    }
+
+    /// Applies one batch of changes, returning how many there were
+    async fn apply_changes(&self) -> Result<usize, anyhow::Error> {
+        let mut transaction = self.db.begin().await?;
+        let changes =
+            IndexRepositoryImpl::find_changes(&mut transaction, self.settings.batch_size).await?;
+
+        if changes.is_empty() {
+            return Ok(0);
+        }
+
+        let mut articles = vec![];
+        let mut users = vec![];
+        let mut tags = vec![];
+
+        for change in changes.iter() {
+            let ids = match Kind::from_str(&change.kind) {
+                Ok(Kind::Article) => &mut articles,
+                Ok(Kind::User) => &mut users,
+                Ok(Kind::Tag) => &mut tags,
+                Err(err) => {
+                    warn!("Skipping search change {}: {}", change.id, err);
+                    continue;
+                }
+            };
+            if !ids.contains(&change.target_id) {
+                ids.push(change.target_id.to_owned());
+            }
+        }
+
+        let limit = self.settings.batch_size;
+        let mut documents = vec![];
+        documents.extend(
+            IndexRepositoryImpl::find_articles(
+                &mut transaction,
+                Some(articles.as_slice()),
+                None,
+                limit,
+            )
+            .await?
+            .into_iter()
+            .map(Document::from),
+        );
+        documents.extend(
+            IndexRepositoryImpl::find_users(&mut transaction, Some(users.as_slice()), None, limit)
+                .await?
+                .into_iter()
+                .map(Document::from),
+        );
+        documents.extend(
+            IndexRepositoryImpl::find_tags(&mut transaction, Some(tags.as_slice()), None, limit)
+                .await?
+                .into_iter()
+                .map(Document::from),
+        );
+
+        // Rows gone from the database are only deleted
+        self.blocking(move |index| {
+            for (kind, ids) in [
+                (Kind::Article, &articles),
+                (Kind::User, &users),
+                (Kind::Tag, &tags),
+            ] {
+                for id in ids.iter() {
+                    index.delete(kind, id)?;
+                }
+            }
+            for document in documents.iter() {
+                index.upsert(document)?;
+            }
+            index.commit()
+        })
+        .await?;
+
+        let ids: Vec<i64> = changes.iter().map(|change| change.id).collect();
+        IndexRepositoryImpl::delete_changes(&mut transaction, &ids).await?;
+        transaction.commit().await?;
+
+        info!("{} search changes applied", changes.len());
+
+        Ok(changes.len())
+    }
+
+    async fn replace_all(&self) -> Result<Rebuilt, anyhow::Error> {
+        let mut transaction = self.db.begin().await?;
+
+        // Changes recorded before the rows are read are covered by the
+        // rebuild, later ones are applied by the next sync
+        let changes = IndexRepositoryImpl::find_change_ids(&mut transaction).await?;
+
+        self.blocking(|index| index.clear()).await?;
+
+        let rebuilt = Rebuilt {
+            articles: self.rebuild_articles(&mut transaction).await?,
+            users: self.rebuild_users(&mut transaction).await?,
+            tags: self.rebuild_tags(&mut transaction).await?,
+        };
+
+        self.blocking(|index| index.commit()).await?;
+
+        IndexRepositoryImpl::delete_changes(&mut transaction, &changes).await?;
+        transaction.commit().await?;
+
+        Ok(rebuilt)
+    }
+
+    async fn upsert(&self, documents: Vec<Document>) -> Result<(), anyhow::Error> {
+        self.blocking(move |index| {
+            documents
+                .iter()
+                .try_for_each(|document| index.upsert(document))
+        })
+        .await
+    }
+
+    async fn rebuild_articles(
+        &self,
+        transaction: &mut Transaction<'_, Postgres>,
+    ) -> Result<usize, anyhow::Error> {
+        let mut count = 0;
+        let mut after = None;
+        loop {
+            let articles = IndexRepositoryImpl::find_articles(
+                transaction,
+                None,
+                after.as_deref(),
+                self.settings.batch_size,
+            )
+            .await?;
+            let done = (articles.len() as i64) < self.settings.batch_size;
+            after = articles.last().map(|article| article.id.to_owned());
+            count += articles.len();
+            self.upsert(articles.into_iter().map(Document::from).collect())
+                .await?;
+            if done {
+                return Ok(count);
+            }
+        }
+    }
+
+    async fn rebuild_users(
+        &self,
+        transaction: &mut Transaction<'_, Postgres>,
+    ) -> Result<usize, anyhow::Error> {
+        let mut count = 0;
+        let mut after = None;
+        loop {
+            let users = IndexRepositoryImpl::find_users(
+                transaction,
+                None,
+                after.as_deref(),
+                self.settings.batch_size,
+            )
+            .await?;
+            let done = (users.len() as i64) < self.settings.batch_size;
+            after = users.last().map(|user| user.id.to_owned());
+            count += users.len();
+            self.upsert(users.into_iter().map(Document::from).collect())
+                .await?;
+            if done {
+                return Ok(count);
+            }
+        }
+    }
+
+    async fn rebuild_tags(
+        &self,
+        transaction: &mut Transaction<'_, Postgres>,
+    ) -> Result<usize, anyhow::Error> {
+        let mut count = 0;
+        let mut after = None;
+        loop {
+            let tags = IndexRepositoryImpl::find_tags(
+                transaction,
+                None,
+                after.as_deref(),
+                self.settings.batch_size,
+            )
+            .await?;
+            let done = (tags.len() as i64) < self.settings.batch_size;
+            after = tags.last().map(|tag| tag.slug.to_owned());
+            count += tags.len();
+            self.upsert(tags.into_iter().map(Document::from).collect())
+                .await?;
+            if done {
+                return Ok(count);
+            }
+        }
+    }
+}
@@ -0,0 +1,4 @@
+pub mod application;
+pub mod index;
+pub mod indexer;
+pub mod service;
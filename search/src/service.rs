@@ -0,0 +1,106 @@
+use std::{fmt::Debug, sync::Arc};
+
+use shared::search_index::{
+    search_index_service_server::SearchIndexService, DocumentKind, Hit, QueryRequest,
+    QueryResponse, RebuildRequest, RebuildResponse,
+};
+use tonic::{Request, Response, Status};
+use tracing::{error, info};
+
+use crate::{
+    index::{Kind, SearchIndex},
+    indexer::Indexer,
+};
+
+#[derive(Clone)]
+pub struct SearchIndexServiceImpl {
+    pub index: Arc<SearchIndex>,
+    pub indexer: Arc<Indexer>,
+}
+
+impl From<DocumentKind> for Kind {
+    fn from(value: DocumentKind) -> Self {
+        match value {
+            DocumentKind::Article => Kind::Article,
+            DocumentKind::User => Kind::User,
+            DocumentKind::Tag => Kind::Tag,
+        }
+    }
+}
+
+impl From<Kind> for DocumentKind {
+    fn from(value: Kind) -> Self {
+        match value {
+            Kind::Article => DocumentKind::Article,
+            Kind::User => DocumentKind::User,
+            Kind::Tag => DocumentKind::Tag,
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl SearchIndexService for SearchIndexServiceImpl {
+    async fn query(
+        &self,
+        request: Request<QueryRequest>,
+    ) -> Result<Response<QueryResponse>, Status> {
+        let input = request.get_ref();
+
+        info!("Query Request {:?}", input);
+
+        if input.limit <= 0 || input.offset < 0 {
+            return Err(Status::invalid_argument("Invalid data"));
+        }
+
+        let kinds = input
+            .kinds
+            .iter()
+            .map(|kind| DocumentKind::try_from(*kind).map(Kind::from))
+            .collect::<Result<Vec<Kind>, _>>()
+            .map_err(|_| Status::invalid_argument("Invalid data"))?;
+
+        let index = self.index.clone();
+        let query = input.query.to_owned();
+        let (limit, offset) = (input.limit as usize, input.offset as usize);
+
+        // Searching reads segments from disk
+        let (total, hits) =
+            tokio::task::spawn_blocking(move || index.search(&query, &kinds, limit, offset))
+                .await
+                .map_err(internal)?
+                .map_err(internal)?;
+
+        Ok(Response::new(QueryResponse {
+            hits: hits
+                .into_iter()
+                .map(|hit| Hit {
+                    kind: DocumentKind::from(hit.kind) as i32,
+                    id: hit.id,
+                    title: hit.title,
+                    score: hit.score,
+                })
+                .collect(),
+            total: total as i64,
+        }))
+    }
+
+    async fn rebuild(
+        &self,
+        _request: Request<RebuildRequest>,
+    ) -> Result<Response<RebuildResponse>, Status> {
+        info!("Rebuild Request");
+
+        let rebuilt = self.indexer.rebuild().await.map_err(internal)?;
+
+        Ok(Response::new(RebuildResponse {
+            articles: rebuilt.articles as i64,
+            users: rebuilt.users as i64,
+            tags: rebuilt.tags as i64,
+        }))
+    }
+}
+
+fn internal(err: impl Debug) -> Status {
+    error!("{:?}", err);
+    Status::internal("Something went wrong")
+}
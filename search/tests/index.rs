@@ -0,0 +1,126 @@
+use search::index::{Document, Kind, SearchIndex};
+use shared::models::index_model::IndexedArticle;
+use tantivy::tokenizer::Language;
+
+fn document(kind: Kind, id: &str, title: &str, description: &str, content: &str) -> Document {
+    Document {
+        kind,
+        id: id.to_owned(),
+        title: title.to_owned(),
+        description: description.to_owned(),
+        content: content.to_owned(),
+    }
+}
+
+fn index() -> SearchIndex {
+    let index = SearchIndex::in_memory(Language::English, 1).unwrap();
+    index
+        .upsert(&document(
+            Kind::Article,
+            "a1",
+            "Programming in Rust",
+            "Ownership explained",
+            "Borrowing rules keep running programs safe",
+        ))
+        .unwrap();
+    index
+        .upsert(&document(
+            Kind::Article,
+            "a2",
+            "Gardening",
+            "",
+            "Notes on programming a watering robot",
+        ))
+        .unwrap();
+    index
+        .upsert(&document(
+            Kind::User,
+            "u1",
+            "rustacean",
+            "Writes about Rust",
+            "",
+        ))
+        .unwrap();
+    index
+        .upsert(&document(Kind::Tag, "rust", "Rust", "", ""))
+        .unwrap();
+    index.commit().unwrap();
+    index
+}
+
+fn ids(index: &SearchIndex, query: &str, kinds: &[Kind]) -> Vec<String> {
+    let (_, hits) = index.search(query, kinds, 10, 0).unwrap();
+    hits.into_iter().map(|hit| hit.id).collect()
+}
+
+#[test]
+fn ranks_title_matches_first() {
+    let index = index();
+
+    assert_eq!(ids(&index, "programming", &[]), vec!["a1", "a2"]);
+}
+
+#[test]
+fn tolerates_typos_prefixes_and_word_forms() {
+    let index = index();
+
+    assert!(ids(&index, "progarmming", &[Kind::Article]).contains(&"a1".to_owned()));
+    assert!(ids(&index, "garden", &[]).contains(&"a2".to_owned()));
+    assert!(ids(&index, "ownershi", &[]).contains(&"a1".to_owned()));
+    assert!(ids(&index, "runs", &[]).contains(&"a1".to_owned()));
+    assert!(ids(&index, "zebra", &[]).is_empty());
+}
+
+#[test]
+fn filters_by_kind_and_replaces_documents() {
+    let index = index();
+
+    assert_eq!(ids(&index, "rust", &[Kind::Tag]), vec!["rust"]);
+    assert_eq!(ids(&index, "rust", &[Kind::User]), vec!["u1"]);
+
+    index
+        .upsert(&document(Kind::Article, "a2", "Cooking", "", ""))
+        .unwrap();
+    index.delete(Kind::User, "u1").unwrap();
+    index.commit().unwrap();
+
+    assert_eq!(ids(&index, "programming", &[]), vec!["a1"]);
+    assert!(ids(&index, "rust", &[Kind::User]).is_empty());
+
+    let (count, _) = index.search("rust", &[], 1, 0).unwrap();
+    assert_eq!(count, 2);
+}
+
+#[test]
+fn rollback_discards_uncommitted_writes() {
+    let index = index();
+
+    index.clear().unwrap();
+    index
+        .upsert(&document(Kind::Article, "a3", "Half written", "", ""))
+        .unwrap();
+    index.rollback().unwrap();
+    index.commit().unwrap();
+
+    assert_eq!(ids(&index, "programming", &[]), vec!["a1", "a2"]);
+    assert!(ids(&index, "written", &[]).is_empty());
+}
+
+#[test]
+fn indexes_the_text_of_article_markup() {
+    let index = SearchIndex::in_memory(Language::English, 0).unwrap();
+    index
+        .upsert(&Document::from(IndexedArticle {
+            id: "a1".to_owned(),
+            title: "Dinner".to_owned(),
+            description: None,
+            content: r#"<p class="lead">Fish &amp; chips</p>"#.to_owned(),
+        }))
+        .unwrap();
+    index.commit().unwrap();
+
+    assert_eq!(ids(&index, "chips", &[]), vec!["a1"]);
+    assert!(ids(&index, "lead", &[]).is_empty());
+    assert!(ids(&index, "class", &[]).is_empty());
+    assert!(ids(&index, "amp", &[]).is_empty());
+}
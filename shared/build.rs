@@ -28,6 +28,10 @@ fn main() -> Result<(), Box<dyn Error>> {
     tonic_build::compile_protos("./protos/comment.proto")?;
     tonic_build::compile_protos("./protos/tag.proto")?;
     tonic_build::compile_protos("./protos/storage.proto")?;
+    tonic_build::compile_protos("./protos/webhook.proto")?;
+    tonic_build::compile_protos("./protos/newsletter.proto")?;
+    tonic_build::compile_protos("./protos/search.proto")?;
+    tonic_build::compile_protos("./protos/search_index.proto")?;
 
     Ok(())
 }
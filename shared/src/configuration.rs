@@ -83,6 +83,7 @@ pub struct Settings {
     pub auth_server: ApplicationSettings,
     pub storage_server: ApplicationSettings,
     pub notification_server: ApplicationSettings,
+    pub search_server: ApplicationSettings,
     pub database: DatabaseSettings,
     pub storage: StorageSettings,
     pub cookies: CookiesSettings,
@@ -90,6 +91,13 @@ pub struct Settings {
     pub query: QuerySettings,
     pub message_broker: MessageBrokerSettings,
     pub mail: MailSettings,
+    pub digest: DigestSettings,
+    pub webhook: WebhookSettings,
+    pub newsletter: NewsletterSettings,
+    pub stream: StreamSettings,
+    pub reply: ReplySettings,
+    pub search: SearchSettings,
+    pub history: HistorySettings,
     // pub redis_uri: Secret<String>,
 }
 
@@ -122,6 +130,68 @@ pub struct StorageSettings {
     pub access_key: Secret<String>,
     pub secret_key: Secret<String>,
     pub bucket_name: String,
+    /// Encoding quality of processed images, 1 to 100
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub quality: u8,
+    pub renditions: Vec<RenditionSettings>,
+    /// Images are decoded in memory, larger ones are refused
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub max_image_size: usize,
+    /// Seconds presigned URLs stay valid
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub presign_expiry: u32,
+    pub collector: CollectorSettings,
+    pub limits: UploadLimitSettings,
+    pub backend: BlobStoreSettings,
+}
+
+/// Where asset bytes are kept
+#[derive(Debug, serde::Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum BlobStoreSettings {
+    /// The MinIO server and bucket configured above
+    Minio,
+    /// Files below `root`
+    Filesystem { root: String },
+    /// Lost on restart, for tests and local runs
+    Memory,
+}
+
+/// What users may upload, and how much of it
+#[derive(Debug, serde::Deserialize, Clone)]
+pub struct UploadLimitSettings {
+    /// Largest single upload, in bytes
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub max_upload_size: u64,
+    /// Total size of the assets a user may own, in bytes
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub quota: u64,
+    /// Content types detected from the file itself, `image/*` allows every
+    /// image. Only PNG, JPEG and WebP are re-encoded, other images are
+    /// stored as uploaded, metadata included.
+    pub allowed_types: Vec<String>,
+}
+
+/// Removal of assets nothing links to anymore
+#[derive(Debug, serde::Deserialize, Clone)]
+pub struct CollectorSettings {
+    /// Seconds between runs
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub interval: u64,
+    /// Hours an asset stays unreferenced before it is marked, and again
+    /// before a marked one is deleted
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub grace_period: i32,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub batch_size: i64,
+}
+
+/// Resized copy generated for every uploaded image, never upscaled
+#[derive(Debug, serde::Deserialize, Clone)]
+pub struct RenditionSettings {
+    pub name: String,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub width: u32,
 }
 
 impl DatabaseSettings {
@@ -166,6 +236,9 @@ pub struct CookieSettings {
 pub struct AuthSettings {
     pub secret: Secret<String>,
     pub hmac_secret: Secret<String>,
+    /// Lifetime of one-click unsubscribe links, in days
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub unsubscribe_token_duration: i64,
     pub google: OAuthClientSettings,
     pub github: OAuthClientSettings,
 }
@@ -219,3 +292,110 @@ pub struct MailSettings {
     pub smtp_user: String,
     pub smtp_password: Secret<String>,
 }
+
+/// Inbound mail listener for replies to notification emails. The MTA for
+/// `domain` delivers to it over LMTP, plain SMTP works for local testing.
+#[derive(Debug, serde::Deserialize, Clone)]
+pub struct ReplySettings {
+    pub host: String,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub port: u16,
+    pub domain: String,
+    /// Largest accepted message, in bytes
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub max_size: usize,
+}
+
+/// Local index of the search service, kept up to date from the search
+/// changes table
+#[derive(Debug, serde::Deserialize, Clone)]
+pub struct SearchSettings {
+    /// Directory the index lives in, rebuilt from the database when empty
+    pub index_path: String,
+    /// Stemming language of the text fields
+    pub language: String,
+    /// Edits a query term may be away from an indexed one, 0 to 2
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub distance: u8,
+    /// Seconds between polls for changes
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub interval: u64,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub batch_size: i64,
+}
+
+/// Thinning of article versions. Every version is kept for `keep_all`
+/// minutes, then the newest of each hour until `keep_hourly` hours, then
+/// the newest of each day. The newest version of an article, the one it was
+/// published with and snapshots are always kept.
+#[derive(Debug, serde::Deserialize, Clone)]
+pub struct HistorySettings {
+    /// Seconds between runs
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub interval: u64,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub keep_all: i32,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub keep_hourly: i32,
+    /// Articles compacted per transaction
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub batch_size: i64,
+}
+
+#[derive(Debug, serde::Deserialize, Clone)]
+pub struct DigestSettings {
+    /// Minutes between scans for due digests
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub interval: u64,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub batch_size: i64,
+    /// Articles per digest section
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub limit: i64,
+}
+
+#[derive(Debug, serde::Deserialize, Clone)]
+pub struct WebhookSettings {
+    /// Seconds between scans for due deliveries
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub interval: u64,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub batch_size: i64,
+    /// Request timeout, in seconds
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub timeout: u64,
+    /// Attempts before a delivery is marked as failed
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub max_attempts: i32,
+    /// Delay before the first retry in seconds, doubled on every attempt
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub backoff: i64,
+}
+
+#[derive(Debug, serde::Deserialize, Clone)]
+pub struct NewsletterSettings {
+    /// Seconds between scans for due sends
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub interval: u64,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub batch_size: i64,
+    /// Emails sent per second
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub rate: u32,
+    /// Attempts before a send is marked as failed
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub max_attempts: i32,
+    /// Delay before the first retry in seconds, doubled on every attempt
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub backoff: i64,
+}
+
+#[derive(Debug, serde::Deserialize, Clone)]
+pub struct StreamSettings {
+    /// Recent events kept to resume streams from a last event id
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub history: usize,
+    /// Seconds between keep-alive comments on idle streams
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub keep_alive: u64,
+}
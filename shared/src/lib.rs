@@ -29,3 +29,15 @@ pub mod tag {
 pub mod storage_proto {
     tonic::include_proto!("storage");
 }
+pub mod webhook {
+    tonic::include_proto!("webhook");
+}
+pub mod newsletter {
+    tonic::include_proto!("newsletter");
+}
+pub mod search {
+    tonic::include_proto!("search");
+}
+pub mod search_index {
+    tonic::include_proto!("search_index");
+}
@@ -1,4 +1,6 @@
-use crate::models::{list_model::List, series_model::Series, tag_model::Tag, user_model::FullUser};
+use crate::models::{
+    enums::DiffOp, list_model::List, series_model::Series, tag_model::Tag, user_model::FullUser,
+};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -34,6 +36,12 @@ pub struct FullArticle {
     pub order: Option<f32>,
 }
 
+#[derive(sqlx::FromRow, Serialize, Deserialize, Debug)]
+pub struct Headline {
+    pub article_id: String,
+    pub headline: String,
+}
+
 #[derive(sqlx::FromRow, Serialize, Deserialize, Debug)]
 pub struct ArticleVersion {
     pub id: String,
@@ -43,6 +51,50 @@ pub struct ArticleVersion {
     pub created_at: DateTime<Utc>,
 }
 
+/// A version labelled by an author, kept however old it gets
+#[derive(sqlx::FromRow, Serialize, Deserialize, Debug)]
+pub struct Snapshot {
+    pub id: String,
+    pub version_id: String,
+    pub article_id: String,
+    pub user_id: Option<String>,
+    pub name: String,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateSnapshot {
+    pub version_id: String,
+    pub article_id: String,
+    pub user_id: String,
+    pub name: String,
+    pub note: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DiffSegment {
+    pub op: DiffOp,
+    pub text: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DiffBlock {
+    pub op: DiffOp,
+    pub segments: Vec<DiffSegment>,
+}
+
+/// Changes from the `from` version to the `to` one, or to the current
+/// content when there is none
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ArticleDiff {
+    pub from: String,
+    pub to: Option<String>,
+    pub blocks: Vec<DiffBlock>,
+    pub insertions: i64,
+    pub deletions: i64,
+}
+
 #[derive(sqlx::FromRow, Serialize, Deserialize, Debug)]
 pub struct Device {
     pub id: String,
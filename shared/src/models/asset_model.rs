@@ -1,10 +1,134 @@
-pub struct List {
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::Postgres;
+
+/// An upload as its owner sees it, with the metadata of the object behind it
+#[derive(Clone, sqlx::FromRow, Serialize, Deserialize, Debug)]
+pub struct Asset {
     pub id: String,
     pub user_id: String,
-    pub label: String,
-    pub image: Option<String>,
-    pub visibility: Visibility,
-    pub article_count: i32,
+    pub asset_name: String,
+    pub file_name: String,
+    pub content_type: String,
+    pub size: i64,
+    /// Hex SHA-256 of the uploaded bytes
+    pub checksum: String,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    /// Only set for images
+    pub blurhash: Option<String>,
+    /// `#rrggbb`, only set for images
+    pub dominant_color: Option<String>,
     pub created_at: DateTime<Utc>,
-    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// An asset nothing links to, `orphaned_at` is unset until the collector
+/// marks it
+#[derive(Clone, sqlx::FromRow, Serialize, Deserialize, Debug)]
+pub struct Orphan {
+    pub id: String,
+    pub user_id: String,
+    pub asset_name: String,
+    pub file_name: String,
+    pub size: i64,
+    pub created_at: DateTime<Utc>,
+    pub orphaned_at: Option<DateTime<Utc>>,
+}
+
+/// What clients show while an image asset loads
+#[derive(Clone, sqlx::FromRow, Serialize, Deserialize, Debug)]
+pub struct Placeholder {
+    pub name: String,
+    pub blurhash: String,
+    pub dominant_color: String,
+}
+
+/// Placeholder of an `image` field, filled in by the resource service when
+/// the image is a stored asset. Queries select `NULL` in its place.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ImagePlaceholder {
+    pub blurhash: String,
+    /// `#rrggbb`
+    pub dominant_color: String,
+}
+
+impl sqlx::Type<Postgres> for ImagePlaceholder {
+    fn type_info() -> sqlx::postgres::PgTypeInfo {
+        sqlx::postgres::PgTypeInfo::with_name("record")
+    }
+}
+
+impl<'r> sqlx::Decode<'r, Postgres> for ImagePlaceholder {
+    fn decode(
+        value: sqlx::postgres::PgValueRef<'r>,
+    ) -> Result<Self, Box<dyn std::error::Error + 'static + Send + Sync>> {
+        let mut decoder = sqlx::postgres::types::PgRecordDecoder::new(value)?;
+        let blurhash = decoder.try_decode()?;
+        let dominant_color = decoder.try_decode()?;
+        Ok(Self {
+            blurhash,
+            dominant_color,
+        })
+    }
+}
+
+/// An object in the bucket, shared by every asset with the same content
+#[derive(Clone, sqlx::FromRow, Serialize, Deserialize, Debug)]
+pub struct Blob {
+    pub name: String,
+    pub checksum: String,
+    pub content_type: String,
+    pub size: i64,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    /// Only set for images
+    pub blurhash: Option<String>,
+    /// `#rrggbb`, only set for images
+    pub dominant_color: Option<String>,
+    pub ref_count: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+pub struct CreateBlob {
+    pub name: String,
+    pub checksum: String,
+    pub content_type: String,
+    pub size: i64,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    /// Only set for images
+    pub blurhash: Option<String>,
+    /// `#rrggbb`, only set for images
+    pub dominant_color: Option<String>,
+    /// Checksum of the upload the stored bytes were made from, only set
+    /// for images
+    pub source_checksum: Option<String>,
+}
+
+pub struct CreateAsset {
+    pub user_id: String,
+    pub asset_name: String,
+    pub file_name: String,
+}
+
+/// A direct upload that was not completed yet. `content_type` and `size` are
+/// what the client declared, the object is checked against them.
+#[derive(Clone, sqlx::FromRow, Serialize, Deserialize, Debug)]
+pub struct PendingUpload {
+    pub id: String,
+    pub user_id: String,
+    pub file_name: String,
+    pub content_type: String,
+    pub size: i64,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+pub struct CreatePendingUpload {
+    pub user_id: String,
+    pub file_name: String,
+    pub content_type: String,
+    pub size: i64,
+    /// Seconds the presigned URL stays valid
+    pub expiry: i32,
 }
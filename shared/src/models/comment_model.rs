@@ -3,13 +3,15 @@ use serde::{Deserialize, Serialize};
 
 use super::enums::CommentableType;
 
-#[derive(sqlx::FromRow, Serialize, Deserialize, Debug)]
+#[derive(Clone, sqlx::FromRow, Serialize, Deserialize, Debug)]
 pub struct Comment {
     pub id: String,
     pub content: String,
     pub commenter_id: String,
     pub target_id: String,
     pub r#type: CommentableType,
+    /// Comment this one replies to, `None` for top level comments
+    pub parent_id: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: Option<DateTime<Utc>>,
 }
@@ -24,6 +26,7 @@ pub struct FullComment {
     pub followed: bool,
     pub target_id: String,
     pub r#type: CommentableType,
+    pub parent_id: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: Option<DateTime<Utc>>,
 }
@@ -34,6 +37,7 @@ pub struct CreateComment {
     pub target_id: String,
     pub content: String,
     pub r#type: CommentableType,
+    pub parent_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
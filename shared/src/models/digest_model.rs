@@ -0,0 +1,30 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, sqlx::FromRow, Serialize, Deserialize, Debug)]
+pub struct DigestRecipient {
+    pub id: String,
+    pub username: String,
+    pub email: String,
+    pub locale: String,
+}
+
+#[derive(Clone, sqlx::FromRow, Serialize, Deserialize, Debug)]
+pub struct DigestArticle {
+    pub id: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub authors: Vec<String>,
+    pub like_count: i32,
+    pub comment_count: i32,
+    pub published_at: DateTime<Utc>,
+}
+
+/// New likes and comments on one of the recipient's articles within the period
+#[derive(Clone, sqlx::FromRow, Serialize, Deserialize, Debug)]
+pub struct DigestActivity {
+    pub id: String,
+    pub title: String,
+    pub likes: i64,
+    pub comments: i64,
+}
@@ -121,3 +121,234 @@ impl FromStr for CommentableType {
         }
     }
 }
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize, sqlx::Type)]
+#[sqlx(type_name = "notificationevent", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum NotificationEvent {
+    Follow,
+    Comment,
+    Like,
+    CoauthorInvite,
+    Digest,
+}
+
+impl std::fmt::Display for NotificationEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            NotificationEvent::Follow => write!(f, "FOLLOW"),
+            NotificationEvent::Comment => write!(f, "COMMENT"),
+            NotificationEvent::Like => write!(f, "LIKE"),
+            NotificationEvent::CoauthorInvite => write!(f, "COAUTHOR_INVITE"),
+            NotificationEvent::Digest => write!(f, "DIGEST"),
+        }
+    }
+}
+
+impl FromStr for NotificationEvent {
+    type Err = Box<dyn Error>;
+    fn from_str(input: &str) -> Result<NotificationEvent, Self::Err> {
+        match input.to_uppercase().trim() {
+            "FOLLOW" => Ok(NotificationEvent::Follow),
+            "COMMENT" => Ok(NotificationEvent::Comment),
+            "LIKE" => Ok(NotificationEvent::Like),
+            "COAUTHOR_INVITE" => Ok(NotificationEvent::CoauthorInvite),
+            "DIGEST" => Ok(NotificationEvent::Digest),
+            _ => Err(format!("Can not parse {} into NotificationEvent Enum", input).into()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize, sqlx::Type)]
+#[sqlx(type_name = "digestfrequency", rename_all = "UPPERCASE")]
+pub enum DigestFrequency {
+    Never,
+    Daily,
+    Weekly,
+}
+
+impl std::fmt::Display for DigestFrequency {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DigestFrequency::Never => write!(f, "NEVER"),
+            DigestFrequency::Daily => write!(f, "DAILY"),
+            DigestFrequency::Weekly => write!(f, "WEEKLY"),
+        }
+    }
+}
+
+impl FromStr for DigestFrequency {
+    type Err = Box<dyn Error>;
+    fn from_str(input: &str) -> Result<DigestFrequency, Self::Err> {
+        match input.to_uppercase().trim() {
+            "NEVER" => Ok(DigestFrequency::Never),
+            "DAILY" => Ok(DigestFrequency::Daily),
+            "WEEKLY" => Ok(DigestFrequency::Weekly),
+            _ => Err(format!("Can not parse {} into DigestFrequency Enum", input).into()),
+        }
+    }
+}
+
+/// Serialized with the dotted names webhook receivers see in payloads
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize, sqlx::Type)]
+#[sqlx(type_name = "webhookevent", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum WebhookEvent {
+    #[serde(rename = "article.published")]
+    ArticlePublished,
+    #[serde(rename = "comment.created")]
+    CommentCreated,
+    #[serde(rename = "user.followed")]
+    UserFollowed,
+}
+
+impl std::fmt::Display for WebhookEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            WebhookEvent::ArticlePublished => write!(f, "article.published"),
+            WebhookEvent::CommentCreated => write!(f, "comment.created"),
+            WebhookEvent::UserFollowed => write!(f, "user.followed"),
+        }
+    }
+}
+
+impl FromStr for WebhookEvent {
+    type Err = Box<dyn Error>;
+    fn from_str(input: &str) -> Result<WebhookEvent, Self::Err> {
+        match input.to_lowercase().trim() {
+            "article.published" => Ok(WebhookEvent::ArticlePublished),
+            "comment.created" => Ok(WebhookEvent::CommentCreated),
+            "user.followed" => Ok(WebhookEvent::UserFollowed),
+            _ => Err(format!("Can not parse {} into WebhookEvent Enum", input).into()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize, sqlx::Type)]
+#[sqlx(type_name = "webhookdeliverystatus", rename_all = "UPPERCASE")]
+pub enum WebhookDeliveryStatus {
+    Pending,
+    Succeeded,
+    Failed,
+}
+
+impl std::fmt::Display for WebhookDeliveryStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            WebhookDeliveryStatus::Pending => write!(f, "PENDING"),
+            WebhookDeliveryStatus::Succeeded => write!(f, "SUCCEEDED"),
+            WebhookDeliveryStatus::Failed => write!(f, "FAILED"),
+        }
+    }
+}
+
+impl FromStr for WebhookDeliveryStatus {
+    type Err = Box<dyn Error>;
+    fn from_str(input: &str) -> Result<WebhookDeliveryStatus, Self::Err> {
+        match input.to_uppercase().trim() {
+            "PENDING" => Ok(WebhookDeliveryStatus::Pending),
+            "SUCCEEDED" => Ok(WebhookDeliveryStatus::Succeeded),
+            "FAILED" => Ok(WebhookDeliveryStatus::Failed),
+            _ => Err(format!("Can not parse {} into WebhookDeliveryStatus Enum", input).into()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize, sqlx::Type)]
+#[sqlx(type_name = "subscriberstatus", rename_all = "UPPERCASE")]
+pub enum SubscriberStatus {
+    Pending,
+    Confirmed,
+    Unsubscribed,
+    Bounced,
+}
+
+impl std::fmt::Display for SubscriberStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SubscriberStatus::Pending => write!(f, "PENDING"),
+            SubscriberStatus::Confirmed => write!(f, "CONFIRMED"),
+            SubscriberStatus::Unsubscribed => write!(f, "UNSUBSCRIBED"),
+            SubscriberStatus::Bounced => write!(f, "BOUNCED"),
+        }
+    }
+}
+
+impl FromStr for SubscriberStatus {
+    type Err = Box<dyn Error>;
+    fn from_str(input: &str) -> Result<SubscriberStatus, Self::Err> {
+        match input.to_uppercase().trim() {
+            "PENDING" => Ok(SubscriberStatus::Pending),
+            "CONFIRMED" => Ok(SubscriberStatus::Confirmed),
+            "UNSUBSCRIBED" => Ok(SubscriberStatus::Unsubscribed),
+            "BOUNCED" => Ok(SubscriberStatus::Bounced),
+            _ => Err(format!("Can not parse {} into SubscriberStatus Enum", input).into()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize, sqlx::Type)]
+#[sqlx(type_name = "newslettersendstatus", rename_all = "UPPERCASE")]
+pub enum NewsletterSendStatus {
+    Pending,
+    Sent,
+    Bounced,
+    Failed,
+}
+
+impl std::fmt::Display for NewsletterSendStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            NewsletterSendStatus::Pending => write!(f, "PENDING"),
+            NewsletterSendStatus::Sent => write!(f, "SENT"),
+            NewsletterSendStatus::Bounced => write!(f, "BOUNCED"),
+            NewsletterSendStatus::Failed => write!(f, "FAILED"),
+        }
+    }
+}
+
+impl FromStr for NewsletterSendStatus {
+    type Err = Box<dyn Error>;
+    fn from_str(input: &str) -> Result<NewsletterSendStatus, Self::Err> {
+        match input.to_uppercase().trim() {
+            "PENDING" => Ok(NewsletterSendStatus::Pending),
+            "SENT" => Ok(NewsletterSendStatus::Sent),
+            "BOUNCED" => Ok(NewsletterSendStatus::Bounced),
+            "FAILED" => Ok(NewsletterSendStatus::Failed),
+            _ => Err(format!("Can not parse {} into NewsletterSendStatus Enum", input).into()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchSort {
+    #[default]
+    Relevance,
+    Newest,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchType {
+    Articles,
+    Users,
+    Tags,
+    Lists,
+    Series,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SuggestType {
+    User,
+    Tag,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffOp {
+    Equal,
+    Insert,
+    Delete,
+    /// Blocks only, compared word by word
+    Replace,
+}
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+/// A row of the search changes table, `kind` is `article`, `user` or `tag`
+#[derive(sqlx::FromRow, Serialize, Deserialize, Debug)]
+pub struct SearchChange {
+    pub id: i64,
+    pub kind: String,
+    pub target_id: String,
+}
+
+#[derive(sqlx::FromRow, Serialize, Deserialize, Debug)]
+pub struct IndexedArticle {
+    pub id: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub content: String,
+}
+
+#[derive(sqlx::FromRow, Serialize, Deserialize, Debug)]
+pub struct IndexedUser {
+    pub id: String,
+    pub username: String,
+    pub bio: String,
+}
+
+#[derive(sqlx::FromRow, Serialize, Deserialize, Debug)]
+pub struct IndexedTag {
+    pub slug: String,
+    pub label: String,
+}
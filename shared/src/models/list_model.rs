@@ -1,4 +1,4 @@
-use super::{enums::Visibility, user_model::FullUser};
+use super::{asset_model::ImagePlaceholder, enums::Visibility, user_model::FullUser};
 use chrono::prelude::*;
 use serde::{Deserialize, Serialize};
 use sqlx::Postgres;
@@ -9,6 +9,8 @@ pub struct List {
     pub user_id: String,
     pub label: String,
     pub image: Option<String>,
+    #[sqlx(default)]
+    pub image_placeholder: Option<ImagePlaceholder>,
     pub visibility: Visibility,
     pub article_count: i32,
     pub created_at: DateTime<Utc>,
@@ -39,6 +41,7 @@ impl<'r> sqlx::Decode<'r, Postgres> for List {
             user_id,
             label,
             image,
+            image_placeholder: None,
             visibility,
             article_count,
             created_at,
@@ -54,6 +57,8 @@ pub struct FullList {
     pub user: FullUser,
     pub label: String,
     pub image: Option<String>,
+    #[sqlx(default)]
+    pub image_placeholder: Option<ImagePlaceholder>,
     pub visibility: Visibility,
     pub article_count: i32,
     pub created_at: DateTime<Utc>,
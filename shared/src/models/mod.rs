@@ -1,9 +1,15 @@
 pub mod account_model;
 pub mod article_model;
-// pub mod asset_model;
+pub mod asset_model;
 pub mod comment_model;
+pub mod digest_model;
 pub mod enums;
+pub mod index_model;
 pub mod list_model;
+pub mod newsletter_model;
+pub mod notification_model;
+pub mod search_model;
 pub mod series_model;
 pub mod tag_model;
 pub mod user_model;
+pub mod webhook_model;
@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::enums::SubscriberStatus;
+
+#[derive(Clone, sqlx::FromRow, Serialize, Deserialize, Debug)]
+pub struct NewsletterSubscriber {
+    pub id: String,
+    pub author_id: String,
+    pub email: String,
+    pub locale: String,
+    pub status: SubscriberStatus,
+    /// Never sent to authors, only in emails to the subscriber
+    #[serde(skip)]
+    pub token: String,
+    pub created_at: DateTime<Utc>,
+    pub confirmed_at: Option<DateTime<Utc>>,
+}
+
+/// Sent issue of an author's newsletter with its delivery counts
+#[derive(Clone, sqlx::FromRow, Serialize, Deserialize, Debug)]
+pub struct NewsletterIssue {
+    pub id: String,
+    pub article_id: String,
+    pub title: String,
+    pub recipients: i64,
+    pub sent: i64,
+    pub opened: i64,
+    pub bounced: i64,
+    pub failed: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A send claimed by the dispatcher, with the article to render
+#[derive(Clone, sqlx::FromRow, Debug)]
+pub struct PendingNewsletterSend {
+    pub id: String,
+    pub subscriber_id: String,
+    pub attempts: i32,
+    pub email: String,
+    pub locale: String,
+    pub token: String,
+    pub article_id: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub content: String,
+    pub author: String,
+}
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+use super::enums::{DigestFrequency, NotificationEvent};
+
+#[derive(Clone, sqlx::FromRow, Serialize, Deserialize, Debug)]
+pub struct NotificationSetting {
+    pub event: NotificationEvent,
+    pub in_app: bool,
+    pub email: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct NotificationSettings {
+    pub settings: Vec<NotificationSetting>,
+    pub digest_frequency: DigestFrequency,
+}
+
+pub struct UpdateNotificationSetting {
+    pub user_id: String,
+    pub event: NotificationEvent,
+    pub in_app: Option<bool>,
+    pub email: Option<bool>,
+}
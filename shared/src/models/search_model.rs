@@ -0,0 +1,39 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SearchFilter {
+    pub query: String,
+    pub author: Option<String>,
+    pub tag: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub published_only: bool,
+}
+
+/// Matches per group
+#[derive(sqlx::FromRow, Serialize, Deserialize, Debug)]
+pub struct SearchCounts {
+    pub articles: i64,
+    pub users: i64,
+    pub tags: i64,
+    pub lists: i64,
+    pub series: i64,
+}
+
+#[derive(sqlx::FromRow, Serialize, Deserialize, Debug)]
+pub struct TagFacet {
+    pub slug: String,
+    pub label: String,
+    pub count: i64,
+}
+
+/// A user or tag completing what is being typed
+#[derive(sqlx::FromRow, Serialize, Deserialize, Debug)]
+pub struct Suggestion {
+    pub id: String,
+    pub label: String,
+    pub image: Option<String>,
+    pub score: f32,
+}
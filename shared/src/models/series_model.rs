@@ -2,12 +2,16 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{prelude::FromRow, Postgres};
 
+use super::asset_model::ImagePlaceholder;
+
 #[derive(FromRow, Serialize, Deserialize, Debug)]
 pub struct Series {
     pub id: String,
     pub user_id: String,
     pub label: String,
     pub image: Option<String>,
+    #[sqlx(default)]
+    pub image_placeholder: Option<ImagePlaceholder>,
     pub article_count: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: Option<DateTime<Utc>>,
@@ -36,6 +40,7 @@ impl<'r> sqlx::Decode<'r, Postgres> for Series {
             user_id,
             label,
             image,
+            image_placeholder: None,
             article_count,
             created_at,
             updated_at,
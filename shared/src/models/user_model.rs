@@ -1,4 +1,4 @@
-use super::enums::Role;
+use super::{asset_model::ImagePlaceholder, enums::Role};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::Postgres;
@@ -10,6 +10,8 @@ pub struct User {
     pub email: String,
     pub email_verified: Option<DateTime<Utc>>,
     pub image: Option<String>,
+    #[sqlx(default)]
+    pub image_placeholder: Option<ImagePlaceholder>,
     pub role: Role,
     pub bio: String,
     pub urls: Vec<String>,
@@ -18,6 +20,7 @@ pub struct User {
     pub created_at: DateTime<Utc>,
     pub approved_at: Option<DateTime<Utc>>,
     pub deleted_at: Option<DateTime<Utc>>,
+    pub locale: String,
 }
 
 impl sqlx::Type<Postgres> for User {
@@ -44,12 +47,14 @@ impl<'r> sqlx::Decode<'r, Postgres> for User {
         let created_at = decoder.try_decode()?;
         let approved_at = decoder.try_decode()?;
         let deleted_at = decoder.try_decode()?;
+        let locale = decoder.try_decode()?;
         Ok(Self {
             id,
             username,
             email,
             email_verified,
             image,
+            image_placeholder: None,
             role,
             bio,
             urls,
@@ -58,6 +63,7 @@ impl<'r> sqlx::Decode<'r, Postgres> for User {
             created_at,
             approved_at,
             deleted_at,
+            locale,
         })
     }
 }
@@ -69,6 +75,8 @@ pub struct FullUser {
     pub email: String,
     pub email_verified: Option<DateTime<Utc>>,
     pub image: Option<String>,
+    #[sqlx(default)]
+    pub image_placeholder: Option<ImagePlaceholder>,
     // pub role: Role,
     pub bio: String,
     pub urls: Vec<String>,
@@ -111,6 +119,7 @@ impl<'r> sqlx::Decode<'r, Postgres> for FullUser {
             email,
             email_verified,
             image,
+            image_placeholder: None,
             // role,
             bio,
             urls,
@@ -128,6 +137,7 @@ pub struct CreateUser {
     pub username: String,
     pub email: String,
     pub image: Option<String>,
+    pub locale: Option<String>,
 }
 
 pub struct UpdateUser {
@@ -136,6 +146,7 @@ pub struct UpdateUser {
     pub bio: Option<String>,
     pub image: Option<String>,
     pub urls: Vec<String>,
+    pub locale: Option<String>,
 }
 
 #[derive(Clone, sqlx::FromRow, Serialize, Deserialize, Debug)]
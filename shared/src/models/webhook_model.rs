@@ -0,0 +1,86 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    enums::{WebhookDeliveryStatus, WebhookEvent},
+    user_model::FullUser,
+};
+
+#[derive(Clone, sqlx::FromRow, Serialize, Deserialize, Debug)]
+pub struct Webhook {
+    pub id: String,
+    pub user_id: String,
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    pub global: bool,
+    pub active: bool,
+    /// Only returned when the webhook is created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, sqlx::FromRow, Serialize, Deserialize, Debug)]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub webhook_id: String,
+    pub event: WebhookEvent,
+    pub payload: serde_json::Value,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub response_status: Option<i32>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+/// A delivery claimed by the dispatcher, with what it needs to send it
+#[derive(Clone, sqlx::FromRow, Debug)]
+pub struct PendingDelivery {
+    pub id: String,
+    pub webhook_id: String,
+    pub event: WebhookEvent,
+    pub payload: serde_json::Value,
+    pub attempts: i32,
+    pub created_at: DateTime<Utc>,
+    pub url: String,
+    pub secret: String,
+}
+
+pub struct CreateWebhook {
+    pub user_id: String,
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    pub global: bool,
+}
+
+pub struct UpdateWebhook {
+    pub id: String,
+    pub user_id: String,
+    pub url: Option<String>,
+    pub events: Option<Vec<WebhookEvent>>,
+    pub active: Option<bool>,
+}
+
+/// What webhook payloads show of a user, leaving out private fields like the
+/// email
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct WebhookUser {
+    pub id: String,
+    pub username: String,
+    pub image: Option<String>,
+    pub bio: String,
+}
+
+impl From<&FullUser> for WebhookUser {
+    fn from(value: &FullUser) -> Self {
+        Self {
+            id: value.id.to_owned(),
+            username: value.username.to_owned(),
+            image: value.image.to_owned(),
+            bio: value.bio.to_owned(),
+        }
+    }
+}
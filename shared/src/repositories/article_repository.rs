@@ -1,7 +1,9 @@
 use crate::models::{
     article_model::{
-        AddAuthor, Article, ArticleVersion, CreateArticle, DeleteAuthor, FullArticle, UpdateArticle,
+        AddAuthor, Article, ArticleVersion, CreateArticle, CreateSnapshot, DeleteAuthor,
+        FullArticle, Headline, Snapshot, UpdateArticle,
     },
+    enums::SearchSort,
     list_model::List,
     series_model::Series,
     tag_model::Tag,
@@ -16,15 +18,27 @@ pub trait ArticleRepository<DB, E>
 where
     DB: Database,
 {
+    /// Sorting by relevance only applies when there is a query, the cursor
+    /// stays the id and creation time of the last article either way
     async fn find_all(
         transaction: &mut Transaction<'_, DB>,
         query: Option<&str>,
+        sort: SearchSort,
         limit: i64,
         id: Option<&str>,
         created_at: Option<DateTime<Utc>>,
         by_user: Option<&str>,
         published: Option<bool>,
     ) -> Result<Vec<FullArticle>, E>;
+    /// Fragments of the given articles matching the query, computed
+    /// separately so only the returned page pays for them. Markup of the
+    /// article is stripped and the rest escaped, only the `<b>` around
+    /// matches is HTML.
+    async fn headlines(
+        transaction: &mut Transaction<'_, DB>,
+        query: &str,
+        article_ids: &[String],
+    ) -> Result<Vec<Headline>, E>;
     async fn find(
         transaction: &mut Transaction<'_, DB>,
         article_id: &str,
@@ -76,6 +90,52 @@ where
         content: &str,
         device_id: Option<&str>,
     ) -> Result<ArticleVersion, E>;
+    async fn find_version(
+        transaction: &mut Transaction<'_, DB>,
+        article_id: &str,
+        version_id: &str,
+    ) -> Result<ArticleVersion, E>;
+    /// Saves a copy of an older version as the newest one
+    async fn restore(
+        transaction: &mut Transaction<'_, DB>,
+        article_id: &str,
+        version_id: &str,
+    ) -> Result<ArticleVersion, E>;
+    async fn update_content(
+        transaction: &mut Transaction<'_, DB>,
+        article_id: &str,
+        content: &str,
+    ) -> Result<Article, E>;
+    /// Names a version, or changes the note of a name it already has
+    async fn create_snapshot(
+        transaction: &mut Transaction<'_, DB>,
+        create_snapshot: &CreateSnapshot,
+    ) -> Result<Snapshot, E>;
+    async fn snapshots(
+        transaction: &mut Transaction<'_, DB>,
+        article_id: &str,
+        limit: i64,
+        id: Option<&str>,
+        created_at: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Snapshot>, E>;
+    /// Articles with more than one version older than `keep_all` minutes,
+    /// after the `after` article
+    async fn find_compactable(
+        transaction: &mut Transaction<'_, DB>,
+        keep_all: i32,
+        after: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<String>, E>;
+    /// Deletes versions older than `keep_all` minutes that are not the
+    /// newest of their hour, or of their day once older than `keep_hourly`
+    /// hours. The newest version, the one the article was published with and
+    /// snapshots are kept.
+    async fn compact(
+        transaction: &mut Transaction<'_, DB>,
+        article_ids: &[String],
+        keep_all: i32,
+        keep_hourly: i32,
+    ) -> Result<u64, E>;
 }
 
 #[derive(Debug, Clone)]
@@ -86,12 +146,15 @@ impl ArticleRepository<Postgres, Error> for ArticleRepositoryImpl {
     async fn find_all(
         transaction: &mut Transaction<'_, Postgres>,
         query: Option<&str>,
+        sort: SearchSort,
         limit: i64,
         id: Option<&str>,
         created_at: Option<DateTime<Utc>>,
         by_user: Option<&str>,
         published: Option<bool>,
     ) -> Result<Vec<FullArticle>, Error> {
+        let ranked = query.is_some() && sort == SearchSort::Relevance;
+
         sqlx::query_as!(
             FullArticle,
             r#"
@@ -142,17 +205,29 @@ impl ArticleRepository<Postgres, Error> for ArticleRepositoryImpl {
             LEFT JOIN lists l ON la.list_id = l.id AND l.user_id = $5
             LEFT JOIN seriesarticle sa ON a.id = sa.article_id
             LEFT JOIN series s ON sa.series_id = s.id
+            LEFT JOIN articlesearch d ON a.id = d.article_id
             WHERE ($6::bool IS NULL OR (
                     CASE 
                         WHEN $6 IS TRUE THEN a.published_at IS NOT NULL
                         ELSE a.published_at IS NULL
                     END)
-                ) AND (($2::TEXT IS NULL AND $3::TIMESTAMPTZ IS NULL) OR (a.created_at, a.id) < ($3, $2))
-                AND (($4::TEXT IS NULL)
-                    OR (to_tsvector(a.title || ' ' || COALESCE(a.description, '') || ' ' || COALESCE(a.content, ''))
-                        @@ websearch_to_tsquery($4)))
-            GROUP BY a.id, s.id, li.user_id, sa.order
-            ORDER BY a.created_at DESC, a.id DESC
+                ) AND (($2::TEXT IS NULL AND $3::TIMESTAMPTZ IS NULL) OR (
+                    CASE
+                        WHEN $7::bool IS TRUE THEN
+                            (ts_rank_cd(d.document, websearch_to_tsquery($4)), a.created_at, a.id) < (
+                                (SELECT ts_rank_cd(document, websearch_to_tsquery($4)) FROM articlesearch WHERE article_id = $2),
+                                $3,
+                                $2
+                            )
+                        ELSE (a.created_at, a.id) < ($3, $2)
+                    END)
+                )
+                AND ($4::TEXT IS NULL OR d.document @@ websearch_to_tsquery($4))
+            GROUP BY a.id, s.id, li.user_id, sa.order, d.article_id
+            ORDER BY
+                CASE WHEN $7 IS TRUE THEN ts_rank_cd(d.document, websearch_to_tsquery($4)) END DESC,
+                a.created_at DESC,
+                a.id DESC
             LIMIT $1
             "#n,
             limit,
@@ -161,6 +236,36 @@ impl ArticleRepository<Postgres, Error> for ArticleRepositoryImpl {
             query,
             by_user,
             published,
+            ranked,
+        )
+        .fetch_all(&mut **transaction)
+        .await
+    }
+
+    async fn headlines(
+        transaction: &mut Transaction<'_, Postgres>,
+        query: &str,
+        article_ids: &[String],
+    ) -> Result<Vec<Headline>, Error> {
+        sqlx::query_as!(
+            Headline,
+            r#"
+            SELECT
+                id AS article_id,
+                replace(replace(replace(replace(
+                    ts_headline(
+                        COALESCE(description, '') || ' ' || regexp_replace(content, '<[^>]*>', ' ', 'g'),
+                        websearch_to_tsquery($1),
+                        E'StartSel="\x02", StopSel="\x03", MaxFragments=2, MinWords=8, MaxWords=24'
+                    ),
+                    '<', '&lt;'), '>', '&gt;'),
+                    E'\x02', '<b>'), E'\x03', '</b>'
+                ) AS "headline!"
+            FROM articles
+            WHERE id = ANY($2)
+            "#n,
+            query,
+            article_ids
         )
         .fetch_all(&mut **transaction)
         .await
@@ -406,6 +511,203 @@ impl ArticleRepository<Postgres, Error> for ArticleRepositoryImpl {
         .await
     }
 
+    async fn find_version(
+        transaction: &mut Transaction<'_, Postgres>,
+        article_id: &str,
+        version_id: &str,
+    ) -> Result<ArticleVersion, Error> {
+        sqlx::query_as!(
+            ArticleVersion,
+            r#"
+            SELECT *
+            FROM articleversions
+            WHERE article_id = $1 AND id = $2
+            "#n,
+            article_id,
+            version_id,
+        )
+        .fetch_one(&mut **transaction)
+        .await
+    }
+
+    async fn restore(
+        transaction: &mut Transaction<'_, Postgres>,
+        article_id: &str,
+        version_id: &str,
+    ) -> Result<ArticleVersion, Error> {
+        sqlx::query_as!(
+            ArticleVersion,
+            r#"
+            INSERT INTO articleversions (article_id, content)
+            SELECT article_id, content
+            FROM articleversions
+            WHERE article_id = $1 AND id = $2
+            RETURNING *
+            "#n,
+            article_id,
+            version_id,
+        )
+        .fetch_one(&mut **transaction)
+        .await
+    }
+
+    async fn update_content(
+        transaction: &mut Transaction<'_, Postgres>,
+        article_id: &str,
+        content: &str,
+    ) -> Result<Article, Error> {
+        sqlx::query_as!(
+            Article,
+            r#"
+            UPDATE articles
+            SET content = $2
+            WHERE articles.id = $1
+            RETURNING *
+            "#n,
+            article_id,
+            content,
+        )
+        .fetch_one(&mut **transaction)
+        .await
+    }
+
+    async fn create_snapshot(
+        transaction: &mut Transaction<'_, Postgres>,
+        create_snapshot: &CreateSnapshot,
+    ) -> Result<Snapshot, Error> {
+        sqlx::query_as!(
+            Snapshot,
+            r#"
+            INSERT INTO articlesnapshots (version_id, article_id, user_id, name, note)
+            SELECT v.id, v.article_id, $3, $4, $5
+            FROM articleversions v
+            WHERE v.id = $1 AND v.article_id = $2
+            ON CONFLICT (version_id, name) DO UPDATE
+            SET note = EXCLUDED.note
+            RETURNING *
+            "#n,
+            create_snapshot.version_id,
+            create_snapshot.article_id,
+            create_snapshot.user_id,
+            create_snapshot.name,
+            create_snapshot.note,
+        )
+        .fetch_one(&mut **transaction)
+        .await
+    }
+
+    async fn snapshots(
+        transaction: &mut Transaction<'_, Postgres>,
+        article_id: &str,
+        limit: i64,
+        id: Option<&str>,
+        created_at: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Snapshot>, Error> {
+        sqlx::query_as!(
+            Snapshot,
+            r#"
+            SELECT *
+            FROM articlesnapshots
+            WHERE article_id = $4
+                AND (($2::text IS NULL AND $3::timestamptz IS NULL)
+                    OR (created_at, id) < ($3, $2))
+            ORDER BY created_at DESC, id DESC
+            LIMIT $1
+            "#n,
+            limit,
+            id,
+            created_at,
+            article_id,
+        )
+        .fetch_all(&mut **transaction)
+        .await
+    }
+
+    async fn find_compactable(
+        transaction: &mut Transaction<'_, Postgres>,
+        keep_all: i32,
+        after: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<String>, Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT article_id
+            FROM articleversions
+            WHERE created_at < now() - make_interval(mins => $1)
+                AND ($2::text IS NULL OR article_id > $2)
+            GROUP BY article_id
+            HAVING COUNT(*) > 1
+            ORDER BY article_id ASC
+            LIMIT $3
+            "#n,
+            keep_all,
+            after,
+            limit,
+        )
+        .fetch_all(&mut **transaction)
+        .await
+    }
+
+    async fn compact(
+        transaction: &mut Transaction<'_, Postgres>,
+        article_ids: &[String],
+        keep_all: i32,
+        keep_hourly: i32,
+    ) -> Result<u64, Error> {
+        let result = sqlx::query!(
+            r#"
+            WITH periods AS (
+                SELECT
+                    v.id,
+                    v.article_id,
+                    v.created_at,
+                    v.created_at >= now() - make_interval(hours => $3) AS hourly,
+                    COALESCE(v.created_at <= a.published_at, FALSE) AS before_published
+                FROM articleversions v
+                JOIN articles a ON v.article_id = a.id
+                WHERE v.article_id = ANY($1)
+            ),
+            ranked AS (
+                SELECT
+                    p.id,
+                    p.created_at,
+                    ROW_NUMBER() OVER (
+                        PARTITION BY p.article_id
+                        ORDER BY p.created_at DESC, p.id DESC
+                    ) AS newest,
+                    ROW_NUMBER() OVER (
+                        PARTITION BY
+                            p.article_id,
+                            p.hourly,
+                            date_trunc(CASE WHEN p.hourly THEN 'hour' ELSE 'day' END, p.created_at)
+                        ORDER BY p.created_at DESC, p.id DESC
+                    ) AS in_period,
+                    p.before_published AND ROW_NUMBER() OVER (
+                        PARTITION BY p.article_id, p.before_published
+                        ORDER BY p.created_at DESC, p.id DESC
+                    ) = 1 AS published
+                FROM periods p
+            )
+            DELETE FROM articleversions v
+            USING ranked r
+            WHERE v.id = r.id
+                AND r.created_at < now() - make_interval(mins => $2)
+                AND r.newest > 1
+                AND r.in_period > 1
+                AND NOT r.published
+                AND NOT EXISTS (
+                    SELECT 1 FROM articlesnapshots s WHERE s.version_id = v.id
+                )
+            "#n,
+            article_ids,
+            keep_all,
+            keep_hourly,
+        )
+        .execute(&mut **transaction)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
     async fn like(
         transaction: &mut Transaction<'_, Postgres>,
         article_id: &str,
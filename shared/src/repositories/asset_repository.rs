@@ -1,4 +1,10 @@
-use sqlx::{Database, Transaction};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{Database, Error, Postgres, Transaction};
+
+use crate::models::asset_model::{
+    Asset, Blob, CreateAsset, CreateBlob, CreatePendingUpload, Orphan, PendingUpload, Placeholder,
+};
 
 #[async_trait]
 pub trait AssetRepository<DB, E>
@@ -7,19 +13,85 @@ where
 {
     async fn find_all(
         transaction: &mut Transaction<'_, DB>,
-        tag_status: Option<TagStatus>,
-        user_id: Option<&str>,
-    ) -> Result<Vec<Tag>, E>;
-    async fn find(transaction: &mut Transaction<'_, DB>, tag_id: &str) -> Result<Tag, E>;
+        user_id: &str,
+        limit: i64,
+        id: Option<&str>,
+        created_at: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Asset>, E>;
+    async fn find(transaction: &mut Transaction<'_, DB>, asset_id: &str) -> Result<Asset, E>;
+    /// Total size of the assets a user owns, in bytes
+    async fn find_usage(transaction: &mut Transaction<'_, DB>, user_id: &str) -> Result<i64, E>;
+    /// Locks the user until the transaction ends, so their uploads are
+    /// checked against the quota one at a time
+    async fn lock_owner(transaction: &mut Transaction<'_, DB>, user_id: &str) -> Result<(), E>;
     async fn create(
         transaction: &mut Transaction<'_, DB>,
-        create_tag: &CreateTag,
-    ) -> Result<Tag, E>;
-    async fn update(
+        create_asset: &CreateAsset,
+    ) -> Result<Asset, E>;
+    /// Returns the blob the asset pointed to, with its remaining references
+    async fn delete(transaction: &mut Transaction<'_, DB>, asset_id: &str) -> Result<Blob, E>;
+    async fn find_blob(transaction: &mut Transaction<'_, DB>, name: &str) -> Result<Blob, E>;
+    /// Locks the blob until the transaction ends, so it is not removed meanwhile
+    async fn lock_blob(transaction: &mut Transaction<'_, DB>, name: &str) -> Result<Blob, E>;
+    /// Finds and locks an image stored from an upload with this checksum
+    async fn find_blob_by_source(
+        transaction: &mut Transaction<'_, DB>,
+        source_checksum: &str,
+    ) -> Result<Blob, E>;
+    /// Skips names that are missing or have no placeholder
+    async fn find_placeholders(
+        transaction: &mut Transaction<'_, DB>,
+        names: &[String],
+    ) -> Result<Vec<Placeholder>, E>;
+    /// Does nothing when the blob is already registered
+    async fn create_blob(
+        transaction: &mut Transaction<'_, DB>,
+        create_blob: &CreateBlob,
+    ) -> Result<(), E>;
+    /// Only removes blobs nothing refers to anymore
+    async fn delete_blob(transaction: &mut Transaction<'_, DB>, name: &str) -> Result<Blob, E>;
+    /// Every asset nothing links to, marked or not
+    async fn find_orphans(transaction: &mut Transaction<'_, DB>) -> Result<Vec<Orphan>, E>;
+    /// Marks assets that have been unreferenced for `grace_period` hours
+    async fn mark_orphans(
+        transaction: &mut Transaction<'_, DB>,
+        grace_period: i32,
+    ) -> Result<u64, E>;
+    /// Clears the mark of assets that are linked again
+    async fn unmark_orphans(transaction: &mut Transaction<'_, DB>) -> Result<u64, E>;
+    /// Assets marked more than `grace_period` hours ago and still unreferenced
+    async fn find_expired(
+        transaction: &mut Transaction<'_, DB>,
+        grace_period: i32,
+        limit: i64,
+    ) -> Result<Vec<Orphan>, E>;
+    /// Removes blobs left without assets, once those were deleted
+    async fn delete_dangling_blobs(
+        transaction: &mut Transaction<'_, DB>,
+        grace_period: i32,
+        limit: i64,
+    ) -> Result<Vec<Blob>, E>;
+    async fn create_pending(
+        transaction: &mut Transaction<'_, DB>,
+        create_pending: &CreatePendingUpload,
+    ) -> Result<PendingUpload, E>;
+    /// Locks the upload until the transaction ends, so it is completed once
+    async fn find_pending(
+        transaction: &mut Transaction<'_, DB>,
+        upload_id: &str,
+        user_id: &str,
+    ) -> Result<PendingUpload, E>;
+    async fn delete_pending(
+        transaction: &mut Transaction<'_, DB>,
+        upload_id: &str,
+    ) -> Result<(), E>;
+    /// Uploads that expired more than `grace_period` hours ago without
+    /// being completed
+    async fn delete_abandoned(
         transaction: &mut Transaction<'_, DB>,
-        update_tag: &UpdateTag,
-    ) -> Result<Tag, E>;
-    async fn delete(transaction: &mut Transaction<'_, DB>, tag_id: &str) -> Result<Tag, E>;
+        grace_period: i32,
+        limit: i64,
+    ) -> Result<Vec<PendingUpload>, E>;
 }
 
 #[derive(Debug, Clone)]
@@ -29,34 +101,38 @@ pub struct AssetRepositoryImpl;
 impl AssetRepository<Postgres, Error> for AssetRepositoryImpl {
     async fn find_all(
         transaction: &mut Transaction<'_, Postgres>,
-        tag_status: Option<TagStatus>,
-        user_id: Option<&str>,
-        article_id: Option<&str>,
-        filters: &Filter,
-    ) -> Result<Vec<Tag>, Error> {
+        user_id: &str,
+        limit: i64,
+        id: Option<&str>,
+        created_at: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Asset>, Error> {
         sqlx::query_as!(
-            Tag,
+            Asset,
             r#"
             SELECT
-                t.label,
-                t.slug,
-                t.article_count,
-                t.tag_status AS "tag_status: TagStatus",
-                t.created_at,
-                t.updated_at
-            FROM tags t
-            LEFT JOIN interests i ON t.slug = i.tag_slug
-            LEFT JOIN articletags at ON t.slug = at.tag_slug
-            WHERE (at.article_id = $5 OR i.user_id = $4) AND tag_status = coalesce($1, tag_status)
-            ORDER BY t.created_at DESC
+                a.id,
+                a.user_id,
+                a.asset_name,
+                a.file_name,
+                b.content_type,
+                b.size,
+                b.checksum,
+                b.width,
+                b.height,
+                b.blurhash,
+                b.dominant_color,
+                a.created_at
+            FROM assets a
+            JOIN blobs b ON b.name = a.asset_name
+            WHERE a.user_id = $1
+                AND (($3::text IS NULL AND $4::timestamptz IS NULL) OR (a.created_at, a.id) < ($4, $3))
+            ORDER BY a.created_at DESC, a.id DESC
             LIMIT $2
-            OFFSET $3
             "#n,
-            tag_status as Option<TagStatus>,
-            filters.limit,
-            filters.offset,
             user_id,
-            article_id,
+            limit,
+            id,
+            created_at
         )
         .fetch_all(&mut **transaction)
         .await
@@ -64,148 +140,534 @@ impl AssetRepository<Postgres, Error> for AssetRepositoryImpl {
 
     async fn find(
         transaction: &mut Transaction<'_, Postgres>,
-        tag_slug: &str,
-    ) -> Result<Tag, Error> {
+        asset_id: &str,
+    ) -> Result<Asset, Error> {
         sqlx::query_as!(
-            Tag,
+            Asset,
             r#"
             SELECT
-                label,
-                slug,
-                article_count,
-                tag_status AS "tag_status: TagStatus",
-                created_at,
-                updated_at
-            FROM tags
-            WHERE slug = $1
+                a.id,
+                a.user_id,
+                a.asset_name,
+                a.file_name,
+                b.content_type,
+                b.size,
+                b.checksum,
+                b.width,
+                b.height,
+                b.blurhash,
+                b.dominant_color,
+                a.created_at
+            FROM assets a
+            JOIN blobs b ON b.name = a.asset_name
+            WHERE a.id = $1
             "#n,
-            tag_slug
+            asset_id
         )
         .fetch_one(&mut **transaction)
         .await
     }
 
+    async fn find_usage(
+        transaction: &mut Transaction<'_, Postgres>,
+        user_id: &str,
+    ) -> Result<i64, Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(SUM(b.size), 0)::bigint AS "usage!"
+            FROM assets a
+            JOIN blobs b ON b.name = a.asset_name
+            WHERE a.user_id = $1
+            "#n,
+            user_id
+        )
+        .fetch_one(&mut **transaction)
+        .await
+    }
+
+    async fn lock_owner(
+        transaction: &mut Transaction<'_, Postgres>,
+        user_id: &str,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            SELECT id
+            FROM users
+            WHERE id = $1
+            FOR UPDATE
+            "#n,
+            user_id
+        )
+        .fetch_one(&mut **transaction)
+        .await?;
+        Ok(())
+    }
+
     async fn create(
         transaction: &mut Transaction<'_, Postgres>,
-        create_tag: &CreateTag,
-    ) -> Result<Tag, Error> {
+        create_asset: &CreateAsset,
+    ) -> Result<Asset, Error> {
         sqlx::query_as!(
-            Tag,
+            Asset,
             r#"
-            INSERT INTO tags (label, slug, tag_status)
-            VALUES ($1, $2, $3)
-            RETURNING
-                label,
-                slug,
-                article_count,
-                tag_status AS "tag_status: TagStatus",
-                created_at,
-                updated_at
+            WITH blob AS (
+                UPDATE blobs
+                SET ref_count = ref_count + 1
+                WHERE name = $2
+                RETURNING *
+            ), asset AS (
+                INSERT INTO assets (user_id, asset_name, file_name)
+                VALUES ($1, (SELECT name FROM blob), $3)
+                RETURNING *
+            )
+            SELECT
+                a.id AS "id!",
+                a.user_id AS "user_id!",
+                a.asset_name AS "asset_name!",
+                a.file_name AS "file_name!",
+                b.content_type AS "content_type!",
+                b.size AS "size!",
+                b.checksum AS "checksum!",
+                b.width,
+                b.height,
+                b.blurhash,
+                b.dominant_color,
+                a.created_at AS "created_at!"
+            FROM asset a
+            JOIN blob b ON b.name = a.asset_name
             "#n,
-            create_tag.label,
-            slugify(format!("{}", create_tag.label)),
-            create_tag.tag_status as TagStatus
+            create_asset.user_id,
+            create_asset.asset_name,
+            create_asset.file_name
         )
         .fetch_one(&mut **transaction)
         .await
     }
 
-    async fn update(
+    async fn delete(
         transaction: &mut Transaction<'_, Postgres>,
-        update_tag: &UpdateTag,
-    ) -> Result<Tag, Error> {
-        let label = match &update_tag.label {
-            Some(label) => Some(slugify(label)),
-            None => None,
-        };
+        asset_id: &str,
+    ) -> Result<Blob, Error> {
         sqlx::query_as!(
-            Tag,
-            r#"
-            UPDATE tags
-            SET
-                label = coalesce($2, tags.label),
-                tag_status = coalesce($3, tags.tag_status),
-                slug = coalesce($4, tags.slug)
-            WHERE slug = $1
+            Blob,
+            r#"
+            WITH asset AS (
+                DELETE FROM assets
+                WHERE id = $1
+                RETURNING asset_name
+            )
+            UPDATE blobs
+            SET ref_count = ref_count - 1
+            FROM asset
+            WHERE blobs.name = asset.asset_name
             RETURNING
-                label,
-                slug,
-                article_count,
-                tag_status AS "tag_status: TagStatus",
-                created_at,
-                updated_at
-            "#n,
-            update_tag.slug,
-            label,
-            update_tag.tag_status as Option<TagStatus>,
-            update_tag.label.clone().map(|v| slugify(v))
+                blobs.name,
+                blobs.checksum,
+                blobs.content_type,
+                blobs.size,
+                blobs.width,
+                blobs.height,
+                blobs.blurhash,
+                blobs.dominant_color,
+                blobs.ref_count,
+                blobs.created_at
+            "#n,
+            asset_id
         )
         .fetch_one(&mut **transaction)
         .await
     }
 
-    async fn delete(
+    async fn find_blob(
         transaction: &mut Transaction<'_, Postgres>,
-        tag_id: &str,
-    ) -> Result<Tag, Error> {
+        name: &str,
+    ) -> Result<Blob, Error> {
         sqlx::query_as!(
-            Tag,
+            Blob,
             r#"
-            DELETE FROM tags
-            WHERE slug = $1
-            RETURNING
-                label,
-                slug,
-                article_count,
-                tag_status AS "tag_status: TagStatus",
-                created_at,
-                updated_at
+            SELECT
+                name,
+                checksum,
+                content_type,
+                size,
+                width,
+                height,
+                blurhash,
+                dominant_color,
+                ref_count,
+                created_at
+            FROM blobs
+            WHERE name = $1
             "#n,
-            tag_id
+            name
         )
         .fetch_one(&mut **transaction)
         .await
     }
 
-    async fn add_article_tags(
+    async fn lock_blob(
         transaction: &mut Transaction<'_, Postgres>,
-        article_id: &str,
-        tag_slug: &str,
-    ) -> Result<(String, String), Error> {
-        let _ = sqlx::query!(
+        name: &str,
+    ) -> Result<Blob, Error> {
+        sqlx::query_as!(
+            Blob,
             r#"
-            WITH article_count_increment AS (
-                UPDATE tags
-                SET article_count = article_count + 1
-                WHERE slug = $2
+            SELECT
+                name,
+                checksum,
+                content_type,
+                size,
+                width,
+                height,
+                blurhash,
+                dominant_color,
+                ref_count,
+                created_at
+            FROM blobs
+            WHERE name = $1
+            FOR UPDATE
+            "#n,
+            name
+        )
+        .fetch_one(&mut **transaction)
+        .await
+    }
+
+    async fn find_blob_by_source(
+        transaction: &mut Transaction<'_, Postgres>,
+        source_checksum: &str,
+    ) -> Result<Blob, Error> {
+        sqlx::query_as!(
+            Blob,
+            r#"
+            SELECT
+                name,
+                checksum,
+                content_type,
+                size,
+                width,
+                height,
+                blurhash,
+                dominant_color,
+                ref_count,
+                created_at
+            FROM blobs
+            WHERE source_checksum = $1
+            LIMIT 1
+            FOR UPDATE
+            "#n,
+            source_checksum
+        )
+        .fetch_one(&mut **transaction)
+        .await
+    }
+
+    async fn find_placeholders(
+        transaction: &mut Transaction<'_, Postgres>,
+        names: &[String],
+    ) -> Result<Vec<Placeholder>, Error> {
+        sqlx::query_as!(
+            Placeholder,
+            r#"
+            SELECT
+                name,
+                blurhash AS "blurhash!",
+                dominant_color AS "dominant_color!"
+            FROM blobs
+            WHERE name = ANY($1)
+            AND blurhash IS NOT NULL
+            AND dominant_color IS NOT NULL
+            "#n,
+            names
+        )
+        .fetch_all(&mut **transaction)
+        .await
+    }
+
+    async fn create_blob(
+        transaction: &mut Transaction<'_, Postgres>,
+        create_blob: &CreateBlob,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO blobs (
+                name,
+                checksum,
+                content_type,
+                size,
+                width,
+                height,
+                blurhash,
+                dominant_color,
+                source_checksum
             )
-            INSERT INTO articletags (article_id, tag_slug)
-            VALUES ($1, $2)
-            ON CONFLICT DO NOTHING
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (name) DO NOTHING
             "#n,
-            article_id,
-            tag_slug
+            create_blob.name,
+            create_blob.checksum,
+            create_blob.content_type,
+            create_blob.size,
+            create_blob.width,
+            create_blob.height,
+            create_blob.blurhash,
+            create_blob.dominant_color,
+            create_blob.source_checksum
         )
         .execute(&mut **transaction)
-        .await;
-        Ok((article_id.to_string(), tag_slug.to_string()))
+        .await?;
+        Ok(())
     }
 
-    async fn remove_article_tags(
+    async fn delete_blob(
         transaction: &mut Transaction<'_, Postgres>,
-        article_id: &str,
-        tag_slug: &str,
-    ) -> Result<(String, String), Error> {
-        let _ = sqlx::query!(
+        name: &str,
+    ) -> Result<Blob, Error> {
+        sqlx::query_as!(
+            Blob,
             r#"
-            DELETE FROM articletags
-            WHERE article_id = $1 AND tag_slug = $2
+            DELETE FROM blobs
+            WHERE name = $1 AND ref_count <= 0
+            RETURNING
+                name,
+                checksum,
+                content_type,
+                size,
+                width,
+                height,
+                blurhash,
+                dominant_color,
+                ref_count,
+                created_at
             "#n,
-            article_id,
-            tag_slug
+            name
+        )
+        .fetch_one(&mut **transaction)
+        .await
+    }
+
+    async fn find_orphans(
+        transaction: &mut Transaction<'_, Postgres>,
+    ) -> Result<Vec<Orphan>, Error> {
+        sqlx::query_as!(
+            Orphan,
+            r#"
+            SELECT
+                a.id,
+                a.user_id,
+                a.asset_name,
+                a.file_name,
+                b.size,
+                a.created_at,
+                a.orphaned_at
+            FROM assets a
+            JOIN blobs b ON b.name = a.asset_name
+            WHERE NOT asset_is_referenced(a.asset_name)
+            ORDER BY a.orphaned_at ASC NULLS LAST, a.created_at ASC
+            "#n
+        )
+        .fetch_all(&mut **transaction)
+        .await
+    }
+
+    async fn mark_orphans(
+        transaction: &mut Transaction<'_, Postgres>,
+        grace_period: i32,
+    ) -> Result<u64, Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE assets
+            SET orphaned_at = now()
+            WHERE orphaned_at IS NULL
+                AND created_at < now() - make_interval(hours => $1)
+                AND NOT asset_is_referenced(asset_name)
+            "#n,
+            grace_period
+        )
+        .execute(&mut **transaction)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn unmark_orphans(transaction: &mut Transaction<'_, Postgres>) -> Result<u64, Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE assets
+            SET orphaned_at = NULL
+            WHERE orphaned_at IS NOT NULL AND asset_is_referenced(asset_name)
+            "#n
+        )
+        .execute(&mut **transaction)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn find_expired(
+        transaction: &mut Transaction<'_, Postgres>,
+        grace_period: i32,
+        limit: i64,
+    ) -> Result<Vec<Orphan>, Error> {
+        sqlx::query_as!(
+            Orphan,
+            r#"
+            SELECT
+                a.id,
+                a.user_id,
+                a.asset_name,
+                a.file_name,
+                b.size,
+                a.created_at,
+                a.orphaned_at
+            FROM assets a
+            JOIN blobs b ON b.name = a.asset_name
+            WHERE a.orphaned_at < now() - make_interval(hours => $1)
+                AND NOT asset_is_referenced(a.asset_name)
+            ORDER BY a.orphaned_at ASC
+            LIMIT $2
+            "#n,
+            grace_period,
+            limit
+        )
+        .fetch_all(&mut **transaction)
+        .await
+    }
+
+    async fn delete_dangling_blobs(
+        transaction: &mut Transaction<'_, Postgres>,
+        grace_period: i32,
+        limit: i64,
+    ) -> Result<Vec<Blob>, Error> {
+        sqlx::query_as!(
+            Blob,
+            r#"
+            DELETE FROM blobs
+            WHERE name IN (
+                SELECT b.name
+                FROM blobs b
+                WHERE b.created_at < now() - make_interval(hours => $1)
+                    AND NOT EXISTS (SELECT 1 FROM assets a WHERE a.asset_name = b.name)
+                    AND NOT asset_is_referenced(b.name)
+                LIMIT $2
+            )
+            AND ref_count <= 0
+            RETURNING
+                name,
+                checksum,
+                content_type,
+                size,
+                width,
+                height,
+                blurhash,
+                dominant_color,
+                ref_count,
+                created_at
+            "#n,
+            grace_period,
+            limit
+        )
+        .fetch_all(&mut **transaction)
+        .await
+    }
+
+    async fn create_pending(
+        transaction: &mut Transaction<'_, Postgres>,
+        create_pending: &CreatePendingUpload,
+    ) -> Result<PendingUpload, Error> {
+        sqlx::query_as!(
+            PendingUpload,
+            r#"
+            INSERT INTO pendinguploads (user_id, file_name, content_type, size, expires_at)
+            VALUES ($1, $2, $3, $4, now() + make_interval(secs => $5))
+            RETURNING
+                id,
+                user_id,
+                file_name,
+                content_type,
+                size,
+                expires_at,
+                created_at
+            "#n,
+            create_pending.user_id,
+            create_pending.file_name,
+            create_pending.content_type,
+            create_pending.size,
+            create_pending.expiry as f64
+        )
+        .fetch_one(&mut **transaction)
+        .await
+    }
+
+    async fn find_pending(
+        transaction: &mut Transaction<'_, Postgres>,
+        upload_id: &str,
+        user_id: &str,
+    ) -> Result<PendingUpload, Error> {
+        sqlx::query_as!(
+            PendingUpload,
+            r#"
+            SELECT
+                id,
+                user_id,
+                file_name,
+                content_type,
+                size,
+                expires_at,
+                created_at
+            FROM pendinguploads
+            WHERE id = $1 AND user_id = $2
+            FOR UPDATE
+            "#n,
+            upload_id,
+            user_id
+        )
+        .fetch_one(&mut **transaction)
+        .await
+    }
+
+    async fn delete_pending(
+        transaction: &mut Transaction<'_, Postgres>,
+        upload_id: &str,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            DELETE FROM pendinguploads
+            WHERE id = $1
+            "#n,
+            upload_id
         )
         .execute(&mut **transaction)
-        .await;
-        Ok((article_id.to_string(), tag_slug.to_string()))
+        .await?;
+        Ok(())
+    }
+
+    async fn delete_abandoned(
+        transaction: &mut Transaction<'_, Postgres>,
+        grace_period: i32,
+        limit: i64,
+    ) -> Result<Vec<PendingUpload>, Error> {
+        sqlx::query_as!(
+            PendingUpload,
+            r#"
+            DELETE FROM pendinguploads
+            WHERE id IN (
+                SELECT id
+                FROM pendinguploads
+                WHERE expires_at < now() - make_interval(hours => $1)
+                LIMIT $2
+            )
+            RETURNING
+                id,
+                user_id,
+                file_name,
+                content_type,
+                size,
+                expires_at,
+                created_at
+            "#n,
+            grace_period,
+            limit
+        )
+        .fetch_all(&mut **transaction)
+        .await
     }
 }
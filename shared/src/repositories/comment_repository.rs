@@ -87,6 +87,7 @@ impl CommentRepository<Postgres, Error> for CommentRepositoryImpl {
                 f.followed as "followed!: bool",
                 c.target_id,
                 c.type as "type: CommentableType",
+                ltree2text(subpath(c.parent, -1)) AS parent_id,
                 c.created_at,
                 c.updated_at
             FROM comments c
@@ -145,6 +146,7 @@ impl CommentRepository<Postgres, Error> for CommentRepositoryImpl {
                 f.followed as "followed!: bool",
                 c.target_id,
                 c.type as "type: CommentableType",
+                ltree2text(subpath(c.parent, -1)) AS parent_id,
                 c.created_at,
                 c.updated_at
             FROM comments c
@@ -184,21 +186,34 @@ impl CommentRepository<Postgres, Error> for CommentRepositoryImpl {
                 commenter_id,
                 target_id,
                 type,
-                content
-            ) VALUES ($1, $2, $3, $4)
+                content,
+                parent
+            ) VALUES (
+                $1,
+                $2,
+                $3,
+                $4,
+                (
+                    SELECT coalesce(p.parent, ''::ltree) || text2ltree(p.id)
+                    FROM comments p
+                    WHERE p.id = $5
+                )
+            )
             RETURNING
                 id,
                 content,
                 commenter_id,
                 target_id,
                 type as "type: CommentableType",
+                ltree2text(subpath(parent, -1)) AS parent_id,
                 created_at,
                 updated_at
             "#n,
             create_comment.user_id,
             create_comment.target_id,
             create_comment.r#type as CommentableType,
-            create_comment.content
+            create_comment.content,
+            create_comment.parent_id
         )
         .fetch_one(&mut **transaction)
         .await
@@ -220,6 +235,7 @@ impl CommentRepository<Postgres, Error> for CommentRepositoryImpl {
                 commenter_id,
                 target_id,
                 type as "type: CommentableType",
+                ltree2text(subpath(parent, -1)) AS parent_id,
                 created_at,
                 updated_at
             "#n,
@@ -245,6 +261,7 @@ impl CommentRepository<Postgres, Error> for CommentRepositoryImpl {
                 commenter_id,
                 target_id,
                 type as "type: CommentableType",
+                ltree2text(subpath(parent, -1)) AS parent_id,
                 created_at,
                 updated_at
             "#n,
@@ -0,0 +1,349 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{Database, Error, Postgres, Transaction};
+
+use crate::models::{
+    digest_model::{DigestActivity, DigestArticle, DigestRecipient},
+    enums::DigestFrequency,
+};
+
+#[async_trait]
+pub trait DigestRepository<DB, E>
+where
+    DB: Database,
+{
+    async fn find_frequency(
+        transaction: &mut Transaction<'_, DB>,
+        user_id: &str,
+    ) -> Result<DigestFrequency, E>;
+    async fn update_frequency(
+        transaction: &mut Transaction<'_, DB>,
+        user_id: &str,
+        frequency: DigestFrequency,
+    ) -> Result<DigestFrequency, E>;
+    async fn find_due(
+        transaction: &mut Transaction<'_, DB>,
+        frequency: DigestFrequency,
+        period_start: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<DigestRecipient>, E>;
+    async fn claim(
+        transaction: &mut Transaction<'_, DB>,
+        user_id: &str,
+        frequency: DigestFrequency,
+        period_start: DateTime<Utc>,
+    ) -> Result<bool, E>;
+    async fn mark_sent(
+        transaction: &mut Transaction<'_, DB>,
+        user_id: &str,
+        frequency: DigestFrequency,
+        period_start: DateTime<Utc>,
+    ) -> Result<(), E>;
+    async fn release(
+        transaction: &mut Transaction<'_, DB>,
+        user_id: &str,
+        frequency: DigestFrequency,
+        period_start: DateTime<Utc>,
+    ) -> Result<(), E>;
+    async fn followed_articles(
+        transaction: &mut Transaction<'_, DB>,
+        user_id: &str,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<DigestArticle>, E>;
+    async fn interest_articles(
+        transaction: &mut Transaction<'_, DB>,
+        user_id: &str,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<DigestArticle>, E>;
+    async fn activity(
+        transaction: &mut Transaction<'_, DB>,
+        user_id: &str,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<DigestActivity>, E>;
+}
+
+#[derive(Debug, Clone)]
+pub struct DigestRepositoryImpl;
+
+#[async_trait]
+impl DigestRepository<Postgres, Error> for DigestRepositoryImpl {
+    async fn find_frequency(
+        transaction: &mut Transaction<'_, Postgres>,
+        user_id: &str,
+    ) -> Result<DigestFrequency, Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT coalesce(
+                (SELECT frequency FROM digestsubscriptions WHERE user_id = $1),
+                'WEEKLY'
+            ) AS "frequency!: DigestFrequency"
+            "#n,
+            user_id
+        )
+        .fetch_one(&mut **transaction)
+        .await
+    }
+
+    async fn update_frequency(
+        transaction: &mut Transaction<'_, Postgres>,
+        user_id: &str,
+        frequency: DigestFrequency,
+    ) -> Result<DigestFrequency, Error> {
+        sqlx::query_scalar!(
+            r#"
+            INSERT INTO digestsubscriptions (user_id, frequency)
+            VALUES ($1, $2)
+            ON CONFLICT (user_id) DO UPDATE
+            SET frequency = $2
+            RETURNING frequency AS "frequency: DigestFrequency"
+            "#n,
+            user_id,
+            frequency as DigestFrequency
+        )
+        .fetch_one(&mut **transaction)
+        .await
+    }
+
+    async fn find_due(
+        transaction: &mut Transaction<'_, Postgres>,
+        frequency: DigestFrequency,
+        period_start: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<DigestRecipient>, Error> {
+        sqlx::query_as!(
+            DigestRecipient,
+            r#"
+            SELECT
+                u.id,
+                u.username,
+                u.email,
+                u.locale
+            FROM users u
+            LEFT JOIN digestsubscriptions ds ON ds.user_id = u.id
+            LEFT JOIN notificationsettings ns ON ns.user_id = u.id AND ns.event = 'DIGEST'
+            WHERE coalesce(ds.frequency, 'WEEKLY') = $1
+                AND coalesce(ns.email, TRUE)
+                AND u.email_verified IS NOT NULL
+                AND u.deleted_at IS NULL
+                AND NOT EXISTS (
+                    SELECT 1
+                    FROM digestdeliveries dd
+                    WHERE dd.user_id = u.id
+                        AND dd.frequency = $1
+                        AND dd.period_start = $2
+                )
+            ORDER BY u.id
+            LIMIT $3
+            "#n,
+            frequency as DigestFrequency,
+            period_start,
+            limit
+        )
+        .fetch_all(&mut **transaction)
+        .await
+    }
+
+    async fn claim(
+        transaction: &mut Transaction<'_, Postgres>,
+        user_id: &str,
+        frequency: DigestFrequency,
+        period_start: DateTime<Utc>,
+    ) -> Result<bool, Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO digestdeliveries (user_id, frequency, period_start)
+            VALUES ($1, $2, $3)
+            ON CONFLICT DO NOTHING
+            "#n,
+            user_id,
+            frequency as DigestFrequency,
+            period_start
+        )
+        .execute(&mut **transaction)
+        .await
+        .map(|result| result.rows_affected() == 1)
+    }
+
+    async fn mark_sent(
+        transaction: &mut Transaction<'_, Postgres>,
+        user_id: &str,
+        frequency: DigestFrequency,
+        period_start: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            UPDATE digestdeliveries
+            SET sent_at = now()
+            WHERE user_id = $1
+                AND frequency = $2
+                AND period_start = $3
+            "#n,
+            user_id,
+            frequency as DigestFrequency,
+            period_start
+        )
+        .execute(&mut **transaction)
+        .await
+        .map(|_| ())
+    }
+
+    async fn release(
+        transaction: &mut Transaction<'_, Postgres>,
+        user_id: &str,
+        frequency: DigestFrequency,
+        period_start: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            DELETE FROM digestdeliveries
+            WHERE user_id = $1
+                AND frequency = $2
+                AND period_start = $3
+                AND sent_at IS NULL
+            "#n,
+            user_id,
+            frequency as DigestFrequency,
+            period_start
+        )
+        .execute(&mut **transaction)
+        .await
+        .map(|_| ())
+    }
+
+    async fn followed_articles(
+        transaction: &mut Transaction<'_, Postgres>,
+        user_id: &str,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<DigestArticle>, Error> {
+        sqlx::query_as!(
+            DigestArticle,
+            r#"
+            SELECT
+                a.id,
+                a.title,
+                a.description,
+                ARRAY_AGG(DISTINCT u.username) AS "authors!",
+                a.like_count,
+                a.comment_count,
+                a.published_at AS "published_at!"
+            FROM follow fo
+            JOIN authors aut ON fo.following_id = aut.author_id
+            JOIN articles a ON a.id = aut.article_id
+            JOIN authors au ON a.id = au.article_id
+            JOIN users u ON au.author_id = u.id
+            WHERE fo.follower_id = $1
+                AND a.published_at >= $2
+                AND a.published_at < $3
+            GROUP BY a.id
+            ORDER BY a.published_at DESC, a.id DESC
+            LIMIT $4
+            "#n,
+            user_id,
+            since,
+            until,
+            limit
+        )
+        .fetch_all(&mut **transaction)
+        .await
+    }
+
+    async fn interest_articles(
+        transaction: &mut Transaction<'_, Postgres>,
+        user_id: &str,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<DigestArticle>, Error> {
+        sqlx::query_as!(
+            DigestArticle,
+            r#"
+            SELECT
+                a.id,
+                a.title,
+                a.description,
+                ARRAY_AGG(DISTINCT u.username) AS "authors!",
+                a.like_count,
+                a.comment_count,
+                a.published_at AS "published_at!"
+            FROM interests i
+            JOIN articletags at ON at.tag_slug = i.tag_slug
+            JOIN articles a ON a.id = at.article_id
+            JOIN authors au ON a.id = au.article_id
+            JOIN users u ON au.author_id = u.id
+            WHERE i.user_id = $1
+                AND a.published_at >= $2
+                AND a.published_at < $3
+                AND NOT EXISTS (
+                    SELECT 1 FROM authors own
+                    WHERE own.article_id = a.id AND own.author_id = $1
+                )
+            GROUP BY a.id
+            ORDER BY a.like_count DESC, a.comment_count DESC, a.id DESC
+            LIMIT $4
+            "#n,
+            user_id,
+            since,
+            until,
+            limit
+        )
+        .fetch_all(&mut **transaction)
+        .await
+    }
+
+    async fn activity(
+        transaction: &mut Transaction<'_, Postgres>,
+        user_id: &str,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<DigestActivity>, Error> {
+        sqlx::query_as!(
+            DigestActivity,
+            r#"
+            SELECT
+                a.id,
+                a.title,
+                (
+                    SELECT count(*) FROM likes li
+                    WHERE li.article_id = a.id
+                        AND li.created_at >= $2
+                        AND li.created_at < $3
+                ) AS "likes!",
+                (
+                    SELECT count(*) FROM comments c
+                    WHERE c.target_id = a.id
+                        AND c.type = 'ARTICLE'
+                        AND c.commenter_id != $1
+                        AND c.created_at >= $2
+                        AND c.created_at < $3
+                ) AS "comments!"
+            FROM authors au
+            JOIN articles a ON a.id = au.article_id
+            WHERE au.author_id = $1
+                AND a.published_at IS NOT NULL
+            ORDER BY 3 DESC, 4 DESC, a.id
+            LIMIT $4
+            "#n,
+            user_id,
+            since,
+            until,
+            limit
+        )
+        .fetch_all(&mut **transaction)
+        .await
+        .map(|activity| {
+            activity
+                .into_iter()
+                .filter(|article| article.likes > 0 || article.comments > 0)
+                .collect()
+        })
+    }
+}
@@ -0,0 +1,169 @@
+use crate::models::index_model::{IndexedArticle, IndexedTag, IndexedUser, SearchChange};
+use async_trait::async_trait;
+use sqlx::{Database, Error, Postgres, Transaction};
+
+/// What the search index is built from. Only published articles, users that
+/// are not deleted and approved tags are returned, `ids` narrows them down
+/// and `after` pages through them by id.
+#[async_trait]
+pub trait IndexRepository<DB, E>
+where
+    DB: Database,
+{
+    async fn find_changes(
+        transaction: &mut Transaction<'_, DB>,
+        limit: i64,
+    ) -> Result<Vec<SearchChange>, E>;
+    /// Every change recorded so far
+    async fn find_change_ids(transaction: &mut Transaction<'_, DB>) -> Result<Vec<i64>, E>;
+    /// Drops exactly the changes given. Ids are taken when a change is
+    /// recorded, not when it commits, so one below another applied may still
+    /// be pending.
+    async fn delete_changes(transaction: &mut Transaction<'_, DB>, ids: &[i64]) -> Result<u64, E>;
+    async fn find_articles(
+        transaction: &mut Transaction<'_, DB>,
+        ids: Option<&[String]>,
+        after: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<IndexedArticle>, E>;
+    async fn find_users(
+        transaction: &mut Transaction<'_, DB>,
+        ids: Option<&[String]>,
+        after: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<IndexedUser>, E>;
+    async fn find_tags(
+        transaction: &mut Transaction<'_, DB>,
+        slugs: Option<&[String]>,
+        after: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<IndexedTag>, E>;
+}
+
+#[derive(Debug, Clone)]
+pub struct IndexRepositoryImpl;
+
+#[async_trait]
+impl IndexRepository<Postgres, Error> for IndexRepositoryImpl {
+    async fn find_changes(
+        transaction: &mut Transaction<'_, Postgres>,
+        limit: i64,
+    ) -> Result<Vec<SearchChange>, Error> {
+        sqlx::query_as!(
+            SearchChange,
+            r#"
+            SELECT id, kind, target_id
+            FROM searchchanges
+            ORDER BY id ASC
+            LIMIT $1
+            "#n,
+            limit
+        )
+        .fetch_all(&mut **transaction)
+        .await
+    }
+
+    async fn find_change_ids(
+        transaction: &mut Transaction<'_, Postgres>,
+    ) -> Result<Vec<i64>, Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT id
+            FROM searchchanges
+            "#n
+        )
+        .fetch_all(&mut **transaction)
+        .await
+    }
+
+    async fn delete_changes(
+        transaction: &mut Transaction<'_, Postgres>,
+        ids: &[i64],
+    ) -> Result<u64, Error> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM searchchanges
+            WHERE id = ANY($1)
+            "#n,
+            ids
+        )
+        .execute(&mut **transaction)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn find_articles(
+        transaction: &mut Transaction<'_, Postgres>,
+        ids: Option<&[String]>,
+        after: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<IndexedArticle>, Error> {
+        sqlx::query_as!(
+            IndexedArticle,
+            r#"
+            SELECT id, title, description, content
+            FROM articles
+            WHERE published_at IS NOT NULL
+                AND ($1::text[] IS NULL OR id = ANY($1))
+                AND ($2::text IS NULL OR id > $2)
+            ORDER BY id ASC
+            LIMIT $3
+            "#n,
+            ids,
+            after,
+            limit
+        )
+        .fetch_all(&mut **transaction)
+        .await
+    }
+
+    async fn find_users(
+        transaction: &mut Transaction<'_, Postgres>,
+        ids: Option<&[String]>,
+        after: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<IndexedUser>, Error> {
+        sqlx::query_as!(
+            IndexedUser,
+            r#"
+            SELECT id, username, bio
+            FROM users
+            WHERE deleted_at IS NULL
+                AND ($1::text[] IS NULL OR id = ANY($1))
+                AND ($2::text IS NULL OR id > $2)
+            ORDER BY id ASC
+            LIMIT $3
+            "#n,
+            ids,
+            after,
+            limit
+        )
+        .fetch_all(&mut **transaction)
+        .await
+    }
+
+    async fn find_tags(
+        transaction: &mut Transaction<'_, Postgres>,
+        slugs: Option<&[String]>,
+        after: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<IndexedTag>, Error> {
+        sqlx::query_as!(
+            IndexedTag,
+            r#"
+            SELECT slug, label
+            FROM tags
+            WHERE tag_status = 'APPROVED'
+                AND ($1::text[] IS NULL OR slug = ANY($1))
+                AND ($2::text IS NULL OR slug > $2)
+            ORDER BY slug ASC
+            LIMIT $3
+            "#n,
+            slugs,
+            after,
+            limit
+        )
+        .fetch_all(&mut **transaction)
+        .await
+    }
+}
@@ -4,6 +4,7 @@ use sqlx::{Database, Error, Postgres, Transaction};
 
 use crate::models::{
     article_model::FullArticle,
+    asset_model::ImagePlaceholder,
     enums::Visibility,
     list_model::{CreateList, List, UpdateList},
     series_model::Series,
@@ -82,7 +83,8 @@ impl ListRepository<Postgres, Error> for ListRepositoryImpl {
                 visibility AS "visibility: Visibility",
                 article_count,
                 created_at,
-                updated_at
+                updated_at,
+                NULL AS "image_placeholder: ImagePlaceholder"
             FROM lists
             WHERE (($2::timestamptz IS NULL AND $3::text IS NULL)
                     OR (created_at, id) < ($2, $3))
@@ -118,7 +120,8 @@ impl ListRepository<Postgres, Error> for ListRepositoryImpl {
                 visibility AS "visibility: Visibility",
                 article_count,
                 created_at,
-                updated_at
+                updated_at,
+                NULL AS "image_placeholder: ImagePlaceholder"
             FROM lists
             WHERE id = $1
                 AND (($2 = user_id AND visibility = 'PRIVATE')
@@ -154,7 +157,8 @@ impl ListRepository<Postgres, Error> for ListRepositoryImpl {
                 visibility AS "visibility: Visibility",
                 article_count,
                 created_at,
-                updated_at
+                updated_at,
+                NULL AS "image_placeholder: ImagePlaceholder"
             "#n,
             create_list.user_id,
             create_list.label,
@@ -185,7 +189,8 @@ impl ListRepository<Postgres, Error> for ListRepositoryImpl {
                 visibility AS "visibility: Visibility",
                 article_count,
                 created_at,
-                updated_at
+                updated_at,
+                NULL AS "image_placeholder: ImagePlaceholder"
             "#n,
             update_list.id,
             update_list.label,
@@ -213,7 +218,8 @@ impl ListRepository<Postgres, Error> for ListRepositoryImpl {
                 visibility AS "visibility: Visibility",
                 article_count,
                 created_at,
-                updated_at
+                updated_at,
+                NULL AS "image_placeholder: ImagePlaceholder"
             "#n,
             list_id,
         )
@@ -1,9 +1,16 @@
 pub mod account_repository;
 pub mod article_repository;
-// pub mod asset_repository;
+pub mod asset_repository;
 pub mod comment_repository;
+pub mod digest_repository;
+pub mod index_repository;
 pub mod list_repository;
+pub mod newsletter_repository;
+pub mod notification_settings_repository;
+pub mod processed_message_repository;
+pub mod search_repository;
 pub mod series_repository;
 pub mod tag_repository;
 pub mod user_repository;
 pub mod validation_token;
+pub mod webhook_repository;
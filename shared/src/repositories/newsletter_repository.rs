@@ -0,0 +1,391 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{Database, Error, Postgres, Transaction};
+
+use crate::models::{
+    enums::{NewsletterSendStatus, SubscriberStatus},
+    newsletter_model::{NewsletterIssue, NewsletterSubscriber, PendingNewsletterSend},
+};
+
+#[async_trait]
+pub trait NewsletterRepository<DB, E>
+where
+    DB: Database,
+{
+    async fn subscribe(
+        transaction: &mut Transaction<'_, DB>,
+        author_id: &str,
+        email: &str,
+        locale: &str,
+    ) -> Result<NewsletterSubscriber, E>;
+    async fn confirm(
+        transaction: &mut Transaction<'_, DB>,
+        token: &str,
+    ) -> Result<NewsletterSubscriber, E>;
+    async fn unsubscribe(
+        transaction: &mut Transaction<'_, DB>,
+        token: &str,
+    ) -> Result<NewsletterSubscriber, E>;
+    async fn find_subscribers(
+        transaction: &mut Transaction<'_, DB>,
+        author_id: &str,
+        limit: i64,
+        id: Option<&str>,
+        created_at: Option<DateTime<Utc>>,
+    ) -> Result<Vec<NewsletterSubscriber>, E>;
+    async fn find_issues(
+        transaction: &mut Transaction<'_, DB>,
+        author_id: &str,
+        limit: i64,
+        id: Option<&str>,
+        created_at: Option<DateTime<Utc>>,
+    ) -> Result<Vec<NewsletterIssue>, E>;
+    async fn create_issue(
+        transaction: &mut Transaction<'_, DB>,
+        article_id: &str,
+    ) -> Result<u64, E>;
+    async fn open(transaction: &mut Transaction<'_, DB>, send_id: &str) -> Result<u64, E>;
+    async fn claim_due(
+        transaction: &mut Transaction<'_, DB>,
+        limit: i64,
+        lease: i64,
+    ) -> Result<Vec<PendingNewsletterSend>, E>;
+    async fn complete(
+        transaction: &mut Transaction<'_, DB>,
+        send_id: &str,
+        status: NewsletterSendStatus,
+        next_attempt_at: Option<DateTime<Utc>>,
+        error: Option<&str>,
+    ) -> Result<(), E>;
+    async fn bounce(
+        transaction: &mut Transaction<'_, DB>,
+        send_id: &str,
+        error: &str,
+    ) -> Result<(), E>;
+}
+
+#[derive(Debug, Clone)]
+pub struct NewsletterRepositoryImpl;
+
+#[async_trait]
+impl NewsletterRepository<Postgres, Error> for NewsletterRepositoryImpl {
+    async fn subscribe(
+        transaction: &mut Transaction<'_, Postgres>,
+        author_id: &str,
+        email: &str,
+        locale: &str,
+    ) -> Result<NewsletterSubscriber, Error> {
+        // Confirmed subscribers stay confirmed, anyone else starts over
+        sqlx::query_as!(
+            NewsletterSubscriber,
+            r#"
+            INSERT INTO newslettersubscribers (author_id, email, locale)
+            VALUES ($1, lower($2), $3)
+            ON CONFLICT (author_id, email) DO UPDATE
+            SET
+                locale = excluded.locale,
+                status = CASE
+                    WHEN newslettersubscribers.status = 'CONFIRMED' THEN newslettersubscribers.status
+                    ELSE 'PENDING'
+                END
+            RETURNING
+                id,
+                author_id,
+                email,
+                locale,
+                status AS "status: SubscriberStatus",
+                token,
+                created_at,
+                confirmed_at
+            "#n,
+            author_id,
+            email,
+            locale
+        )
+        .fetch_one(&mut **transaction)
+        .await
+    }
+
+    async fn confirm(
+        transaction: &mut Transaction<'_, Postgres>,
+        token: &str,
+    ) -> Result<NewsletterSubscriber, Error> {
+        sqlx::query_as!(
+            NewsletterSubscriber,
+            r#"
+            UPDATE newslettersubscribers
+            SET
+                status = 'CONFIRMED',
+                confirmed_at = coalesce(confirmed_at, now())
+            WHERE token = $1 AND status IN ('PENDING', 'CONFIRMED')
+            RETURNING
+                id,
+                author_id,
+                email,
+                locale,
+                status AS "status: SubscriberStatus",
+                token,
+                created_at,
+                confirmed_at
+            "#n,
+            token
+        )
+        .fetch_one(&mut **transaction)
+        .await
+    }
+
+    async fn unsubscribe(
+        transaction: &mut Transaction<'_, Postgres>,
+        token: &str,
+    ) -> Result<NewsletterSubscriber, Error> {
+        // Queued sends are dropped with the subscription
+        sqlx::query_as!(
+            NewsletterSubscriber,
+            r#"
+            WITH subscriber AS (
+                UPDATE newslettersubscribers
+                SET status = 'UNSUBSCRIBED'
+                WHERE token = $1
+                RETURNING *
+            ), dropped AS (
+                DELETE FROM newslettersends
+                WHERE status = 'PENDING'
+                    AND subscriber_id IN (SELECT id FROM subscriber)
+            )
+            SELECT
+                id AS "id!",
+                author_id AS "author_id!",
+                email AS "email!",
+                locale AS "locale!",
+                status AS "status!: SubscriberStatus",
+                token AS "token!",
+                created_at AS "created_at!",
+                confirmed_at
+            FROM subscriber
+            "#n,
+            token
+        )
+        .fetch_one(&mut **transaction)
+        .await
+    }
+
+    async fn find_subscribers(
+        transaction: &mut Transaction<'_, Postgres>,
+        author_id: &str,
+        limit: i64,
+        id: Option<&str>,
+        created_at: Option<DateTime<Utc>>,
+    ) -> Result<Vec<NewsletterSubscriber>, Error> {
+        sqlx::query_as!(
+            NewsletterSubscriber,
+            r#"
+            SELECT
+                id,
+                author_id,
+                email,
+                locale,
+                status AS "status: SubscriberStatus",
+                token,
+                created_at,
+                confirmed_at
+            FROM newslettersubscribers
+            WHERE author_id = $1
+                AND (($3::text IS NULL AND $4::timestamptz IS NULL) OR (created_at, id) < ($4, $3))
+            ORDER BY created_at DESC, id DESC
+            LIMIT $2
+            "#n,
+            author_id,
+            limit,
+            id,
+            created_at
+        )
+        .fetch_all(&mut **transaction)
+        .await
+    }
+
+    async fn find_issues(
+        transaction: &mut Transaction<'_, Postgres>,
+        author_id: &str,
+        limit: i64,
+        id: Option<&str>,
+        created_at: Option<DateTime<Utc>>,
+    ) -> Result<Vec<NewsletterIssue>, Error> {
+        sqlx::query_as!(
+            NewsletterIssue,
+            r#"
+            SELECT
+                n.id,
+                n.article_id,
+                a.title,
+                count(s.id) AS "recipients!",
+                count(s.id) FILTER (WHERE s.status = 'SENT') AS "sent!",
+                count(s.opened_at) AS "opened!",
+                count(s.id) FILTER (WHERE s.status = 'BOUNCED') AS "bounced!",
+                count(s.id) FILTER (WHERE s.status = 'FAILED') AS "failed!",
+                n.created_at
+            FROM newsletters n
+            JOIN articles a ON a.id = n.article_id
+            JOIN authors au ON au.article_id = n.article_id AND au.author_id = $1
+            LEFT JOIN newslettersends s ON s.newsletter_id = n.id
+            WHERE (($3::text IS NULL AND $4::timestamptz IS NULL) OR (n.created_at, n.id) < ($4, $3))
+            GROUP BY n.id, a.id
+            ORDER BY n.created_at DESC, n.id DESC
+            LIMIT $2
+            "#n,
+            author_id,
+            limit,
+            id,
+            created_at
+        )
+        .fetch_all(&mut **transaction)
+        .await
+    }
+
+    async fn create_issue(
+        transaction: &mut Transaction<'_, Postgres>,
+        article_id: &str,
+    ) -> Result<u64, Error> {
+        // Readers subscribed to several co-authors get a single email
+        sqlx::query!(
+            r#"
+            WITH issue AS (
+                INSERT INTO newsletters (article_id)
+                VALUES ($1)
+                ON CONFLICT (article_id) DO NOTHING
+                RETURNING id
+            )
+            INSERT INTO newslettersends (newsletter_id, subscriber_id)
+            SELECT DISTINCT ON (s.email) issue.id, s.id
+            FROM issue, newslettersubscribers s
+            JOIN authors au ON au.author_id = s.author_id
+            WHERE au.article_id = $1 AND s.status = 'CONFIRMED'
+            ORDER BY s.email, s.created_at
+            "#n,
+            article_id
+        )
+        .execute(&mut **transaction)
+        .await
+        .map(|result| result.rows_affected())
+    }
+
+    async fn open(
+        transaction: &mut Transaction<'_, Postgres>,
+        send_id: &str,
+    ) -> Result<u64, Error> {
+        sqlx::query!(
+            r#"
+            UPDATE newslettersends
+            SET opened_at = coalesce(opened_at, now())
+            WHERE id = $1
+            "#n,
+            send_id
+        )
+        .execute(&mut **transaction)
+        .await
+        .map(|result| result.rows_affected())
+    }
+
+    async fn claim_due(
+        transaction: &mut Transaction<'_, Postgres>,
+        limit: i64,
+        lease: i64,
+    ) -> Result<Vec<PendingNewsletterSend>, Error> {
+        sqlx::query_as!(
+            PendingNewsletterSend,
+            r#"
+            WITH due AS (
+                SELECT s.id
+                FROM newslettersends s
+                WHERE s.status = 'PENDING'
+                    AND s.next_attempt_at <= now()
+                ORDER BY s.next_attempt_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            UPDATE newslettersends s
+            SET
+                attempts = s.attempts + 1,
+                next_attempt_at = now() + make_interval(secs => $2)
+            FROM due, newsletters n, newslettersubscribers sub, articles a, users u
+            WHERE s.id = due.id
+                AND n.id = s.newsletter_id
+                AND sub.id = s.subscriber_id
+                AND a.id = n.article_id
+                AND u.id = sub.author_id
+            RETURNING
+                s.id,
+                s.subscriber_id,
+                s.attempts,
+                sub.email,
+                sub.locale,
+                sub.token,
+                a.id AS article_id,
+                a.title,
+                a.description,
+                a.content,
+                u.username AS author
+            "#n,
+            limit,
+            lease as f64
+        )
+        .fetch_all(&mut **transaction)
+        .await
+    }
+
+    async fn complete(
+        transaction: &mut Transaction<'_, Postgres>,
+        send_id: &str,
+        status: NewsletterSendStatus,
+        next_attempt_at: Option<DateTime<Utc>>,
+        error: Option<&str>,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            UPDATE newslettersends
+            SET
+                status = $2,
+                next_attempt_at = coalesce($3, next_attempt_at),
+                error = $4,
+                sent_at = CASE WHEN $2 = 'SENT' THEN now() ELSE sent_at END
+            WHERE id = $1
+            "#n,
+            send_id,
+            status as NewsletterSendStatus,
+            next_attempt_at,
+            error
+        )
+        .execute(&mut **transaction)
+        .await
+        .map(|_| ())
+    }
+
+    async fn bounce(
+        transaction: &mut Transaction<'_, Postgres>,
+        send_id: &str,
+        error: &str,
+    ) -> Result<(), Error> {
+        // Hard bounces stop every newsletter to the address, not just this one
+        sqlx::query!(
+            r#"
+            WITH send AS (
+                UPDATE newslettersends
+                SET status = 'BOUNCED', error = $2
+                WHERE id = $1
+                RETURNING subscriber_id
+            ), subscriber AS (
+                SELECT email FROM newslettersubscribers
+                WHERE id = (SELECT subscriber_id FROM send)
+            )
+            UPDATE newslettersubscribers
+            SET status = 'BOUNCED'
+            WHERE email = (SELECT email FROM subscriber)
+                AND status = 'CONFIRMED'
+            "#n,
+            send_id,
+            error
+        )
+        .execute(&mut **transaction)
+        .await
+        .map(|_| ())
+    }
+}
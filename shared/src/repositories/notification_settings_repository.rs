@@ -0,0 +1,105 @@
+use async_trait::async_trait;
+use sqlx::{Database, Error, Postgres, Transaction};
+
+use crate::models::{
+    enums::NotificationEvent,
+    notification_model::{NotificationSetting, UpdateNotificationSetting},
+};
+
+#[async_trait]
+pub trait NotificationSettingsRepository<DB, E>
+where
+    DB: Database,
+{
+    async fn find_all(
+        transaction: &mut Transaction<'_, DB>,
+        user_id: &str,
+    ) -> Result<Vec<NotificationSetting>, E>;
+    async fn find(
+        transaction: &mut Transaction<'_, DB>,
+        user_id: &str,
+        event: NotificationEvent,
+    ) -> Result<NotificationSetting, E>;
+    async fn update(
+        transaction: &mut Transaction<'_, DB>,
+        update_setting: &UpdateNotificationSetting,
+    ) -> Result<NotificationSetting, E>;
+}
+
+#[derive(Debug, Clone)]
+pub struct NotificationSettingsRepositoryImpl;
+
+#[async_trait]
+impl NotificationSettingsRepository<Postgres, Error> for NotificationSettingsRepositoryImpl {
+    async fn find_all(
+        transaction: &mut Transaction<'_, Postgres>,
+        user_id: &str,
+    ) -> Result<Vec<NotificationSetting>, Error> {
+        sqlx::query_as!(
+            NotificationSetting,
+            r#"
+            SELECT
+                e.event AS "event!: NotificationEvent",
+                coalesce(ns.in_app, TRUE) AS "in_app!",
+                coalesce(ns.email, TRUE) AS "email!"
+            FROM unnest(enum_range(NULL::NotificationEvent)) AS e(event)
+            LEFT JOIN notificationsettings ns
+                ON ns.event = e.event AND ns.user_id = $1
+            ORDER BY e.event
+            "#n,
+            user_id
+        )
+        .fetch_all(&mut **transaction)
+        .await
+    }
+
+    async fn find(
+        transaction: &mut Transaction<'_, Postgres>,
+        user_id: &str,
+        event: NotificationEvent,
+    ) -> Result<NotificationSetting, Error> {
+        sqlx::query_as!(
+            NotificationSetting,
+            r#"
+            SELECT
+                $2::NotificationEvent AS "event!: NotificationEvent",
+                coalesce(ns.in_app, TRUE) AS "in_app!",
+                coalesce(ns.email, TRUE) AS "email!"
+            FROM (SELECT 1) AS _
+            LEFT JOIN notificationsettings ns
+                ON ns.event = $2 AND ns.user_id = $1
+            "#n,
+            user_id,
+            event as NotificationEvent
+        )
+        .fetch_one(&mut **transaction)
+        .await
+    }
+
+    async fn update(
+        transaction: &mut Transaction<'_, Postgres>,
+        update_setting: &UpdateNotificationSetting,
+    ) -> Result<NotificationSetting, Error> {
+        sqlx::query_as!(
+            NotificationSetting,
+            r#"
+            INSERT INTO notificationsettings (user_id, event, in_app, email)
+            VALUES ($1, $2, coalesce($3, TRUE), coalesce($4, TRUE))
+            ON CONFLICT (user_id, event) DO UPDATE
+            SET
+                in_app = coalesce($3, notificationsettings.in_app),
+                email = coalesce($4, notificationsettings.email)
+            RETURNING
+                event AS "event: NotificationEvent",
+                in_app,
+                email
+            "#n,
+            update_setting.user_id,
+            update_setting.event as NotificationEvent,
+            update_setting.in_app,
+            update_setting.email
+        )
+        .fetch_one(&mut **transaction)
+        .await
+    }
+}
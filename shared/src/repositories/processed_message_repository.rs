@@ -0,0 +1,95 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{Database, Error, Postgres, Transaction};
+
+#[async_trait]
+pub trait ProcessedMessageRepository<DB, E>
+where
+    DB: Database,
+{
+    async fn claim(
+        transaction: &mut Transaction<'_, DB>,
+        id: &str,
+        consumer: &str,
+        message_type: &str,
+    ) -> Result<bool, E>;
+    async fn claim_recipient(
+        transaction: &mut Transaction<'_, DB>,
+        message_id: &str,
+        recipient_id: &str,
+    ) -> Result<bool, E>;
+    async fn prune(transaction: &mut Transaction<'_, DB>, before: DateTime<Utc>) -> Result<u64, E>;
+}
+
+#[derive(Debug, Clone)]
+pub struct ProcessedMessageRepositoryImpl;
+
+#[async_trait]
+impl ProcessedMessageRepository<Postgres, Error> for ProcessedMessageRepositoryImpl {
+    async fn claim(
+        transaction: &mut Transaction<'_, Postgres>,
+        id: &str,
+        consumer: &str,
+        message_type: &str,
+    ) -> Result<bool, Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO processedmessages (id, consumer, message_type)
+            VALUES ($1, $2, $3)
+            ON CONFLICT DO NOTHING
+            "#n,
+            id,
+            consumer,
+            message_type
+        )
+        .execute(&mut **transaction)
+        .await
+        .map(|result| result.rows_affected() == 1)
+    }
+
+    async fn claim_recipient(
+        transaction: &mut Transaction<'_, Postgres>,
+        message_id: &str,
+        recipient_id: &str,
+    ) -> Result<bool, Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO processedrecipients (message_id, recipient_id)
+            VALUES ($1, $2)
+            ON CONFLICT DO NOTHING
+            "#n,
+            message_id,
+            recipient_id
+        )
+        .execute(&mut **transaction)
+        .await
+        .map(|result| result.rows_affected() == 1)
+    }
+
+    async fn prune(
+        transaction: &mut Transaction<'_, Postgres>,
+        before: DateTime<Utc>,
+    ) -> Result<u64, Error> {
+        let recipients = sqlx::query!(
+            r#"
+            DELETE FROM processedrecipients
+            WHERE processed_at < $1
+            "#n,
+            before
+        )
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected();
+
+        sqlx::query!(
+            r#"
+            DELETE FROM processedmessages
+            WHERE processed_at < $1
+            "#n,
+            before
+        )
+        .execute(&mut **transaction)
+        .await
+        .map(|result| result.rows_affected() + recipients)
+    }
+}
@@ -0,0 +1,298 @@
+use crate::models::{
+    article_model::FullArticle,
+    list_model::List,
+    search_model::{SearchCounts, SearchFilter, Suggestion, TagFacet},
+    series_model::Series,
+    tag_model::Tag,
+    user_model::FullUser,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{Database, Error, Postgres, Transaction};
+
+#[async_trait]
+pub trait SearchRepository<DB, E>
+where
+    DB: Database,
+{
+    /// Most relevant first, the cursor is the id and creation time of the
+    /// last article
+    async fn articles(
+        transaction: &mut Transaction<'_, DB>,
+        filter: &SearchFilter,
+        limit: i64,
+        id: Option<&str>,
+        created_at: Option<DateTime<Utc>>,
+        by_user: Option<&str>,
+    ) -> Result<Vec<FullArticle>, E>;
+    async fn counts(
+        transaction: &mut Transaction<'_, DB>,
+        filter: &SearchFilter,
+        by_user: Option<&str>,
+    ) -> Result<SearchCounts, E>;
+    /// Tags of the matching articles, most common first
+    async fn tag_facets(
+        transaction: &mut Transaction<'_, DB>,
+        filter: &SearchFilter,
+        limit: i64,
+        by_user: Option<&str>,
+    ) -> Result<Vec<TagFacet>, E>;
+    /// Usernames starting with the query first, then the most similar ones
+    async fn suggest_users(
+        transaction: &mut Transaction<'_, DB>,
+        query: &str,
+        limit: i64,
+    ) -> Result<Vec<Suggestion>, E>;
+    /// Approved tags whose label starts with the query first, then the most
+    /// similar ones
+    async fn suggest_tags(
+        transaction: &mut Transaction<'_, DB>,
+        query: &str,
+        limit: i64,
+    ) -> Result<Vec<Suggestion>, E>;
+}
+
+/// `query` with the wildcards of `ILIKE` taken literally
+fn escape_like(query: &str) -> String {
+    query
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// `ILIKE` pattern matching values starting with `query` as typed
+fn prefix_pattern(query: &str) -> String {
+    format!("{}%", escape_like(query))
+}
+
+/// `ILIKE` pattern matching values containing `query` as typed
+fn contains_pattern(query: &str) -> String {
+    format!("%{}%", escape_like(query))
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchRepositoryImpl;
+
+#[async_trait]
+impl SearchRepository<Postgres, Error> for SearchRepositoryImpl {
+    async fn articles(
+        transaction: &mut Transaction<'_, Postgres>,
+        filter: &SearchFilter,
+        limit: i64,
+        id: Option<&str>,
+        created_at: Option<DateTime<Utc>>,
+        by_user: Option<&str>,
+    ) -> Result<Vec<FullArticle>, Error> {
+        sqlx::query_as!(
+            FullArticle,
+            r#"
+            WITH followers AS (
+                SELECT
+                    u.id,
+                    u.username,
+                    u.email,
+                    u.email_verified,
+                    u.image,
+                    u.bio,
+                    u.urls,
+                    u.follower_count,
+                    u.following_count,
+                    u.created_at,
+                    u.approved_at,
+                    u.deleted_at,
+                    CASE
+                        WHEN $5 IS NULL THEN FALSE
+                        WHEN f.follower_id IS NOT NULL THEN TRUE
+                        ELSE FALSE
+                    END AS followed
+                FROM users u
+                LEFT JOIN follow f ON u.id = f.following_id AND f.follower_id = $5
+            )
+            SELECT
+                a.*,
+                ARRAY_REMOVE(ARRAY_AGG(DISTINCT f.*) FILTER (WHERE f.id IS NOT NULL), NULL) as "users: Vec<FullUser>",
+                ARRAY_REMOVE(ARRAY_AGG(DISTINCT t.*) FILTER (WHERE t.slug IS NOT NULL), NULL) as "tags: Vec<Tag>",
+                ARRAY_REMOVE(ARRAY_AGG(DISTINCT s.*) FILTER (WHERE s.id IS NOT NULL), null) as "series: Vec<Series>",
+                CASE
+                    WHEN $5::text is NULL then ARRAY[]::lists[]
+                    ELSE ARRAY_REMOVE(ARRAY_AGG(DISTINCT l.*) FILTER (WHERE l.id IS NOT NULL), NULL)
+                END AS "lists: Vec<List>",
+                CASE
+                    WHEN $5::text IS NULL THEN FALSE
+                    WHEN li.user_id IS NOT NULL THEN TRUE
+                    ELSE FALSE
+                END AS liked,
+                sa.order AS "order: Option<f32>"
+            FROM matching_articles($4, $6, $7, $8, $9, $10, $5) m
+            JOIN articles a ON a.id = m.article_id
+            LEFT JOIN authors au ON a.id = au.article_id
+            LEFT JOIN followers f ON au.author_id = f.id
+            LEFT JOIN likes li ON a.id = li.article_id AND li.user_id = $5
+            LEFT JOIN articletags at ON a.id = at.article_id
+            LEFT JOIN tags t ON at.tag_slug = t.slug
+            LEFT JOIN listarticle la ON a.id = la.article_id
+            LEFT JOIN lists l ON la.list_id = l.id AND l.user_id = $5
+            LEFT JOIN seriesarticle sa ON a.id = sa.article_id
+            LEFT JOIN series s ON sa.series_id = s.id
+            WHERE ($2::TEXT IS NULL AND $3::TIMESTAMPTZ IS NULL)
+                OR (m.rank, a.created_at, a.id) < (
+                    (SELECT ts_rank_cd(document, websearch_to_tsquery($4)) FROM articlesearch WHERE article_id = $2),
+                    $3,
+                    $2
+                )
+            GROUP BY a.id, s.id, li.user_id, sa.order, m.rank
+            ORDER BY m.rank DESC, a.created_at DESC, a.id DESC
+            LIMIT $1
+            "#n,
+            limit,
+            id,
+            created_at,
+            filter.query,
+            by_user,
+            filter.author,
+            filter.tag,
+            filter.from,
+            filter.to,
+            filter.published_only,
+        )
+        .fetch_all(&mut **transaction)
+        .await
+    }
+
+    async fn counts(
+        transaction: &mut Transaction<'_, Postgres>,
+        filter: &SearchFilter,
+        by_user: Option<&str>,
+    ) -> Result<SearchCounts, Error> {
+        sqlx::query_as!(
+            SearchCounts,
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM matching_articles($1, $3, $4, $5, $6, $7, $2)) AS "articles!",
+                (
+                    SELECT COUNT(*)
+                    FROM users
+                    WHERE deleted_at IS NULL AND username ILIKE $8
+                ) AS "users!",
+                (SELECT COUNT(*) FROM tags WHERE tag_status = 'APPROVED' AND label ILIKE $8) AS "tags!",
+                (
+                    SELECT COUNT(*)
+                    FROM lists
+                    WHERE (visibility = 'PUBLIC' OR user_id = $2) AND label ILIKE $8
+                ) AS "lists!",
+                (SELECT COUNT(*) FROM series WHERE label ILIKE $8) AS "series!"
+            "#n,
+            filter.query,
+            by_user,
+            filter.author,
+            filter.tag,
+            filter.from,
+            filter.to,
+            filter.published_only,
+            contains_pattern(&filter.query),
+        )
+        .fetch_one(&mut **transaction)
+        .await
+    }
+
+    async fn tag_facets(
+        transaction: &mut Transaction<'_, Postgres>,
+        filter: &SearchFilter,
+        limit: i64,
+        by_user: Option<&str>,
+    ) -> Result<Vec<TagFacet>, Error> {
+        sqlx::query_as!(
+            TagFacet,
+            r#"
+            SELECT
+                t.slug,
+                t.label,
+                COUNT(*) AS "count!"
+            FROM matching_articles($1, $3, $4, $5, $6, $7, $2) m
+            JOIN articletags at ON m.article_id = at.article_id
+            JOIN tags t ON at.tag_slug = t.slug
+            WHERE t.tag_status = 'APPROVED'
+            GROUP BY t.slug
+            ORDER BY COUNT(*) DESC, t.slug ASC
+            LIMIT $8
+            "#n,
+            filter.query,
+            by_user,
+            filter.author,
+            filter.tag,
+            filter.from,
+            filter.to,
+            filter.published_only,
+            limit,
+        )
+        .fetch_all(&mut **transaction)
+        .await
+    }
+
+    async fn suggest_users(
+        transaction: &mut Transaction<'_, Postgres>,
+        query: &str,
+        limit: i64,
+    ) -> Result<Vec<Suggestion>, Error> {
+        sqlx::query_as!(
+            Suggestion,
+            r#"
+            SELECT
+                u.id,
+                u.username AS label,
+                u.image,
+                CASE
+                    WHEN u.username ILIKE $2 THEN 1
+                    ELSE similarity(u.username, $1)
+                END::REAL AS "score!"
+            FROM users u
+            WHERE u.deleted_at IS NULL
+                AND (u.username ILIKE $2 OR u.username % $1)
+            ORDER BY
+                u.username ILIKE $2 DESC,
+                similarity(u.username, $1) DESC,
+                u.username ASC
+            LIMIT $3
+            "#n,
+            query,
+            prefix_pattern(query),
+            limit,
+        )
+        .fetch_all(&mut **transaction)
+        .await
+    }
+
+    async fn suggest_tags(
+        transaction: &mut Transaction<'_, Postgres>,
+        query: &str,
+        limit: i64,
+    ) -> Result<Vec<Suggestion>, Error> {
+        sqlx::query_as!(
+            Suggestion,
+            r#"
+            SELECT
+                t.slug AS id,
+                t.label,
+                NULL::TEXT AS image,
+                CASE
+                    WHEN t.label ILIKE $2 THEN 1
+                    ELSE similarity(t.label, $1)
+                END::REAL AS "score!"
+            FROM tags t
+            WHERE t.tag_status = 'APPROVED'
+                AND (t.label ILIKE $2 OR t.label % $1)
+            ORDER BY
+                t.label ILIKE $2 DESC,
+                similarity(t.label, $1) DESC,
+                t.article_count DESC,
+                t.label ASC
+            LIMIT $3
+            "#n,
+            query,
+            prefix_pattern(query),
+            limit,
+        )
+        .fetch_all(&mut **transaction)
+        .await
+    }
+}
@@ -4,6 +4,7 @@ use sqlx::{Database, Error, Postgres, Transaction};
 
 use crate::models::{
     article_model::FullArticle,
+    asset_model::ImagePlaceholder,
     list_model::List,
     series_model::{CreateSeries, Series, UpdateSeries},
     tag_model::Tag,
@@ -72,7 +73,7 @@ impl SeriesRepository<Postgres, Error> for SeriesRepositoryImpl {
         sqlx::query_as!(
             Series,
             r#"
-            SELECT *
+            SELECT *, NULL AS "image_placeholder: ImagePlaceholder"
             FROM series
             WHERE (($2::timestamptz IS NULL AND $3::text IS NULL)
                     OR (created_at, id) < ($2, $3))
@@ -96,7 +97,7 @@ impl SeriesRepository<Postgres, Error> for SeriesRepositoryImpl {
         sqlx::query_as!(
             Series,
             r#"
-            SELECT *
+            SELECT *, NULL AS "image_placeholder: ImagePlaceholder"
             FROM series
             WHERE id = $1
             "#n,
@@ -119,7 +120,7 @@ impl SeriesRepository<Postgres, Error> for SeriesRepositoryImpl {
                 image
             )
             VALUES ($1, $2, $3)
-            RETURNING *
+            RETURNING *, NULL AS "image_placeholder: ImagePlaceholder"
             "#n,
             create_series.user_id,
             create_series.label,
@@ -141,7 +142,7 @@ impl SeriesRepository<Postgres, Error> for SeriesRepositoryImpl {
                 label = coalesce($2, series.label),
                 image = coalesce($3, series.image)
             WHERE id = $1
-            RETURNING *
+            RETURNING *, NULL AS "image_placeholder: ImagePlaceholder"
             "#n,
             update_series.id,
             update_series.label,
@@ -160,7 +161,7 @@ impl SeriesRepository<Postgres, Error> for SeriesRepositoryImpl {
             r#"
             DELETE FROM series
             WHERE id = $1
-            RETURNING *
+            RETURNING *, NULL AS "image_placeholder: ImagePlaceholder"
             "#n,
             series_id
         )
@@ -4,6 +4,7 @@ use sqlx::{Database, Error, Postgres, Transaction};
 
 use crate::models::{
     article_model::FullArticle,
+    asset_model::ImagePlaceholder,
     enums::{Role, Visibility},
     list_model::List,
     series_model::Series,
@@ -144,7 +145,8 @@ impl UserRepository<Postgres, Error> for UserRepositoryImpl {
                     WHEN $4 IS NULL THEN FALSE
                     WHEN f.follower_id IS NOT NULL THEN TRUE
                     ELSE FALSE
-                END AS followed
+                END AS followed,
+                NULL AS "image_placeholder: ImagePlaceholder"
             FROM users u
             LEFT JOIN follow f ON u.id = f.following_id AND f.follower_id = $4
             WHERE ($5::text IS NULL OR u.username ILIKE $5) AND (($2::timestamptz IS NULL AND $3::text IS NULL) OR (u.created_at, u.id) < ($2, $3))
@@ -181,7 +183,9 @@ impl UserRepository<Postgres, Error> for UserRepositoryImpl {
                 following_count,
                 created_at,
                 approved_at,
-                deleted_at
+                deleted_at,
+                locale,
+                NULL AS "image_placeholder: ImagePlaceholder"
             FROM users
             WHERE id = $1"#,
             user_id,
@@ -210,7 +214,9 @@ impl UserRepository<Postgres, Error> for UserRepositoryImpl {
                 following_count,
                 created_at,
                 approved_at,
-                deleted_at
+                deleted_at,
+                locale,
+                NULL AS "image_placeholder: ImagePlaceholder"
             FROM users
             WHERE email = $1
             "#,
@@ -245,7 +251,8 @@ impl UserRepository<Postgres, Error> for UserRepositoryImpl {
                     WHEN $2 IS NULL THEN FALSE
                     WHEN f.follower_id IS NOT NULL THEN TRUE
                     ELSE FALSE
-                END AS followed
+                END AS followed,
+                NULL AS "image_placeholder: ImagePlaceholder"
             FROM users u
             LEFT JOIN follow f ON u.id = f.following_id AND f.follower_id = $2
             WHERE u.username = $1
@@ -278,7 +285,9 @@ impl UserRepository<Postgres, Error> for UserRepositoryImpl {
                 u.following_count,
                 u.created_at,
                 u.approved_at,
-                u.deleted_at
+                u.deleted_at,
+                u.locale,
+                NULL AS "image_placeholder: ImagePlaceholder"
             FROM users u 
             JOIN accounts a ON u.id = a.user_id
             WHERE a.provider = $1 AND a.provider_account_id = $2
@@ -390,7 +399,8 @@ impl UserRepository<Postgres, Error> for UserRepositoryImpl {
                 l.visibility AS "visibility: Visibility",
                 l.article_count,
                 l.created_at,
-                l.updated_at
+                l.updated_at,
+                NULL AS "image_placeholder: ImagePlaceholder"
             FROM lists l
             LEFT JOIN users u ON l.user_id = u.id
             WHERE u.username = $5
@@ -427,7 +437,7 @@ impl UserRepository<Postgres, Error> for UserRepositoryImpl {
         sqlx::query_as!(
             Series,
             r#"
-            SELECT s.*
+            SELECT s.*, NULL AS "image_placeholder: ImagePlaceholder"
             FROM series s
             LEFT JOIN users u ON s.user_id = u.id
             WHERE u.username = $4
@@ -452,8 +462,8 @@ impl UserRepository<Postgres, Error> for UserRepositoryImpl {
         sqlx::query_as!(
             User,
             r#"
-            INSERT INTO users (username, email, image)
-            VALUES ($1, $2, $3)
+            INSERT INTO users (username, email, image, locale)
+            VALUES ($1, $2, $3, COALESCE($4, 'en'))
             RETURNING
                 id,
                 username,
@@ -467,11 +477,14 @@ impl UserRepository<Postgres, Error> for UserRepositoryImpl {
                 following_count,
                 created_at,
                 approved_at,
-                deleted_at
+                deleted_at,
+                locale,
+                NULL AS "image_placeholder: ImagePlaceholder"
             "#,
             create_user.username,
             create_user.email,
             create_user.image,
+            create_user.locale,
         )
         .fetch_one(&mut **transaction)
         .await
@@ -489,7 +502,8 @@ impl UserRepository<Postgres, Error> for UserRepositoryImpl {
                 username = coalesce($2, users.username),
                 image = coalesce($3, users.image),
                 bio = coalesce($4, users.bio),
-                urls = coalesce($5, users.urls)
+                urls = coalesce($5, users.urls),
+                locale = coalesce($6, users.locale)
             WHERE 
                 id = $1
             RETURNING 
@@ -505,13 +519,16 @@ impl UserRepository<Postgres, Error> for UserRepositoryImpl {
                 following_count,
                 created_at,
                 approved_at,
-                deleted_at
+                deleted_at,
+                locale,
+                NULL AS "image_placeholder: ImagePlaceholder"
             "#,
             update_user.id,
             update_user.username,
             update_user.image,
             update_user.bio,
-            &update_user.urls
+            &update_user.urls,
+            update_user.locale
         )
         .fetch_one(&mut **transaction)
         .await
@@ -542,7 +559,9 @@ impl UserRepository<Postgres, Error> for UserRepositoryImpl {
                 following_count,
                 created_at,
                 approved_at,
-                deleted_at
+                deleted_at,
+                locale,
+                NULL AS "image_placeholder: ImagePlaceholder"
             "#,
             user_id
         )
@@ -575,7 +594,9 @@ impl UserRepository<Postgres, Error> for UserRepositoryImpl {
                 following_count,
                 created_at,
                 approved_at,
-                deleted_at
+                deleted_at,
+                locale,
+                NULL AS "image_placeholder: ImagePlaceholder"
             "#,
             user_id
         )
@@ -608,7 +629,9 @@ impl UserRepository<Postgres, Error> for UserRepositoryImpl {
                 following_count,
                 created_at,
                 approved_at,
-                deleted_at
+                deleted_at,
+                locale,
+                NULL AS "image_placeholder: ImagePlaceholder"
             "#,
             user_id
         )
@@ -641,7 +664,9 @@ impl UserRepository<Postgres, Error> for UserRepositoryImpl {
                 following_count,
                 created_at,
                 approved_at,
-                deleted_at
+                deleted_at,
+                locale,
+                NULL AS "image_placeholder: ImagePlaceholder"
             "#,
             user_id
         )
@@ -671,7 +696,9 @@ impl UserRepository<Postgres, Error> for UserRepositoryImpl {
                 following_count,
                 created_at,
                 approved_at,
-                deleted_at
+                deleted_at,
+                locale,
+                NULL AS "image_placeholder: ImagePlaceholder"
             "#,
             user_id
         )
@@ -781,7 +808,8 @@ impl UserRepository<Postgres, Error> for UserRepositoryImpl {
                     WHEN $5::text IS NULL THEN FALSE
                     WHEN fo.follower_id IS NOT NULL THEN TRUE
                     ELSE FALSE
-                END AS followed
+                END AS followed,
+                NULL AS "image_placeholder: ImagePlaceholder"
             FROM following fn
             LEFT JOIN follow fo ON fn.id = fo.following_id AND fo.follower_id = $5
             "#,
@@ -833,7 +861,8 @@ impl UserRepository<Postgres, Error> for UserRepositoryImpl {
                     WHEN $5::text IS NULL THEN FALSE
                     WHEN fo.follower_id IS NOT NULL THEN TRUE
                     ELSE FALSE
-                END AS followed
+                END AS followed,
+                NULL AS "image_placeholder: ImagePlaceholder"
             FROM followers fs
             LEFT JOIN follow fo ON fs.id = fo.following_id AND fo.follower_id = $5
             "#,
@@ -0,0 +1,385 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{Database, Error, Postgres, Transaction};
+
+use crate::models::{
+    enums::{WebhookDeliveryStatus, WebhookEvent},
+    webhook_model::{CreateWebhook, PendingDelivery, UpdateWebhook, Webhook, WebhookDelivery},
+};
+
+#[async_trait]
+pub trait WebhookRepository<DB, E>
+where
+    DB: Database,
+{
+    async fn find_all(
+        transaction: &mut Transaction<'_, DB>,
+        user_id: &str,
+    ) -> Result<Vec<Webhook>, E>;
+    async fn find(
+        transaction: &mut Transaction<'_, DB>,
+        user_id: &str,
+        webhook_id: &str,
+    ) -> Result<Webhook, E>;
+    async fn create(
+        transaction: &mut Transaction<'_, DB>,
+        create_webhook: &CreateWebhook,
+    ) -> Result<Webhook, E>;
+    async fn update(
+        transaction: &mut Transaction<'_, DB>,
+        update_webhook: &UpdateWebhook,
+    ) -> Result<Webhook, E>;
+    async fn delete(
+        transaction: &mut Transaction<'_, DB>,
+        user_id: &str,
+        webhook_id: &str,
+    ) -> Result<Webhook, E>;
+    async fn enqueue(
+        transaction: &mut Transaction<'_, DB>,
+        event: WebhookEvent,
+        subjects: &[String],
+        payload: &serde_json::Value,
+    ) -> Result<u64, E>;
+    async fn find_deliveries(
+        transaction: &mut Transaction<'_, DB>,
+        webhook_id: &str,
+        limit: i64,
+        id: Option<&str>,
+        created_at: Option<DateTime<Utc>>,
+    ) -> Result<Vec<WebhookDelivery>, E>;
+    async fn redeliver(
+        transaction: &mut Transaction<'_, DB>,
+        webhook_id: &str,
+        delivery_id: &str,
+    ) -> Result<WebhookDelivery, E>;
+    async fn claim_due(
+        transaction: &mut Transaction<'_, DB>,
+        limit: i64,
+        lease: i64,
+    ) -> Result<Vec<PendingDelivery>, E>;
+    async fn complete(
+        transaction: &mut Transaction<'_, DB>,
+        delivery_id: &str,
+        status: WebhookDeliveryStatus,
+        next_attempt_at: Option<DateTime<Utc>>,
+        response_status: Option<i32>,
+        error: Option<&str>,
+    ) -> Result<(), E>;
+}
+
+#[derive(Debug, Clone)]
+pub struct WebhookRepositoryImpl;
+
+#[async_trait]
+impl WebhookRepository<Postgres, Error> for WebhookRepositoryImpl {
+    async fn find_all(
+        transaction: &mut Transaction<'_, Postgres>,
+        user_id: &str,
+    ) -> Result<Vec<Webhook>, Error> {
+        sqlx::query_as!(
+            Webhook,
+            r#"
+            SELECT
+                id,
+                user_id,
+                url,
+                events AS "events: Vec<WebhookEvent>",
+                global,
+                active,
+                NULL::text AS secret,
+                created_at,
+                updated_at
+            FROM webhooks
+            WHERE user_id = $1
+            ORDER BY created_at DESC, id DESC
+            "#n,
+            user_id
+        )
+        .fetch_all(&mut **transaction)
+        .await
+    }
+
+    async fn find(
+        transaction: &mut Transaction<'_, Postgres>,
+        user_id: &str,
+        webhook_id: &str,
+    ) -> Result<Webhook, Error> {
+        sqlx::query_as!(
+            Webhook,
+            r#"
+            SELECT
+                id,
+                user_id,
+                url,
+                events AS "events: Vec<WebhookEvent>",
+                global,
+                active,
+                NULL::text AS secret,
+                created_at,
+                updated_at
+            FROM webhooks
+            WHERE user_id = $1 AND id = $2
+            "#n,
+            user_id,
+            webhook_id
+        )
+        .fetch_one(&mut **transaction)
+        .await
+    }
+
+    async fn create(
+        transaction: &mut Transaction<'_, Postgres>,
+        create_webhook: &CreateWebhook,
+    ) -> Result<Webhook, Error> {
+        sqlx::query_as!(
+            Webhook,
+            r#"
+            INSERT INTO webhooks (user_id, url, events, global)
+            VALUES ($1, $2, $3, $4)
+            RETURNING
+                id,
+                user_id,
+                url,
+                events AS "events: Vec<WebhookEvent>",
+                global,
+                active,
+                secret AS "secret?",
+                created_at,
+                updated_at
+            "#n,
+            create_webhook.user_id,
+            create_webhook.url,
+            &create_webhook.events as &[WebhookEvent],
+            create_webhook.global
+        )
+        .fetch_one(&mut **transaction)
+        .await
+    }
+
+    async fn update(
+        transaction: &mut Transaction<'_, Postgres>,
+        update_webhook: &UpdateWebhook,
+    ) -> Result<Webhook, Error> {
+        sqlx::query_as!(
+            Webhook,
+            r#"
+            UPDATE webhooks
+            SET
+                url = coalesce($3, webhooks.url),
+                events = coalesce($4, webhooks.events),
+                active = coalesce($5, webhooks.active)
+            WHERE id = $1 AND user_id = $2
+            RETURNING
+                id,
+                user_id,
+                url,
+                events AS "events: Vec<WebhookEvent>",
+                global,
+                active,
+                NULL::text AS secret,
+                created_at,
+                updated_at
+            "#n,
+            update_webhook.id,
+            update_webhook.user_id,
+            update_webhook.url,
+            update_webhook.events.as_deref() as Option<&[WebhookEvent]>,
+            update_webhook.active
+        )
+        .fetch_one(&mut **transaction)
+        .await
+    }
+
+    async fn delete(
+        transaction: &mut Transaction<'_, Postgres>,
+        user_id: &str,
+        webhook_id: &str,
+    ) -> Result<Webhook, Error> {
+        sqlx::query_as!(
+            Webhook,
+            r#"
+            DELETE FROM webhooks
+            WHERE id = $2 AND user_id = $1
+            RETURNING
+                id,
+                user_id,
+                url,
+                events AS "events: Vec<WebhookEvent>",
+                global,
+                active,
+                NULL::text AS secret,
+                created_at,
+                updated_at
+            "#n,
+            user_id,
+            webhook_id
+        )
+        .fetch_one(&mut **transaction)
+        .await
+    }
+
+    async fn enqueue(
+        transaction: &mut Transaction<'_, Postgres>,
+        event: WebhookEvent,
+        subjects: &[String],
+        payload: &serde_json::Value,
+    ) -> Result<u64, Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO webhookdeliveries (webhook_id, event, payload)
+            SELECT w.id, $1, $3
+            FROM webhooks w
+            WHERE w.active
+                AND $1 = ANY(w.events)
+                AND (w.global OR w.user_id = ANY($2))
+            "#n,
+            event as WebhookEvent,
+            subjects,
+            payload
+        )
+        .execute(&mut **transaction)
+        .await
+        .map(|result| result.rows_affected())
+    }
+
+    async fn find_deliveries(
+        transaction: &mut Transaction<'_, Postgres>,
+        webhook_id: &str,
+        limit: i64,
+        id: Option<&str>,
+        created_at: Option<DateTime<Utc>>,
+    ) -> Result<Vec<WebhookDelivery>, Error> {
+        sqlx::query_as!(
+            WebhookDelivery,
+            r#"
+            SELECT
+                id,
+                webhook_id,
+                event AS "event: WebhookEvent",
+                payload,
+                status AS "status: WebhookDeliveryStatus",
+                attempts,
+                next_attempt_at,
+                response_status,
+                error,
+                created_at,
+                delivered_at
+            FROM webhookdeliveries
+            WHERE webhook_id = $1
+                AND (($3::text IS NULL AND $4::timestamptz IS NULL) OR (created_at, id) < ($4, $3))
+            ORDER BY created_at DESC, id DESC
+            LIMIT $2
+            "#n,
+            webhook_id,
+            limit,
+            id,
+            created_at
+        )
+        .fetch_all(&mut **transaction)
+        .await
+    }
+
+    async fn redeliver(
+        transaction: &mut Transaction<'_, Postgres>,
+        webhook_id: &str,
+        delivery_id: &str,
+    ) -> Result<WebhookDelivery, Error> {
+        sqlx::query_as!(
+            WebhookDelivery,
+            r#"
+            INSERT INTO webhookdeliveries (webhook_id, event, payload)
+            SELECT webhook_id, event, payload
+            FROM webhookdeliveries
+            WHERE webhook_id = $1 AND id = $2
+            RETURNING
+                id,
+                webhook_id,
+                event AS "event: WebhookEvent",
+                payload,
+                status AS "status: WebhookDeliveryStatus",
+                attempts,
+                next_attempt_at,
+                response_status,
+                error,
+                created_at,
+                delivered_at
+            "#n,
+            webhook_id,
+            delivery_id
+        )
+        .fetch_one(&mut **transaction)
+        .await
+    }
+
+    async fn claim_due(
+        transaction: &mut Transaction<'_, Postgres>,
+        limit: i64,
+        lease: i64,
+    ) -> Result<Vec<PendingDelivery>, Error> {
+        sqlx::query_as!(
+            PendingDelivery,
+            r#"
+            WITH due AS (
+                SELECT d.id
+                FROM webhookdeliveries d
+                JOIN webhooks w ON w.id = d.webhook_id
+                WHERE d.status = 'PENDING'
+                    AND d.next_attempt_at <= now()
+                    AND w.active
+                ORDER BY d.next_attempt_at
+                LIMIT $1
+                FOR UPDATE OF d SKIP LOCKED
+            )
+            UPDATE webhookdeliveries d
+            SET
+                attempts = d.attempts + 1,
+                next_attempt_at = now() + make_interval(secs => $2)
+            FROM due, webhooks w
+            WHERE d.id = due.id AND w.id = d.webhook_id
+            RETURNING
+                d.id,
+                d.webhook_id,
+                d.event AS "event: WebhookEvent",
+                d.payload,
+                d.attempts,
+                d.created_at,
+                w.url,
+                w.secret
+            "#n,
+            limit,
+            lease as f64
+        )
+        .fetch_all(&mut **transaction)
+        .await
+    }
+
+    async fn complete(
+        transaction: &mut Transaction<'_, Postgres>,
+        delivery_id: &str,
+        status: WebhookDeliveryStatus,
+        next_attempt_at: Option<DateTime<Utc>>,
+        response_status: Option<i32>,
+        error: Option<&str>,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            UPDATE webhookdeliveries
+            SET
+                status = $2,
+                next_attempt_at = coalesce($3, next_attempt_at),
+                response_status = $4,
+                error = $5,
+                delivered_at = CASE WHEN $2 = 'SUCCEEDED' THEN now() ELSE delivered_at END
+            WHERE id = $1
+            "#n,
+            delivery_id,
+            status as WebhookDeliveryStatus,
+            next_attempt_at,
+            response_status,
+            error
+        )
+        .execute(&mut **transaction)
+        .await
+        .map(|_| ())
+    }
+}
@@ -0,0 +1,297 @@
+use crate::models::{
+    article_model::{DiffBlock, DiffSegment},
+    enums::DiffOp,
+};
+
+/// Elements an article is split into blocks at
+const BLOCK_TAGS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "details",
+    "div",
+    "dl",
+    "figure",
+    "footer",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "ul",
+];
+
+/// Block elements without content or a closing tag
+const VOID_TAGS: &[&str] = &["hr"];
+
+/// Past this many cells comparing two sequences costs too much, whatever is
+/// left after the common start and end is then shown as replaced
+const MAX_CELLS: usize = 4_000_000;
+
+enum Edit {
+    Equal(usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Name of the element a tag opens or closes, and whether it closes it
+fn parse_tag(tag: &str) -> Option<(String, bool)> {
+    let inner = tag.strip_prefix('<')?;
+    let (inner, closing) = match inner.strip_prefix('/') {
+        Some(inner) => (inner, true),
+        None => (inner, false),
+    };
+    let name: String = inner
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    if name.is_empty() {
+        return None;
+    }
+    Some((name, closing))
+}
+
+/// Top level block elements, and text between them, trimmed
+pub fn blocks(html: &str) -> Vec<&str> {
+    fn push<'a>(blocks: &mut Vec<&'a str>, block: &'a str) {
+        let block = block.trim();
+        if !block.is_empty() {
+            blocks.push(block);
+        }
+    }
+
+    let mut blocks = vec![];
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut pos = 0;
+
+    while pos < html.len() {
+        let rest = &html[pos..];
+        if !rest.starts_with('<') {
+            pos += rest.find('<').unwrap_or(rest.len());
+            continue;
+        }
+
+        let end = rest.find('>').map_or(html.len(), |i| pos + i + 1);
+        let tag = &html[pos..end];
+
+        if let Some((name, closing)) = parse_tag(tag) {
+            if BLOCK_TAGS.contains(&name.as_str()) {
+                if closing {
+                    depth = depth.saturating_sub(1);
+                } else {
+                    if depth == 0 {
+                        push(&mut blocks, &html[start..pos]);
+                        start = pos;
+                    }
+                    if !VOID_TAGS.contains(&name.as_str()) && !tag.ends_with("/>") {
+                        depth += 1;
+                    }
+                }
+                if depth == 0 {
+                    push(&mut blocks, &html[start..end]);
+                    start = end;
+                }
+            }
+        }
+
+        pos = end;
+    }
+
+    push(&mut blocks, &html[start..]);
+    blocks
+}
+
+/// Tags, words, runs of whitespace and single other characters
+pub fn words(block: &str) -> Vec<&str> {
+    let mut words = vec![];
+    let mut chars = block.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        let mut end = start + c.len_utf8();
+        if c == '<' {
+            end = block[start..]
+                .find('>')
+                .map_or(block.len(), |i| start + i + 1);
+            while chars.peek().is_some_and(|(i, _)| *i < end) {
+                chars.next();
+            }
+        } else if c.is_alphanumeric() || c.is_whitespace() {
+            let alphanumeric = c.is_alphanumeric();
+            let same = |next: char| match alphanumeric {
+                true => next.is_alphanumeric(),
+                false => next.is_whitespace(),
+            };
+            while let Some((i, next)) = chars.next_if(|(_, next)| same(*next)) {
+                end = i + next.len_utf8();
+            }
+        }
+        words.push(&block[start..end]);
+    }
+
+    words
+}
+
+/// Shortest edits turning `old` into `new`, deletions before insertions
+fn edits<T: PartialEq>(old: &[T], new: &[T]) -> Vec<Edit> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let (n, m) = (old.len() - prefix - suffix, new.len() - prefix - suffix);
+    let mut edits: Vec<Edit> = (0..prefix).map(Edit::Equal).collect();
+
+    if (n + 1) * (m + 1) > MAX_CELLS {
+        edits.extend((prefix..prefix + n).map(Edit::Delete));
+        edits.extend((prefix..prefix + m).map(Edit::Insert));
+    } else {
+        let old_middle = &old[prefix..prefix + n];
+        let new_middle = &new[prefix..prefix + m];
+
+        // Longest common subsequence of what follows each pair of positions
+        let mut lengths = vec![0u32; (n + 1) * (m + 1)];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lengths[i * (m + 1) + j] = if old_middle[i] == new_middle[j] {
+                    lengths[(i + 1) * (m + 1) + j + 1] + 1
+                } else {
+                    lengths[(i + 1) * (m + 1) + j].max(lengths[i * (m + 1) + j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            if i < n && j < m && old_middle[i] == new_middle[j] {
+                edits.push(Edit::Equal(prefix + i));
+                i += 1;
+                j += 1;
+            } else if j == m
+                || (i < n && lengths[(i + 1) * (m + 1) + j] >= lengths[i * (m + 1) + j + 1])
+            {
+                edits.push(Edit::Delete(prefix + i));
+                i += 1;
+            } else {
+                edits.push(Edit::Insert(prefix + j));
+                j += 1;
+            }
+        }
+    }
+
+    edits.extend((old.len() - suffix..old.len()).map(Edit::Equal));
+    edits
+}
+
+fn push_segment(segments: &mut Vec<DiffSegment>, op: DiffOp, text: &str) {
+    match segments.last_mut() {
+        Some(last) if last.op == op => last.text.push_str(text),
+        _ => segments.push(DiffSegment {
+            op,
+            text: text.to_owned(),
+        }),
+    }
+}
+
+/// Word level changes within a block
+pub fn diff_words(old: &str, new: &str) -> Vec<DiffSegment> {
+    let old_words = words(old);
+    let new_words = words(new);
+
+    let mut segments = vec![];
+    for edit in edits(&old_words, &new_words) {
+        match edit {
+            Edit::Equal(i) => push_segment(&mut segments, DiffOp::Equal, old_words[i]),
+            Edit::Delete(i) => push_segment(&mut segments, DiffOp::Delete, old_words[i]),
+            Edit::Insert(j) => push_segment(&mut segments, DiffOp::Insert, new_words[j]),
+        }
+    }
+    segments
+}
+
+fn whole(op: DiffOp, block: &str) -> DiffBlock {
+    DiffBlock {
+        op,
+        segments: vec![DiffSegment {
+            op,
+            text: block.to_owned(),
+        }],
+    }
+}
+
+/// Blocks removed in place of others are compared word by word, the rest
+/// are removed or added whole
+fn flush<'a>(diff: &mut Vec<DiffBlock>, deleted: &mut Vec<&'a str>, inserted: &mut Vec<&'a str>) {
+    let replaced = deleted.len().min(inserted.len());
+    for (old, new) in deleted.iter().zip(inserted.iter()) {
+        diff.push(DiffBlock {
+            op: DiffOp::Replace,
+            segments: diff_words(old, new),
+        });
+    }
+    for old in deleted.drain(..).skip(replaced) {
+        diff.push(whole(DiffOp::Delete, old));
+    }
+    for new in inserted.drain(..).skip(replaced) {
+        diff.push(whole(DiffOp::Insert, new));
+    }
+}
+
+/// Blocks added, removed, replaced or kept between two versions of an
+/// article
+pub fn diff_html(old: &str, new: &str) -> Vec<DiffBlock> {
+    let old_blocks = blocks(old);
+    let new_blocks = blocks(new);
+
+    let mut diff = vec![];
+    let mut deleted = vec![];
+    let mut inserted = vec![];
+
+    for edit in edits(&old_blocks, &new_blocks) {
+        match edit {
+            Edit::Equal(i) => {
+                flush(&mut diff, &mut deleted, &mut inserted);
+                diff.push(whole(DiffOp::Equal, old_blocks[i]));
+            }
+            Edit::Delete(i) => deleted.push(old_blocks[i]),
+            Edit::Insert(j) => inserted.push(new_blocks[j]),
+        }
+    }
+    flush(&mut diff, &mut deleted, &mut inserted);
+
+    diff
+}
+
+/// Words inserted and deleted, leaving out markup
+pub fn changed_words(diff: &[DiffBlock]) -> (i64, i64) {
+    let count = |text: &str| {
+        words(text)
+            .iter()
+            .filter(|word| word.chars().next().is_some_and(char::is_alphanumeric))
+            .count() as i64
+    };
+
+    let (mut insertions, mut deletions) = (0, 0);
+    for segment in diff.iter().flat_map(|block| block.segments.iter()) {
+        match segment.op {
+            DiffOp::Insert => insertions += count(&segment.text),
+            DiffOp::Delete => deletions += count(&segment.text),
+            _ => {}
+        }
+    }
+    (insertions, deletions)
+}
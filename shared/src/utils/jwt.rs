@@ -5,7 +5,10 @@ use jsonwebtoken::{
 use secrecy::ExposeSecret;
 use serde::{Deserialize, Serialize};
 
-use crate::{configuration::CONFIG, models::enums::Role};
+use crate::{
+    configuration::CONFIG,
+    models::enums::{NotificationEvent, Role},
+};
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Claims<T> {
@@ -119,3 +122,65 @@ impl JWT<RefreshTokenPayload> for RefreshToken {
         .map(|token_data| token_data.claims)
     }
 }
+
+pub struct UnsubscribeToken;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct UnsubscribeTokenPayload {
+    pub user_id: String,
+    pub event: NotificationEvent,
+}
+
+impl UnsubscribeToken {
+    /// One-click unsubscribe URL for `List-Unsubscribe` headers and email footers.
+    pub fn link(payload: UnsubscribeTokenPayload) -> Result<String, Error> {
+        let token = Self::generate(payload)?;
+        let scheme = match CONFIG.api_server.ssl {
+            true => "https",
+            false => "http",
+        };
+        Ok(format!(
+            "{}://{}:{}/api/unsubscribe?token={}",
+            scheme, CONFIG.api_server.host, CONFIG.api_server.port, token
+        ))
+    }
+}
+
+impl JWT<UnsubscribeTokenPayload> for UnsubscribeToken {
+    fn generate(payload: UnsubscribeTokenPayload) -> Result<String, Error> {
+        let now = Utc::now();
+
+        let expiration = now
+            .checked_add_signed(chrono::Duration::days(
+                CONFIG.auth.unsubscribe_token_duration,
+            ))
+            .expect("valid timestamp");
+
+        let claims = Claims {
+            iat: now.timestamp(),
+            exp: expiration.timestamp(),
+            iss: CONFIG.api_server.host.to_string(),
+            sub: payload.user_id.clone(),
+            payload,
+        };
+
+        encode(
+            &Header::new(Algorithm::HS256),
+            &claims,
+            &EncodingKey::from_secret(CONFIG.auth.secret.expose_secret().as_bytes()),
+        )
+    }
+
+    fn validate(token: &str) -> Result<Claims<UnsubscribeTokenPayload>, Error> {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.set_issuer(&[&CONFIG.api_server.host]);
+        validation.validate_exp = true;
+
+        decode::<Claims<UnsubscribeTokenPayload>>(
+            &token,
+            &DecodingKey::from_secret(CONFIG.auth.secret.expose_secret().as_bytes()),
+            &validation,
+        )
+        .map(|token_data| token_data.claims)
+    }
+}
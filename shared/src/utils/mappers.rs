@@ -1,10 +1,17 @@
+use crate::article::{ArticleDiff, DiffBlock, DiffOp, DiffSegment, SearchSort, Snapshot};
 use crate::common::{
-    Article, ArticleVersion, Comment, CommentableType, FullArticle, FullComment, FullList,
-    FullUser, List, Role, Series, Tag, TagStatus, User, Visibility,
+    Article, ArticleVersion, Comment, CommentableType, DigestFrequency, FullArticle, FullComment,
+    FullList, FullUser, ImagePlaceholder, List, NotificationEvent, NotificationSetting,
+    NotificationSettings, Role, Series, Tag, TagStatus, User, Visibility,
 };
 use crate::models::{
-    article_model, comment_model, enums, list_model, series_model, tag_model, user_model,
+    article_model, asset_model, comment_model, enums, list_model, newsletter_model,
+    notification_model, search_model, series_model, tag_model, user_model, webhook_model,
 };
+use crate::newsletter::{Issue, Subscriber, SubscriberStatus};
+use crate::search::{SearchFilter, SearchType, SuggestType, Suggestion, TagFacet};
+use crate::storage_proto::{Asset, Orphan};
+use crate::webhook::{Delivery, DeliveryStatus, Webhook, WebhookEvent};
 use chrono::{DateTime, Utc};
 
 struct W<T>(T);
@@ -128,6 +135,108 @@ impl From<CommentableType> for enums::CommentableType {
     }
 }
 
+impl From<enums::NotificationEvent> for NotificationEvent {
+    fn from(value: enums::NotificationEvent) -> Self {
+        match value {
+            enums::NotificationEvent::Follow => Self::Follow,
+            enums::NotificationEvent::Comment => Self::Comment,
+            enums::NotificationEvent::Like => Self::Like,
+            enums::NotificationEvent::CoauthorInvite => Self::CoauthorInvite,
+            enums::NotificationEvent::Digest => Self::Digest,
+        }
+    }
+}
+
+impl From<NotificationEvent> for enums::NotificationEvent {
+    fn from(value: NotificationEvent) -> Self {
+        match value {
+            NotificationEvent::Follow => Self::Follow,
+            NotificationEvent::Comment => Self::Comment,
+            NotificationEvent::Like => Self::Like,
+            NotificationEvent::CoauthorInvite => Self::CoauthorInvite,
+            NotificationEvent::Digest => Self::Digest,
+        }
+    }
+}
+
+impl From<enums::DigestFrequency> for DigestFrequency {
+    fn from(value: enums::DigestFrequency) -> Self {
+        match value {
+            enums::DigestFrequency::Never => Self::Never,
+            enums::DigestFrequency::Daily => Self::Daily,
+            enums::DigestFrequency::Weekly => Self::Weekly,
+        }
+    }
+}
+
+impl From<DigestFrequency> for enums::DigestFrequency {
+    fn from(value: DigestFrequency) -> Self {
+        match value {
+            DigestFrequency::Never => Self::Never,
+            DigestFrequency::Daily => Self::Daily,
+            DigestFrequency::Weekly => Self::Weekly,
+        }
+    }
+}
+
+impl From<SearchSort> for enums::SearchSort {
+    fn from(value: SearchSort) -> Self {
+        match value {
+            SearchSort::Relevance => Self::Relevance,
+            SearchSort::Newest => Self::Newest,
+        }
+    }
+}
+
+impl From<enums::SearchSort> for SearchSort {
+    fn from(value: enums::SearchSort) -> Self {
+        match value {
+            enums::SearchSort::Relevance => Self::Relevance,
+            enums::SearchSort::Newest => Self::Newest,
+        }
+    }
+}
+
+impl From<DiffOp> for enums::DiffOp {
+    fn from(value: DiffOp) -> Self {
+        match value {
+            DiffOp::Equal => Self::Equal,
+            DiffOp::Insert => Self::Insert,
+            DiffOp::Delete => Self::Delete,
+            DiffOp::Replace => Self::Replace,
+        }
+    }
+}
+
+impl From<enums::DiffOp> for DiffOp {
+    fn from(value: enums::DiffOp) -> Self {
+        match value {
+            enums::DiffOp::Equal => Self::Equal,
+            enums::DiffOp::Insert => Self::Insert,
+            enums::DiffOp::Delete => Self::Delete,
+            enums::DiffOp::Replace => Self::Replace,
+        }
+    }
+}
+
+impl From<&asset_model::ImagePlaceholder> for ImagePlaceholder {
+    fn from(value: &asset_model::ImagePlaceholder) -> Self {
+        ImagePlaceholder {
+            blurhash: value.blurhash.clone(),
+            dominant_color: value.dominant_color.clone(),
+        }
+    }
+}
+
+impl From<&ImagePlaceholder> for asset_model::ImagePlaceholder {
+    fn from(value: &ImagePlaceholder) -> Self {
+        asset_model::ImagePlaceholder {
+            blurhash: value.blurhash.clone(),
+            dominant_color: value.dominant_color.clone(),
+        }
+    }
+}
+
 impl From<&user_model::User> for User {
     fn from(value: &user_model::User) -> Self {
         User {
@@ -136,6 +245,7 @@ impl From<&user_model::User> for User {
             email_verified: W(value.email_verified.as_ref()).into(),
             username: value.username.clone(),
             image: value.image.clone(),
+            image_placeholder: value.image_placeholder.as_ref().map(ImagePlaceholder::from),
             role: Role::from(value.role) as i32,
             bio: value.bio.clone(),
             urls: value.urls.clone(),
@@ -144,6 +254,7 @@ impl From<&user_model::User> for User {
             created_at: W(&value.created_at).into(),
             approved_at: W(value.approved_at.as_ref()).into(),
             deleted_at: W(value.deleted_at.as_ref()).into(),
+            locale: value.locale.clone(),
         }
     }
 }
@@ -156,6 +267,10 @@ impl From<&User> for user_model::User {
             email_verified: W(value.email_verified.as_ref()).into(),
             username: value.username.clone(),
             image: value.image.clone(),
+            image_placeholder: value
+                .image_placeholder
+                .as_ref()
+                .map(asset_model::ImagePlaceholder::from),
             role: value.role().into(),
             bio: value.bio.clone(),
             urls: value.urls.clone(),
@@ -164,6 +279,7 @@ impl From<&User> for user_model::User {
             created_at: W(value.created_at.as_ref()).into(),
             approved_at: W(value.approved_at.as_ref()).into(),
             deleted_at: W(value.deleted_at.as_ref()).into(),
+            locale: value.locale.clone(),
         }
     }
 }
@@ -176,6 +292,7 @@ impl From<&user_model::FullUser> for FullUser {
             email_verified: W(value.email_verified.as_ref()).into(),
             username: value.username.clone(),
             image: value.image.clone(),
+            image_placeholder: value.image_placeholder.as_ref().map(ImagePlaceholder::from),
             bio: value.bio.clone(),
             urls: value.urls.clone(),
             following_count: value.following_count,
@@ -196,6 +313,10 @@ impl From<&FullUser> for user_model::FullUser {
             email_verified: W(value.email_verified.as_ref()).into(),
             username: value.username.clone(),
             image: value.image.clone(),
+            image_placeholder: value
+                .image_placeholder
+                .as_ref()
+                .map(asset_model::ImagePlaceholder::from),
             bio: value.bio.clone(),
             urls: value.urls.clone(),
             following_count: value.following_count,
@@ -247,6 +368,7 @@ impl From<&series_model::Series> for Series {
             user_id: value.user_id.clone(),
             label: value.label.clone(),
             image: value.image.clone(),
+            image_placeholder: value.image_placeholder.as_ref().map(ImagePlaceholder::from),
             article_count: value.article_count,
             created_at: W(&value.created_at).into(),
             updated_at: W(value.updated_at.as_ref()).into(),
@@ -261,6 +383,10 @@ impl From<&Series> for series_model::Series {
             user_id: value.user_id.clone(),
             label: value.label.clone(),
             image: value.image.clone(),
+            image_placeholder: value
+                .image_placeholder
+                .as_ref()
+                .map(asset_model::ImagePlaceholder::from),
             article_count: value.article_count,
             created_at: W(value.created_at.as_ref()).into(),
             updated_at: W(value.updated_at.as_ref()).into(),
@@ -298,6 +424,7 @@ impl From<&article_model::FullArticle> for FullArticle {
             },
             order: value.order,
             liked: value.liked,
+            headline: None,
         }
     }
 }
@@ -355,6 +482,7 @@ impl From<&list_model::List> for List {
             user_id: value.user_id.clone(),
             label: value.label.clone(),
             image: value.image.clone(),
+            image_placeholder: value.image_placeholder.as_ref().map(ImagePlaceholder::from),
             visibility: Visibility::from(value.visibility) as i32,
             article_count: value.article_count,
             created_at: W(&value.created_at).into(),
@@ -370,6 +498,10 @@ impl From<&List> for list_model::List {
             user_id: value.user_id.clone(),
             label: value.label.clone(),
             image: value.image.clone(),
+            image_placeholder: value
+                .image_placeholder
+                .as_ref()
+                .map(asset_model::ImagePlaceholder::from),
             visibility: enums::Visibility::from(value.visibility()),
             article_count: value.article_count,
             created_at: W(value.created_at.as_ref()).into(),
@@ -385,6 +517,7 @@ impl From<&list_model::FullList> for FullList {
             user: Some(FullUser::from(&value.user)),
             label: value.label.clone(),
             image: value.image.clone(),
+            image_placeholder: value.image_placeholder.as_ref().map(ImagePlaceholder::from),
             visibility: Visibility::from(value.visibility) as i32,
             article_count: value.article_count,
             created_at: W(&value.created_at).into(),
@@ -400,6 +533,10 @@ impl From<&FullList> for list_model::FullList {
             label: value.label.clone(),
             user: user_model::FullUser::from(&value.user.clone().unwrap()),
             image: value.image.clone(),
+            image_placeholder: value
+                .image_placeholder
+                .as_ref()
+                .map(asset_model::ImagePlaceholder::from),
             visibility: enums::Visibility::from(value.visibility()),
             article_count: value.article_count,
             created_at: W(value.created_at.as_ref()).into(),
@@ -415,6 +552,7 @@ impl From<&comment_model::Comment> for Comment {
             commenter_id: value.commenter_id.clone(),
             target_id: value.target_id.clone(),
             r#type: CommentableType::from(value.r#type) as i32,
+            parent_id: value.parent_id.clone(),
             content: value.content.clone(),
             created_at: W(&value.created_at).into(),
             updated_at: W(value.updated_at.as_ref()).into(),
@@ -429,6 +567,7 @@ impl From<&Comment> for comment_model::Comment {
             commenter_id: value.commenter_id.clone(),
             target_id: value.target_id.clone(),
             r#type: value.r#type().into(),
+            parent_id: value.parent_id.clone(),
             content: value.content.clone(),
             created_at: W(value.created_at.as_ref()).into(),
             updated_at: W(value.updated_at.as_ref()).into(),
@@ -443,6 +582,7 @@ impl From<&comment_model::FullComment> for FullComment {
             commenter_id: value.commenter_id.clone(),
             target_id: value.target_id.clone(),
             r#type: CommentableType::from(value.r#type) as i32,
+            parent_id: value.parent_id.clone(),
             content: value.content.clone(),
             created_at: W(&value.created_at).into(),
             updated_at: W(value.updated_at.as_ref()).into(),
@@ -460,6 +600,7 @@ impl From<&FullComment> for comment_model::FullComment {
             commenter_id: value.commenter_id.clone(),
             target_id: value.target_id.clone(),
             r#type: value.r#type().into(),
+            parent_id: value.parent_id.clone(),
             content: value.content.clone(),
             created_at: W(value.created_at.as_ref()).into(),
             updated_at: W(value.updated_at.as_ref()).into(),
@@ -519,3 +660,483 @@ impl From<&ArticleVersion> for article_model::ArticleVersion {
         }
     }
 }
+
+impl From<&notification_model::NotificationSetting> for NotificationSetting {
+    fn from(value: &notification_model::NotificationSetting) -> Self {
+        Self {
+            event: NotificationEvent::from(value.event) as i32,
+            in_app: value.in_app,
+            email: value.email,
+        }
+    }
+}
+
+impl From<&NotificationSetting> for notification_model::NotificationSetting {
+    fn from(value: &NotificationSetting) -> Self {
+        Self {
+            event: value.event().into(),
+            in_app: value.in_app,
+            email: value.email,
+        }
+    }
+}
+
+impl From<&NotificationSettings> for notification_model::NotificationSettings {
+    fn from(value: &NotificationSettings) -> Self {
+        Self {
+            settings: value
+                .settings
+                .iter()
+                .map(|setting| notification_model::NotificationSetting::from(setting))
+                .collect(),
+            digest_frequency: value.digest_frequency().into(),
+        }
+    }
+}
+
+impl From<enums::WebhookEvent> for WebhookEvent {
+    fn from(value: enums::WebhookEvent) -> Self {
+        match value {
+            enums::WebhookEvent::ArticlePublished => Self::ArticlePublished,
+            enums::WebhookEvent::CommentCreated => Self::CommentCreated,
+            enums::WebhookEvent::UserFollowed => Self::UserFollowed,
+        }
+    }
+}
+
+impl From<WebhookEvent> for enums::WebhookEvent {
+    fn from(value: WebhookEvent) -> Self {
+        match value {
+            WebhookEvent::ArticlePublished => Self::ArticlePublished,
+            WebhookEvent::CommentCreated => Self::CommentCreated,
+            WebhookEvent::UserFollowed => Self::UserFollowed,
+        }
+    }
+}
+
+impl From<enums::WebhookDeliveryStatus> for DeliveryStatus {
+    fn from(value: enums::WebhookDeliveryStatus) -> Self {
+        match value {
+            enums::WebhookDeliveryStatus::Pending => Self::Pending,
+            enums::WebhookDeliveryStatus::Succeeded => Self::Succeeded,
+            enums::WebhookDeliveryStatus::Failed => Self::Failed,
+        }
+    }
+}
+
+impl From<DeliveryStatus> for enums::WebhookDeliveryStatus {
+    fn from(value: DeliveryStatus) -> Self {
+        match value {
+            DeliveryStatus::Pending => Self::Pending,
+            DeliveryStatus::Succeeded => Self::Succeeded,
+            DeliveryStatus::Failed => Self::Failed,
+        }
+    }
+}
+
+impl From<&webhook_model::Webhook> for Webhook {
+    fn from(value: &webhook_model::Webhook) -> Self {
+        Self {
+            id: value.id.clone(),
+            user_id: value.user_id.clone(),
+            url: value.url.clone(),
+            events: value
+                .events
+                .iter()
+                .map(|event| WebhookEvent::from(*event) as i32)
+                .collect(),
+            global: value.global,
+            active: value.active,
+            secret: value.secret.clone(),
+            created_at: W(&value.created_at).into(),
+            updated_at: W(value.updated_at.as_ref()).into(),
+        }
+    }
+}
+
+impl From<&Webhook> for webhook_model::Webhook {
+    fn from(value: &Webhook) -> Self {
+        Self {
+            id: value.id.clone(),
+            user_id: value.user_id.clone(),
+            url: value.url.clone(),
+            events: value.events().map(|event| event.into()).collect(),
+            global: value.global,
+            active: value.active,
+            secret: value.secret.clone(),
+            created_at: W(value.created_at.as_ref()).into(),
+            updated_at: W(value.updated_at.as_ref()).into(),
+        }
+    }
+}
+
+impl From<&webhook_model::WebhookDelivery> for Delivery {
+    fn from(value: &webhook_model::WebhookDelivery) -> Self {
+        Self {
+            id: value.id.clone(),
+            webhook_id: value.webhook_id.clone(),
+            event: WebhookEvent::from(value.event) as i32,
+            payload: value.payload.to_string(),
+            status: DeliveryStatus::from(value.status) as i32,
+            attempts: value.attempts,
+            next_attempt_at: W(&value.next_attempt_at).into(),
+            response_status: value.response_status,
+            error: value.error.clone(),
+            created_at: W(&value.created_at).into(),
+            delivered_at: W(value.delivered_at.as_ref()).into(),
+        }
+    }
+}
+
+impl From<&Delivery> for webhook_model::WebhookDelivery {
+    fn from(value: &Delivery) -> Self {
+        Self {
+            id: value.id.clone(),
+            webhook_id: value.webhook_id.clone(),
+            event: value.event().into(),
+            payload: serde_json::from_str(&value.payload).unwrap_or_default(),
+            status: value.status().into(),
+            attempts: value.attempts,
+            next_attempt_at: W(value.next_attempt_at.as_ref()).into(),
+            response_status: value.response_status,
+            error: value.error.clone(),
+            created_at: W(value.created_at.as_ref()).into(),
+            delivered_at: W(value.delivered_at.as_ref()).into(),
+        }
+    }
+}
+
+impl From<enums::SubscriberStatus> for SubscriberStatus {
+    fn from(value: enums::SubscriberStatus) -> Self {
+        match value {
+            enums::SubscriberStatus::Pending => Self::Pending,
+            enums::SubscriberStatus::Confirmed => Self::Confirmed,
+            enums::SubscriberStatus::Unsubscribed => Self::Unsubscribed,
+            enums::SubscriberStatus::Bounced => Self::Bounced,
+        }
+    }
+}
+
+impl From<SubscriberStatus> for enums::SubscriberStatus {
+    fn from(value: SubscriberStatus) -> Self {
+        match value {
+            SubscriberStatus::Pending => Self::Pending,
+            SubscriberStatus::Confirmed => Self::Confirmed,
+            SubscriberStatus::Unsubscribed => Self::Unsubscribed,
+            SubscriberStatus::Bounced => Self::Bounced,
+        }
+    }
+}
+
+impl From<&newsletter_model::NewsletterSubscriber> for Subscriber {
+    fn from(value: &newsletter_model::NewsletterSubscriber) -> Self {
+        Self {
+            id: value.id.clone(),
+            author_id: value.author_id.clone(),
+            email: value.email.clone(),
+            locale: value.locale.clone(),
+            status: SubscriberStatus::from(value.status) as i32,
+            created_at: W(&value.created_at).into(),
+            confirmed_at: W(value.confirmed_at.as_ref()).into(),
+        }
+    }
+}
+
+impl From<&Subscriber> for newsletter_model::NewsletterSubscriber {
+    fn from(value: &Subscriber) -> Self {
+        Self {
+            id: value.id.clone(),
+            author_id: value.author_id.clone(),
+            email: value.email.clone(),
+            locale: value.locale.clone(),
+            status: value.status().into(),
+            token: String::new(),
+            created_at: W(value.created_at.as_ref()).into(),
+            confirmed_at: W(value.confirmed_at.as_ref()).into(),
+        }
+    }
+}
+
+impl From<&newsletter_model::NewsletterIssue> for Issue {
+    fn from(value: &newsletter_model::NewsletterIssue) -> Self {
+        Self {
+            id: value.id.clone(),
+            article_id: value.article_id.clone(),
+            title: value.title.clone(),
+            recipients: value.recipients,
+            sent: value.sent,
+            opened: value.opened,
+            bounced: value.bounced,
+            failed: value.failed,
+            created_at: W(&value.created_at).into(),
+        }
+    }
+}
+
+impl From<&Issue> for newsletter_model::NewsletterIssue {
+    fn from(value: &Issue) -> Self {
+        Self {
+            id: value.id.clone(),
+            article_id: value.article_id.clone(),
+            title: value.title.clone(),
+            recipients: value.recipients,
+            sent: value.sent,
+            opened: value.opened,
+            bounced: value.bounced,
+            failed: value.failed,
+            created_at: W(value.created_at.as_ref()).into(),
+        }
+    }
+}
+
+impl From<&asset_model::Asset> for Asset {
+    fn from(value: &asset_model::Asset) -> Self {
+        Self {
+            id: value.id.clone(),
+            user_id: value.user_id.clone(),
+            asset_name: value.asset_name.clone(),
+            file_name: value.file_name.clone(),
+            content_type: value.content_type.clone(),
+            size: value.size as u64,
+            checksum: value.checksum.clone(),
+            width: value.width.map(|width| width as u32),
+            height: value.height.map(|height| height as u32),
+            created_at: W(&value.created_at).into(),
+            blurhash: value.blurhash.clone(),
+            dominant_color: value.dominant_color.clone(),
+        }
+    }
+}
+
+impl From<&Asset> for asset_model::Asset {
+    fn from(value: &Asset) -> Self {
+        Self {
+            id: value.id.clone(),
+            user_id: value.user_id.clone(),
+            asset_name: value.asset_name.clone(),
+            file_name: value.file_name.clone(),
+            content_type: value.content_type.clone(),
+            size: value.size as i64,
+            checksum: value.checksum.clone(),
+            width: value.width.map(|width| width as i32),
+            height: value.height.map(|height| height as i32),
+            blurhash: value.blurhash.clone(),
+            dominant_color: value.dominant_color.clone(),
+            created_at: W(value.created_at.as_ref()).into(),
+        }
+    }
+}
+
+impl From<&asset_model::Orphan> for Orphan {
+    fn from(value: &asset_model::Orphan) -> Self {
+        Self {
+            id: value.id.clone(),
+            user_id: value.user_id.clone(),
+            asset_name: value.asset_name.clone(),
+            file_name: value.file_name.clone(),
+            size: value.size as u64,
+            created_at: W(&value.created_at).into(),
+            orphaned_at: W(value.orphaned_at.as_ref()).into(),
+            expired: false,
+        }
+    }
+}
+
+impl From<&Orphan> for asset_model::Orphan {
+    fn from(value: &Orphan) -> Self {
+        Self {
+            id: value.id.clone(),
+            user_id: value.user_id.clone(),
+            asset_name: value.asset_name.clone(),
+            file_name: value.file_name.clone(),
+            size: value.size as i64,
+            created_at: W(value.created_at.as_ref()).into(),
+            orphaned_at: W(value.orphaned_at.as_ref()).into(),
+        }
+    }
+}
+
+impl From<enums::SearchType> for SearchType {
+    fn from(value: enums::SearchType) -> Self {
+        match value {
+            enums::SearchType::Articles => Self::Articles,
+            enums::SearchType::Users => Self::Users,
+            enums::SearchType::Tags => Self::Tags,
+            enums::SearchType::Lists => Self::Lists,
+            enums::SearchType::Series => Self::Series,
+        }
+    }
+}
+
+impl From<&search_model::SearchFilter> for SearchFilter {
+    fn from(value: &search_model::SearchFilter) -> Self {
+        SearchFilter {
+            query: value.query.clone(),
+            author: value.author.clone(),
+            tag: value.tag.clone(),
+            from: W(value.from.as_ref()).into(),
+            to: W(value.to.as_ref()).into(),
+            published_only: value.published_only,
+        }
+    }
+}
+
+impl From<&SearchFilter> for search_model::SearchFilter {
+    fn from(value: &SearchFilter) -> Self {
+        search_model::SearchFilter {
+            query: value.query.clone(),
+            author: value.author.clone(),
+            tag: value.tag.clone(),
+            from: W(value.from.as_ref()).into(),
+            to: W(value.to.as_ref()).into(),
+            published_only: value.published_only,
+        }
+    }
+}
+
+impl From<&search_model::TagFacet> for TagFacet {
+    fn from(value: &search_model::TagFacet) -> Self {
+        TagFacet {
+            slug: value.slug.clone(),
+            label: value.label.clone(),
+            count: value.count,
+        }
+    }
+}
+
+impl From<&TagFacet> for search_model::TagFacet {
+    fn from(value: &TagFacet) -> Self {
+        search_model::TagFacet {
+            slug: value.slug.clone(),
+            label: value.label.clone(),
+            count: value.count,
+        }
+    }
+}
+
+impl From<enums::SuggestType> for SuggestType {
+    fn from(value: enums::SuggestType) -> Self {
+        match value {
+            enums::SuggestType::User => Self::User,
+            enums::SuggestType::Tag => Self::Tag,
+        }
+    }
+}
+
+impl From<&search_model::Suggestion> for Suggestion {
+    fn from(value: &search_model::Suggestion) -> Self {
+        Suggestion {
+            id: value.id.clone(),
+            label: value.label.clone(),
+            image: value.image.clone(),
+            score: value.score,
+        }
+    }
+}
+
+impl From<&Suggestion> for search_model::Suggestion {
+    fn from(value: &Suggestion) -> Self {
+        search_model::Suggestion {
+            id: value.id.clone(),
+            label: value.label.clone(),
+            image: value.image.clone(),
+            score: value.score,
+        }
+    }
+}
+
+impl From<&article_model::DiffSegment> for DiffSegment {
+    fn from(value: &article_model::DiffSegment) -> Self {
+        DiffSegment {
+            op: DiffOp::from(value.op) as i32,
+            text: value.text.clone(),
+        }
+    }
+}
+
+impl From<&DiffSegment> for article_model::DiffSegment {
+    fn from(value: &DiffSegment) -> Self {
+        article_model::DiffSegment {
+            op: value.op().into(),
+            text: value.text.clone(),
+        }
+    }
+}
+
+impl From<&article_model::DiffBlock> for DiffBlock {
+    fn from(value: &article_model::DiffBlock) -> Self {
+        DiffBlock {
+            op: DiffOp::from(value.op) as i32,
+            segments: value.segments.iter().map(DiffSegment::from).collect(),
+        }
+    }
+}
+
+impl From<&DiffBlock> for article_model::DiffBlock {
+    fn from(value: &DiffBlock) -> Self {
+        article_model::DiffBlock {
+            op: value.op().into(),
+            segments: value
+                .segments
+                .iter()
+                .map(article_model::DiffSegment::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<&article_model::ArticleDiff> for ArticleDiff {
+    fn from(value: &article_model::ArticleDiff) -> Self {
+        ArticleDiff {
+            from: value.from.clone(),
+            to: value.to.clone(),
+            blocks: value.blocks.iter().map(DiffBlock::from).collect(),
+            insertions: value.insertions,
+            deletions: value.deletions,
+        }
+    }
+}
+
+impl From<&ArticleDiff> for article_model::ArticleDiff {
+    fn from(value: &ArticleDiff) -> Self {
+        article_model::ArticleDiff {
+            from: value.from.clone(),
+            to: value.to.clone(),
+            blocks: value
+                .blocks
+                .iter()
+                .map(article_model::DiffBlock::from)
+                .collect(),
+            insertions: value.insertions,
+            deletions: value.deletions,
+        }
+    }
+}
+
+impl From<&article_model::Snapshot> for Snapshot {
+    fn from(value: &article_model::Snapshot) -> Self {
+        Snapshot {
+            id: value.id.clone(),
+            version_id: value.version_id.clone(),
+            article_id: value.article_id.clone(),
+            user_id: value.user_id.clone(),
+            name: value.name.clone(),
+            note: value.note.clone(),
+            created_at: W(&value.created_at).into(),
+        }
+    }
+}
+
+impl From<&Snapshot> for article_model::Snapshot {
+    fn from(value: &Snapshot) -> Self {
+        article_model::Snapshot {
+            id: value.id.clone(),
+            version_id: value.version_id.clone(),
+            article_id: value.article_id.clone(),
+            user_id: value.user_id.clone(),
+            name: value.name.clone(),
+            note: value.note.clone(),
+            created_at: W(value.created_at.as_ref()).into(),
+        }
+    }
+}
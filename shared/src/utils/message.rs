@@ -1,7 +1,197 @@
+use amqprs::{BasicProperties, DELIVERY_MODE_PERSISTENT};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
-#[derive(Deserialize, Serialize, Debug)]
+use crate::models::comment_model::Comment;
+
+/// Version of the envelope and payload schemas produced by this build.
+/// Consumers reject envelopes newer than what they understand.
+pub const SCHEMA_VERSION: u16 = 1;
+
+/// Topic exchange domain events are published to, routed by message type
+pub const EVENTS_EXCHANGE: &str = "orta.events";
+
+pub const VERIFICATION: &str = "orta.notification.verification";
+pub const NEWSLETTER_CONFIRMATION: &str = "orta.notification.newsletter_confirmation";
+pub const COMMENT_CREATED: &str = "orta.event.comment_created";
+pub const ARTICLE_LIKED: &str = "orta.event.article_liked";
+pub const USER_FOLLOWED: &str = "orta.event.user_followed";
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct VerificationMessage {
     pub email: String,
     pub verification_link: String,
+    #[serde(default = "default_locale")]
+    pub locale: String,
+}
+
+fn default_locale() -> String {
+    String::from("en")
+}
+
+/// Double opt-in email for a newsletter subscription
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct NewsletterConfirmationMessage {
+    pub email: String,
+    pub author: String,
+    pub confirmation_link: String,
+    #[serde(default = "default_locale")]
+    pub locale: String,
+}
+
+/// `recipients` are the users to notify, owners of the commented content
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct CommentCreatedEvent {
+    pub comment: Comment,
+    pub recipients: Vec<String>,
+}
+
+/// Sent on both like and unlike, `recipients` are the article authors
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ArticleLikedEvent {
+    pub article_id: String,
+    pub user_id: String,
+    pub liked: bool,
+    pub like_count: i32,
+    pub recipients: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct UserFollowedEvent {
+    pub follower_id: String,
+    pub following_id: String,
+}
+
+/// Every message that goes through the broker, tagged with its message type
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(tag = "type", content = "data")]
+#[non_exhaustive]
+pub enum Payload {
+    #[serde(rename = "orta.notification.verification")]
+    Verification(VerificationMessage),
+    #[serde(rename = "orta.notification.newsletter_confirmation")]
+    NewsletterConfirmation(NewsletterConfirmationMessage),
+    #[serde(rename = "orta.event.comment_created")]
+    CommentCreated(CommentCreatedEvent),
+    #[serde(rename = "orta.event.article_liked")]
+    ArticleLiked(ArticleLikedEvent),
+    #[serde(rename = "orta.event.user_followed")]
+    UserFollowed(UserFollowedEvent),
+}
+
+impl Payload {
+    pub fn message_type(&self) -> &'static str {
+        match self {
+            Payload::Verification(_) => VERIFICATION,
+            Payload::NewsletterConfirmation(_) => NEWSLETTER_CONFIRMATION,
+            Payload::CommentCreated(_) => COMMENT_CREATED,
+            Payload::ArticleLiked(_) => ARTICLE_LIKED,
+            Payload::UserFollowed(_) => USER_FOLLOWED,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Envelope {
+    /// Unique per event, consumers use it to drop redeliveries
+    pub id: String,
+    pub version: u16,
+    pub created_at: DateTime<Utc>,
+    /// Id of the request or event that caused this one
+    pub correlation_id: Option<String>,
+    #[serde(flatten)]
+    pub payload: Payload,
+}
+
+#[derive(Debug)]
+pub enum EnvelopeError {
+    Malformed(serde_json::Error),
+    UnsupportedVersion(u16),
+}
+
+impl std::fmt::Display for EnvelopeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            EnvelopeError::Malformed(err) => write!(f, "Malformed message: {}", err),
+            EnvelopeError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported message version {}", version)
+            }
+        }
+    }
+}
+
+impl std::error::Error for EnvelopeError {}
+
+impl Envelope {
+    pub fn new(payload: Payload) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            version: SCHEMA_VERSION,
+            created_at: Utc::now(),
+            correlation_id: None,
+            payload,
+        }
+    }
+
+    pub fn with_correlation_id(mut self, correlation_id: &str) -> Self {
+        self.correlation_id = Some(correlation_id.to_owned());
+        self
+    }
+
+    /// Properties to publish the envelope with, mirrors its metadata so it
+    /// can be routed and inspected without parsing the body
+    pub fn properties(&self) -> BasicProperties {
+        let mut properties = BasicProperties::default();
+        properties
+            .with_message_id(&self.id)
+            .with_message_type(self.payload.message_type())
+            .with_content_type("application/json")
+            .with_timestamp(self.created_at.timestamp() as u64)
+            .with_delivery_mode(DELIVERY_MODE_PERSISTENT);
+        if let Some(correlation_id) = &self.correlation_id {
+            properties.with_correlation_id(correlation_id);
+        }
+        properties.finish()
+    }
+
+    /// Parses a message body. Bodies published before the envelope existed
+    /// carry only the payload, they are wrapped using the `message_type`
+    /// and `message_id` properties they were sent with.
+    pub fn parse(
+        content: &[u8],
+        message_type: Option<&str>,
+        message_id: Option<&str>,
+    ) -> Result<Self, EnvelopeError> {
+        let value: serde_json::Value =
+            serde_json::from_slice(content).map_err(EnvelopeError::Malformed)?;
+
+        if let Some(version) = value.get("version") {
+            let version = version
+                .as_u64()
+                .and_then(|version| u16::try_from(version).ok())
+                .unwrap_or(u16::MAX);
+            if version > SCHEMA_VERSION {
+                return Err(EnvelopeError::UnsupportedVersion(version));
+            }
+            return serde_json::from_value(value).map_err(EnvelopeError::Malformed);
+        }
+
+        match message_type {
+            Some(message_type) => Ok(Envelope {
+                id: message_id
+                    .map(|id| id.to_owned())
+                    .unwrap_or_else(|| Uuid::new_v4().to_string()),
+                version: 0,
+                created_at: Utc::now(),
+                correlation_id: None,
+                payload: serde_json::from_value(serde_json::json!({
+                    "type": message_type,
+                    "data": value,
+                }))
+                .map_err(EnvelopeError::Malformed)?,
+            }),
+            None => serde_json::from_value(value).map_err(EnvelopeError::Malformed),
+        }
+    }
 }
@@ -1,3 +1,8 @@
+pub mod diff;
 pub mod jwt;
 pub mod mappers;
 pub mod message;
+pub mod placeholder;
+pub mod reply;
+pub mod split_cursor;
+pub mod webhook;
@@ -0,0 +1,24 @@
+/// Length of the hex encoded sha256 checksum stored assets are named after
+const ASSET_NAME_LEN: usize = 64;
+
+/// Finds the stored asset an `image` field points at, whether it holds the
+/// bare name or a url to it
+pub fn asset_name(image: &str) -> Option<&str> {
+    let bytes = image.as_bytes();
+    let mut start = 0;
+
+    while start < bytes.len() {
+        let end = bytes[start..]
+            .iter()
+            .position(|byte| !matches!(byte, b'0'..=b'9' | b'a'..=b'f'))
+            .map_or(bytes.len(), |len| start + len);
+
+        if end - start == ASSET_NAME_LEN {
+            return Some(&image[start..end]);
+        }
+
+        start = end + 1;
+    }
+
+    None
+}
@@ -0,0 +1,80 @@
+use hmac::{digest::InvalidLength, Hmac, Mac};
+use sha2::Sha256;
+
+/// Local part prefix of reply addresses, `reply+{comment_id}.{signature}@{domain}`
+pub const PREFIX: &str = "reply+";
+
+/// Hex characters of the HMAC kept in the address, local parts are limited
+/// to 64 characters
+const SIGNATURE_LENGTH: usize = 16;
+
+fn mac(secret: &str, comment_id: &str, user_id: &str) -> Result<Hmac<Sha256>, InvalidLength> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())?;
+
+    mac.update(format!("{}.{}", comment_id, user_id).as_bytes());
+
+    Ok(mac)
+}
+
+/// Signs a comment for the user the notification is sent to. Only a reply
+/// coming from that user's email verifies, so a forwarded notification
+/// can not be used to comment as someone else.
+pub fn sign(secret: &str, comment_id: &str, user_id: &str) -> Result<String, InvalidLength> {
+    let mut signature = format!(
+        "{:x}",
+        mac(secret, comment_id, user_id)?.finalize().into_bytes()
+    );
+    signature.truncate(SIGNATURE_LENGTH);
+    Ok(signature)
+}
+
+pub fn address(
+    secret: &str,
+    domain: &str,
+    comment_id: &str,
+    user_id: &str,
+) -> Result<String, InvalidLength> {
+    Ok(format!(
+        "{}{}.{}@{}",
+        PREFIX,
+        comment_id,
+        sign(secret, comment_id, user_id)?,
+        domain
+    ))
+}
+
+/// Splits a reply address into the comment id and signature, `None` if it
+/// is not one of ours
+pub fn parse<'a>(address: &'a str, domain: &str) -> Option<(&'a str, &'a str)> {
+    let address = address.trim().trim_start_matches('<').trim_end_matches('>');
+    let (local, address_domain) = address.rsplit_once('@')?;
+
+    if !address_domain.eq_ignore_ascii_case(domain) {
+        return None;
+    }
+
+    let (comment_id, signature) = local.strip_prefix(PREFIX)?.split_once('.')?;
+
+    if comment_id.is_empty() || signature.len() != SIGNATURE_LENGTH {
+        return None;
+    }
+
+    Some((comment_id, signature))
+}
+
+/// Checks a signature made by [`sign`] in constant time
+pub fn verify(
+    secret: &str,
+    comment_id: &str,
+    user_id: &str,
+    signature: &str,
+) -> Result<bool, InvalidLength> {
+    let signature = match hex::decode(signature) {
+        Ok(signature) if signature.len() * 2 == SIGNATURE_LENGTH => signature,
+        _ => return Ok(false),
+    };
+
+    Ok(mac(secret, comment_id, user_id)?
+        .verify_truncated_left(&signature)
+        .is_ok())
+}
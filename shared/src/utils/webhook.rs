@@ -0,0 +1,131 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use hmac::{digest::InvalidLength, Hmac, Mac};
+use sha2::Sha256;
+
+pub const EVENT_HEADER: &str = "X-Orta-Event";
+pub const DELIVERY_HEADER: &str = "X-Orta-Delivery";
+pub const TIMESTAMP_HEADER: &str = "X-Orta-Timestamp";
+pub const SIGNATURE_HEADER: &str = "X-Orta-Signature";
+
+fn mac(secret: &str, timestamp: i64, body: &str) -> Result<Hmac<Sha256>, InvalidLength> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())?;
+
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+
+    Ok(mac)
+}
+
+/// `sha256=` followed by the hex HMAC-SHA256 of `{timestamp}.{body}`.
+/// Receivers recompute it with the webhook secret and reject stale
+/// timestamps to guard against replays.
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> Result<String, InvalidLength> {
+    Ok(format!(
+        "sha256={:x}",
+        mac(secret, timestamp, body)?.finalize().into_bytes()
+    ))
+}
+
+/// Checks a signature made by [`sign`] in constant time
+pub fn verify(
+    secret: &str,
+    timestamp: i64,
+    body: &str,
+    signature: &str,
+) -> Result<bool, InvalidLength> {
+    let signature = match signature
+        .strip_prefix("sha256=")
+        .and_then(|signature| hex::decode(signature).ok())
+    {
+        Some(signature) => signature,
+        None => return Ok(false),
+    };
+
+    Ok(mac(secret, timestamp, body)?
+        .verify_slice(&signature)
+        .is_ok())
+}
+
+/// Host and port of an `https://` url. Credentials and anything but plain
+/// host names and IP addresses are refused, so the host checked is the one
+/// the request goes to.
+pub fn https_host(url: &str) -> Option<(String, u16)> {
+    let rest = url.strip_prefix("https://")?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+
+    let (host, port) = match authority.strip_prefix('[') {
+        Some(bracketed) => {
+            let (host, port) = bracketed.split_once(']')?;
+            (host, port)
+        }
+        None => match authority.rsplit_once(':') {
+            Some((host, _)) => (host, &authority[host.len()..]),
+            None => (authority, ""),
+        },
+    };
+
+    let port = match port {
+        "" => 443,
+        port => port.strip_prefix(':')?.parse().ok()?,
+    };
+
+    let valid = |c: char| c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == ':';
+    if host.is_empty() || !host.chars().all(valid) {
+        return None;
+    }
+
+    Some((host.to_lowercase(), port))
+}
+
+/// Whether `ip` is reachable from the internet, rather than an address of
+/// this host or of a private network
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_ipv4(ip),
+            None => is_public_ipv6(ip),
+        },
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || a == 0
+        // Shared address space used by carrier-grade NAT
+        || (a == 100 && (64..128).contains(&b)))
+}
+
+fn is_public_ipv6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        // Unique local
+        || (first & 0xfe00) == 0xfc00
+        // Link local
+        || (first & 0xffc0) == 0xfe80)
+}
+
+/// Whether webhooks may be sent to `url`: it must be https and its host
+/// must only resolve to public addresses, so that webhooks can't be used to
+/// reach internal services
+pub async fn is_public_url(url: &str) -> bool {
+    let (host, port) = match https_host(url) {
+        Some(host) => host,
+        None => return false,
+    };
+
+    let addresses: Vec<SocketAddr> = match tokio::net::lookup_host((host.as_str(), port)).await {
+        Ok(addresses) => addresses.collect(),
+        Err(_) => return false,
+    };
+
+    !addresses.is_empty() && addresses.iter().all(|address| is_public_ip(address.ip()))
+}
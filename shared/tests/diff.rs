@@ -0,0 +1,66 @@
+use shared::{
+    models::enums::DiffOp,
+    utils::diff::{blocks, changed_words, diff_html, words},
+};
+
+#[test]
+pub fn diff_blocks_test() {
+    assert_eq!(
+        blocks("<h1>Title</h1>\n<p>One <b>two</b></p><hr><ul><li>a</li><li>b</li></ul>tail"),
+        vec![
+            "<h1>Title</h1>",
+            "<p>One <b>two</b></p>",
+            "<hr>",
+            "<ul><li>a</li><li>b</li></ul>",
+            "tail"
+        ]
+    );
+    assert_eq!(blocks("plain text"), vec!["plain text"]);
+    assert!(blocks("  \n").is_empty());
+}
+
+#[test]
+pub fn diff_words_test() {
+    assert_eq!(
+        words("Hello, <b>wide</b> world"),
+        vec!["Hello", ",", " ", "<b>", "wide", "</b>", " ", "world"]
+    );
+}
+
+#[test]
+pub fn diff_html_test() {
+    let old = "<h1>Title</h1><p>The quick fox</p><p>Removed</p>";
+    let new = "<h1>Title</h1><p>The slow fox</p><p>Added</p><p>More</p>";
+
+    let diff = diff_html(old, new);
+    let ops: Vec<DiffOp> = diff.iter().map(|block| block.op).collect();
+    assert_eq!(
+        ops,
+        vec![
+            DiffOp::Equal,
+            DiffOp::Replace,
+            DiffOp::Replace,
+            DiffOp::Insert
+        ]
+    );
+
+    let segments: Vec<(DiffOp, &str)> = diff[1]
+        .segments
+        .iter()
+        .map(|segment| (segment.op, segment.text.as_str()))
+        .collect();
+    assert_eq!(
+        segments,
+        vec![
+            (DiffOp::Equal, "<p>The "),
+            (DiffOp::Delete, "quick"),
+            (DiffOp::Insert, "slow"),
+            (DiffOp::Equal, " fox</p>"),
+        ]
+    );
+
+    assert_eq!(changed_words(&diff), (3, 2));
+    assert!(diff_html(old, old)
+        .iter()
+        .all(|block| block.op == DiffOp::Equal));
+}
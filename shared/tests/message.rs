@@ -0,0 +1,44 @@
+use shared::utils::message::{
+    Envelope, EnvelopeError, Payload, VerificationMessage, SCHEMA_VERSION, VERIFICATION,
+};
+
+#[test]
+pub fn envelope_round_trip_test() {
+    let envelope = Envelope::new(Payload::Verification(VerificationMessage {
+        email: String::from("user@example.com"),
+        verification_link: String::from("http://localhost/auth?token=token"),
+        locale: String::from("ru"),
+    }))
+    .with_correlation_id("request");
+
+    let content = serde_json::to_vec(&envelope).unwrap();
+    let parsed = Envelope::parse(&content, None, None).unwrap();
+
+    assert_eq!(parsed.id, envelope.id);
+    assert_eq!(parsed.version, SCHEMA_VERSION);
+    assert_eq!(parsed.correlation_id.as_deref(), Some("request"));
+    assert_eq!(parsed.payload.message_type(), VERIFICATION);
+}
+
+#[test]
+pub fn legacy_message_test() {
+    let content = br#"{"email":"user@example.com","verification_link":"link"}"#;
+    let parsed = Envelope::parse(content, Some(VERIFICATION), Some("legacy")).unwrap();
+
+    assert_eq!(parsed.id, "legacy");
+    assert_eq!(parsed.version, 0);
+    match parsed.payload {
+        Payload::Verification(message) => assert_eq!(message.locale, "en"),
+        _ => panic!("Unexpected payload"),
+    }
+}
+
+#[test]
+pub fn newer_version_is_rejected_test() {
+    let content = br#"{"id":"id","version":99,"type":"orta.future","data":{}}"#;
+
+    assert!(matches!(
+        Envelope::parse(content, None, None),
+        Err(EnvelopeError::UnsupportedVersion(99))
+    ));
+}
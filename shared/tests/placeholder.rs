@@ -0,0 +1,18 @@
+use shared::utils::placeholder::asset_name;
+
+#[test]
+pub fn placeholder_asset_name_test() {
+    let name = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+
+    assert_eq!(asset_name(name), Some(name));
+    assert_eq!(asset_name(&format!("/api/assets/{}", name)), Some(name));
+    assert_eq!(
+        asset_name(&format!("https://orta.kz/api/assets/{}?width=320", name)),
+        Some(name)
+    );
+
+    assert_eq!(asset_name("https://example.com/avatar.png"), None);
+    assert_eq!(asset_name(&name[1..]), None);
+    assert_eq!(asset_name(&format!("{}0", name)), None);
+    assert_eq!(asset_name(""), None);
+}
@@ -0,0 +1,23 @@
+use shared::utils::reply::{address, parse, verify};
+
+#[test]
+pub fn reply_address_test() {
+    let reply_to = address("secret", "reply.orta.kz", "c0mment1d", "us3r1d").unwrap();
+
+    assert!(reply_to.starts_with("reply+c0mment1d."));
+    assert!(reply_to.len() - "@reply.orta.kz".len() <= 64);
+
+    let (comment_id, signature) = parse(&reply_to, "REPLY.orta.kz").unwrap();
+    assert_eq!(comment_id, "c0mment1d");
+    assert!(verify("secret", comment_id, "us3r1d", signature).unwrap());
+    assert!(!verify("secret", comment_id, "0ther", signature).unwrap());
+    assert!(!verify("other", comment_id, "us3r1d", signature).unwrap());
+    assert!(verify("secret", comment_id, "us3r1d", &signature.to_uppercase()).unwrap());
+    assert!(!verify("secret", comment_id, "us3r1d", &signature[1..]).unwrap());
+    assert!(!verify("secret", comment_id, "us3r1d", "zzzzzzzzzzzzzzzz").unwrap());
+
+    assert!(parse(&format!("<{}>", reply_to), "reply.orta.kz").is_some());
+    assert!(parse(&reply_to, "orta.kz").is_none());
+    assert!(parse("reply+c0mment1d@reply.orta.kz", "reply.orta.kz").is_none());
+    assert!(parse("someone@reply.orta.kz", "reply.orta.kz").is_none());
+}
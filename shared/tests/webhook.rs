@@ -0,0 +1,63 @@
+use shared::utils::webhook::{https_host, is_public_ip, sign, verify};
+
+#[test]
+pub fn webhook_signature_test() {
+    let body = r#"{"event":"article.published"}"#;
+    let signature = sign("secret", 1700000000, body).unwrap();
+
+    assert!(signature.starts_with("sha256="));
+    assert_eq!(signature.len(), "sha256=".len() + 64);
+    assert!(verify("secret", 1700000000, body, &signature).unwrap());
+    assert!(!verify("secret", 1700000001, body, &signature).unwrap());
+    assert!(!verify("other", 1700000000, body, &signature).unwrap());
+    assert!(!verify("secret", 1700000000, body, &signature["sha256=".len()..]).unwrap());
+    assert!(!verify("secret", 1700000000, body, "sha256=not-hex").unwrap());
+}
+
+#[test]
+pub fn webhook_url_host_test() {
+    assert_eq!(
+        https_host("https://example.com/hooks?a=b"),
+        Some((String::from("example.com"), 443))
+    );
+    assert_eq!(
+        https_host("https://Example.com:8443"),
+        Some((String::from("example.com"), 8443))
+    );
+    assert_eq!(
+        https_host("https://[::1]:8443/"),
+        Some((String::from("::1"), 8443))
+    );
+    assert_eq!(https_host("http://example.com/"), None);
+    assert_eq!(https_host("https://user@example.com/"), None);
+    assert_eq!(https_host("https://127.0.0.1\\@example.com/"), None);
+    assert_eq!(https_host("https://example.com:port/"), None);
+    assert_eq!(https_host("https:///hooks"), None);
+}
+
+#[test]
+pub fn webhook_public_ip_test() {
+    for ip in [
+        "93.184.216.34",
+        "2606:2800:220:1::1",
+        "::ffff:93.184.216.34",
+    ] {
+        assert!(is_public_ip(ip.parse().unwrap()), "{}", ip);
+    }
+    for ip in [
+        "127.0.0.1",
+        "10.1.2.3",
+        "172.16.0.1",
+        "192.168.1.1",
+        "169.254.169.254",
+        "100.64.0.1",
+        "0.0.0.0",
+        "::1",
+        "::",
+        "fd00::1",
+        "fe80::1",
+        "::ffff:127.0.0.1",
+    ] {
+        assert!(!is_public_ip(ip.parse().unwrap()), "{}", ip);
+    }
+}
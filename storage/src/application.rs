@@ -1,4 +1,7 @@
-use std::net::{Ipv4Addr, SocketAddr};
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    sync::Arc,
+};
 
 use minio::s3::{
     args::{BucketExistsArgs, MakeBucketArgs},
@@ -8,33 +11,57 @@ use minio::s3::{
 };
 use secrecy::ExposeSecret;
 use shared::{
-    configuration::{Settings, StorageSettings},
+    configuration::{BlobStoreSettings, DatabaseSettings, Settings, StorageSettings},
     storage_proto::storage_service_server::StorageServiceServer,
 };
+use sqlx::{postgres::PgPoolOptions, PgPool};
 use tonic::transport::{server::Router, Server};
 use tracing::info;
 
-use crate::service::StorageServiceImpl;
+use crate::{
+    blob_store::{BlobStore, FsStore, MemoryStore, MinioStore, Signer},
+    collector::Collector,
+    service::StorageServiceImpl,
+};
+
+const MINIO_ENDPOINT: &str = "http://localhost:9000";
+
+/// What MinIO answers to unless configured otherwise
+const MINIO_REGION: &str = "us-east-1";
 
 pub struct Application {
     pub port: u16,
     pub server: Router,
     pub address: SocketAddr,
+    pub collector: Collector,
 }
 
 impl Application {
     pub async fn build(configuration: Settings) -> Result<Self, anyhow::Error> {
         info!("Building storage service");
 
-        let client = get_minio_client(&configuration.storage).await;
+        let store = get_blob_store(&configuration.storage).await;
+
+        let db = get_connection_pool(&configuration.database).await;
 
         let port = configuration.storage_server.port;
 
         let address = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
 
         let storage_service = StorageServiceImpl {
-            client,
-            bucket_name: configuration.storage.bucket_name,
+            db,
+            store,
+            renditions: configuration.storage.renditions,
+            quality: configuration.storage.quality,
+            max_image_size: configuration.storage.max_image_size,
+            presign_expiry: configuration.storage.presign_expiry,
+            grace_period: configuration.storage.collector.grace_period,
+            limits: configuration.storage.limits,
+        };
+
+        let collector = Collector {
+            service: storage_service.clone(),
+            settings: configuration.storage.collector,
         };
 
         let server = Server::builder().add_service(StorageServiceServer::new(storage_service));
@@ -45,17 +72,43 @@ impl Application {
             port,
             server,
             address,
+            collector,
         })
     }
 
     pub async fn run(self) -> Result<(), tonic::transport::Error> {
+        tokio::spawn(self.collector.run());
+
         info!("Server is running on {}", self.port);
         self.server.serve(self.address).await
     }
 }
 
+pub async fn get_connection_pool(configuration: &DatabaseSettings) -> PgPool {
+    PgPoolOptions::new().connect_lazy_with(configuration.with_db())
+}
+
+pub async fn get_blob_store(configuration: &StorageSettings) -> Arc<dyn BlobStore> {
+    info!("Keeping assets in {:?}", configuration.backend);
+
+    match &configuration.backend {
+        BlobStoreSettings::Minio => Arc::new(MinioStore::new(
+            get_minio_client(configuration).await,
+            &configuration.bucket_name,
+            Signer {
+                endpoint: MINIO_ENDPOINT.to_string(),
+                region: MINIO_REGION.to_string(),
+                access_key: configuration.access_key.expose_secret().to_owned(),
+                secret_key: configuration.secret_key.clone(),
+            },
+        )),
+        BlobStoreSettings::Filesystem { root } => Arc::new(FsStore::new(root)),
+        BlobStoreSettings::Memory => Arc::new(MemoryStore::new()),
+    }
+}
+
 pub async fn get_minio_client(configuration: &StorageSettings) -> Client {
-    let base_url = "http://localhost:9000".parse::<BaseUrl>().unwrap();
+    let base_url = MINIO_ENDPOINT.parse::<BaseUrl>().unwrap();
 
     info!("Trying to connect to MinIO at: `{:?}`", base_url);
 
@@ -0,0 +1,241 @@
+use std::{
+    io::{ErrorKind, SeekFrom},
+    path::{Component, Path, PathBuf},
+};
+
+use async_trait::async_trait;
+use futures::stream;
+use tokio::{
+    fs::{self, File},
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
+};
+use tracing::error;
+use uuid::Uuid;
+
+use super::{range, BlobError, BlobStore, BlobWriter, ObjectInfo, ObjectReader};
+
+/// Objects are read in chunks of this size
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Directory below the root for objects still being written, they are
+/// moved in place once complete
+const TEMPORARY: &str = ".tmp";
+
+/// Keeps objects as files below a root directory, names with slashes end up
+/// in subdirectories
+#[derive(Debug, Clone)]
+pub struct FsStore {
+    root: PathBuf,
+}
+
+impl FsStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Names may not leave the root or point into the temporary directory
+    fn path(&self, name: &str) -> Result<PathBuf, BlobError> {
+        let relative = Path::new(name);
+
+        let valid = relative
+            .components()
+            .next()
+            .is_some_and(|first| first != Component::Normal(TEMPORARY.as_ref()))
+            && relative
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)));
+
+        match valid {
+            true => Ok(self.root.join(relative)),
+            false => Err(BlobError::NotFound),
+        }
+    }
+
+    async fn temporary(&self) -> Result<PathBuf, BlobError> {
+        let directory = self.root.join(TEMPORARY);
+        fs::create_dir_all(&directory).await.map_err(io)?;
+        Ok(directory.join(Uuid::new_v4().to_string()))
+    }
+
+    /// Moves a complete file to where the object is read from
+    async fn publish(&self, temporary: &Path, path: &Path) -> Result<(), BlobError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await.map_err(io)?;
+        }
+        fs::rename(temporary, path).await.map_err(io)
+    }
+}
+
+#[async_trait]
+impl BlobStore for FsStore {
+    async fn put(&self, name: &str, data: &[u8]) -> Result<(), BlobError> {
+        let path = self.path(name)?;
+        let temporary = self.temporary().await?;
+
+        fs::write(&temporary, data).await.map_err(io)?;
+        self.publish(&temporary, &path).await
+    }
+
+    async fn writer(&self, name: &str) -> Result<Box<dyn BlobWriter>, BlobError> {
+        let path = self.path(name)?;
+        let temporary = self.temporary().await?;
+        let file = File::create(&temporary).await.map_err(io)?;
+
+        Ok(Box::new(FsWriter {
+            store: self.clone(),
+            file: Some(file),
+            temporary,
+            path,
+            size: 0,
+        }))
+    }
+
+    async fn get(
+        &self,
+        name: &str,
+        offset: Option<u64>,
+        length: Option<u64>,
+    ) -> Result<ObjectReader, BlobError> {
+        let mut file = File::open(self.path(name)?).await.map_err(io)?;
+        let size = file.metadata().await.map_err(io)?.len();
+
+        let (start, end) = range(size, offset, length);
+        file.seek(SeekFrom::Start(start)).await.map_err(io)?;
+
+        let chunks = stream::unfold(Some(file.take(end - start)), |reader| async move {
+            let mut reader = reader?;
+            let mut buffer = vec![0; CHUNK_SIZE];
+            match reader.read(&mut buffer).await {
+                Ok(0) => None,
+                Ok(read) => {
+                    buffer.truncate(read);
+                    Some((Ok(buffer), Some(reader)))
+                }
+                Err(err) => Some((Err(io(err)), None)),
+            }
+        });
+
+        Ok(ObjectReader {
+            size: end - start,
+            chunks: Box::pin(chunks),
+        })
+    }
+
+    async fn delete(&self, name: &str) -> Result<(), BlobError> {
+        match fs::remove_file(self.path(name)?).await {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(io(err)),
+        }
+    }
+
+    async fn head(&self, name: &str) -> Result<ObjectInfo, BlobError> {
+        let metadata = fs::metadata(self.path(name)?).await.map_err(io)?;
+
+        if !metadata.is_file() {
+            return Err(BlobError::NotFound);
+        }
+
+        Ok(ObjectInfo {
+            name: name.to_string(),
+            size: metadata.len(),
+            etag: None,
+        })
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<ObjectInfo>, BlobError> {
+        let mut objects = Vec::new();
+        let mut directories = vec![self.root.to_owned()];
+
+        while let Some(directory) = directories.pop() {
+            let mut entries = match fs::read_dir(&directory).await {
+                Ok(entries) => entries,
+                Err(err) if err.kind() == ErrorKind::NotFound => continue,
+                Err(err) => return Err(io(err)),
+            };
+
+            while let Some(entry) = entries.next_entry().await.map_err(io)? {
+                let path = entry.path();
+                let metadata = entry.metadata().await.map_err(io)?;
+
+                if metadata.is_dir() {
+                    if path != self.root.join(TEMPORARY) {
+                        directories.push(path);
+                    }
+                    continue;
+                }
+
+                let name = path
+                    .strip_prefix(&self.root)
+                    .unwrap_or(&path)
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+
+                if name.starts_with(prefix) {
+                    objects.push(ObjectInfo {
+                        name,
+                        size: metadata.len(),
+                        etag: None,
+                    });
+                }
+            }
+        }
+
+        Ok(objects)
+    }
+
+    async fn copy(&self, from: &str, to: &str) -> Result<(), BlobError> {
+        let from = self.path(from)?;
+        let to = self.path(to)?;
+        let temporary = self.temporary().await?;
+
+        fs::copy(&from, &temporary).await.map_err(io)?;
+        self.publish(&temporary, &to).await
+    }
+}
+
+pub struct FsWriter {
+    store: FsStore,
+    file: Option<File>,
+    temporary: PathBuf,
+    path: PathBuf,
+    size: u64,
+}
+
+#[async_trait]
+impl BlobWriter for FsWriter {
+    async fn write(&mut self, data: &[u8]) -> Result<(), BlobError> {
+        let file = self.file.as_mut().ok_or(BlobError::NotFound)?;
+        file.write_all(data).await.map_err(io)?;
+        self.size += data.len() as u64;
+        Ok(())
+    }
+
+    async fn finish(&mut self) -> Result<u64, BlobError> {
+        let mut file = self.file.take().ok_or(BlobError::NotFound)?;
+        file.flush().await.map_err(io)?;
+        drop(file);
+
+        self.store.publish(&self.temporary, &self.path).await?;
+
+        Ok(self.size)
+    }
+
+    async fn abort(&mut self) {
+        drop(self.file.take());
+        if let Err(err) = fs::remove_file(&self.temporary).await {
+            if err.kind() != ErrorKind::NotFound {
+                error!(?err);
+            }
+        }
+    }
+}
+
+fn io(err: std::io::Error) -> BlobError {
+    match err.kind() {
+        ErrorKind::NotFound => BlobError::NotFound,
+        _ => BlobError::Backend(err.into()),
+    }
+}
@@ -0,0 +1,126 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use async_trait::async_trait;
+use futures::stream;
+
+use super::{range, BlobError, BlobStore, BlobWriter, ObjectInfo, ObjectReader};
+
+type Objects = Arc<RwLock<HashMap<String, Vec<u8>>>>;
+
+/// Keeps objects in memory, for tests and local runs
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    objects: Objects,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl BlobStore for MemoryStore {
+    async fn put(&self, name: &str, data: &[u8]) -> Result<(), BlobError> {
+        self.objects
+            .write()
+            .unwrap()
+            .insert(name.to_string(), data.to_vec());
+        Ok(())
+    }
+
+    async fn writer(&self, name: &str) -> Result<Box<dyn BlobWriter>, BlobError> {
+        Ok(Box::new(MemoryWriter {
+            objects: self.objects.clone(),
+            name: name.to_string(),
+            buffer: Vec::new(),
+        }))
+    }
+
+    async fn get(
+        &self,
+        name: &str,
+        offset: Option<u64>,
+        length: Option<u64>,
+    ) -> Result<ObjectReader, BlobError> {
+        let objects = self.objects.read().unwrap();
+        let data = objects.get(name).ok_or(BlobError::NotFound)?;
+
+        let (start, end) = range(data.len() as u64, offset, length);
+        let chunk = data[start as usize..end as usize].to_vec();
+
+        Ok(ObjectReader {
+            size: end - start,
+            chunks: Box::pin(stream::iter((!chunk.is_empty()).then_some(Ok(chunk)))),
+        })
+    }
+
+    async fn delete(&self, name: &str) -> Result<(), BlobError> {
+        self.objects.write().unwrap().remove(name);
+        Ok(())
+    }
+
+    async fn head(&self, name: &str) -> Result<ObjectInfo, BlobError> {
+        let objects = self.objects.read().unwrap();
+        let data = objects.get(name).ok_or(BlobError::NotFound)?;
+
+        Ok(ObjectInfo {
+            name: name.to_string(),
+            size: data.len() as u64,
+            etag: None,
+        })
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<ObjectInfo>, BlobError> {
+        Ok(self
+            .objects
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(name, _)| name.starts_with(prefix))
+            .map(|(name, data)| ObjectInfo {
+                name: name.to_owned(),
+                size: data.len() as u64,
+                etag: None,
+            })
+            .collect())
+    }
+
+    async fn copy(&self, from: &str, to: &str) -> Result<(), BlobError> {
+        let mut objects = self.objects.write().unwrap();
+        let data = objects.get(from).ok_or(BlobError::NotFound)?.to_owned();
+        objects.insert(to.to_string(), data);
+        Ok(())
+    }
+}
+
+pub struct MemoryWriter {
+    objects: Objects,
+    name: String,
+    buffer: Vec<u8>,
+}
+
+#[async_trait]
+impl BlobWriter for MemoryWriter {
+    async fn write(&mut self, data: &[u8]) -> Result<(), BlobError> {
+        self.buffer.extend_from_slice(data);
+        Ok(())
+    }
+
+    async fn finish(&mut self) -> Result<u64, BlobError> {
+        let data = std::mem::take(&mut self.buffer);
+        let size = data.len() as u64;
+        self.objects
+            .write()
+            .unwrap()
+            .insert(self.name.to_owned(), data);
+        Ok(size)
+    }
+
+    async fn abort(&mut self) {
+        self.buffer.clear();
+    }
+}
@@ -0,0 +1,297 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use futures::stream;
+use minio::s3::{
+    args::{
+        AbortMultipartUploadArgs, CompleteMultipartUploadArgs, CopyObjectArgs, CopySource,
+        CreateMultipartUploadArgs, GetObjectArgs, ListObjectsV2Args, PutObjectApiArgs,
+        RemoveObjectArgs, StatObjectArgs, UploadPartArgs,
+    },
+    client::Client,
+    error::Error,
+    types::Part,
+};
+use tracing::error;
+
+use super::{
+    signer::uri_encode, Access, BlobError, BlobStore, BlobWriter, ObjectInfo, ObjectReader, Signer,
+};
+
+/// Smallest part S3 accepts, except for the last one
+pub const PART_SIZE: usize = 5 * 1024 * 1024;
+
+/// Keeps objects in a MinIO or any other S3 compatible bucket
+#[derive(Clone)]
+pub struct MinioStore {
+    client: Client,
+    bucket_name: String,
+    signer: Signer,
+}
+
+impl MinioStore {
+    pub fn new(client: Client, bucket_name: &str, signer: Signer) -> Self {
+        Self {
+            client,
+            bucket_name: bucket_name.to_string(),
+            signer,
+        }
+    }
+}
+
+#[async_trait]
+impl BlobStore for MinioStore {
+    async fn put(&self, name: &str, data: &[u8]) -> Result<(), BlobError> {
+        self.client
+            .put_object_api(&mut PutObjectApiArgs::new(&self.bucket_name, name, data)?)
+            .await?;
+        Ok(())
+    }
+
+    async fn writer(&self, name: &str) -> Result<Box<dyn BlobWriter>, BlobError> {
+        Ok(Box::new(MinioWriter {
+            client: self.client.clone(),
+            bucket_name: self.bucket_name.to_owned(),
+            object_name: name.to_string(),
+            buffer: Vec::with_capacity(PART_SIZE),
+            upload_id: None,
+            parts: Vec::new(),
+            size: 0,
+        }))
+    }
+
+    async fn get(
+        &self,
+        name: &str,
+        offset: Option<u64>,
+        length: Option<u64>,
+    ) -> Result<ObjectReader, BlobError> {
+        let mut args = GetObjectArgs::new(&self.bucket_name, name)?;
+        args.offset = offset.map(|offset| offset as usize);
+        args.length = length.map(|length| length as usize);
+
+        let response = self.client.get_object_old(&args).await?;
+
+        let size = response.content_length().unwrap_or_default();
+
+        let chunks = stream::unfold(Some(response), |response| async move {
+            let mut response = response?;
+            match response.chunk().await {
+                Ok(Some(chunk)) => Some((Ok(chunk.to_vec()), Some(response))),
+                Ok(None) => None,
+                Err(err) => Some((Err(BlobError::Backend(err.into())), None)),
+            }
+        });
+
+        Ok(ObjectReader {
+            size,
+            chunks: Box::pin(chunks),
+        })
+    }
+
+    async fn delete(&self, name: &str) -> Result<(), BlobError> {
+        self.client
+            .remove_object(&RemoveObjectArgs::new(&self.bucket_name, name)?)
+            .await?;
+        Ok(())
+    }
+
+    async fn head(&self, name: &str) -> Result<ObjectInfo, BlobError> {
+        let object = self
+            .client
+            .stat_object(&StatObjectArgs::new(&self.bucket_name, name)?)
+            .await?;
+
+        Ok(ObjectInfo {
+            name: name.to_string(),
+            size: object.size as u64,
+            etag: Some(object.etag.trim_matches('"').to_string()),
+        })
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<ObjectInfo>, BlobError> {
+        let mut objects = Vec::new();
+        let mut continuation_token: Option<String> = None;
+
+        loop {
+            let mut args = ListObjectsV2Args::new(&self.bucket_name)?;
+            args.prefix = Some(prefix);
+            args.continuation_token = continuation_token.as_deref();
+
+            let response = self.client.list_objects_v2(&args).await?;
+
+            objects.extend(response.contents.into_iter().map(|item| ObjectInfo {
+                name: item.name,
+                size: item.size.unwrap_or_default() as u64,
+                etag: item.etag.map(|etag| etag.trim_matches('"').to_string()),
+            }));
+
+            match response.next_continuation_token {
+                Some(token) if response.is_truncated => continuation_token = Some(token),
+                _ => break,
+            }
+        }
+
+        Ok(objects)
+    }
+
+    /// MinIO has no rename, objects are copied
+    async fn copy(&self, from: &str, to: &str) -> Result<(), BlobError> {
+        let source = CopySource::new(&self.bucket_name, from)?;
+        self.client
+            .copy_object(&CopyObjectArgs::new(&self.bucket_name, to, source)?)
+            .await?;
+        Ok(())
+    }
+
+    /// Writes sign the declared type and size, the bucket refuses a PUT
+    /// with any other
+    async fn presign(&self, name: &str, access: Access, expiry: u32) -> Result<String, BlobError> {
+        let path = format!(
+            "/{}/{}",
+            uri_encode(&self.bucket_name, false),
+            uri_encode(name, false)
+        );
+
+        let url = match access {
+            Access::Read => self.signer.presign("GET", &path, &[], expiry, Utc::now()),
+            Access::Write { content_type, size } => self.signer.presign(
+                "PUT",
+                &path,
+                &[
+                    ("content-length", &size.to_string()),
+                    ("content-type", &content_type),
+                ],
+                expiry,
+                Utc::now(),
+            ),
+        };
+
+        Ok(url)
+    }
+}
+
+/// Writes an object as it arrives, holding at most one part in memory.
+/// Objects smaller than a part are written with a single put.
+pub struct MinioWriter {
+    client: Client,
+    bucket_name: String,
+    object_name: String,
+    buffer: Vec<u8>,
+    upload_id: Option<String>,
+    parts: Vec<Part>,
+    size: u64,
+}
+
+impl MinioWriter {
+    async fn upload_part(&mut self) -> Result<(), Error> {
+        let upload_id = match &self.upload_id {
+            Some(upload_id) => upload_id.to_owned(),
+            None => {
+                let response = self
+                    .client
+                    .create_multipart_upload(&CreateMultipartUploadArgs::new(
+                        &self.bucket_name,
+                        &self.object_name,
+                    )?)
+                    .await?;
+                self.upload_id = Some(response.upload_id.to_owned());
+                response.upload_id
+            }
+        };
+
+        let number = self.parts.len() as u16 + 1;
+
+        let response = self
+            .client
+            .upload_part(&UploadPartArgs::new(
+                &self.bucket_name,
+                &self.object_name,
+                &upload_id,
+                number,
+                &self.buffer,
+            )?)
+            .await?;
+
+        self.parts.push(Part {
+            number,
+            etag: response.etag,
+        });
+        self.buffer.clear();
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl BlobWriter for MinioWriter {
+    async fn write(&mut self, data: &[u8]) -> Result<(), BlobError> {
+        self.buffer.extend_from_slice(data);
+        self.size += data.len() as u64;
+
+        if self.buffer.len() >= PART_SIZE {
+            self.upload_part().await?;
+        }
+
+        Ok(())
+    }
+
+    async fn finish(&mut self) -> Result<u64, BlobError> {
+        let upload_id = match self.upload_id.to_owned() {
+            Some(upload_id) => upload_id,
+            None => {
+                self.client
+                    .put_object_api(&mut PutObjectApiArgs::new(
+                        &self.bucket_name,
+                        &self.object_name,
+                        &self.buffer,
+                    )?)
+                    .await?;
+                return Ok(self.size);
+            }
+        };
+
+        if !self.buffer.is_empty() {
+            self.upload_part().await?;
+        }
+
+        self.client
+            .complete_multipart_upload(&CompleteMultipartUploadArgs::new(
+                &self.bucket_name,
+                &self.object_name,
+                &upload_id,
+                &self.parts,
+            )?)
+            .await?;
+
+        self.upload_id = None;
+
+        Ok(self.size)
+    }
+
+    /// Drops the parts uploaded so far
+    async fn abort(&mut self) {
+        let upload_id = match &self.upload_id {
+            Some(upload_id) => upload_id,
+            None => return,
+        };
+
+        let aborted =
+            match AbortMultipartUploadArgs::new(&self.bucket_name, &self.object_name, upload_id) {
+                Ok(args) => self.client.abort_multipart_upload(&args).await.map(|_| ()),
+                Err(err) => Err(err),
+            };
+
+        if let Err(err) = aborted {
+            error!(?err);
+        }
+    }
+}
+
+impl From<Error> for BlobError {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::S3Error(err) if err.code == "NoSuchKey" => BlobError::NotFound,
+            err => BlobError::Backend(err.into()),
+        }
+    }
+}
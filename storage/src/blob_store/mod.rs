@@ -0,0 +1,101 @@
+use std::pin::Pin;
+
+use async_trait::async_trait;
+use futures::Stream;
+use thiserror::Error;
+
+pub mod fs;
+pub mod memory;
+pub mod minio;
+pub mod signer;
+
+pub use self::{fs::FsStore, memory::MemoryStore, minio::MinioStore, signer::Signer};
+
+/// Chunks of an object as they are read
+pub type ObjectStream = Pin<Box<dyn Stream<Item = Result<Vec<u8>, BlobError>> + Send>>;
+
+#[derive(Debug, Error)]
+pub enum BlobError {
+    #[error("object not found")]
+    NotFound,
+    #[error("not supported by this backend")]
+    Unsupported,
+    #[error(transparent)]
+    Backend(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectInfo {
+    pub name: String,
+    pub size: u64,
+    /// Set by backends that compute one
+    pub etag: Option<String>,
+}
+
+/// What a presigned URL lets its holder do
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    /// A single PUT of exactly this type and size
+    Write {
+        content_type: String,
+        size: u64,
+    },
+}
+
+pub struct ObjectReader {
+    /// Size of the requested range, the whole object when none was given
+    pub size: u64,
+    pub chunks: ObjectStream,
+}
+
+/// Where asset bytes live. Objects are addressed by name only, everything
+/// else about them is kept in the asset registry.
+#[async_trait]
+pub trait BlobStore: Send + Sync {
+    async fn put(&self, name: &str, data: &[u8]) -> Result<(), BlobError>;
+    /// For objects that arrive in chunks and may not fit in memory
+    async fn writer(&self, name: &str) -> Result<Box<dyn BlobWriter>, BlobError>;
+    async fn get(
+        &self,
+        name: &str,
+        offset: Option<u64>,
+        length: Option<u64>,
+    ) -> Result<ObjectReader, BlobError>;
+    /// Deleting a missing object is not an error
+    async fn delete(&self, name: &str) -> Result<(), BlobError>;
+    async fn head(&self, name: &str) -> Result<ObjectInfo, BlobError>;
+    /// Objects whose name starts with `prefix`, in no particular order
+    async fn list(&self, prefix: &str) -> Result<Vec<ObjectInfo>, BlobError>;
+    async fn copy(&self, from: &str, to: &str) -> Result<(), BlobError>;
+    /// A URL clients use to read or write the object directly, valid for
+    /// `expiry` seconds. Only backends reachable by clients have one.
+    async fn presign(
+        &self,
+        _name: &str,
+        _access: Access,
+        _expiry: u32,
+    ) -> Result<String, BlobError> {
+        Err(BlobError::Unsupported)
+    }
+}
+
+/// An object being written. Nothing is visible under its name before
+/// `finish`, and `abort` drops whatever was written so far.
+#[async_trait]
+pub trait BlobWriter: Send {
+    async fn write(&mut self, data: &[u8]) -> Result<(), BlobError>;
+    /// Completes the object and returns its size
+    async fn finish(&mut self) -> Result<u64, BlobError>;
+    async fn abort(&mut self);
+}
+
+/// Start and end of a range within an object of `size` bytes
+fn range(size: u64, offset: Option<u64>, length: Option<u64>) -> (u64, u64) {
+    let start = offset.unwrap_or(0).min(size);
+    let end = match length {
+        Some(length) => start.saturating_add(length).min(size),
+        None => size,
+    };
+    (start, end)
+}
@@ -0,0 +1,111 @@
+use std::f32::consts::PI;
+
+const CHARACTERS: &[u8; 83] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz#$%*+,-.:;=?@[]^_{|}~";
+
+/// Encodes RGB pixels as a BlurHash with `components_x` by `components_y`
+/// components, each between 1 and 9. See https://blurha.sh for the format.
+pub fn encode(
+    components_x: u32,
+    components_y: u32,
+    width: u32,
+    height: u32,
+    pixels: &[u8],
+) -> String {
+    let mut factors = Vec::with_capacity((components_x * components_y) as usize);
+
+    for j in 0..components_y {
+        for i in 0..components_x {
+            factors.push(factor(i, j, width, height, pixels));
+        }
+    }
+
+    let (dc, ac) = factors.split_first().expect("at least one component");
+
+    let mut hash = String::new();
+    hash.push_str(&encode83((components_x - 1) + (components_y - 1) * 9, 1));
+
+    let maximum = match ac.is_empty() {
+        true => {
+            hash.push_str(&encode83(0, 1));
+            1.0
+        }
+        false => {
+            let actual = ac
+                .iter()
+                .flat_map(|factor| factor.iter())
+                .fold(0.0_f32, |maximum, value| maximum.max(value.abs()));
+            let quantised = ((actual * 166.0 - 0.5).floor() as i32).clamp(0, 82) as u32;
+            hash.push_str(&encode83(quantised, 1));
+            (quantised + 1) as f32 / 166.0
+        }
+    };
+
+    hash.push_str(&encode83(encode_dc(dc), 4));
+
+    for factor in ac.iter() {
+        hash.push_str(&encode83(encode_ac(factor, maximum), 2));
+    }
+
+    hash
+}
+
+fn factor(i: u32, j: u32, width: u32, height: u32, pixels: &[u8]) -> [f32; 3] {
+    let normalisation = match (i, j) {
+        (0, 0) => 1.0,
+        _ => 2.0,
+    };
+
+    let mut sum = [0.0_f32; 3];
+
+    for y in 0..height {
+        for x in 0..width {
+            let basis = normalisation
+                * (PI * i as f32 * x as f32 / width as f32).cos()
+                * (PI * j as f32 * y as f32 / height as f32).cos();
+            let offset = 3 * (y * width + x) as usize;
+            for (channel, value) in sum.iter_mut().enumerate() {
+                *value += basis * srgb_to_linear(pixels[offset + channel]);
+            }
+        }
+    }
+
+    let scale = 1.0 / (width * height) as f32;
+    sum.map(|value| value * scale)
+}
+
+fn encode_dc(value: &[f32; 3]) -> u32 {
+    let [r, g, b] = value.map(linear_to_srgb);
+    (r << 16) + (g << 8) + b
+}
+
+fn encode_ac(value: &[f32; 3], maximum: f32) -> u32 {
+    let [r, g, b] = value.map(|value| {
+        let value = value / maximum;
+        ((value.abs().sqrt().copysign(value) * 9.0 + 9.5).floor() as i32).clamp(0, 18) as u32
+    });
+    r * 19 * 19 + g * 19 + b
+}
+
+fn srgb_to_linear(value: u8) -> f32 {
+    let value = value as f32 / 255.0;
+    match value <= 0.04045 {
+        true => value / 12.92,
+        false => ((value + 0.055) / 1.055).powf(2.4),
+    }
+}
+
+fn linear_to_srgb(value: f32) -> u32 {
+    let value = value.clamp(0.0, 1.0);
+    let srgb = match value <= 0.003_130_8 {
+        true => value * 12.92,
+        false => 1.055 * value.powf(1.0 / 2.4) - 0.055,
+    };
+    (srgb * 255.0 + 0.5) as u32
+}
+
+fn encode83(value: u32, length: u32) -> String {
+    (1..=length)
+        .map(|i| CHARACTERS[(value / 83_u32.pow(length - i) % 83) as usize] as char)
+        .collect()
+}
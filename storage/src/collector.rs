@@ -0,0 +1,111 @@
+use shared::{
+    configuration::CollectorSettings,
+    repositories::asset_repository::{AssetRepository, AssetRepositoryImpl},
+};
+use tracing::{error, info};
+
+use crate::service::{upload_object, StorageServiceImpl};
+
+/// Removes assets nothing links to. An asset unreferenced for the grace
+/// period is marked first, and deleted once it stays marked for another one.
+/// Blobs whose assets were all deleted go as well, and direct uploads never
+/// completed are dropped after the grace period.
+pub struct Collector {
+    pub service: StorageServiceImpl,
+    pub settings: CollectorSettings,
+}
+
+impl Collector {
+    pub async fn run(self) {
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(self.settings.interval));
+        loop {
+            interval.tick().await;
+            if let Err(err) = self.collect().await {
+                error!("Unable to collect orphaned assets: {:?}", err);
+            }
+        }
+    }
+
+    pub async fn collect(&self) -> Result<(), anyhow::Error> {
+        let mut transaction = self.service.db.begin().await?;
+        let unmarked = AssetRepositoryImpl::unmark_orphans(&mut transaction).await?;
+        let marked =
+            AssetRepositoryImpl::mark_orphans(&mut transaction, self.settings.grace_period).await?;
+        transaction.commit().await?;
+
+        info!(
+            "{} assets marked as orphaned, {} unmarked",
+            marked, unmarked
+        );
+
+        let mut transaction = self.service.db.begin().await?;
+        let abandoned = AssetRepositoryImpl::delete_abandoned(
+            &mut transaction,
+            self.settings.grace_period,
+            self.settings.batch_size,
+        )
+        .await?;
+        transaction.commit().await?;
+
+        for upload in abandoned.iter() {
+            self.service.remove_object(&upload_object(&upload.id)).await;
+        }
+
+        info!("{} abandoned uploads removed", abandoned.len());
+
+        loop {
+            let mut transaction = self.service.db.begin().await?;
+
+            let expired = AssetRepositoryImpl::find_expired(
+                &mut transaction,
+                self.settings.grace_period,
+                self.settings.batch_size,
+            )
+            .await?;
+
+            let dangling = AssetRepositoryImpl::delete_dangling_blobs(
+                &mut transaction,
+                self.settings.grace_period,
+                self.settings.batch_size,
+            )
+            .await?;
+
+            let mut removed: Vec<String> = dangling.iter().map(|blob| blob.name.clone()).collect();
+
+            for orphan in expired.iter() {
+                let blob = AssetRepositoryImpl::delete(&mut transaction, &orphan.id).await?;
+
+                if blob.ref_count > 0 {
+                    continue;
+                }
+
+                match AssetRepositoryImpl::delete_blob(&mut transaction, &blob.name).await {
+                    Ok(blob) => removed.push(blob.name),
+                    Err(sqlx::error::Error::RowNotFound) => {}
+                    Err(err) => return Err(err.into()),
+                }
+            }
+
+            // Objects go while the deleted rows are locked, so an upload of
+            // the same content waits and then writes them again
+            for name in removed.iter() {
+                self.service.remove(name).await;
+            }
+
+            transaction.commit().await?;
+
+            info!(
+                "{} orphaned assets deleted, {} objects removed",
+                expired.len(),
+                removed.len()
+            );
+
+            if (expired.len() as i64) < self.settings.batch_size
+                && (dangling.len() as i64) < self.settings.batch_size
+            {
+                return Ok(());
+            }
+        }
+    }
+}
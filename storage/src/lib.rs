@@ -1,2 +1,7 @@
 pub mod application;
+pub mod blob_store;
+pub mod blurhash;
+pub mod collector;
+pub mod renditions;
 pub mod service;
+pub mod sniff;
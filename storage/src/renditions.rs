@@ -0,0 +1,203 @@
+use std::{collections::BTreeMap, io::Cursor};
+
+use image::{
+    codecs::jpeg::JpegEncoder,
+    error::{EncodingError, ImageFormatHint},
+    imageops::FilterType,
+    DynamicImage, ImageDecoder, ImageError, ImageFormat, ImageReader, ImageResult, RgbaImage,
+};
+use shared::configuration::RenditionSettings;
+
+use crate::blurhash;
+
+/// Formats that are decoded and re-encoded, anything else is stored as uploaded
+const SUPPORTED_FORMATS: [ImageFormat; 3] =
+    [ImageFormat::Jpeg, ImageFormat::Png, ImageFormat::WebP];
+
+/// Placeholders are computed from a thumbnail this size, a BlurHash only
+/// keeps a few components anyway
+const PLACEHOLDER_SIZE: u32 = 32;
+
+#[derive(Debug, Clone)]
+pub struct EncodedImage {
+    pub data: Vec<u8>,
+    pub format: ImageFormat,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl EncodedImage {
+    pub fn content_type(&self) -> &'static str {
+        self.format.to_mime_type()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Rendition {
+    pub name: String,
+    pub image: EncodedImage,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProcessedImage {
+    /// The upload re-encoded in its own format, without metadata
+    pub original: EncodedImage,
+    pub renditions: Vec<Rendition>,
+    pub placeholder: Placeholder,
+}
+
+/// What clients show while the image loads
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placeholder {
+    pub blurhash: String,
+    /// `#rrggbb`
+    pub color: String,
+}
+
+/// Whether the upload is an image that gets processed, judged by its first bytes
+pub fn is_image(data: &[u8]) -> bool {
+    image::guess_format(data)
+        .map(|format| SUPPORTED_FORMATS.contains(&format))
+        .unwrap_or(false)
+}
+
+/// Object name of a rendition. WebP renditions get the extension, ones in
+/// the original format keep the format of the original object.
+pub fn rendition_name(asset_name: &str, rendition: &str, webp: bool) -> String {
+    match webp {
+        true => format!("{}@{}.webp", asset_name, rendition),
+        false => format!("{}@{}", asset_name, rendition),
+    }
+}
+
+/// Decodes an uploaded image and builds every configured rendition in WebP
+/// and in the original format. Re-encoding drops EXIF and other metadata,
+/// GPS location included. Returns `None` for uploads that are not images.
+pub fn process(
+    data: &[u8],
+    renditions: &[RenditionSettings],
+    quality: u8,
+) -> ImageResult<Option<ProcessedImage>> {
+    let reader = ImageReader::new(Cursor::new(data)).with_guessed_format()?;
+
+    let format = match reader.format() {
+        Some(format) if SUPPORTED_FORMATS.contains(&format) => format,
+        _ => return Ok(None),
+    };
+
+    let mut decoder = reader.into_decoder()?;
+    let orientation = decoder.orientation()?;
+    let mut image = DynamicImage::from_decoder(decoder)?;
+
+    // Rotate the pixels while the orientation tag is still known, it is
+    // lost with the rest of the metadata
+    image.apply_orientation(orientation);
+
+    let original = encode(&image, format, quality)?;
+    let placeholder = placeholder(&image);
+
+    let mut encoded = Vec::new();
+
+    for rendition in renditions.iter() {
+        let resized = match image.width() > rendition.width {
+            true => image.resize(rendition.width, u32::MAX, FilterType::Lanczos3),
+            false => image.clone(),
+        };
+
+        encoded.push(Rendition {
+            name: rendition.name.to_owned(),
+            image: encode(&resized, ImageFormat::WebP, quality)?,
+        });
+
+        if format != ImageFormat::WebP {
+            encoded.push(Rendition {
+                name: rendition.name.to_owned(),
+                image: encode(&resized, format, quality)?,
+            });
+        }
+    }
+
+    Ok(Some(ProcessedImage {
+        original,
+        renditions: encoded,
+        placeholder,
+    }))
+}
+
+pub fn placeholder(image: &DynamicImage) -> Placeholder {
+    let thumbnail = image
+        .thumbnail(PLACEHOLDER_SIZE, PLACEHOLDER_SIZE)
+        .to_rgba8();
+    let (width, height) = thumbnail.dimensions();
+
+    let pixels: Vec<u8> = thumbnail
+        .pixels()
+        .flat_map(|pixel| [pixel[0], pixel[1], pixel[2]])
+        .collect();
+
+    // More components along the longer side
+    let (components_x, components_y) = match width >= height {
+        true => (4, 3),
+        false => (3, 4),
+    };
+
+    Placeholder {
+        blurhash: blurhash::encode(components_x, components_y, width, height, &pixels),
+        color: dominant_color(&thumbnail),
+    }
+}
+
+/// Pixels are grouped into coarse buckets, the average of the fullest one
+/// wins. Mostly transparent pixels are left out.
+fn dominant_color(image: &RgbaImage) -> String {
+    let mut buckets: BTreeMap<[u8; 3], (u32, [u32; 3])> = BTreeMap::new();
+
+    for pixel in image.pixels().filter(|pixel| pixel[3] >= 128) {
+        let (count, sum) = buckets
+            .entry([pixel[0] >> 4, pixel[1] >> 4, pixel[2] >> 4])
+            .or_default();
+        *count += 1;
+        for (channel, value) in sum.iter_mut().enumerate() {
+            *value += pixel[channel] as u32;
+        }
+    }
+
+    let [r, g, b] = buckets
+        .values()
+        .max_by_key(|(count, _)| *count)
+        .map(|(count, sum)| sum.map(|value| (value / count) as u8))
+        .unwrap_or_default();
+
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+fn encode(image: &DynamicImage, format: ImageFormat, quality: u8) -> ImageResult<EncodedImage> {
+    let mut data = Vec::new();
+
+    match format {
+        ImageFormat::Jpeg => DynamicImage::ImageRgb8(image.to_rgb8())
+            .write_with_encoder(JpegEncoder::new_with_quality(&mut data, quality))?,
+        // The image encoder only writes lossless WebP
+        ImageFormat::WebP => {
+            let pixels = match image.color().has_alpha() {
+                true => DynamicImage::ImageRgba8(image.to_rgba8()),
+                false => DynamicImage::ImageRgb8(image.to_rgb8()),
+            };
+            let encoder = webp::Encoder::from_image(&pixels).map_err(|err| {
+                ImageError::Encoding(EncodingError::new(
+                    ImageFormatHint::Exact(ImageFormat::WebP),
+                    err.to_string(),
+                ))
+            })?;
+            data = encoder.encode(quality as f32).to_vec();
+        }
+        _ => image.write_to(&mut Cursor::new(&mut data), format)?,
+    }
+
+    Ok(EncodedImage {
+        data,
+        format,
+        width: image.width(),
+        height: image.height(),
+    })
+}
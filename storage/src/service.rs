@@ -1,86 +1,1108 @@
-use minio::s3::{
-    args::{GetObjectArgs, PutObjectApiArgs},
-    client::Client,
-};
-use shared::storage_proto::{
-    storage_service_server::StorageService, RetriveRequest, RetriveResponse, StoreRequest,
-    StoreResponse,
+use std::{pin::Pin, sync::Arc};
+
+use futures::{Stream, StreamExt};
+use sha2::{Digest, Sha256};
+use shared::{
+    common::MessageResponse,
+    configuration::{RenditionSettings, UploadLimitSettings},
+    models::{
+        asset_model::{Blob, CreateAsset, CreateBlob, CreatePendingUpload, PendingUpload},
+        enums::Role,
+    },
+    repositories::{
+        asset_repository::{AssetRepository, AssetRepositoryImpl},
+        user_repository::{UserRepository, UserRepositoryImpl},
+    },
+    storage_proto::{
+        storage_service_server::StorageService, Asset, Assets, CompleteUploadRequest,
+        DeleteRequest, GetAllRequest, Orphan, Orphans, OrphansRequest, PresignDownloadRequest,
+        PresignUploadRequest, PresignedUpload, PresignedUrl, Rendition, RenditionFormat,
+        RetriveRenditionRequest, RetriveRequest, RetriveResponse, StatRequest, StatResponse,
+        StoreRequest, StoreResponse,
+    },
+    utils::split_cursor::parse_cursor,
 };
-use tonic::{Request, Response, Status};
+use sqlx::{PgPool, Postgres, Transaction};
+use tonic::{Request, Response, Status, Streaming};
 use tracing::{error, info};
 
+use uuid::Uuid;
+
+use crate::{
+    blob_store::{Access, BlobError, BlobStore},
+    renditions::{is_image, process, rendition_name},
+    sniff::{is_allowed, sniff},
+};
+
+pub type RetriveStream = Pin<Box<dyn Stream<Item = Result<RetriveResponse, Status>> + Send>>;
+
+/// An upload ready to be registered. Its object is named by its checksum,
+/// and only written once the blob's row is locked.
+struct Stored {
+    size: u64,
+    checksum: String,
+    /// Checksum of the upload, when what was stored differs from it
+    source_checksum: Option<String>,
+    content_type: String,
+    width: Option<u32>,
+    height: Option<u32>,
+    blurhash: Option<String>,
+    dominant_color: Option<String>,
+    renditions: Vec<Rendition>,
+    content: Content,
+}
+
+/// Where the bytes of an upload are until it is registered
+enum Content {
+    /// A temporary object, copied under the checksum
+    Temporary(String),
+    /// The re-encoded image and its renditions, by object name
+    Objects(Vec<(String, Vec<u8>)>),
+    /// Stored for an earlier upload, the blob is locked already
+    Existing,
+}
+
 #[derive(Clone)]
 pub struct StorageServiceImpl {
-    pub bucket_name: String,
-    pub client: Client,
+    pub db: PgPool,
+    pub store: Arc<dyn BlobStore>,
+    pub renditions: Vec<RenditionSettings>,
+    pub quality: u8,
+    pub max_image_size: usize,
+    /// Seconds presigned URLs stay valid
+    pub presign_expiry: u32,
+    /// Hours before orphaned assets are marked, and marked ones removed
+    pub grace_period: i32,
+    pub limits: UploadLimitSettings,
+}
+
+impl StorageServiceImpl {
+    async fn put(&self, asset_name: &str, data: &[u8]) -> Result<(), Status> {
+        self.store.put(asset_name, data).await.map_err(internal)
+    }
+
+    pub(crate) async fn remove_object(&self, name: &str) {
+        if let Err(err) = self.store.delete(name).await {
+            error!(?err);
+        }
+    }
+
+    /// Removes an object and all of its renditions
+    pub(crate) async fn remove(&self, asset_name: &str) {
+        self.remove_object(asset_name).await;
+
+        for rendition in self.renditions.iter() {
+            self.remove_object(&rendition_name(asset_name, &rendition.name, true))
+                .await;
+            self.remove_object(&rendition_name(asset_name, &rendition.name, false))
+                .await;
+        }
+    }
+
+    async fn begin(&self) -> Result<Transaction<'static, Postgres>, Status> {
+        self.db.begin().await.map_err(|err| {
+            error!("{:?}", err);
+            Status::internal("Something went wrong")
+        })
+    }
+
+    /// Removes the temporary object an upload was written to
+    async fn discard(&self, stored: &Stored) {
+        if let Content::Temporary(temporary) = &stored.content {
+            self.remove_object(temporary).await;
+        }
+    }
+
+    /// Writes the bytes of an upload, unless its blob has them already. The
+    /// row is locked first, so they are not removed before the asset is
+    /// recorded, and a blob removed meanwhile gets them back.
+    async fn write(
+        &self,
+        transaction: &mut Transaction<'_, Postgres>,
+        stored: &Stored,
+    ) -> Result<(), Status> {
+        if let Content::Existing = stored.content {
+            return Ok(());
+        }
+
+        if self.is_stored(transaction, &stored.checksum).await? {
+            return Ok(());
+        }
+
+        match &stored.content {
+            Content::Temporary(temporary) => self
+                .store
+                .copy(temporary, &stored.checksum)
+                .await
+                .map_err(internal),
+            Content::Objects(objects) => {
+                for (name, data) in objects.iter() {
+                    self.put(name, data).await?;
+                }
+                Ok(())
+            }
+            Content::Existing => Ok(()),
+        }
+    }
+
+    /// Whether a blob and its object exist. The row stays locked until the
+    /// transaction ends.
+    async fn is_stored(
+        &self,
+        transaction: &mut Transaction<'_, Postgres>,
+        name: &str,
+    ) -> Result<bool, Status> {
+        match AssetRepositoryImpl::lock_blob(transaction, name).await {
+            Ok(_) => {}
+            Err(sqlx::error::Error::RowNotFound) => return Ok(false),
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        }
+
+        // A removal whose transaction failed leaves the row behind
+        match self.store.head(name).await {
+            Ok(_) => Ok(true),
+            Err(BlobError::NotFound) => Ok(false),
+            Err(err) => Err(internal(err)),
+        }
+    }
+
+    /// Whether an object with this content is stored already
+    async fn find_blob(&self, name: &str) -> Result<Option<Blob>, Status> {
+        let mut transaction = match self.db.begin().await {
+            Ok(transaction) => transaction,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        match AssetRepositoryImpl::find_blob(&mut transaction, name).await {
+            Ok(blob) => Ok(Some(blob)),
+            Err(sqlx::error::Error::RowNotFound) => Ok(None),
+            Err(err) => {
+                error!("{:?}", err);
+                Err(Status::internal("Something went wrong"))
+            }
+        }
+    }
+
+    /// An image stored before from an upload with the same content. Its row
+    /// stays locked until the transaction ends.
+    async fn find_image(
+        &self,
+        transaction: &mut Transaction<'_, Postgres>,
+        source_checksum: &str,
+    ) -> Result<Option<Blob>, Status> {
+        let blob =
+            match AssetRepositoryImpl::find_blob_by_source(transaction, source_checksum).await {
+                Ok(blob) => blob,
+                Err(sqlx::error::Error::RowNotFound) => return Ok(None),
+                Err(err) => {
+                    error!("{:?}", err);
+                    return Err(Status::internal("Something went wrong"));
+                }
+            };
+
+        match self.store.head(&blob.name).await {
+            Ok(_) => Ok(Some(blob)),
+            Err(BlobError::NotFound) => Ok(None),
+            Err(err) => Err(internal(err)),
+        }
+    }
+
+    /// Total size of the assets a user owns already
+    async fn find_usage(&self, user_id: &str) -> Result<u64, Status> {
+        let mut transaction = match self.db.begin().await {
+            Ok(transaction) => transaction,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        match AssetRepositoryImpl::find_usage(&mut transaction, user_id).await {
+            Ok(usage) => Ok(usage as u64),
+            Err(err) => {
+                error!("{:?}", err);
+                Err(Status::internal("Something went wrong"))
+            }
+        }
+    }
+
+    /// Checked as an upload arrives, so oversized ones are cut short
+    fn check_limits(&self, size: u64, usage: u64) -> Result<(), Status> {
+        if size > self.limits.max_upload_size {
+            return Err(Status::resource_exhausted("File is too large"));
+        }
+        if usage + size > self.limits.quota {
+            return Err(Status::resource_exhausted("Storage quota exceeded"));
+        }
+        Ok(())
+    }
+
+    /// Checks the quota again with the user locked, so concurrent uploads
+    /// are not each measured against the same usage
+    async fn check_quota(
+        &self,
+        transaction: &mut Transaction<'_, Postgres>,
+        user_id: &str,
+        size: u64,
+    ) -> Result<(), Status> {
+        let usage = match AssetRepositoryImpl::lock_owner(transaction, user_id).await {
+            Ok(()) => AssetRepositoryImpl::find_usage(transaction, user_id).await,
+            Err(err) => Err(err),
+        };
+
+        match usage {
+            Ok(usage) => self.check_limits(size, usage as u64),
+            Err(err) => {
+                error!("{:?}", err);
+                Err(Status::internal("Something went wrong"))
+            }
+        }
+    }
+
+    /// Object name of the original or of one of its renditions
+    fn object_name(
+        &self,
+        asset_name: &str,
+        rendition: Option<&str>,
+        format: RenditionFormat,
+    ) -> Result<String, Status> {
+        let rendition = match rendition {
+            Some(rendition) => rendition,
+            None => return Ok(asset_name.to_string()),
+        };
+
+        if !self.renditions.iter().any(|known| known.name == rendition) {
+            return Err(Status::invalid_argument("Unknown rendition"));
+        }
+
+        Ok(rendition_name(
+            asset_name,
+            rendition,
+            format == RenditionFormat::Webp,
+        ))
+    }
+
+    /// Streams an object chunk by chunk
+    async fn get(
+        &self,
+        asset_name: &str,
+        offset: Option<u64>,
+        length: Option<u64>,
+    ) -> Result<RetriveStream, Status> {
+        let reader = match self.store.get(asset_name, offset, length).await {
+            Ok(reader) => reader,
+            Err(BlobError::NotFound) => return Err(Status::not_found("Asset not found")),
+            Err(err) => return Err(internal(err)),
+        };
+
+        let size = reader.size;
+
+        let chunks = reader.chunks.enumerate().map(move |(index, chunk)| {
+            let first = index == 0;
+            chunk
+                .map(|chunk| RetriveResponse {
+                    content_type: first.then(|| content_type(&chunk)).flatten(),
+                    size: first.then_some(size),
+                    data: chunk,
+                })
+                .map_err(internal)
+        });
+
+        Ok(Box::pin(chunks))
+    }
+
+    /// Writes the rest of a non-image upload to the store as it arrives. The
+    /// checksum is only known at the end, so it goes to a temporary object,
+    /// which the caller removes once the upload is registered.
+    async fn store_stream(
+        &self,
+        first: StoreRequest,
+        inbound: &mut Streaming<StoreRequest>,
+        content_type: &str,
+        usage: u64,
+    ) -> Result<Stored, Status> {
+        let temporary = upload_object(&Uuid::new_v4().to_string());
+        let mut upload = self.store.writer(&temporary).await.map_err(internal)?;
+        let mut hasher = Sha256::new();
+
+        let uploaded = async {
+            let mut received = first.asset_data.len() as u64;
+            hasher.update(&first.asset_data);
+            upload.write(&first.asset_data).await.map_err(internal)?;
+            while let Some(message) = inbound.message().await? {
+                received += message.asset_data.len() as u64;
+                self.check_limits(received, usage)?;
+                hasher.update(&message.asset_data);
+                upload.write(&message.asset_data).await.map_err(internal)?;
+            }
+            upload.finish().await.map_err(internal)
+        }
+        .await;
+
+        let size = match uploaded {
+            Ok(size) => size,
+            Err(status) => {
+                upload.abort().await;
+                return Err(status);
+            }
+        };
+
+        Ok(Stored {
+            size,
+            checksum: format!("{:x}", hasher.finalize()),
+            source_checksum: None,
+            content_type: content_type.to_string(),
+            width: None,
+            height: None,
+            blurhash: None,
+            dominant_color: None,
+            renditions: vec![],
+            content: Content::Temporary(temporary),
+        })
+    }
+
+    /// Re-encodes an image with its renditions, unless the same image was
+    /// uploaded before
+    async fn store_image(
+        &self,
+        transaction: &mut Transaction<'_, Postgres>,
+        data: Vec<u8>,
+    ) -> Result<Stored, Status> {
+        let source_checksum = format!("{:x}", Sha256::digest(&data));
+
+        if let Some(blob) = self.find_image(transaction, &source_checksum).await? {
+            return Ok(Stored {
+                size: blob.size as u64,
+                checksum: blob.name,
+                source_checksum: Some(source_checksum),
+                content_type: blob.content_type,
+                width: blob.width.map(|width| width as u32),
+                height: blob.height.map(|height| height as u32),
+                blurhash: blob.blurhash,
+                dominant_color: blob.dominant_color,
+                renditions: vec![],
+                content: Content::Existing,
+            });
+        }
+
+        let renditions = self.renditions.clone();
+        let quality = self.quality;
+
+        // Decoding and resizing is CPU bound, keep it off the runtime
+        let processed =
+            match tokio::task::spawn_blocking(move || process(&data, &renditions, quality)).await {
+                Ok(Ok(Some(processed))) => processed,
+                Ok(Ok(None)) => return Err(Status::invalid_argument("Invalid image")),
+                Ok(Err(err)) => {
+                    error!(?err);
+                    return Err(Status::invalid_argument("Invalid image"));
+                }
+                Err(err) => {
+                    error!(?err);
+                    return Err(Status::internal("Something went wrong"));
+                }
+            };
+
+        let checksum = format!("{:x}", Sha256::digest(&processed.original.data));
+
+        let mut renditions = Vec::new();
+        let mut objects = Vec::new();
+
+        for rendition in processed.renditions.into_iter() {
+            let format = match rendition.image.format == image::ImageFormat::WebP {
+                true => RenditionFormat::Webp,
+                false => RenditionFormat::Original,
+            };
+            let name = rendition_name(&checksum, &rendition.name, format == RenditionFormat::Webp);
+
+            renditions.push(Rendition {
+                name: rendition.name,
+                format: format as i32,
+                asset_name: name.to_owned(),
+                content_type: rendition.image.content_type().to_string(),
+                width: rendition.image.width,
+                height: rendition.image.height,
+            });
+            objects.push((name, rendition.image.data));
+        }
+
+        let original = processed.original;
+        let size = original.data.len() as u64;
+        let content_type = original.content_type().to_string();
+        objects.push((checksum.to_owned(), original.data));
+
+        Ok(Stored {
+            size,
+            checksum,
+            source_checksum: Some(source_checksum),
+            content_type,
+            width: Some(original.width),
+            height: Some(original.height),
+            blurhash: Some(processed.placeholder.blurhash),
+            dominant_color: Some(processed.placeholder.color),
+            renditions,
+            content: Content::Objects(objects),
+        })
+    }
+
+    /// Writes an upload and records it as a new asset of the user
+    async fn register(
+        &self,
+        transaction: &mut Transaction<'_, Postgres>,
+        user_id: String,
+        file_name: String,
+        stored: &Stored,
+    ) -> Result<StoreResponse, Status> {
+        self.check_quota(transaction, &user_id, stored.size).await?;
+
+        if let Err(err) = AssetRepositoryImpl::create_blob(
+            transaction,
+            &CreateBlob {
+                name: stored.checksum.to_owned(),
+                checksum: stored.checksum.to_owned(),
+                content_type: stored.content_type.to_owned(),
+                size: stored.size as i64,
+                width: stored.width.map(|width| width as i32),
+                height: stored.height.map(|height| height as i32),
+                blurhash: stored.blurhash.to_owned(),
+                dominant_color: stored.dominant_color.to_owned(),
+                source_checksum: stored.source_checksum.to_owned(),
+            },
+        )
+        .await
+        {
+            error!("{:?}", err);
+            return Err(Status::internal("Something went wrong"));
+        }
+
+        self.write(transaction, stored).await?;
+
+        let asset = match AssetRepositoryImpl::create(
+            transaction,
+            &CreateAsset {
+                user_id,
+                asset_name: stored.checksum.to_owned(),
+                file_name,
+            },
+        )
+        .await
+        {
+            Ok(asset) => asset,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        info!(
+            "asset `{}` uploaded with {} renditions.",
+            asset.asset_name,
+            stored.renditions.len()
+        );
+
+        Ok(StoreResponse {
+            asset_name: asset.asset_name.to_owned(),
+            width: stored.width,
+            height: stored.height,
+            renditions: stored.renditions.to_owned(),
+            size: stored.size,
+            asset: Some(Asset::from(&asset)),
+        })
+    }
+
+    /// Checks a direct upload against what was declared for it, to be stored
+    /// under its checksum like a proxied one
+    async fn store_pending(
+        &self,
+        transaction: &mut Transaction<'_, Postgres>,
+        pending: &PendingUpload,
+        temporary: &str,
+    ) -> Result<Stored, Status> {
+        let reader = self
+            .store
+            .get(temporary, None, None)
+            .await
+            .map_err(internal)?;
+        let size = reader.size;
+        let mut chunks = reader.chunks;
+
+        if size != pending.size as u64 {
+            return Err(Status::invalid_argument(
+                "Upload does not match its declared size",
+            ));
+        }
+
+        let usage = self.find_usage(&pending.user_id).await?;
+        self.check_limits(size, usage)?;
+
+        let first = match chunks.next().await {
+            Some(chunk) => chunk.map_err(internal)?,
+            None => Vec::new(),
+        };
+
+        match sniff(&first) {
+            Some(content_type) if is_allowed(content_type, &self.limits.allowed_types) => {
+                if content_type != pending.content_type {
+                    return Err(Status::invalid_argument(
+                        "Upload does not match its declared type",
+                    ));
+                }
+            }
+            _ => return Err(Status::failed_precondition("Unsupported file type")),
+        }
+
+        if is_image(&first) {
+            if size as usize > self.max_image_size {
+                return Err(Status::resource_exhausted("Image is too large"));
+            }
+
+            let mut data = first;
+            while let Some(chunk) = chunks.next().await {
+                data.extend_from_slice(&chunk.map_err(internal)?);
+            }
+
+            return self.store_image(transaction, data).await;
+        }
+
+        let mut hasher = Sha256::new();
+        hasher.update(&first);
+        while let Some(chunk) = chunks.next().await {
+            hasher.update(&chunk.map_err(internal)?);
+        }
+
+        Ok(Stored {
+            size,
+            checksum: format!("{:x}", hasher.finalize()),
+            source_checksum: None,
+            content_type: pending.content_type.to_owned(),
+            width: None,
+            height: None,
+            blurhash: None,
+            dominant_color: None,
+            renditions: vec![],
+            content: Content::Temporary(temporary.to_owned()),
+        })
+    }
 }
 
 #[tonic::async_trait]
 impl StorageService for StorageServiceImpl {
     async fn store(
         &self,
-        request: Request<StoreRequest>,
+        request: Request<Streaming<StoreRequest>>,
     ) -> Result<Response<StoreResponse>, Status> {
+        let mut inbound = request.into_inner();
+
+        let first = match inbound.message().await? {
+            Some(first) => first,
+            None => return Err(Status::invalid_argument("Empty upload")),
+        };
+
+        info!("Store Request {:?}", first.asset_name);
+
+        let user_id = first.user_id.to_owned();
+        let file_name = first.asset_name.to_owned();
+
+        // The first chunk holds the file's signature, the claimed type is ignored
+        let content_type = match sniff(&first.asset_data) {
+            Some(content_type) if is_allowed(content_type, &self.limits.allowed_types) => {
+                content_type
+            }
+            _ => return Err(Status::failed_precondition("Unsupported file type")),
+        };
+
+        let usage = self.find_usage(&user_id).await?;
+        self.check_limits(first.asset_data.len() as u64, usage)?;
+
+        // Images have to be decoded whole, anything else is passed through
+        let (mut transaction, stored) = match is_image(&first.asset_data) {
+            true => {
+                let mut data = first.asset_data;
+
+                while let Some(message) = inbound.message().await? {
+                    if data.len() + message.asset_data.len() > self.max_image_size {
+                        return Err(Status::resource_exhausted("Image is too large"));
+                    }
+                    data.extend_from_slice(&message.asset_data);
+                    self.check_limits(data.len() as u64, usage)?;
+                }
+
+                let mut transaction = self.begin().await?;
+                let stored = self.store_image(&mut transaction, data).await?;
+                (transaction, stored)
+            }
+            false => {
+                let stored = self
+                    .store_stream(first, &mut inbound, content_type, usage)
+                    .await?;
+                match self.begin().await {
+                    Ok(transaction) => (transaction, stored),
+                    Err(status) => {
+                        self.discard(&stored).await;
+                        return Err(status);
+                    }
+                }
+            }
+        };
+
+        let response = self
+            .register(&mut transaction, user_id, file_name, &stored)
+            .await;
+
+        self.discard(&stored).await;
+
+        let response = response?;
+
+        if let Err(err) = transaction.commit().await {
+            error!("{:?}", err);
+            return Err(Status::internal("Something went wrong"));
+        }
+
+        Ok(Response::new(response))
+    }
+
+    type retriveStream = RetriveStream;
+
+    async fn retrive(
+        &self,
+        request: Request<RetriveRequest>,
+    ) -> Result<Response<Self::retriveStream>, Status> {
+        info!("Retrive Request");
+
         let input = request.get_ref();
 
-        info!("Store Request {:?}", input.asset_name);
+        let chunks = self
+            .get(&input.asset_name, input.offset, input.length)
+            .await?;
 
-        let asset_name = format!(
-            "{}_{}",
-            chrono::Utc::now().format("%d-%m-%Y_%H:%M:%S"),
-            &input.asset_name
-        );
+        info!("asset `{}` retrived.", input.asset_name);
 
-        match self
-            .client
-            .put_object_api(
-                &mut PutObjectApiArgs::new(&self.bucket_name, &asset_name, &input.asset_data)
-                    .unwrap(),
-            )
-            .await
+        Ok(Response::new(chunks))
+    }
+
+    type retrive_renditionStream = RetriveStream;
+
+    async fn retrive_rendition(
+        &self,
+        request: Request<RetriveRenditionRequest>,
+    ) -> Result<Response<Self::retrive_renditionStream>, Status> {
+        let input = request.get_ref();
+
+        info!("Retrive Rendition Request {:?}", input);
+
+        let name = self.object_name(&input.asset_name, Some(&input.rendition), input.format())?;
+
+        let chunks = self.get(&name, input.offset, input.length).await?;
+
+        info!("asset `{}` retrived.", name);
+
+        Ok(Response::new(chunks))
+    }
+
+    async fn stat(&self, request: Request<StatRequest>) -> Result<Response<StatResponse>, Status> {
+        let input = request.get_ref();
+
+        info!("Stat Request {:?}", input);
+
+        let name = self.object_name(
+            &input.asset_name,
+            input.rendition.as_deref(),
+            input.format(),
+        )?;
+
+        let object = match self.store.head(&name).await {
+            Ok(object) => object,
+            Err(BlobError::NotFound) => return Err(Status::not_found("Asset not found")),
+            Err(err) => return Err(internal(err)),
+        };
+
+        let blob = self.find_blob(&input.asset_name).await?;
+
+        let content_type = match (&input.rendition, input.format(), &blob) {
+            (Some(_), RenditionFormat::Webp, _) => String::from("image/webp"),
+            (_, _, Some(blob)) => blob.content_type.to_owned(),
+            (_, _, None) => String::from("application/octet-stream"),
+        };
+
+        // Renditions are derived from the original, so are their tags
+        let etag = match (&blob, &input.rendition) {
+            (Some(blob), None) => blob.checksum.to_owned(),
+            (Some(blob), Some(_)) => name.replacen(&input.asset_name, &blob.checksum, 1),
+            (None, _) => object.etag.unwrap_or(object.name),
+        };
+
+        Ok(Response::new(StatResponse {
+            content_type,
+            size: object.size,
+            etag: format!("\"{}\"", etag),
+            immutable: blob.is_some_and(|blob| blob.name == blob.checksum),
+        }))
+    }
+
+    async fn get_all(&self, request: Request<GetAllRequest>) -> Result<Response<Assets>, Status> {
+        let mut transaction = match self.db.begin().await {
+            Ok(transaction) => transaction,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        let input = request.get_ref();
+
+        info!("Get Assets Request {:?}", input);
+
+        let mut id = None;
+        let mut created_at = None;
+
+        if let Some(cursor_str) = &input.cursor {
+            (id, created_at) = match parse_cursor(cursor_str) {
+                Ok(parsed) => parsed,
+                Err(err) => {
+                    error!("Parse error {}", err);
+                    return Err(Status::invalid_argument("Invalid data"));
+                }
+            }
+        };
+
+        let assets = match AssetRepositoryImpl::find_all(
+            &mut transaction,
+            &input.user_id,
+            input.limit,
+            id,
+            created_at,
+        )
+        .await
         {
-            Ok(put_object_response) => put_object_response,
+            Ok(assets) => assets,
             Err(err) => {
-                error!(?err);
+                error!("{:?}", err);
                 return Err(Status::internal("Something went wrong"));
             }
         };
 
-        info!("asset `{asset_name}` uploaded.");
+        let next_cursor = assets
+            .iter()
+            .nth(input.limit as usize - 1)
+            .map(|item| format!("{}_{}", item.id, item.created_at.to_rfc3339()));
 
-        Ok(Response::new(StoreResponse { asset_name }))
+        match transaction.commit().await {
+            Ok(_) => Ok(Response::new(Assets {
+                assets: assets.iter().map(|asset| Asset::from(asset)).collect(),
+                next_cursor,
+            })),
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        }
     }
 
-    async fn retrive(
+    async fn delete(
         &self,
-        request: Request<RetriveRequest>,
-    ) -> Result<Response<RetriveResponse>, Status> {
-        info!("Retrive Request");
+        request: Request<DeleteRequest>,
+    ) -> Result<Response<MessageResponse>, Status> {
+        let mut transaction = match self.db.begin().await {
+            Ok(transaction) => transaction,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
 
         let input = request.get_ref();
 
-        let response = match self
-            .client
-            .get_object_old(&GetObjectArgs::new(&self.bucket_name, &input.asset_name).unwrap())
-            .await
+        info!("Delete Asset Request {:?}", input);
+
+        let asset = match AssetRepositoryImpl::find(&mut transaction, &input.asset_id).await {
+            Ok(asset) => asset,
+            Err(err) => {
+                error!("{:?}", err);
+                if let sqlx::error::Error::RowNotFound = err {
+                    return Err(Status::not_found("Asset not found"));
+                }
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        // Admins may remove anyone's uploads
+        if asset.user_id != input.user_id {
+            match UserRepositoryImpl::find(&mut transaction, &input.user_id).await {
+                Ok(user) => {
+                    if user.role != Role::Admin {
+                        return Err(Status::permission_denied("Forbidden"));
+                    }
+                }
+                Err(err) => {
+                    error!("{:?}", err);
+                    if let sqlx::error::Error::RowNotFound = err {
+                        return Err(Status::not_found("User not found"));
+                    }
+                    return Err(Status::internal("Something went wrong"));
+                }
+            }
+        }
+
+        // The collector removes the bytes once no asset refers to them
+        if let Err(err) = AssetRepositoryImpl::delete(&mut transaction, &asset.id).await {
+            error!("{:?}", err);
+            return Err(Status::internal("Something went wrong"));
+        }
+
+        if let Err(err) = transaction.commit().await {
+            error!("{:?}", err);
+            return Err(Status::internal("Something went wrong"));
+        }
+
+        Ok(Response::new(MessageResponse {
+            message: format!("Deleted asset: {}", asset.id),
+        }))
+    }
+
+    async fn orphans(
+        &self,
+        _request: Request<OrphansRequest>,
+    ) -> Result<Response<Orphans>, Status> {
+        let mut transaction = match self.db.begin().await {
+            Ok(transaction) => transaction,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        info!("Get Orphaned Assets Request");
+
+        let orphans = match AssetRepositoryImpl::find_orphans(&mut transaction).await {
+            Ok(orphans) => orphans,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        let expires = chrono::Utc::now() - chrono::Duration::hours(self.grace_period as i64);
+
+        match transaction.commit().await {
+            Ok(_) => Ok(Response::new(Orphans {
+                size: orphans.iter().map(|orphan| orphan.size as u64).sum(),
+                orphans: orphans
+                    .iter()
+                    .map(|orphan| Orphan {
+                        expired: orphan
+                            .orphaned_at
+                            .is_some_and(|orphaned_at| orphaned_at < expires),
+                        ..Orphan::from(orphan)
+                    })
+                    .collect(),
+                grace_period: self.grace_period,
+            })),
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        }
+    }
+    async fn presign_upload(
+        &self,
+        request: Request<PresignUploadRequest>,
+    ) -> Result<Response<PresignedUpload>, Status> {
+        let input = request.into_inner();
+
+        info!("Presign Upload Request {:?}", input);
+
+        if !is_allowed(&input.content_type, &self.limits.allowed_types) {
+            return Err(Status::failed_precondition("Unsupported file type"));
+        }
+
+        let mut transaction = match self.db.begin().await {
+            Ok(transaction) => transaction,
+            Err(err) => {
+                error!("{:?}", err);
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        self.check_quota(&mut transaction, &input.user_id, input.size)
+            .await?;
+
+        let pending = match AssetRepositoryImpl::create_pending(
+            &mut transaction,
+            &CreatePendingUpload {
+                user_id: input.user_id,
+                file_name: input.file_name,
+                content_type: input.content_type,
+                size: input.size as i64,
+                expiry: self.presign_expiry as i32,
+            },
+        )
+        .await
         {
-            Ok(res) => res,
+            Ok(pending) => pending,
             Err(err) => {
-                error!(?err);
+                error!("{:?}", err);
                 return Err(Status::internal("Something went wrong"));
             }
         };
 
-        info!("asset `{}` retrived.", input.asset_name);
+        let url = self
+            .store
+            .presign(
+                &upload_object(&pending.id),
+                Access::Write {
+                    content_type: pending.content_type.to_owned(),
+                    size: pending.size as u64,
+                },
+                self.presign_expiry,
+            )
+            .await
+            .map_err(presign_error)?;
 
-        let data = match response.bytes().await {
-            Ok(data) => data.to_vec(),
+        if let Err(err) = transaction.commit().await {
+            error!("{:?}", err);
+            return Err(Status::internal("Something went wrong"));
+        }
+
+        Ok(Response::new(PresignedUpload {
+            upload_id: pending.id,
+            url,
+            expires_in: self.presign_expiry,
+        }))
+    }
+
+    async fn complete_upload(
+        &self,
+        request: Request<CompleteUploadRequest>,
+    ) -> Result<Response<StoreResponse>, Status> {
+        let input = request.get_ref();
+
+        info!("Complete Upload Request {:?}", input);
+
+        let mut transaction = match self.db.begin().await {
+            Ok(transaction) => transaction,
             Err(err) => {
-                error!(?err);
+                error!("{:?}", err);
                 return Err(Status::internal("Something went wrong"));
             }
         };
 
-        Ok(Response::new(RetriveResponse { data }))
+        let pending = match AssetRepositoryImpl::find_pending(
+            &mut transaction,
+            &input.upload_id,
+            &input.user_id,
+        )
+        .await
+        {
+            Ok(pending) => pending,
+            Err(err) => {
+                error!("{:?}", err);
+                if let sqlx::error::Error::RowNotFound = err {
+                    return Err(Status::not_found("Upload not found"));
+                }
+                return Err(Status::internal("Something went wrong"));
+            }
+        };
+
+        let temporary = upload_object(&pending.id);
+
+        // Nothing was put yet, the client may still do so
+        match self.store.head(&temporary).await {
+            Ok(_) => {}
+            Err(BlobError::NotFound) => return Err(Status::not_found("Nothing was uploaded")),
+            Err(err) => return Err(internal(err)),
+        }
+
+        let stored = self
+            .store_pending(&mut transaction, &pending, &temporary)
+            .await;
+
+        let response = match stored {
+            Ok(stored) => {
+                self.register(
+                    &mut transaction,
+                    pending.user_id,
+                    pending.file_name,
+                    &stored,
+                )
+                .await
+            }
+            Err(status) => Err(status),
+        };
+
+        // An upload is checked once, a rejected one has to be started over
+        self.remove_object(&temporary).await;
+
+        if let Err(err) = AssetRepositoryImpl::delete_pending(&mut transaction, &pending.id).await {
+            error!("{:?}", err);
+            return Err(Status::internal("Something went wrong"));
+        }
+
+        if let Err(err) = transaction.commit().await {
+            error!("{:?}", err);
+            return Err(Status::internal("Something went wrong"));
+        }
+
+        response.map(Response::new)
+    }
+
+    async fn presign_download(
+        &self,
+        request: Request<PresignDownloadRequest>,
+    ) -> Result<Response<PresignedUrl>, Status> {
+        let input = request.get_ref();
+
+        info!("Presign Download Request {:?}", input);
+
+        let name = self.object_name(
+            &input.asset_name,
+            input.rendition.as_deref(),
+            input.format(),
+        )?;
+
+        match self.store.head(&name).await {
+            Ok(_) => {}
+            Err(BlobError::NotFound) => return Err(Status::not_found("Asset not found")),
+            Err(err) => return Err(internal(err)),
+        }
+
+        let url = self
+            .store
+            .presign(&name, Access::Read, self.presign_expiry)
+            .await
+            .map_err(presign_error)?;
+
+        Ok(Response::new(PresignedUrl {
+            url,
+            expires_in: self.presign_expiry,
+        }))
     }
 }
+
+fn content_type(data: &[u8]) -> Option<String> {
+    image::guess_format(data)
+        .ok()
+        .map(|format| format.to_mime_type().to_string())
+}
+
+/// Where an upload is kept until its checksum is known
+pub(crate) fn upload_object(id: &str) -> String {
+    format!("uploads/{}", id)
+}
+
+fn presign_error(err: BlobError) -> Status {
+    match err {
+        BlobError::Unsupported => Status::unimplemented("Direct transfers are not supported"),
+        err => internal(err),
+    }
+}
+
+fn internal(err: BlobError) -> Status {
+    error!(?err);
+    Status::internal("Something went wrong")
+}
@@ -0,0 +1,61 @@
+/// Leading bytes of the file types that can be recognised
+const SIGNATURES: [(&[u8], &str); 11] = [
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"II*\x00", "image/tiff"),
+    (b"MM\x00*", "image/tiff"),
+    (b"%PDF-", "application/pdf"),
+    (b"\x1a\x45\xdf\xa3", "video/webm"),
+    (b"OggS", "audio/ogg"),
+    (b"ID3", "audio/mpeg"),
+    (b"fLaC", "audio/flac"),
+];
+
+/// Content type of an upload judged by its first bytes, the type the client
+/// claims is never trusted
+pub fn sniff(data: &[u8]) -> Option<&'static str> {
+    if let Some((_, content_type)) = SIGNATURES
+        .iter()
+        .find(|(signature, _)| data.starts_with(signature))
+    {
+        return Some(content_type);
+    }
+
+    // RIFF containers name their format at offset 8
+    if data.starts_with(b"RIFF") && data.len() >= 12 {
+        return match &data[8..12] {
+            b"WEBP" => Some("image/webp"),
+            b"WAVE" => Some("audio/wav"),
+            b"AVI " => Some("video/x-msvideo"),
+            _ => None,
+        };
+    }
+
+    // ISO media files start with a box naming their brand
+    if data.len() >= 12 && &data[4..8] == b"ftyp" {
+        return match &data[8..12] {
+            b"avif" | b"avis" => Some("image/avif"),
+            b"heic" | b"heix" | b"mif1" => Some("image/heic"),
+            b"qt  " => Some("video/quicktime"),
+            b"M4A " => Some("audio/mp4"),
+            _ => Some("video/mp4"),
+        };
+    }
+
+    None
+}
+
+/// Whether a content type is in the allow-list, where `image/*` allows
+/// every image
+pub fn is_allowed(content_type: &str, allowed: &[String]) -> bool {
+    allowed
+        .iter()
+        .any(|allowed| match allowed.strip_suffix("/*") {
+            Some(kind) => content_type
+                .split_once('/')
+                .is_some_and(|(prefix, _)| prefix == kind),
+            None => allowed == content_type,
+        })
+}
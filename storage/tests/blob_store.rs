@@ -0,0 +1,116 @@
+use std::path::PathBuf;
+
+use futures::TryStreamExt;
+use storage::blob_store::{BlobError, BlobStore, FsStore};
+use uuid::Uuid;
+
+fn store() -> (FsStore, PathBuf) {
+    let root = std::env::temp_dir().join(format!("blob-store-{}", Uuid::new_v4()));
+    (FsStore::new(&root), root)
+}
+
+async fn read(store: &FsStore, name: &str, offset: Option<u64>, length: Option<u64>) -> Vec<u8> {
+    let reader = store.get(name, offset, length).await.unwrap();
+    let chunks: Vec<Vec<u8>> = reader.chunks.try_collect().await.unwrap();
+    chunks.concat()
+}
+
+#[tokio::test]
+async fn puts_and_reads_objects_and_ranges() {
+    let (store, root) = store();
+
+    store.put("abc", b"hello world").await.unwrap();
+
+    assert_eq!(read(&store, "abc", None, None).await, b"hello world");
+    assert_eq!(read(&store, "abc", Some(6), Some(3)).await, b"wor");
+    assert_eq!(read(&store, "abc", Some(6), Some(100)).await, b"world");
+    assert_eq!(store.head("abc").await.unwrap().size, 11);
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[tokio::test]
+async fn writes_objects_in_chunks_and_aborts_them() {
+    let (store, root) = store();
+
+    let mut writer = store.writer("uploads/complete").await.unwrap();
+    writer.write(b"hello ").await.unwrap();
+    writer.write(b"world").await.unwrap();
+    assert!(matches!(
+        store.head("uploads/complete").await,
+        Err(BlobError::NotFound)
+    ));
+    assert_eq!(writer.finish().await.unwrap(), 11);
+    assert_eq!(
+        read(&store, "uploads/complete", None, None).await,
+        b"hello world"
+    );
+
+    let mut writer = store.writer("uploads/aborted").await.unwrap();
+    writer.write(b"partial").await.unwrap();
+    writer.abort().await;
+
+    let names: Vec<String> = store
+        .list("uploads/")
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|object| object.name)
+        .collect();
+    assert_eq!(names, vec![String::from("uploads/complete")]);
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[tokio::test]
+async fn copies_lists_and_deletes_objects() {
+    let (store, root) = store();
+
+    store.put("abc", b"data").await.unwrap();
+    store.copy("abc", "abc@thumbnail.webp").await.unwrap();
+    store.put("other", b"more data").await.unwrap();
+
+    let mut names: Vec<String> = store
+        .list("abc")
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|object| object.name)
+        .collect();
+    names.sort();
+    assert_eq!(names, vec!["abc", "abc@thumbnail.webp"]);
+
+    store.delete("abc").await.unwrap();
+    store.delete("abc").await.unwrap();
+    assert!(matches!(
+        store.get("abc", None, None).await,
+        Err(BlobError::NotFound)
+    ));
+    assert_eq!(
+        read(&store, "abc@thumbnail.webp", None, None).await,
+        b"data"
+    );
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[tokio::test]
+async fn refuses_names_outside_the_root() {
+    let (store, root) = store();
+
+    assert!(matches!(
+        store.put("../escaped", b"data").await,
+        Err(BlobError::NotFound)
+    ));
+    assert!(matches!(
+        store.head("/etc/passwd").await,
+        Err(BlobError::NotFound)
+    ));
+    assert!(matches!(
+        store.get(".tmp/anything", None, None).await,
+        Err(BlobError::NotFound)
+    ));
+    assert!(!root.join("..").join("escaped").exists());
+
+    let _ = std::fs::remove_dir_all(root);
+}
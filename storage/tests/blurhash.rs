@@ -0,0 +1,43 @@
+use storage::blurhash::encode;
+
+const CHARACTERS: &str =
+    "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz#$%*+,-.:;=?@[]^_{|}~";
+
+fn decode83(value: &str) -> u32 {
+    value.chars().fold(0, |sum, char| {
+        sum * 83 + CHARACTERS.find(char).unwrap() as u32
+    })
+}
+
+#[test]
+fn encodes_the_average_colour_and_component_count() {
+    let pixels = [0x12, 0x34, 0x56].repeat(8 * 6);
+
+    let hash = encode(4, 3, 8, 6, &pixels);
+
+    assert_eq!(hash.len(), 6 + 2 * (4 * 3 - 1));
+    assert_eq!(decode83(&hash[..1]), 3 + 2 * 9);
+    assert_eq!(decode83(&hash[2..6]), 0x123456);
+}
+
+#[test]
+fn tells_gradients_apart() {
+    let horizontal: Vec<u8> = (0..8 * 8).flat_map(|i| [(i % 8) as u8 * 32; 3]).collect();
+    let vertical: Vec<u8> = (0..8 * 8).flat_map(|i| [(i / 8) as u8 * 32; 3]).collect();
+
+    let horizontal = encode(4, 3, 8, 8, &horizontal);
+    let vertical = encode(4, 3, 8, 8, &vertical);
+
+    assert_ne!(horizontal, vertical);
+    assert!(horizontal
+        .chars()
+        .chain(vertical.chars())
+        .all(|char| CHARACTERS.contains(char)));
+}
+
+#[test]
+fn a_single_component_is_only_the_colour() {
+    let hash = encode(1, 1, 2, 2, &[255, 255, 255].repeat(4));
+
+    assert_eq!(hash, "00TSUA");
+}
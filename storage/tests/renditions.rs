@@ -0,0 +1,85 @@
+use std::io::Cursor;
+
+use image::{DynamicImage, ImageFormat, Rgb, RgbImage};
+use shared::configuration::RenditionSettings;
+use storage::renditions::{placeholder, process, rendition_name};
+
+fn settings() -> Vec<RenditionSettings> {
+    vec![
+        RenditionSettings {
+            name: String::from("thumbnail"),
+            width: 320,
+        },
+        RenditionSettings {
+            name: String::from("large"),
+            width: 1920,
+        },
+    ]
+}
+
+#[test]
+fn builds_webp_and_original_renditions_without_upscaling() {
+    let mut data = Vec::new();
+    DynamicImage::ImageRgb8(RgbImage::new(1280, 640))
+        .write_to(&mut Cursor::new(&mut data), ImageFormat::Png)
+        .unwrap();
+
+    let processed = process(&data, &settings(), 80).unwrap().unwrap();
+
+    assert_eq!(processed.original.format, ImageFormat::Png);
+    assert_eq!(
+        (processed.original.width, processed.original.height),
+        (1280, 640)
+    );
+
+    let sizes: Vec<_> = processed
+        .renditions
+        .iter()
+        .map(|rendition| {
+            (
+                rendition.name.as_str(),
+                rendition.image.format,
+                rendition.image.width,
+                rendition.image.height,
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        sizes,
+        vec![
+            ("thumbnail", ImageFormat::WebP, 320, 160),
+            ("thumbnail", ImageFormat::Png, 320, 160),
+            ("large", ImageFormat::WebP, 1280, 640),
+            ("large", ImageFormat::Png, 1280, 640),
+        ]
+    );
+}
+
+#[test]
+fn stores_other_files_as_uploaded() {
+    assert!(process(b"%PDF-1.7", &settings(), 80).unwrap().is_none());
+    assert_eq!(
+        rendition_name("avatar.png", "thumbnail", true),
+        "avatar.png@thumbnail.webp"
+    );
+    assert_eq!(
+        rendition_name("avatar.png", "thumbnail", false),
+        "avatar.png@thumbnail"
+    );
+}
+
+#[test]
+fn picks_the_colour_most_of_the_image_has() {
+    let mut image = RgbImage::from_pixel(64, 32, Rgb([0x20, 0x60, 0xa0]));
+    for x in 0..16 {
+        for y in 0..32 {
+            image.put_pixel(x, y, Rgb([0xff, 0xff, 0xff]));
+        }
+    }
+
+    let placeholder = placeholder(&DynamicImage::ImageRgb8(image));
+
+    assert_eq!(placeholder.color, "#2060a0");
+    assert_eq!(placeholder.blurhash.len(), 6 + 2 * (4 * 3 - 1));
+}
@@ -0,0 +1,343 @@
+use std::{io::Cursor, sync::Arc};
+
+use futures::{stream, TryStreamExt};
+use image::{DynamicImage, ImageFormat, RgbImage};
+use sha2::{Digest, Sha256};
+use shared::{
+    configuration::{get_configuration, CollectorSettings},
+    storage_proto::{
+        storage_service_client::StorageServiceClient, storage_service_server::StorageServiceServer,
+        DeleteRequest, RenditionFormat, RetriveRenditionRequest, RetriveRequest, StoreRequest,
+        StoreResponse,
+    },
+};
+use sqlx::{Connection, Executor, PgConnection, PgPool};
+use storage::{
+    blob_store::{BlobError, BlobStore, FsStore},
+    collector::Collector,
+    service::StorageServiceImpl,
+};
+use tonic::{
+    transport::{server::TcpIncoming, Channel, Server},
+    Code,
+};
+use uuid::Uuid;
+
+/// The storage service on the filesystem backend and a database of its own
+struct TestApp {
+    client: StorageServiceClient<Channel>,
+    store: Arc<FsStore>,
+    collector: Collector,
+    db: PgPool,
+    root: std::path::PathBuf,
+}
+
+impl TestApp {
+    async fn spawn() -> Self {
+        let mut configuration = get_configuration().unwrap();
+        configuration.database.database_name = Uuid::new_v4().to_string();
+
+        let mut connection = PgConnection::connect_with(&configuration.database.without_db())
+            .await
+            .unwrap();
+        connection
+            .execute(
+                format!(
+                    r#"CREATE DATABASE "{}";"#,
+                    configuration.database.database_name
+                )
+                .as_str(),
+            )
+            .await
+            .unwrap();
+
+        let db = PgPool::connect_with(configuration.database.with_db())
+            .await
+            .unwrap();
+        sqlx::migrate!("../migrations").run(&db).await.unwrap();
+
+        let root = std::env::temp_dir().join(format!("storage-{}", Uuid::new_v4()));
+        let store = Arc::new(FsStore::new(&root));
+
+        let service = StorageServiceImpl {
+            db: db.clone(),
+            store: store.clone(),
+            renditions: configuration.storage.renditions,
+            quality: configuration.storage.quality,
+            max_image_size: configuration.storage.max_image_size,
+            presign_expiry: configuration.storage.presign_expiry,
+            grace_period: 0,
+            limits: configuration.storage.limits,
+        };
+
+        // Nothing waits for the grace period
+        let collector = Collector {
+            service: service.clone(),
+            settings: CollectorSettings {
+                grace_period: 0,
+                ..configuration.storage.collector
+            },
+        };
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let incoming = TcpIncoming::from_listener(listener, true, None).unwrap();
+
+        tokio::spawn(
+            Server::builder()
+                .add_service(StorageServiceServer::new(service))
+                .serve_with_incoming(incoming),
+        );
+
+        let client = StorageServiceClient::connect(format!("http://{}", address))
+            .await
+            .unwrap();
+
+        Self {
+            client,
+            store,
+            collector,
+            db,
+            root,
+        }
+    }
+
+    async fn create_user(&self, username: &str) -> String {
+        sqlx::query_scalar("INSERT INTO users (username, email) VALUES ($1, $2) RETURNING id")
+            .bind(username)
+            .bind(format!("{}@example.com", username))
+            .fetch_one(&self.db)
+            .await
+            .unwrap()
+    }
+
+    /// Uploads `data` in chunks of `chunk_size` bytes
+    async fn store(
+        &self,
+        user_id: &str,
+        data: &[u8],
+        chunk_size: usize,
+    ) -> Result<StoreResponse, tonic::Status> {
+        let chunks: Vec<StoreRequest> = data
+            .chunks(chunk_size)
+            .map(|chunk| StoreRequest {
+                asset_data: chunk.to_vec(),
+                asset_name: String::from("upload"),
+                content_type: None,
+                user_id: user_id.to_string(),
+            })
+            .collect();
+
+        self.client
+            .clone()
+            .store(stream::iter(chunks))
+            .await
+            .map(|response| response.into_inner())
+    }
+
+    async fn retrive(&self, asset_name: &str) -> Vec<u8> {
+        let chunks: Vec<Vec<u8>> = self
+            .client
+            .clone()
+            .retrive(RetriveRequest {
+                asset_name: asset_name.to_string(),
+                offset: None,
+                length: None,
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .map_ok(|response| response.data)
+            .try_collect()
+            .await
+            .unwrap();
+        chunks.concat()
+    }
+
+    async fn delete(&self, user_id: &str, asset_id: &str) -> Result<(), tonic::Status> {
+        self.client
+            .clone()
+            .delete(DeleteRequest {
+                user_id: user_id.to_string(),
+                asset_id: asset_id.to_string(),
+            })
+            .await
+            .map(|_| ())
+    }
+
+    async fn ref_count(&self, name: &str) -> Option<i32> {
+        sqlx::query_scalar("SELECT ref_count FROM blobs WHERE name = $1")
+            .bind(name)
+            .fetch_optional(&self.db)
+            .await
+            .unwrap()
+    }
+
+    async fn objects(&self, prefix: &str) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .store
+            .list(prefix)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|object| object.name)
+            .collect();
+        names.sort();
+        names
+    }
+
+    fn cleanup(self) {
+        let _ = std::fs::remove_dir_all(self.root);
+    }
+}
+
+fn pdf() -> Vec<u8> {
+    let mut data = b"%PDF-1.4\n".to_vec();
+    data.extend((0..4096).map(|index| (index % 251) as u8));
+    data
+}
+
+fn png() -> Vec<u8> {
+    let mut data = Vec::new();
+    DynamicImage::ImageRgb8(RgbImage::new(640, 320))
+        .write_to(&mut Cursor::new(&mut data), ImageFormat::Png)
+        .unwrap();
+    data
+}
+
+#[tokio::test]
+async fn stores_uploads_under_their_checksum_and_retrieves_them() {
+    let app = TestApp::spawn().await;
+    let user_id = app.create_user("uploader").await;
+    let data = pdf();
+    let checksum = format!("{:x}", Sha256::digest(&data));
+
+    let first = app.store(&user_id, &data, 1000).await.unwrap();
+    let second = app.store(&user_id, &data, 4096).await.unwrap();
+
+    assert_eq!(first.asset_name, checksum);
+    assert_eq!(second.asset_name, checksum);
+    assert_eq!(first.size, data.len() as u64);
+    assert_ne!(first.asset.unwrap().id, second.asset.unwrap().id);
+    assert_eq!(app.ref_count(&checksum).await, Some(2));
+
+    // Temporary objects are gone once the upload is registered
+    assert_eq!(app.objects("").await, vec![checksum.clone()]);
+    assert_eq!(app.retrive(&checksum).await, data);
+
+    app.cleanup();
+}
+
+#[tokio::test]
+async fn stores_images_with_their_renditions() {
+    let app = TestApp::spawn().await;
+    let user_id = app.create_user("photographer").await;
+
+    let stored = app.store(&user_id, &png(), 1024).await.unwrap();
+
+    assert_eq!((stored.width, stored.height), (Some(640), Some(320)));
+    assert!(!stored.renditions.is_empty());
+    for rendition in stored.renditions.iter() {
+        assert!(app.store.head(&rendition.asset_name).await.is_ok());
+    }
+
+    let thumbnail: Vec<Vec<u8>> = app
+        .client
+        .clone()
+        .retrive_rendition(RetriveRenditionRequest {
+            asset_name: stored.asset_name.to_owned(),
+            rendition: String::from("thumbnail"),
+            format: RenditionFormat::Webp as i32,
+            offset: None,
+            length: None,
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .map_ok(|response| response.data)
+        .try_collect()
+        .await
+        .unwrap();
+    assert!(thumbnail.concat().starts_with(b"RIFF"));
+
+    app.cleanup();
+}
+
+#[tokio::test]
+async fn refuses_unsupported_uploads() {
+    let app = TestApp::spawn().await;
+    let user_id = app.create_user("scripter").await;
+
+    let status = app
+        .store(&user_id, b"<script>alert(1)</script>", 1024)
+        .await
+        .unwrap_err();
+
+    assert_eq!(status.code(), Code::FailedPrecondition);
+    assert!(app.objects("").await.is_empty());
+
+    app.cleanup();
+}
+
+#[tokio::test]
+async fn deletes_assets_and_collects_their_objects() {
+    let app = TestApp::spawn().await;
+    let owner = app.create_user("owner").await;
+    let other = app.create_user("other").await;
+
+    let stored = app.store(&owner, &png(), 1024).await.unwrap();
+    let asset_id = stored.asset.unwrap().id;
+
+    let status = app.delete(&other, &asset_id).await.unwrap_err();
+    assert_eq!(status.code(), Code::PermissionDenied);
+
+    app.delete(&owner, &asset_id).await.unwrap();
+
+    // The bytes stay until the collector finds the blob unused
+    assert_eq!(app.ref_count(&stored.asset_name).await, Some(0));
+    assert!(!app.objects(&stored.asset_name).await.is_empty());
+
+    app.collector.collect().await.unwrap();
+
+    assert_eq!(app.ref_count(&stored.asset_name).await, None);
+    assert!(app.objects("").await.is_empty());
+    assert!(matches!(
+        app.store.head(&stored.asset_name).await,
+        Err(BlobError::NotFound)
+    ));
+
+    app.cleanup();
+}
+
+#[tokio::test]
+async fn collects_unreferenced_assets_only() {
+    let app = TestApp::spawn().await;
+    let user_id = app.create_user("author").await;
+
+    let avatar = app.store(&user_id, &png(), 1024).await.unwrap();
+    let orphan = app.store(&user_id, &pdf(), 1024).await.unwrap();
+
+    sqlx::query("UPDATE users SET image = $1 WHERE id = $2")
+        .bind(format!(
+            "http://localhost:8000/assets/{}",
+            avatar.asset_name
+        ))
+        .bind(&user_id)
+        .execute(&app.db)
+        .await
+        .unwrap();
+
+    // Without a grace period, assets are marked and deleted in one pass
+    app.collector.collect().await.unwrap();
+
+    assert_eq!(app.ref_count(&orphan.asset_name).await, None);
+    assert!(app.objects(&orphan.asset_name).await.is_empty());
+
+    assert_eq!(app.ref_count(&avatar.asset_name).await, Some(1));
+    assert_eq!(
+        app.retrive(&avatar.asset_name).await.len() as u64,
+        avatar.size
+    );
+
+    app.cleanup();
+}
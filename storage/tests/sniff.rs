@@ -0,0 +1,28 @@
+use storage::sniff::{is_allowed, sniff};
+
+#[test]
+fn recognises_files_by_their_first_bytes() {
+    assert_eq!(sniff(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), Some("image/png"));
+    assert_eq!(sniff(b"\xff\xd8\xff\xe0\0\x10JFIF"), Some("image/jpeg"));
+    assert_eq!(sniff(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+    assert_eq!(sniff(b"%PDF-1.7\n"), Some("application/pdf"));
+    assert_eq!(sniff(b"\0\0\0\x1cftypavif"), Some("image/avif"));
+    assert_eq!(sniff(b"\0\0\0\x18ftypisom"), Some("video/mp4"));
+}
+
+#[test]
+fn does_not_guess_unknown_or_text_files() {
+    assert_eq!(sniff(b"<svg xmlns=\"http://www.w3.org/2000/svg\"/>"), None);
+    assert_eq!(sniff(b"RIFF\0\0\0\0"), None);
+    assert_eq!(sniff(b""), None);
+}
+
+#[test]
+fn allows_exact_types_and_wildcards() {
+    let allowed = vec![String::from("image/*"), String::from("application/pdf")];
+
+    assert!(is_allowed("image/png", &allowed));
+    assert!(is_allowed("application/pdf", &allowed));
+    assert!(!is_allowed("video/mp4", &allowed));
+    assert!(!is_allowed("imagery/png", &allowed));
+}